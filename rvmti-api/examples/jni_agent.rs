@@ -0,0 +1,243 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+// Minimal agent calling into Java through JNI on VM init, loaded by tests/jvm.rs
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use rvmti_api::{Agent, AgentEventHandler, ConstantPoolError, Disassembly, JArg, JClass, JObject, JavaType, JniCallError,
+                JniEnv, JPrimitive, JThread, Jvm, JvmtiEnv, JvmtiError, Operands};
+use rvmti_api::agent::NegotiatedCapabilities;
+
+struct JniAgent {
+    // Times String passed through the class file load hook while being retransformed
+    retransformed: AtomicU32,
+}
+
+impl Agent for JniAgent {
+
+    type Error = String;
+
+    fn on_load(_vm: &Jvm, _env: &mut JvmtiEnv, _options: &Option<String>,
+               _capabilities: &NegotiatedCapabilities) -> Result<JniAgent, String>
+    {
+        Ok(JniAgent{retransformed: AtomicU32::new(0)})
+    }
+
+}
+
+impl AgentEventHandler for JniAgent {
+
+    fn vm_init(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv, _thread: &JThread) {
+        if let Err(e) = report(jni_env) {
+            println!("error: {}", e);
+        }
+        report_threads(env, jni_env);
+        report_raw_monitor(env);
+        // Every loaded class becomes a local reference, the default frame capacity is far too small for them
+        let frame = jni_env.with_local_frame(4096, |frame_env| {
+            if let Err(e) = report_string_class(env, frame_env) {
+                println!("string class: error {}", e);
+            }
+            if let Err(e) = report_retransform(env, frame_env, &self.retransformed) {
+                println!("retransform: error {}", e);
+            }
+            Ok(None)
+        });
+        if let Err(e) = frame {
+            println!("string class: error {}", e);
+        }
+    }
+
+    // Retransformed String gets its original bytes back unchanged, the replacement still goes through Allocate
+    fn class_file_load_hook(&self, _env: &mut JvmtiEnv, _jni_env: &mut JniEnv, class_being_redefined: Option<&JClass>,
+                            _loader: Option<&JObject>, name: &Option<String>, _protection_domain: Option<&JObject>,
+                            class_data: &[u8]) -> Option<Vec<u8>>
+    {
+        if class_being_redefined.is_none() || name.as_deref() != Some("java/lang/String") {
+            return None;
+        }
+        self.retransformed.fetch_add(1, Ordering::SeqCst);
+        Some(class_data.to_vec())
+    }
+
+}
+
+fn report(jni_env: &JniEnv) -> Result<(), JniCallError> {
+    let system = jni_env.find_class("java/lang/System")?;
+    let get_property = jni_env.get_static_method_id(&system, "getProperty", "(Ljava/lang/String;)Ljava/lang/String;")?;
+    let key = jni_env.new_string("rvmti.test.property")?;
+    match jni_env.call_static_object_method(&system, &get_property, &[JArg::Object(Some(&key))])? {
+        Some(value) => println!("property: {}", jni_env.get_string(&value)?),
+        None => println!("property: <null>"),
+    }
+    let string = jni_env.find_class("java/lang/String")?;
+    let length = jni_env.get_method_id(&string, "length", "()I")?;
+    let text = jni_env.new_string("h\u{e9}llo \u{1f600}")?;
+    if let JPrimitive::Int(length) = jni_env.call_method(&text, &length, JavaType::Int, &[])? {
+        println!("length: {}", length);
+    }
+    match jni_env.find_class("rvmti/does/not/Exist") {
+        Err(JniCallError::PendingException) => {
+            let exception = jni_env.exception_occurred();
+            jni_env.exception_clear();
+            println!("missing class: {}", exception.is_some() && !jni_env.exception_check());
+        },
+        other => println!("missing class: unexpected {:?}", other.map(|_| ())),
+    }
+    let kept = jni_env.with_local_frame(16, |frame_env| {
+        let _discarded = frame_env.new_string("discarded")?;
+        frame_env.new_string("kept").map(Some)
+    })?;
+    match kept {
+        Some(kept) => {
+            // Global reference stays valid after the local one is deleted
+            let global = kept.to_global()?;
+            drop(kept);
+            println!("frame: {}", jni_env.get_string(&global)?);
+        },
+        None => println!("frame: <null>"),
+    }
+    Ok(())
+}
+
+// VM init runs on the main thread, system threads already wait in Java code
+fn report_threads(env: &mut JvmtiEnv, jni_env: &JniEnv) {
+    match (env.get_thread_info(jni_env, None), env.get_thread_state(None)) {
+        (Ok(info), Ok(state)) => println!("current thread: {} {:?}", info.name, state.java_state()),
+        (info, state) => println!("current thread: error {:?} {:?}", info.err(), state.err()),
+    }
+    let threads = match env.get_all_threads(jni_env) {
+        Ok(threads) => threads,
+        Err(e) => {
+            println!("threads: error {}", e);
+            return;
+        }
+    };
+    let thread_refs: Vec<&JThread> = threads.iter().map(|t| &**t).collect();
+    match (env.get_all_stack_traces(jni_env, 16), env.get_thread_list_stack_traces(&thread_refs, 16)) {
+        (Ok(all), Ok(list)) => {
+            let frames: Vec<_> = all.iter().flat_map(|(_, trace)| trace.frames.iter()).collect();
+            let resolved = frames.iter().all(|frame| env.get_method_name(&frame.method).is_ok());
+            println!("stack traces: {}", all.len() == threads.len() && list.len() == threads.len()
+                && !frames.is_empty() && resolved);
+        },
+        (all, list) => println!("stack traces: error {:?} {:?}", all.err(), list.err()),
+    }
+    // Every thread but the current one is stopped while captured and running again afterwards
+    let captured = env.with_threads_suspended(jni_env, &thread_refs, 16, |snapshots| {
+        let stopped = snapshots.iter().filter(|s| s.stack.state.is_suspended()).count();
+        let contended = snapshots.iter().any(|s| s.contended_monitor.is_some());
+        (stopped, contended)
+    });
+    match captured {
+        Ok((stopped, contended)) => {
+            let resumed = thread_refs.iter().all(|t| env.get_thread_state(Some(t)).is_ok_and(|s| !s.is_suspended()));
+            println!("suspended snapshot: {} {} {}", stopped + 1 == threads.len(), contended, resumed);
+        },
+        Err(e) => println!("suspended snapshot: error {}", e),
+    }
+}
+
+// Native thread hands a value over to the event thread through a raw monitor
+fn report_raw_monitor(env: &mut JvmtiEnv) {
+    let monitor = match env.create_raw_monitor("rvmti example monitor") {
+        Ok(monitor) => Arc::new(monitor),
+        Err(e) => {
+            println!("raw monitor: error {}", e);
+            return;
+        }
+    };
+    let value = Arc::new(AtomicU32::new(0));
+    let producer = {
+        let monitor = monitor.clone();
+        let value = value.clone();
+        std::thread::spawn(move || -> Result<(), JvmtiError> {
+            let guard = monitor.enter()?;
+            value.store(42, Ordering::SeqCst);
+            guard.notify_all()
+        })
+    };
+    let waited = monitor.enter().and_then(|guard| {
+        while value.load(Ordering::SeqCst) == 0 {
+            guard.wait(Some(Duration::from_millis(100)))?;
+        }
+        Ok(value.load(Ordering::SeqCst))
+    });
+    match (waited, producer.join()) {
+        (Ok(value), Ok(Ok(()))) => println!("raw monitor: {}", value),
+        (waited, produced) => println!("raw monitor: error {:?} {:?}", waited.err(), produced),
+    }
+}
+
+fn report_retransform(env: &mut JvmtiEnv, jni_env: &JniEnv, retransformed: &AtomicU32) -> Result<(), Box<dyn std::error::Error>> {
+    let string = jni_env.find_class("java/lang/String")?;
+    let int_array = jni_env.find_class("[I")?;
+    let modifiable = env.is_modifiable_class(&string)?;
+    let array_modifiable = env.is_modifiable_class(&int_array)?;
+    env.retransform_classes(&[&string])?;
+    println!("retransform: modifiable {} array modifiable {} hooked {}", modifiable, array_modifiable,
+             retransformed.load(Ordering::SeqCst));
+    Ok(())
+}
+
+// Finds java.lang.String among loaded classes and looks up its length method by name
+fn report_string_class(env: &mut JvmtiEnv, jni_env: &JniEnv) -> Result<(), Box<dyn std::error::Error>> {
+    let classes = env.get_loaded_classes(jni_env)?;
+    let mut string = None;
+    for class in classes.iter() {
+        if env.get_class_signature(class)?.signature == "Ljava/lang/String;" {
+            string = Some(class);
+        }
+    }
+    let string = string.ok_or("not loaded")?;
+    for method in env.get_class_methods(string)? {
+        let name = env.get_method_name(&method)?;
+        if name.name == "length" && name.signature == "()I" {
+            let disassembly = Disassembly::decode(&env.get_bytecodes(&method)?)?;
+            let instructions = disassembly.instructions();
+            let pool = env.get_constant_pool(string)?;
+            let references = instructions.iter().filter(|i| matches!(i.operands, Operands::ConstantPool(_)))
+                .map(|i| pool.resolve_instruction(i)).collect::<Result<Vec<String>, ConstantPoolError>>()?;
+            println!("string length references: {:?}", references);
+        }
+    }
+    // Every loaded method, checks the decoders against whatever the class library contains
+    let mut undecoded = Vec::new();
+    for class in classes.iter() {
+        // Methods of classes loaded but not linked yet are not available
+        let status = env.get_class_status(class)?;
+        if !status.is_prepared() || status.is_array() || status.is_primitive() {
+            continue;
+        }
+        let pool = env.get_constant_pool(class)?;
+        for method in env.get_class_methods(class)? {
+            if env.get_method_modifiers(&method)?.is_native() {
+                continue;
+            }
+            let resolved = Disassembly::decode(&env.get_bytecodes(&method)?).map_err(|e| e.to_string())
+                .and_then(|d| d.instructions().iter().map(|i| pool.resolve_instruction(i).map_err(|e| e.to_string()))
+                    .collect::<Result<Vec<String>, String>>());
+            if let Err(e) = resolved {
+                undecoded.push(format!("{}.{}: {}", env.get_class_signature(class)?.signature,
+                                       env.get_method_name(&method)?.name, e));
+            }
+        }
+    }
+    println!("loaded methods decoded: {:?}", undecoded);
+    Ok(())
+}
+
+rvmti_api::jvmti_agent! {
+    capabilities: [can_get_bytecodes, can_get_constant_pool, can_suspend, can_get_owned_monitor_stack_depth_info,
+                   can_get_current_contended_monitor, can_retransform_classes],
+    events: [VmInit, ClassFileLoadHook],
+    handler: JniAgent,
+}
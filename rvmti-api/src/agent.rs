@@ -0,0 +1,572 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+// Agent lifecycle driven by `jvmti_agent!`, exported entry points forward here
+
+use std::fmt::Display;
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+
+use lazy_static::lazy_static;
+use log::{debug, info, warn, error};
+
+use super::{Jvm, JniError, JvmtiEnv, JvmtiVersion, JvmtiVersionInfo, JvmtiEvent, JvmtiCapabilities, AgentEventHandler, from_platform};
+
+pub trait Agent: AgentEventHandler + Sized + 'static {
+
+    type Error: Display;
+
+    // Called before anything else, nothing is logged until logger is installed here
+    fn init_logging() {}
+
+    // Features left out are neither negotiated nor get their events enabled, some capabilities slow the VM down
+    // even when unused
+    fn is_feature_requested(_options: &Option<String>, _feature: &str) -> bool {
+        true
+    }
+
+    fn on_load(vm: &Jvm, env: &mut JvmtiEnv, options: &Option<String>,
+               capabilities: &NegotiatedCapabilities) -> Result<Self, Self::Error>;
+
+    fn on_unload(&self, _vm: &Jvm, _env: &mut JvmtiEnv) {}
+
+}
+
+// Feature is enabled only if all required capabilities are available, optional ones are added when possible
+#[derive(Debug, Clone, Copy)]
+pub struct AgentFeature {
+    pub name: &'static str,
+    pub required: u64,
+    pub optional: u64,
+    pub events: &'static [JvmtiEvent],
+}
+
+#[derive(Debug, Clone)]
+pub struct NegotiatedCapabilities {
+    enabled_features: Vec<&'static str>,
+    capabilities: u64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Negotiation {
+    requested: u64,
+    enabled_features: Vec<&'static str>,
+    denied_features: Vec<(&'static str, u64)>,
+}
+
+trait LoadedAgent: Send + Sync {
+
+    fn on_unload(&self, vm: &Jvm, env: &mut JvmtiEnv);
+
+}
+
+impl<A: Agent> LoadedAgent for A {
+
+    fn on_unload(&self, vm: &Jvm, env: &mut JvmtiEnv) {
+        Agent::on_unload(self, vm, env)
+    }
+
+}
+
+struct AgentState {
+    env: JvmtiEnv,
+    agent: Arc<dyn LoadedAgent>,
+}
+
+lazy_static! {
+    static ref AGENT_STATE: Mutex<Option<AgentState>> = Mutex::new(None);
+}
+
+impl NegotiatedCapabilities {
+
+    pub fn is_feature_enabled(&self, name: &str) -> bool {
+        self.enabled_features.contains(&name)
+    }
+
+    pub fn has_capability(&self, capability: u64) -> bool {
+        self.capabilities & capability == capability
+    }
+
+}
+
+pub fn on_load<A: Agent>(vm: *mut rvmti_sys::JavaVM, options: *const c_char, features: &[AgentFeature]) -> rvmti_sys::jint {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        A::init_logging();
+        debug!("Agent 'on load'");
+        // Options is a platform string, not modified utf-8 string, see https://bugs.openjdk.java.net/browse/JDK-5049313
+        match from_platform(options) {
+            Ok(options) => load::<A>(&Jvm::cons(vm), &options, features),
+            Err(e) => {
+                error!("Failed to process options string {}", e);
+                -1
+            },
+        }
+    }));
+    match result {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Failed to load agent: {:?}", e);
+            -1
+        }
+    }
+}
+
+pub fn on_unload(vm: *mut rvmti_sys::JavaVM) {
+    debug!("Agent 'on unload'");
+    let result = panic::catch_unwind(|| {
+        let state = AGENT_STATE.lock().unwrap_or_else(|e| e.into_inner()).take();
+        match state {
+            Some(mut state) => {
+                state.agent.on_unload(&Jvm::cons(vm), &mut state.env);
+                state.env.clear_event_handler();
+            },
+            None => {
+                warn!("Agent was not initialized, skipping shutdown");
+            }
+        }
+    });
+    if let Err(e) = result {
+        warn!("Failed to unload agent: {:?}", e);
+    }
+}
+
+fn load<A: Agent>(vm: &Jvm, options: &Option<String>, features: &[AgentFeature]) -> rvmti_sys::jint {
+    let mut guard = AGENT_STATE.lock().unwrap_or_else(|e| e.into_inner());
+    if guard.is_some() {
+        warn!("Agent was already initialized, skipping initialization");
+        return 0;
+    }
+    let mut env = match get_jvmti_env(vm) {
+        Ok(env) => env,
+        Err(e) => {
+            error!("Unable to obtain JVMTI environment: {}", e);
+            return -1;
+        }
+    };
+    match env.get_version_number() {
+        Ok(version) => debug!("Environment obtained, JVMTI version {}", version),
+        Err(e) => warn!("Environment obtained, unable to get JVMTI version: {}", e),
+    }
+    let features: Vec<AgentFeature> = features.iter().filter(|f| A::is_feature_requested(options, f.name)).cloned().collect();
+    let capabilities = match negotiate_capabilities(&mut env, &features) {
+        Some(capabilities) => capabilities,
+        None => return -1,
+    };
+    let agent = match A::on_load(vm, &mut env, options, &capabilities) {
+        Ok(agent) => Arc::new(agent),
+        Err(e) => {
+            error!("Agent initialization error: {}", e);
+            return -1;
+        }
+    };
+    if let Err(e) = env.set_event_handler(agent.clone()) {
+        error!("Unable to set event handler: {}", e);
+        agent.on_unload(vm, &mut env);
+        return -1;
+    }
+    let events = features.iter().filter(|f| capabilities.is_feature_enabled(f.name)).flat_map(|f| f.events.iter());
+    for event in events {
+        if let Err(e) = env.enable_event(*event, None) {
+            error!("Unable to enable {:?} event: {}", event, e);
+            env.clear_event_handler();
+            agent.on_unload(vm, &mut env);
+            return -1;
+        }
+    }
+    debug!("Events enabled for the environment");
+    *guard = Some(AgentState{env, agent});
+    0
+}
+
+// VM rejects versions newer than its own, bindings may come from newer headers. Every VM accepts the older versions
+fn get_jvmti_env(vm: &Jvm) -> Result<JvmtiEnv, JniError> {
+    let versions = [JvmtiVersion::CurrentVersion, JvmtiVersion::Version11, JvmtiVersion::Version9,
+        JvmtiVersion::Version1dot2];
+    for version in versions.iter() {
+        match vm.get_jvmti_env(*version) {
+            Ok(env) => return Ok(env),
+            Err(JniError::JniVersionError) => {
+                debug!("JVMTI version {} is not supported by the VM", JvmtiVersionInfo::from(*version));
+            },
+            Err(e) => return Err(e),
+        }
+    }
+    Err(JniError::JniVersionError)
+}
+
+fn negotiate_capabilities(env: &mut JvmtiEnv, features: &[AgentFeature]) -> Option<NegotiatedCapabilities> {
+    let potential = match env.get_potential_capabilities() {
+        Ok(c) => c.mask(),
+        Err(e) => {
+            error!("Unable to get potential capabilities: {}", e);
+            return None;
+        }
+    };
+    let negotiation = negotiate(potential, features);
+    for (name, missing) in negotiation.denied_features.iter() {
+        warn!("Feature '{}' is disabled, capabilities are not available: {}", name, capability_names(*missing).join(", "));
+    }
+    if negotiation.enabled_features.is_empty() {
+        error!("None of agent features is available");
+        return None;
+    }
+    let negotiation = match env.add_capabilities(&JvmtiCapabilities::from_mask(negotiation.requested)) {
+        Ok(_) => negotiation,
+        Err(e) => {
+            warn!("Unable to add capabilities, adding them feature by feature: {}", e);
+            add_by_feature(negotiation, features, |mask| env.add_capabilities(&JvmtiCapabilities::from_mask(mask)))
+        }
+    };
+    if negotiation.enabled_features.is_empty() {
+        error!("None of agent features could be enabled");
+        return None;
+    }
+    let granted = match env.get_capabilities() {
+        Ok(c) => c.mask(),
+        Err(e) => {
+            error!("Unable to get capabilities: {}", e);
+            return None;
+        }
+    };
+    info!("Capabilities granted: {}", capability_names(granted).join(", "));
+    info!("Features enabled: {}", negotiation.enabled_features.join(", "));
+    Some(NegotiatedCapabilities{enabled_features: negotiation.enabled_features, capabilities: granted})
+}
+
+fn negotiate(potential: u64, features: &[AgentFeature]) -> Negotiation {
+    let mut negotiation = Negotiation{requested: 0, enabled_features: Vec::new(), denied_features: Vec::new()};
+    for feature in features {
+        let missing = feature.required & !potential;
+        if missing == 0 {
+            negotiation.requested |= feature.required | (feature.optional & potential);
+            negotiation.enabled_features.push(feature.name);
+        } else {
+            negotiation.denied_features.push((feature.name, missing));
+        }
+    }
+    negotiation
+}
+
+// Potential capabilities may still be refused, e.g. one that only a single environment can possess. Each feature is
+// added on its own then, features whose required capabilities are refused are disabled, refused optional ones left out
+fn add_by_feature<F, E>(negotiation: Negotiation, features: &[AgentFeature], mut add: F) -> Negotiation
+    where F: FnMut(u64) -> Result<(), E>, E: Display
+{
+    let Negotiation{requested, enabled_features, denied_features} = negotiation;
+    let mut result = Negotiation{requested: 0, enabled_features: Vec::new(), denied_features};
+    for feature in features.iter().filter(|f| enabled_features.contains(&f.name)) {
+        if feature.required != 0 {
+            if let Err(e) = add(feature.required) {
+                warn!("Feature '{}' is disabled, capabilities were refused: {}: {}", feature.name,
+                      capability_names(feature.required).join(", "), e);
+                result.denied_features.push((feature.name, feature.required));
+                continue;
+            }
+        }
+        result.requested |= feature.required;
+        let optional = feature.optional & requested & !result.requested;
+        for capability in (0..64).map(|bit| 1u64 << bit).filter(|c| optional & c != 0) {
+            match add(capability) {
+                Ok(_) => result.requested |= capability,
+                Err(e) => warn!("Capability {} of feature '{}' was refused: {}", capability_names(capability).join(", "),
+                                feature.name, e),
+            }
+        }
+        result.enabled_features.push(feature.name);
+    }
+    result
+}
+
+macro_rules! capabilities {
+    ($($name:ident = $bit:expr),* $(,)?) => {
+        #[allow(non_upper_case_globals)]
+        pub mod capability {
+            $(pub const $name: u64 = 1 << $bit;)*
+        }
+
+        pub fn capability_names(capabilities: u64) -> Vec<&'static str> {
+            let mut names = Vec::new();
+            $(if capabilities & capability::$name != 0 {
+                names.push(stringify!($name));
+            })*
+            names
+        }
+
+        impl JvmtiCapabilities {
+
+            pub fn from_mask(capabilities: u64) -> JvmtiCapabilities {
+                let mut result = JvmtiCapabilities::new_empty_capabilities();
+                $(if capabilities & capability::$name != 0 {
+                    result.$name();
+                })*
+                result
+            }
+
+            pub fn mask(&self) -> u64 {
+                let mut result = 0u64;
+                $(if self.caps.$name() != 0 {
+                    result |= capability::$name;
+                })*
+                result
+            }
+
+        }
+    };
+}
+
+// Capability names accepted by `jvmti_agent!`, each maps to a bit of a capability mask
+capabilities! {
+    can_tag_objects = 0,
+    can_generate_field_modification_events = 1,
+    can_generate_field_access_events = 2,
+    can_get_bytecodes = 3,
+    can_get_synthetic_attribute = 4,
+    can_get_owned_monitor_info = 5,
+    can_get_current_contended_monitor = 6,
+    can_get_monitor_info = 7,
+    can_pop_frame = 8,
+    can_redefine_classes = 9,
+    can_signal_thread = 10,
+    can_get_source_file_name = 11,
+    can_get_line_numbers = 12,
+    can_get_source_debug_extension = 13,
+    can_access_local_variables = 14,
+    can_maintain_original_method_order = 15,
+    can_generate_single_step_events = 16,
+    can_generate_exception_events = 17,
+    can_generate_frame_pop_events = 18,
+    can_generate_breakpoint_events = 19,
+    can_suspend = 20,
+    can_redefine_any_class = 21,
+    can_get_current_thread_cpu_time = 22,
+    can_get_thread_cpu_time = 23,
+    can_generate_method_entry_events = 24,
+    can_generate_method_exit_events = 25,
+    can_generate_all_class_hook_events = 26,
+    can_generate_compiled_method_load_events = 27,
+    can_generate_monitor_events = 28,
+    can_generate_vm_object_alloc_events = 29,
+    can_generate_native_method_bind_events = 30,
+    can_generate_garbage_collection_events = 31,
+    can_generate_object_free_events = 32,
+    can_force_early_return = 33,
+    can_get_owned_monitor_stack_depth_info = 34,
+    can_get_constant_pool = 35,
+    can_set_native_method_prefix = 36,
+    can_retransform_classes = 37,
+    can_retransform_any_class = 38,
+    can_generate_resource_exhaustion_heap_events = 39,
+    can_generate_resource_exhaustion_threads_events = 40,
+    can_generate_early_vmstart = 41,
+    can_generate_early_class_hook_events = 42,
+    can_generate_sampled_object_alloc_events = 43,
+}
+
+// Events not listed here need no capability
+const fn required_capability(event: JvmtiEvent) -> Option<(u64, &'static str)> {
+    match event {
+        JvmtiEvent::SingleStep => Some((capability::can_generate_single_step_events,
+                                        "SingleStep event requires can_generate_single_step_events capability")),
+        JvmtiEvent::Breakpoint => Some((capability::can_generate_breakpoint_events,
+                                        "Breakpoint event requires can_generate_breakpoint_events capability")),
+        JvmtiEvent::FieldAccess => Some((capability::can_generate_field_access_events,
+                                         "FieldAccess event requires can_generate_field_access_events capability")),
+        JvmtiEvent::FieldModification => Some((capability::can_generate_field_modification_events,
+                                               "FieldModification event requires can_generate_field_modification_events capability")),
+        JvmtiEvent::FramePop => Some((capability::can_generate_frame_pop_events,
+                                      "FramePop event requires can_generate_frame_pop_events capability")),
+        JvmtiEvent::MethodEntry => Some((capability::can_generate_method_entry_events,
+                                         "MethodEntry event requires can_generate_method_entry_events capability")),
+        JvmtiEvent::MethodExit => Some((capability::can_generate_method_exit_events,
+                                        "MethodExit event requires can_generate_method_exit_events capability")),
+        JvmtiEvent::NativeMethodBind => Some((capability::can_generate_native_method_bind_events,
+                                              "NativeMethodBind event requires can_generate_native_method_bind_events capability")),
+        JvmtiEvent::Exception => Some((capability::can_generate_exception_events,
+                                       "Exception event requires can_generate_exception_events capability")),
+        JvmtiEvent::ExceptionCatch => Some((capability::can_generate_exception_events,
+                                            "ExceptionCatch event requires can_generate_exception_events capability")),
+        JvmtiEvent::CompiledMethodLoad => Some((capability::can_generate_compiled_method_load_events,
+                                                "CompiledMethodLoad event requires can_generate_compiled_method_load_events capability")),
+        JvmtiEvent::CompiledMethodUnload => Some((capability::can_generate_compiled_method_load_events,
+                                                  "CompiledMethodUnload event requires can_generate_compiled_method_load_events capability")),
+        JvmtiEvent::MonitorWait => Some((capability::can_generate_monitor_events,
+                                         "MonitorWait event requires can_generate_monitor_events capability")),
+        JvmtiEvent::MonitorWaited => Some((capability::can_generate_monitor_events,
+                                           "MonitorWaited event requires can_generate_monitor_events capability")),
+        JvmtiEvent::MonitorContendedEnter => Some((capability::can_generate_monitor_events,
+                                                   "MonitorContendedEnter event requires can_generate_monitor_events capability")),
+        JvmtiEvent::MonitorContendedEntered => Some((capability::can_generate_monitor_events,
+                                                     "MonitorContendedEntered event requires can_generate_monitor_events capability")),
+        JvmtiEvent::GarbageCollectionStart => Some((capability::can_generate_garbage_collection_events,
+                                                    "GarbageCollectionStart event requires can_generate_garbage_collection_events capability")),
+        JvmtiEvent::GarbageCollectionFinish => Some((capability::can_generate_garbage_collection_events,
+                                                     "GarbageCollectionFinish event requires can_generate_garbage_collection_events capability")),
+        JvmtiEvent::ObjectFree => Some((capability::can_generate_object_free_events,
+                                        "ObjectFree event requires can_generate_object_free_events capability")),
+        JvmtiEvent::VmObjectAlloc => Some((capability::can_generate_vm_object_alloc_events,
+                                           "VmObjectAlloc event requires can_generate_vm_object_alloc_events capability")),
+        JvmtiEvent::SampledObjectAlloc => Some((capability::can_generate_sampled_object_alloc_events,
+                                                "SampledObjectAlloc event requires can_generate_sampled_object_alloc_events capability")),
+        _ => None,
+    }
+}
+
+
+// Evaluated in const context by `jvmti_agent!`, so an event without its capability among required ones fails the build
+pub const fn check_agent_features(features: &[AgentFeature]) {
+    let mut i = 0;
+    while i < features.len() {
+        let mut j = 0;
+        while j < features[i].events.len() {
+            if let Some((capability, message)) = required_capability(features[i].events[j]) {
+                if features[i].required & capability == 0 {
+                    panic!("{}", message);
+                }
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+}
+
+#[macro_export]
+macro_rules! jvmti_agent {
+    (capabilities: [$($capability:ident),* $(,)?], events: [$($event:ident),* $(,)?], handler: $handler:ty $(,)?) => {
+        $crate::jvmti_agent! {
+            features: [
+                agent {
+                    required: [$($capability),*],
+                    optional: [],
+                    events: [$($event),*],
+                },
+            ],
+            handler: $handler,
+        }
+    };
+    (features: [$($feature:ident {
+        required: [$($required:ident),* $(,)?],
+        optional: [$($optional:ident),* $(,)?],
+        events: [$($event:ident),* $(,)?] $(,)?
+    }),* $(,)?], handler: $handler:ty $(,)?) => {
+        #[allow(non_snake_case)]
+        #[no_mangle]
+        pub extern "C" fn Agent_OnLoad(vm: *mut $crate::rvmti_sys::JavaVM, options: *const ::std::os::raw::c_char,
+                                       _reserved: *const ::std::os::raw::c_void) -> $crate::rvmti_sys::jint
+        {
+            const FEATURES: &[$crate::agent::AgentFeature] = &[$($crate::agent::AgentFeature{
+                name: stringify!($feature),
+                required: 0 $(| $crate::agent::capability::$required)*,
+                optional: 0 $(| $crate::agent::capability::$optional)*,
+                events: &[$($crate::JvmtiEvent::$event),*],
+            }),*];
+            const _: () = $crate::agent::check_agent_features(FEATURES);
+            $crate::agent::on_load::<$handler>(vm, options, FEATURES)
+        }
+
+        #[allow(non_snake_case)]
+        #[no_mangle]
+        pub extern "C" fn Agent_OnUnload(vm: *mut $crate::rvmti_sys::JavaVM) {
+            $crate::agent::on_unload(vm)
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const COMPILED_METHODS: AgentFeature = AgentFeature{
+        name: "compiled_methods",
+        required: capability::can_generate_compiled_method_load_events,
+        optional: capability::can_get_line_numbers | capability::can_get_source_file_name,
+        events: &[JvmtiEvent::CompiledMethodLoad, JvmtiEvent::CompiledMethodUnload],
+    };
+
+    const METHOD_TRACING: AgentFeature = AgentFeature{
+        name: "method_tracing",
+        required: capability::can_generate_method_entry_events | capability::can_generate_method_exit_events,
+        optional: 0,
+        events: &[JvmtiEvent::MethodEntry, JvmtiEvent::MethodExit],
+    };
+
+    const DYNAMIC_CODE: AgentFeature = AgentFeature{
+        name: "dynamic_code",
+        required: 0,
+        optional: 0,
+        events: &[JvmtiEvent::DynamicCodeGenerated],
+    };
+
+    #[test]
+    fn test_check_agent_features_valid() {
+        check_agent_features(&[COMPILED_METHODS, METHOD_TRACING, DYNAMIC_CODE]);
+        check_agent_features(&[]);
+    }
+
+    #[test]
+    #[should_panic(expected = "MethodEntry event requires can_generate_method_entry_events capability")]
+    fn test_check_agent_features_missing_capability() {
+        check_agent_features(&[AgentFeature{name: "tracing", required: capability::can_generate_method_exit_events,
+            optional: capability::can_generate_method_entry_events, events: &[JvmtiEvent::MethodExit, JvmtiEvent::MethodEntry]}]);
+    }
+
+    #[test]
+    fn test_negotiate_all_available() {
+        let potential = capability::can_generate_compiled_method_load_events | capability::can_get_line_numbers
+            | capability::can_get_source_file_name | capability::can_generate_method_entry_events
+            | capability::can_generate_method_exit_events | capability::can_tag_objects;
+        assert_eq!(negotiate(potential, &[COMPILED_METHODS, METHOD_TRACING, DYNAMIC_CODE]), Negotiation{
+            requested: potential & !capability::can_tag_objects,
+            enabled_features: vec!["compiled_methods", "method_tracing", "dynamic_code"],
+            denied_features: vec![],
+        });
+    }
+
+    #[test]
+    fn test_negotiate_degraded() {
+        let potential = capability::can_generate_compiled_method_load_events | capability::can_get_source_file_name
+            | capability::can_generate_method_exit_events;
+        assert_eq!(negotiate(potential, &[COMPILED_METHODS, METHOD_TRACING, DYNAMIC_CODE]), Negotiation{
+            requested: capability::can_generate_compiled_method_load_events | capability::can_get_source_file_name,
+            enabled_features: vec!["compiled_methods", "dynamic_code"],
+            denied_features: vec![("method_tracing", capability::can_generate_method_entry_events)],
+        });
+    }
+
+    #[test]
+    fn test_add_by_feature() {
+        let potential = capability::can_generate_compiled_method_load_events | capability::can_get_line_numbers
+            | capability::can_get_source_file_name | capability::can_generate_method_entry_events
+            | capability::can_generate_method_exit_events;
+        let features = [COMPILED_METHODS, METHOD_TRACING, DYNAMIC_CODE];
+        let refused = capability::can_get_line_numbers | capability::can_generate_method_exit_events;
+        let mut added = Vec::new();
+        let negotiation = add_by_feature(negotiate(potential, &features), &features, |mask| {
+            added.push(mask);
+            if mask & refused != 0 { Err("refused") } else { Ok(()) }
+        });
+        assert_eq!(negotiation, Negotiation{
+            requested: capability::can_generate_compiled_method_load_events | capability::can_get_source_file_name,
+            enabled_features: vec!["compiled_methods", "dynamic_code"],
+            denied_features: vec![("method_tracing", METHOD_TRACING.required)],
+        });
+        assert_eq!(added, vec![capability::can_generate_compiled_method_load_events, capability::can_get_source_file_name,
+                               capability::can_get_line_numbers, METHOD_TRACING.required]);
+    }
+
+    #[test]
+    fn test_capabilities_mask_round_trip() {
+        let mask = capability::can_tag_objects | capability::can_get_line_numbers
+            | capability::can_generate_object_free_events | capability::can_generate_sampled_object_alloc_events;
+        assert_eq!(JvmtiCapabilities::from_mask(mask).mask(), mask);
+        assert_eq!(capability_names(mask), vec!["can_tag_objects", "can_get_line_numbers",
+                                                "can_generate_object_free_events", "can_generate_sampled_object_alloc_events"]);
+    }
+
+}
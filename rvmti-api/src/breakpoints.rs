@@ -0,0 +1,62 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+// Breakpoints, require `can_generate_breakpoint_events` capability and are only available in the live phase.
+// Locations are bcis, one breakpoint per location and environment.
+
+use super::{JvmtiEnv, JMethodId, JLocation, JvmtiError};
+
+impl JvmtiEnv {
+
+    // Breakpoint event is sent when a thread reaches the location, fails with `Duplicate` if already set
+    pub fn set_breakpoint(&mut self, method: &JMethodId, location: JLocation) -> Result<(), JvmtiError> {
+        unsafe {
+            let result = (*(*self.env)).SetBreakpoint.unwrap()(self.env, method.method, location);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(());
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    // Fails with `NotFound` if there is no breakpoint at the location
+    pub fn clear_breakpoint(&mut self, method: &JMethodId, location: JLocation) -> Result<(), JvmtiError> {
+        unsafe {
+            let result = (*(*self.env)).ClearBreakpoint.unwrap()(self.env, method.method, location);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(());
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::mock::MockJvmti;
+
+    #[test]
+    fn test_breakpoints() {
+        let mock = MockJvmti::new();
+        let class = mock.add_class("Lcom/example/Worker;", Some("Worker.java"));
+        let run = mock.add_method(class, "run", "()V", None);
+        let mut env = JvmtiEnv::cons(mock.env_ptr());
+        let method = JMethodId::cons(run);
+        env.set_breakpoint(&method, 4).unwrap();
+        assert!(matches!(env.set_breakpoint(&method, 4), Err(JvmtiError::Duplicate)));
+        assert_eq!(mock.breakpoints(), vec![(run, 4)]);
+        env.clear_breakpoint(&method, 4).unwrap();
+        assert!(matches!(env.clear_breakpoint(&method, 4), Err(JvmtiError::NotFound)));
+        assert!(mock.breakpoints().is_empty());
+    }
+
+}
@@ -0,0 +1,881 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+
+// Java bytecode of methods and its decoding into instructions keyed by bci, the same index compiled method load
+// records and stack frames refer to. Bytecode returned by the VM is the original one, without VM internal rewrites.
+
+use std::fmt;
+
+use thiserror::Error;
+
+use super::{JvmtiEnv, JMethodId, JvmtiError, VmOwnedArray};
+
+macro_rules! opcodes {
+    ($($name:ident = $code:expr => $mnemonic:expr),* $(,)?) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[repr(u8)]
+        pub enum Opcode {
+            $($name = $code),*
+        }
+
+        impl Opcode {
+
+            pub fn from_u8(code: u8) -> Option<Opcode> {
+                match code {
+                    $($code => Some(Opcode::$name),)*
+                    _ => None,
+                }
+            }
+
+            pub fn mnemonic(self) -> &'static str {
+                match self {
+                    $(Opcode::$name => $mnemonic),*
+                }
+            }
+
+        }
+    }
+}
+
+opcodes! {
+    Nop = 0x00 => "nop",
+    AconstNull = 0x01 => "aconst_null",
+    IconstM1 = 0x02 => "iconst_m1",
+    Iconst0 = 0x03 => "iconst_0",
+    Iconst1 = 0x04 => "iconst_1",
+    Iconst2 = 0x05 => "iconst_2",
+    Iconst3 = 0x06 => "iconst_3",
+    Iconst4 = 0x07 => "iconst_4",
+    Iconst5 = 0x08 => "iconst_5",
+    Lconst0 = 0x09 => "lconst_0",
+    Lconst1 = 0x0a => "lconst_1",
+    Fconst0 = 0x0b => "fconst_0",
+    Fconst1 = 0x0c => "fconst_1",
+    Fconst2 = 0x0d => "fconst_2",
+    Dconst0 = 0x0e => "dconst_0",
+    Dconst1 = 0x0f => "dconst_1",
+    Bipush = 0x10 => "bipush",
+    Sipush = 0x11 => "sipush",
+    Ldc = 0x12 => "ldc",
+    LdcW = 0x13 => "ldc_w",
+    Ldc2W = 0x14 => "ldc2_w",
+    Iload = 0x15 => "iload",
+    Lload = 0x16 => "lload",
+    Fload = 0x17 => "fload",
+    Dload = 0x18 => "dload",
+    Aload = 0x19 => "aload",
+    Iload0 = 0x1a => "iload_0",
+    Iload1 = 0x1b => "iload_1",
+    Iload2 = 0x1c => "iload_2",
+    Iload3 = 0x1d => "iload_3",
+    Lload0 = 0x1e => "lload_0",
+    Lload1 = 0x1f => "lload_1",
+    Lload2 = 0x20 => "lload_2",
+    Lload3 = 0x21 => "lload_3",
+    Fload0 = 0x22 => "fload_0",
+    Fload1 = 0x23 => "fload_1",
+    Fload2 = 0x24 => "fload_2",
+    Fload3 = 0x25 => "fload_3",
+    Dload0 = 0x26 => "dload_0",
+    Dload1 = 0x27 => "dload_1",
+    Dload2 = 0x28 => "dload_2",
+    Dload3 = 0x29 => "dload_3",
+    Aload0 = 0x2a => "aload_0",
+    Aload1 = 0x2b => "aload_1",
+    Aload2 = 0x2c => "aload_2",
+    Aload3 = 0x2d => "aload_3",
+    Iaload = 0x2e => "iaload",
+    Laload = 0x2f => "laload",
+    Faload = 0x30 => "faload",
+    Daload = 0x31 => "daload",
+    Aaload = 0x32 => "aaload",
+    Baload = 0x33 => "baload",
+    Caload = 0x34 => "caload",
+    Saload = 0x35 => "saload",
+    Istore = 0x36 => "istore",
+    Lstore = 0x37 => "lstore",
+    Fstore = 0x38 => "fstore",
+    Dstore = 0x39 => "dstore",
+    Astore = 0x3a => "astore",
+    Istore0 = 0x3b => "istore_0",
+    Istore1 = 0x3c => "istore_1",
+    Istore2 = 0x3d => "istore_2",
+    Istore3 = 0x3e => "istore_3",
+    Lstore0 = 0x3f => "lstore_0",
+    Lstore1 = 0x40 => "lstore_1",
+    Lstore2 = 0x41 => "lstore_2",
+    Lstore3 = 0x42 => "lstore_3",
+    Fstore0 = 0x43 => "fstore_0",
+    Fstore1 = 0x44 => "fstore_1",
+    Fstore2 = 0x45 => "fstore_2",
+    Fstore3 = 0x46 => "fstore_3",
+    Dstore0 = 0x47 => "dstore_0",
+    Dstore1 = 0x48 => "dstore_1",
+    Dstore2 = 0x49 => "dstore_2",
+    Dstore3 = 0x4a => "dstore_3",
+    Astore0 = 0x4b => "astore_0",
+    Astore1 = 0x4c => "astore_1",
+    Astore2 = 0x4d => "astore_2",
+    Astore3 = 0x4e => "astore_3",
+    Iastore = 0x4f => "iastore",
+    Lastore = 0x50 => "lastore",
+    Fastore = 0x51 => "fastore",
+    Dastore = 0x52 => "dastore",
+    Aastore = 0x53 => "aastore",
+    Bastore = 0x54 => "bastore",
+    Castore = 0x55 => "castore",
+    Sastore = 0x56 => "sastore",
+    Pop = 0x57 => "pop",
+    Pop2 = 0x58 => "pop2",
+    Dup = 0x59 => "dup",
+    DupX1 = 0x5a => "dup_x1",
+    DupX2 = 0x5b => "dup_x2",
+    Dup2 = 0x5c => "dup2",
+    Dup2X1 = 0x5d => "dup2_x1",
+    Dup2X2 = 0x5e => "dup2_x2",
+    Swap = 0x5f => "swap",
+    Iadd = 0x60 => "iadd",
+    Ladd = 0x61 => "ladd",
+    Fadd = 0x62 => "fadd",
+    Dadd = 0x63 => "dadd",
+    Isub = 0x64 => "isub",
+    Lsub = 0x65 => "lsub",
+    Fsub = 0x66 => "fsub",
+    Dsub = 0x67 => "dsub",
+    Imul = 0x68 => "imul",
+    Lmul = 0x69 => "lmul",
+    Fmul = 0x6a => "fmul",
+    Dmul = 0x6b => "dmul",
+    Idiv = 0x6c => "idiv",
+    Ldiv = 0x6d => "ldiv",
+    Fdiv = 0x6e => "fdiv",
+    Ddiv = 0x6f => "ddiv",
+    Irem = 0x70 => "irem",
+    Lrem = 0x71 => "lrem",
+    Frem = 0x72 => "frem",
+    Drem = 0x73 => "drem",
+    Ineg = 0x74 => "ineg",
+    Lneg = 0x75 => "lneg",
+    Fneg = 0x76 => "fneg",
+    Dneg = 0x77 => "dneg",
+    Ishl = 0x78 => "ishl",
+    Lshl = 0x79 => "lshl",
+    Ishr = 0x7a => "ishr",
+    Lshr = 0x7b => "lshr",
+    Iushr = 0x7c => "iushr",
+    Lushr = 0x7d => "lushr",
+    Iand = 0x7e => "iand",
+    Land = 0x7f => "land",
+    Ior = 0x80 => "ior",
+    Lor = 0x81 => "lor",
+    Ixor = 0x82 => "ixor",
+    Lxor = 0x83 => "lxor",
+    Iinc = 0x84 => "iinc",
+    I2l = 0x85 => "i2l",
+    I2f = 0x86 => "i2f",
+    I2d = 0x87 => "i2d",
+    L2i = 0x88 => "l2i",
+    L2f = 0x89 => "l2f",
+    L2d = 0x8a => "l2d",
+    F2i = 0x8b => "f2i",
+    F2l = 0x8c => "f2l",
+    F2d = 0x8d => "f2d",
+    D2i = 0x8e => "d2i",
+    D2l = 0x8f => "d2l",
+    D2f = 0x90 => "d2f",
+    I2b = 0x91 => "i2b",
+    I2c = 0x92 => "i2c",
+    I2s = 0x93 => "i2s",
+    Lcmp = 0x94 => "lcmp",
+    Fcmpl = 0x95 => "fcmpl",
+    Fcmpg = 0x96 => "fcmpg",
+    Dcmpl = 0x97 => "dcmpl",
+    Dcmpg = 0x98 => "dcmpg",
+    Ifeq = 0x99 => "ifeq",
+    Ifne = 0x9a => "ifne",
+    Iflt = 0x9b => "iflt",
+    Ifge = 0x9c => "ifge",
+    Ifgt = 0x9d => "ifgt",
+    Ifle = 0x9e => "ifle",
+    IfIcmpeq = 0x9f => "if_icmpeq",
+    IfIcmpne = 0xa0 => "if_icmpne",
+    IfIcmplt = 0xa1 => "if_icmplt",
+    IfIcmpge = 0xa2 => "if_icmpge",
+    IfIcmpgt = 0xa3 => "if_icmpgt",
+    IfIcmple = 0xa4 => "if_icmple",
+    IfAcmpeq = 0xa5 => "if_acmpeq",
+    IfAcmpne = 0xa6 => "if_acmpne",
+    Goto = 0xa7 => "goto",
+    Jsr = 0xa8 => "jsr",
+    Ret = 0xa9 => "ret",
+    Tableswitch = 0xaa => "tableswitch",
+    Lookupswitch = 0xab => "lookupswitch",
+    Ireturn = 0xac => "ireturn",
+    Lreturn = 0xad => "lreturn",
+    Freturn = 0xae => "freturn",
+    Dreturn = 0xaf => "dreturn",
+    Areturn = 0xb0 => "areturn",
+    Return = 0xb1 => "return",
+    Getstatic = 0xb2 => "getstatic",
+    Putstatic = 0xb3 => "putstatic",
+    Getfield = 0xb4 => "getfield",
+    Putfield = 0xb5 => "putfield",
+    Invokevirtual = 0xb6 => "invokevirtual",
+    Invokespecial = 0xb7 => "invokespecial",
+    Invokestatic = 0xb8 => "invokestatic",
+    Invokeinterface = 0xb9 => "invokeinterface",
+    Invokedynamic = 0xba => "invokedynamic",
+    New = 0xbb => "new",
+    Newarray = 0xbc => "newarray",
+    Anewarray = 0xbd => "anewarray",
+    Arraylength = 0xbe => "arraylength",
+    Athrow = 0xbf => "athrow",
+    Checkcast = 0xc0 => "checkcast",
+    Instanceof = 0xc1 => "instanceof",
+    Monitorenter = 0xc2 => "monitorenter",
+    Monitorexit = 0xc3 => "monitorexit",
+    Wide = 0xc4 => "wide",
+    Multianewarray = 0xc5 => "multianewarray",
+    Ifnull = 0xc6 => "ifnull",
+    Ifnonnull = 0xc7 => "ifnonnull",
+    GotoW = 0xc8 => "goto_w",
+    JsrW = 0xc9 => "jsr_w",
+    Breakpoint = 0xca => "breakpoint",
+    Impdep1 = 0xfe => "impdep1",
+    Impdep2 = 0xff => "impdep2",
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operands {
+    None,
+    Byte(i8),
+    Short(i16),
+    Local(u16),
+    // Constant pool index, also of invokedynamic
+    ConstantPool(u16),
+    Increment { local: u16, value: i16 },
+    // Branch and switch targets are absolute bcis
+    Branch(u32),
+    InvokeInterface { index: u16, count: u8 },
+    ArrayType(u8),
+    MultiANewArray { index: u16, dimensions: u8 },
+    TableSwitch { default: u32, low: i32, targets: Vec<u32> },
+    LookupSwitch { default: u32, pairs: Vec<(i32, u32)> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub bci: u32,
+    // Instruction modified by a `wide` prefix has the modified opcode and `wide` set
+    pub opcode: Opcode,
+    pub wide: bool,
+    pub operands: Operands,
+    pub length: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disassembly {
+    instructions: Vec<Instruction>,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum BytecodeError {
+    #[error("Instruction at bci {bci} is truncated")]
+    Truncated { bci: usize },
+    #[error("Unknown opcode 0x{opcode:02x} at bci {bci}")]
+    UnknownOpcode { bci: usize, opcode: u8 },
+    #[error("Opcode 0x{opcode:02x} at bci {bci} can not be modified by wide")]
+    InvalidWide { bci: usize, opcode: u8 },
+    #[error("Branch at bci {bci} leaves the method")]
+    BranchOutOfRange { bci: usize },
+    #[error("Invalid switch at bci {bci}")]
+    InvalidSwitch { bci: usize },
+    #[error("Branch at bci {bci} does not fit its offset")]
+    BranchOffsetOverflow { bci: usize },
+    #[error("Operands do not match opcode {opcode} at bci {bci}")]
+    InvalidOperands { bci: usize, opcode: Opcode },
+}
+
+struct Reader<'a> {
+    code: &'a [u8],
+    position: usize,
+    // Start of the instruction being read
+    bci: usize,
+}
+
+impl<'a> Reader<'a> {
+
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], BytecodeError> {
+        if self.code.len() - self.position < count {
+            return Err(BytecodeError::Truncated{bci: self.bci});
+        }
+        let bytes = &self.code[self.position..self.position + count];
+        self.position += count;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, BytecodeError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, BytecodeError> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn i32(&mut self) -> Result<i32, BytecodeError> {
+        let bytes = self.bytes(4)?;
+        Ok(i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn remaining(&self) -> usize {
+        self.code.len() - self.position
+    }
+
+    fn target(&self, offset: i32) -> Result<u32, BytecodeError> {
+        let target = self.bci as i64 + offset as i64;
+        if target < 0 || target >= self.code.len() as i64 {
+            return Err(BytecodeError::BranchOutOfRange{bci: self.bci});
+        }
+        Ok(target as u32)
+    }
+
+    fn wide_target(&mut self) -> Result<u32, BytecodeError> {
+        let offset = self.i32()?;
+        self.target(offset)
+    }
+
+    // Switch operands are aligned to four bytes from the method start
+    fn skip_padding(&mut self) -> Result<(), BytecodeError> {
+        let padding = (4 - self.position % 4) % 4;
+        self.bytes(padding).map(|_| ())
+    }
+
+}
+
+impl Disassembly {
+
+    pub fn decode(code: &[u8]) -> Result<Disassembly, BytecodeError> {
+        let mut reader = Reader{code, position: 0, bci: 0};
+        let mut instructions = Vec::new();
+        while reader.position < code.len() {
+            reader.bci = reader.position;
+            instructions.push(decode_instruction(&mut reader)?);
+        }
+        Ok(Disassembly{instructions})
+    }
+
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    // Bcis in compiled method records and stack frames point at instruction starts, others have no instruction
+    pub fn instruction_at(&self, bci: i64) -> Option<&Instruction> {
+        self.instructions.binary_search_by_key(&bci, |i| i.bci as i64).ok().map(|index| &self.instructions[index])
+    }
+
+}
+
+impl Instruction {
+
+    // Instruction at bci zero, `wide` is set when operands need it
+    pub fn new(opcode: Opcode, operands: Operands) -> Instruction {
+        let wide = match operands {
+            Operands::Local(local) => local > 0xff,
+            Operands::Increment{local, value} => local > 0xff || value < i8::MIN as i16 || value > i8::MAX as i16,
+            _ => false,
+        };
+        let mut instruction = Instruction{bci: 0, opcode, wide, operands, length: 0};
+        instruction.length = instruction.encoded_length(0);
+        instruction
+    }
+
+    // Switch padding depends on the bci the instruction is placed at
+    pub fn encoded_length(&self, bci: u32) -> u32 {
+        let padding = 3 - bci % 4;
+        let operands_length = match self.operands {
+            Operands::None => 0,
+            Operands::Byte(_) | Operands::ArrayType(_) => 1,
+            Operands::Short(_) => 2,
+            Operands::ConstantPool(_) => match self.opcode {
+                Opcode::Ldc => 1,
+                Opcode::Invokedynamic => 4,
+                _ => 2,
+            },
+            Operands::Local(_) => if self.wide { 2 } else { 1 },
+            Operands::Increment{..} => if self.wide { 4 } else { 2 },
+            Operands::Branch(_) => match self.opcode {
+                Opcode::GotoW | Opcode::JsrW => 4,
+                _ => 2,
+            },
+            Operands::InvokeInterface{..} => 4,
+            Operands::MultiANewArray{..} => 3,
+            Operands::TableSwitch{ref targets, ..} => padding + 12 + 4 * targets.len() as u32,
+            Operands::LookupSwitch{ref pairs, ..} => padding + 8 + 8 * pairs.len() as u32,
+        };
+        1 + self.wide as u32 + operands_length
+    }
+
+    // Appends the instruction to code, placing it at bci equal to the code length. Branch targets are absolute
+    pub fn encode(&self, code: &mut Vec<u8>) -> Result<(), BytecodeError> {
+        let bci = code.len();
+        if self.wide {
+            code.push(Opcode::Wide as u8);
+        }
+        code.push(self.opcode as u8);
+        self.encode_operands(bci, code)
+    }
+
+    fn encode_operands(&self, bci: usize, code: &mut Vec<u8>) -> Result<(), BytecodeError> {
+        let invalid = || BytecodeError::InvalidOperands{bci, opcode: self.opcode};
+        let offset = |target: u32| target as i64 - bci as i64;
+        match (self.opcode, &self.operands) {
+            (Opcode::Bipush, &Operands::Byte(value)) => code.push(value as u8),
+            (Opcode::Sipush, &Operands::Short(value)) => code.extend_from_slice(&value.to_be_bytes()),
+            (Opcode::Ldc, &Operands::ConstantPool(index)) => {
+                if index > 0xff {
+                    return Err(invalid());
+                }
+                code.push(index as u8);
+            },
+            (Opcode::LdcW, &Operands::ConstantPool(index)) | (Opcode::Ldc2W, &Operands::ConstantPool(index))
+            | (Opcode::Getstatic, &Operands::ConstantPool(index)) | (Opcode::Putstatic, &Operands::ConstantPool(index))
+            | (Opcode::Getfield, &Operands::ConstantPool(index)) | (Opcode::Putfield, &Operands::ConstantPool(index))
+            | (Opcode::Invokevirtual, &Operands::ConstantPool(index)) | (Opcode::Invokespecial, &Operands::ConstantPool(index))
+            | (Opcode::Invokestatic, &Operands::ConstantPool(index)) | (Opcode::New, &Operands::ConstantPool(index))
+            | (Opcode::Anewarray, &Operands::ConstantPool(index)) | (Opcode::Checkcast, &Operands::ConstantPool(index))
+            | (Opcode::Instanceof, &Operands::ConstantPool(index)) => code.extend_from_slice(&index.to_be_bytes()),
+            (Opcode::Invokedynamic, &Operands::ConstantPool(index)) => {
+                code.extend_from_slice(&index.to_be_bytes());
+                code.extend_from_slice(&[0, 0]);
+            },
+            (Opcode::Invokeinterface, &Operands::InvokeInterface{index, count}) => {
+                code.extend_from_slice(&index.to_be_bytes());
+                code.extend_from_slice(&[count, 0]);
+            },
+            (Opcode::Iload, &Operands::Local(local)) | (Opcode::Lload, &Operands::Local(local))
+            | (Opcode::Fload, &Operands::Local(local)) | (Opcode::Dload, &Operands::Local(local))
+            | (Opcode::Aload, &Operands::Local(local)) | (Opcode::Istore, &Operands::Local(local))
+            | (Opcode::Lstore, &Operands::Local(local)) | (Opcode::Fstore, &Operands::Local(local))
+            | (Opcode::Dstore, &Operands::Local(local)) | (Opcode::Astore, &Operands::Local(local))
+            | (Opcode::Ret, &Operands::Local(local)) => {
+                if self.wide {
+                    code.extend_from_slice(&local.to_be_bytes());
+                } else if local <= 0xff {
+                    code.push(local as u8);
+                } else {
+                    return Err(invalid());
+                }
+            },
+            (Opcode::Iinc, &Operands::Increment{local, value}) => {
+                if self.wide {
+                    code.extend_from_slice(&local.to_be_bytes());
+                    code.extend_from_slice(&value.to_be_bytes());
+                } else if local <= 0xff && value >= i8::MIN as i16 && value <= i8::MAX as i16 {
+                    code.push(local as u8);
+                    code.push(value as i8 as u8);
+                } else {
+                    return Err(invalid());
+                }
+            },
+            (Opcode::Ifeq, &Operands::Branch(target)) | (Opcode::Ifne, &Operands::Branch(target))
+            | (Opcode::Iflt, &Operands::Branch(target)) | (Opcode::Ifge, &Operands::Branch(target))
+            | (Opcode::Ifgt, &Operands::Branch(target)) | (Opcode::Ifle, &Operands::Branch(target))
+            | (Opcode::IfIcmpeq, &Operands::Branch(target)) | (Opcode::IfIcmpne, &Operands::Branch(target))
+            | (Opcode::IfIcmplt, &Operands::Branch(target)) | (Opcode::IfIcmpge, &Operands::Branch(target))
+            | (Opcode::IfIcmpgt, &Operands::Branch(target)) | (Opcode::IfIcmple, &Operands::Branch(target))
+            | (Opcode::IfAcmpeq, &Operands::Branch(target)) | (Opcode::IfAcmpne, &Operands::Branch(target))
+            | (Opcode::Goto, &Operands::Branch(target)) | (Opcode::Jsr, &Operands::Branch(target))
+            | (Opcode::Ifnull, &Operands::Branch(target)) | (Opcode::Ifnonnull, &Operands::Branch(target)) => {
+                let offset = offset(target);
+                if offset < i16::MIN as i64 || offset > i16::MAX as i64 {
+                    return Err(BytecodeError::BranchOffsetOverflow{bci});
+                }
+                code.extend_from_slice(&(offset as i16).to_be_bytes());
+            },
+            (Opcode::GotoW, &Operands::Branch(target)) | (Opcode::JsrW, &Operands::Branch(target)) => {
+                code.extend_from_slice(&(offset(target) as i32).to_be_bytes());
+            },
+            (Opcode::Newarray, &Operands::ArrayType(array_type)) => code.push(array_type),
+            (Opcode::Multianewarray, &Operands::MultiANewArray{index, dimensions}) => {
+                code.extend_from_slice(&index.to_be_bytes());
+                code.push(dimensions);
+            },
+            (Opcode::Tableswitch, &Operands::TableSwitch{default, low, ref targets}) => {
+                if targets.is_empty() {
+                    return Err(invalid());
+                }
+                code.resize(code.len() + (3 - bci % 4), 0);
+                code.extend_from_slice(&(offset(default) as i32).to_be_bytes());
+                code.extend_from_slice(&low.to_be_bytes());
+                code.extend_from_slice(&(low as i64 + targets.len() as i64 - 1).to_be_bytes()[4..]);
+                for &target in targets.iter() {
+                    code.extend_from_slice(&(offset(target) as i32).to_be_bytes());
+                }
+            },
+            (Opcode::Lookupswitch, &Operands::LookupSwitch{default, ref pairs}) => {
+                code.resize(code.len() + (3 - bci % 4), 0);
+                code.extend_from_slice(&(offset(default) as i32).to_be_bytes());
+                code.extend_from_slice(&(pairs.len() as i32).to_be_bytes());
+                for &(key, target) in pairs.iter() {
+                    code.extend_from_slice(&key.to_be_bytes());
+                    code.extend_from_slice(&(offset(target) as i32).to_be_bytes());
+                }
+            },
+            (opcode, &Operands::None) if !self.wide && takes_no_operands(opcode) => {},
+            _ => return Err(invalid()),
+        }
+        Ok(())
+    }
+
+}
+
+// Opcodes encoded as the opcode byte alone, others always carry operands
+fn takes_no_operands(opcode: Opcode) -> bool {
+    matches!(opcode,
+        Opcode::Nop | Opcode::AconstNull | Opcode::IconstM1 | Opcode::Iconst0 | Opcode::Iconst1 | Opcode::Iconst2
+        | Opcode::Iconst3 | Opcode::Iconst4 | Opcode::Iconst5 | Opcode::Lconst0 | Opcode::Lconst1 | Opcode::Fconst0
+        | Opcode::Fconst1 | Opcode::Fconst2 | Opcode::Dconst0 | Opcode::Dconst1 | Opcode::Iload0 | Opcode::Iload1
+        | Opcode::Iload2 | Opcode::Iload3 | Opcode::Lload0 | Opcode::Lload1 | Opcode::Lload2 | Opcode::Lload3
+        | Opcode::Fload0 | Opcode::Fload1 | Opcode::Fload2 | Opcode::Fload3 | Opcode::Dload0 | Opcode::Dload1
+        | Opcode::Dload2 | Opcode::Dload3 | Opcode::Aload0 | Opcode::Aload1 | Opcode::Aload2 | Opcode::Aload3
+        | Opcode::Iaload | Opcode::Laload | Opcode::Faload | Opcode::Daload | Opcode::Aaload | Opcode::Baload
+        | Opcode::Caload | Opcode::Saload | Opcode::Istore0 | Opcode::Istore1 | Opcode::Istore2 | Opcode::Istore3
+        | Opcode::Lstore0 | Opcode::Lstore1 | Opcode::Lstore2 | Opcode::Lstore3 | Opcode::Fstore0 | Opcode::Fstore1
+        | Opcode::Fstore2 | Opcode::Fstore3 | Opcode::Dstore0 | Opcode::Dstore1 | Opcode::Dstore2 | Opcode::Dstore3
+        | Opcode::Astore0 | Opcode::Astore1 | Opcode::Astore2 | Opcode::Astore3 | Opcode::Iastore | Opcode::Lastore
+        | Opcode::Fastore | Opcode::Dastore | Opcode::Aastore | Opcode::Bastore | Opcode::Castore | Opcode::Sastore
+        | Opcode::Pop | Opcode::Pop2 | Opcode::Dup | Opcode::DupX1 | Opcode::DupX2 | Opcode::Dup2 | Opcode::Dup2X1
+        | Opcode::Dup2X2 | Opcode::Swap | Opcode::Iadd | Opcode::Ladd | Opcode::Fadd | Opcode::Dadd | Opcode::Isub
+        | Opcode::Lsub | Opcode::Fsub | Opcode::Dsub | Opcode::Imul | Opcode::Lmul | Opcode::Fmul | Opcode::Dmul
+        | Opcode::Idiv | Opcode::Ldiv | Opcode::Fdiv | Opcode::Ddiv | Opcode::Irem | Opcode::Lrem | Opcode::Frem
+        | Opcode::Drem | Opcode::Ineg | Opcode::Lneg | Opcode::Fneg | Opcode::Dneg | Opcode::Ishl | Opcode::Lshl
+        | Opcode::Ishr | Opcode::Lshr | Opcode::Iushr | Opcode::Lushr | Opcode::Iand | Opcode::Land | Opcode::Ior
+        | Opcode::Lor | Opcode::Ixor | Opcode::Lxor | Opcode::I2l | Opcode::I2f | Opcode::I2d | Opcode::L2i
+        | Opcode::L2f | Opcode::L2d | Opcode::F2i | Opcode::F2l | Opcode::F2d | Opcode::D2i | Opcode::D2l
+        | Opcode::D2f | Opcode::I2b | Opcode::I2c | Opcode::I2s | Opcode::Lcmp | Opcode::Fcmpl | Opcode::Fcmpg
+        | Opcode::Dcmpl | Opcode::Dcmpg | Opcode::Ireturn | Opcode::Lreturn | Opcode::Freturn | Opcode::Dreturn
+        | Opcode::Areturn | Opcode::Return | Opcode::Arraylength | Opcode::Athrow | Opcode::Monitorenter
+        | Opcode::Monitorexit | Opcode::Breakpoint | Opcode::Impdep1 | Opcode::Impdep2)
+}
+
+fn decode_instruction(reader: &mut Reader) -> Result<Instruction, BytecodeError> {
+    let bci = reader.bci;
+    let code = reader.u8()?;
+    let opcode = Opcode::from_u8(code).ok_or(BytecodeError::UnknownOpcode{bci, opcode: code})?;
+    let (opcode, wide, operands) = if opcode == Opcode::Wide {
+        let code = reader.u8()?;
+        let opcode = Opcode::from_u8(code).ok_or(BytecodeError::UnknownOpcode{bci, opcode: code})?;
+        let operands = match opcode {
+            Opcode::Iload | Opcode::Lload | Opcode::Fload | Opcode::Dload | Opcode::Aload | Opcode::Istore
+            | Opcode::Lstore | Opcode::Fstore | Opcode::Dstore | Opcode::Astore | Opcode::Ret => Operands::Local(reader.u16()?),
+            Opcode::Iinc => Operands::Increment{local: reader.u16()?, value: reader.u16()? as i16},
+            _ => return Err(BytecodeError::InvalidWide{bci, opcode: code}),
+        };
+        (opcode, true, operands)
+    } else {
+        (opcode, false, decode_operands(reader, opcode)?)
+    };
+    Ok(Instruction{bci: bci as u32, opcode, wide, operands, length: (reader.position - bci) as u32})
+}
+
+fn decode_operands(reader: &mut Reader, opcode: Opcode) -> Result<Operands, BytecodeError> {
+    let operands = match opcode {
+        Opcode::Bipush => Operands::Byte(reader.u8()? as i8),
+        Opcode::Sipush => Operands::Short(reader.u16()? as i16),
+        Opcode::Ldc => Operands::ConstantPool(reader.u8()? as u16),
+        Opcode::LdcW | Opcode::Ldc2W | Opcode::Getstatic | Opcode::Putstatic | Opcode::Getfield | Opcode::Putfield
+        | Opcode::Invokevirtual | Opcode::Invokespecial | Opcode::Invokestatic | Opcode::New | Opcode::Anewarray
+        | Opcode::Checkcast | Opcode::Instanceof => Operands::ConstantPool(reader.u16()?),
+        Opcode::Invokedynamic => {
+            let index = reader.u16()?;
+            reader.bytes(2)?;
+            Operands::ConstantPool(index)
+        },
+        Opcode::Invokeinterface => {
+            let index = reader.u16()?;
+            let count = reader.u8()?;
+            reader.u8()?;
+            Operands::InvokeInterface{index, count}
+        },
+        Opcode::Iload | Opcode::Lload | Opcode::Fload | Opcode::Dload | Opcode::Aload | Opcode::Istore
+        | Opcode::Lstore | Opcode::Fstore | Opcode::Dstore | Opcode::Astore | Opcode::Ret => Operands::Local(reader.u8()? as u16),
+        Opcode::Iinc => Operands::Increment{local: reader.u8()? as u16, value: reader.u8()? as i8 as i16},
+        Opcode::Ifeq | Opcode::Ifne | Opcode::Iflt | Opcode::Ifge | Opcode::Ifgt | Opcode::Ifle | Opcode::IfIcmpeq
+        | Opcode::IfIcmpne | Opcode::IfIcmplt | Opcode::IfIcmpge | Opcode::IfIcmpgt | Opcode::IfIcmple
+        | Opcode::IfAcmpeq | Opcode::IfAcmpne | Opcode::Goto | Opcode::Jsr | Opcode::Ifnull | Opcode::Ifnonnull => {
+            let offset = reader.u16()? as i16;
+            Operands::Branch(reader.target(offset as i32)?)
+        },
+        Opcode::GotoW | Opcode::JsrW => {
+            Operands::Branch(reader.wide_target()?)
+        },
+        Opcode::Newarray => Operands::ArrayType(reader.u8()?),
+        Opcode::Multianewarray => Operands::MultiANewArray{index: reader.u16()?, dimensions: reader.u8()?},
+        Opcode::Tableswitch => {
+            reader.skip_padding()?;
+            let default = reader.wide_target()?;
+            let low = reader.i32()?;
+            let high = reader.i32()?;
+            if high < low {
+                return Err(BytecodeError::InvalidSwitch{bci: reader.bci});
+            }
+            let count = (high as i64 - low as i64 + 1) as usize;
+            // Checked upfront, a corrupt count must not cause a huge allocation
+            if reader.remaining() / 4 < count {
+                return Err(BytecodeError::Truncated{bci: reader.bci});
+            }
+            let targets = (0..count).map(|_| reader.wide_target())
+                .collect::<Result<Vec<u32>, BytecodeError>>()?;
+            Operands::TableSwitch{default, low, targets}
+        },
+        Opcode::Lookupswitch => {
+            reader.skip_padding()?;
+            let default = reader.wide_target()?;
+            let count = reader.i32()?;
+            if count < 0 {
+                return Err(BytecodeError::InvalidSwitch{bci: reader.bci});
+            }
+            if reader.remaining() / 8 < count as usize {
+                return Err(BytecodeError::Truncated{bci: reader.bci});
+            }
+            let pairs = (0..count).map(|_| {
+                let key = reader.i32()?;
+                Ok((key, reader.wide_target()?))
+            }).collect::<Result<Vec<(i32, u32)>, BytecodeError>>()?;
+            Operands::LookupSwitch{default, pairs}
+        },
+        _ => Operands::None,
+    };
+    Ok(operands)
+}
+
+impl fmt::Display for Opcode {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.mnemonic())
+    }
+
+}
+
+impl fmt::Display for Instruction {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.wide {
+            write!(f, "wide ")?;
+        }
+        write!(f, "{}", self.opcode)?;
+        match self.operands {
+            Operands::None => Ok(()),
+            Operands::Byte(value) => write!(f, " {}", value),
+            Operands::Short(value) => write!(f, " {}", value),
+            Operands::Local(index) => write!(f, " {}", index),
+            Operands::ConstantPool(index) => write!(f, " #{}", index),
+            Operands::Increment{local, value} => write!(f, " {}, {}", local, value),
+            Operands::Branch(target) => write!(f, " {}", target),
+            Operands::InvokeInterface{index, count} => write!(f, " #{}, {}", index, count),
+            Operands::ArrayType(array_type) => match array_type_name(array_type) {
+                Some(name) => write!(f, " {}", name),
+                None => write!(f, " {}", array_type),
+            },
+            Operands::MultiANewArray{index, dimensions} => write!(f, " #{}, {}", index, dimensions),
+            Operands::TableSwitch{default, low, ref targets} => {
+                write!(f, " {{")?;
+                for (key, target) in (low as i64..).zip(targets.iter()) {
+                    write!(f, " {}: {},", key, target)?;
+                }
+                write!(f, " default: {} }}", default)
+            },
+            Operands::LookupSwitch{default, ref pairs} => {
+                write!(f, " {{")?;
+                for (key, target) in pairs.iter() {
+                    write!(f, " {}: {},", key, target)?;
+                }
+                write!(f, " default: {} }}", default)
+            },
+        }
+    }
+
+}
+
+impl fmt::Display for Disassembly {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for instruction in self.instructions.iter() {
+            writeln!(f, "{}: {}", instruction.bci, instruction)?;
+        }
+        Ok(())
+    }
+
+}
+
+fn array_type_name(array_type: u8) -> Option<&'static str> {
+    match array_type {
+        4 => Some("boolean"),
+        5 => Some("char"),
+        6 => Some("float"),
+        7 => Some("double"),
+        8 => Some("byte"),
+        9 => Some("short"),
+        10 => Some("int"),
+        11 => Some("long"),
+        _ => None,
+    }
+}
+
+impl JvmtiEnv {
+
+    // Requires `can_get_bytecodes` capability, native methods have no bytecode
+    pub fn get_bytecodes(&mut self, method: &JMethodId) -> Result<Vec<u8>, JvmtiError> {
+        unsafe {
+            let mut count: rvmti_sys::jint = 0;
+            let mut bytecodes_ptr: *mut u8 = std::ptr::null_mut();
+            let result = (*(*self.env)).GetBytecodes.unwrap()(self.env, method.method, &mut count, &mut bytecodes_ptr);
+            let bytecodes = VmOwnedArray{ptr: bytecodes_ptr, count, env: self};
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(bytecodes.as_slice().to_vec());
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::mock::MockJvmti;
+
+    fn mnemonics(disassembly: &Disassembly) -> Vec<String> {
+        disassembly.instructions().iter().map(|i| format!("{}: {}", i.bci, i)).collect()
+    }
+
+    #[test]
+    fn test_decode_operands() {
+        let code = [0x2a, 0xb4, 0x00, 0x02, 0x04, 0x60, 0x10, 0xfb, 0x11, 0x01, 0x2c, 0xb9, 0x00, 0x07, 0x02, 0x00,
+            0x99, 0xff, 0xf0, 0xc8, 0xff, 0xff, 0xff, 0xed, 0xbc, 0x0a, 0xc5, 0x00, 0x03, 0x02, 0xba, 0x00, 0x09, 0x00,
+            0x00, 0xb1];
+        let disassembly = Disassembly::decode(&code).unwrap();
+        assert_eq!(mnemonics(&disassembly), vec!["0: aload_0", "1: getfield #2", "4: iconst_1", "5: iadd", "6: bipush -5",
+            "8: sipush 300", "11: invokeinterface #7, 2", "16: ifeq 0", "19: goto_w 0", "24: newarray int",
+            "26: multianewarray #3, 2", "30: invokedynamic #9", "35: return"]);
+        assert_eq!(disassembly.instructions()[2].opcode, Opcode::Iconst1);
+        assert_eq!(disassembly.instructions()[6].operands, Operands::InvokeInterface{index: 7, count: 2});
+        assert_eq!(disassembly.instructions().iter().map(|i| i.length).sum::<u32>(), code.len() as u32);
+        assert_eq!(disassembly.instruction_at(11).map(|i| i.opcode), Some(Opcode::Invokeinterface));
+        assert_eq!(disassembly.instruction_at(12), None);
+        assert_eq!(disassembly.instruction_at(-1), None);
+        assert_eq!(disassembly.to_string().lines().next(), Some("0: aload_0"));
+    }
+
+    #[test]
+    fn test_decode_wide() {
+        let code = [0xc4, 0x15, 0x01, 0x2c, 0xc4, 0x84, 0x01, 0x2c, 0xfc, 0x18, 0x84, 0x01, 0xff, 0xc4, 0xa9, 0x00, 0x02,
+            0xb1];
+        let disassembly = Disassembly::decode(&code).unwrap();
+        assert_eq!(mnemonics(&disassembly), vec!["0: wide iload 300", "4: wide iinc 300, -1000", "10: iinc 1, -1",
+            "13: wide ret 2", "17: return"]);
+        assert_eq!(disassembly.instructions()[1], Instruction{bci: 4, opcode: Opcode::Iinc, wide: true,
+            operands: Operands::Increment{local: 300, value: -1000}, length: 6});
+    }
+
+    #[test]
+    fn test_decode_switches() {
+        let table = [0x1b, 0xaa, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1b, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+            0x00, 0x00, 0x00, 0x17, 0x00, 0x00, 0x00, 0x19, 0x03, 0xac, 0x04, 0xac, 0x02, 0xac];
+        let disassembly = Disassembly::decode(&table).unwrap();
+        assert_eq!(disassembly.instructions()[1].operands, Operands::TableSwitch{default: 28, low: 0, targets: vec![24, 26]});
+        assert_eq!(disassembly.instructions()[1].length, 23);
+        assert_eq!(disassembly.instructions()[1].to_string(), "tableswitch { 0: 24, 1: 26, default: 28 }");
+        let lookup = [0x00, 0x00, 0x1b, 0xab, 0x00, 0x00, 0x00, 0x1d, 0x00, 0x00, 0x00, 0x02, 0xff, 0xff, 0xff, 0xff,
+            0x00, 0x00, 0x00, 0x19, 0x00, 0x00, 0x00, 0x64, 0x00, 0x00, 0x00, 0x1b, 0x03, 0xac, 0x04, 0xac, 0x02, 0xac];
+        let disassembly = Disassembly::decode(&lookup).unwrap();
+        assert_eq!(disassembly.instructions()[3].operands, Operands::LookupSwitch{default: 32, pairs: vec![(-1, 28), (100, 30)]});
+        assert_eq!(disassembly.instructions()[3].to_string(), "lookupswitch { -1: 28, 100: 30, default: 32 }");
+        assert_eq!(disassembly.instruction_at(28).map(|i| i.opcode), Some(Opcode::Iconst0));
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        let codes: [&[u8]; 3] = [
+            &[0x2a, 0xb4, 0x00, 0x02, 0x04, 0x60, 0x10, 0xfb, 0x11, 0x01, 0x2c, 0xb9, 0x00, 0x07, 0x02, 0x00, 0x99, 0xff,
+              0xf0, 0xc8, 0xff, 0xff, 0xff, 0xed, 0xbc, 0x0a, 0xc5, 0x00, 0x03, 0x02, 0xba, 0x00, 0x09, 0x00, 0x00, 0xb1],
+            &[0xc4, 0x15, 0x01, 0x2c, 0xc4, 0x84, 0x01, 0x2c, 0xfc, 0x18, 0x84, 0x01, 0xff, 0xc4, 0xa9, 0x00, 0x02, 0xb1],
+            &[0x00, 0x00, 0x1b, 0xab, 0x00, 0x00, 0x00, 0x1d, 0x00, 0x00, 0x00, 0x02, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00,
+              0x00, 0x19, 0x00, 0x00, 0x00, 0x64, 0x00, 0x00, 0x00, 0x1b, 0x03, 0xac, 0x04, 0xac, 0x02, 0xac],
+        ];
+        for code in codes.iter() {
+            let mut encoded = Vec::new();
+            for instruction in Disassembly::decode(code).unwrap().instructions() {
+                assert_eq!(instruction.encoded_length(encoded.len() as u32), instruction.length);
+                instruction.encode(&mut encoded).unwrap();
+            }
+            assert_eq!(&encoded[..], *code);
+        }
+        // Switch moved by one byte loses one byte of padding, short branches are range checked
+        let switch = Instruction{bci: 0, opcode: Opcode::Tableswitch, wide: false,
+                                 operands: Operands::TableSwitch{default: 20, low: 0, targets: vec![20]}, length: 0};
+        assert_eq!((switch.encoded_length(0), switch.encoded_length(1)), (20, 19));
+        let mut code = vec![0x00];
+        switch.encode(&mut code).unwrap();
+        assert_eq!(code.len(), 20);
+        assert_eq!(Instruction::new(Opcode::Goto, Operands::Branch(40000)).encode(&mut Vec::new()),
+                   Err(BytecodeError::BranchOffsetOverflow{bci: 0}));
+        assert!(Instruction::new(Opcode::Iload, Operands::Local(300)).wide);
+        assert_eq!(Instruction::new(Opcode::Ldc, Operands::ConstantPool(300)).encode(&mut Vec::new()),
+                   Err(BytecodeError::InvalidOperands{bci: 0, opcode: Opcode::Ldc}));
+        assert_eq!(Instruction::new(Opcode::Getstatic, Operands::None).encode(&mut Vec::new()),
+                   Err(BytecodeError::InvalidOperands{bci: 0, opcode: Opcode::Getstatic}));
+        assert_eq!(Instruction::new(Opcode::Wide, Operands::None).encode(&mut Vec::new()),
+                   Err(BytecodeError::InvalidOperands{bci: 0, opcode: Opcode::Wide}));
+        let mut code = Vec::new();
+        Instruction::new(Opcode::Athrow, Operands::None).encode(&mut code).unwrap();
+        assert_eq!(code, [0xbf]);
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        assert_eq!(Disassembly::decode(&[0x10]), Err(BytecodeError::Truncated{bci: 0}));
+        assert_eq!(Disassembly::decode(&[0x00, 0xcb]), Err(BytecodeError::UnknownOpcode{bci: 1, opcode: 0xcb}));
+        assert_eq!(Disassembly::decode(&[0xc4, 0x60]), Err(BytecodeError::InvalidWide{bci: 0, opcode: 0x60}));
+        assert_eq!(Disassembly::decode(&[0xa7, 0x00, 0x10]), Err(BytecodeError::BranchOutOfRange{bci: 0}));
+        assert_eq!(Disassembly::decode(&[0xaa, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00,
+            0x00, 0x00, 0x00]), Err(BytecodeError::InvalidSwitch{bci: 0}));
+        assert_eq!(Disassembly::decode(&[0xaa, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x7f,
+            0xff, 0xff, 0xff]), Err(BytecodeError::Truncated{bci: 0}));
+    }
+
+    #[test]
+    fn test_bytecodes() {
+        let mock = MockJvmti::new();
+        let class = mock.add_class("Lcom/example/Worker;", Some("Worker.java"));
+        let run = mock.add_method(class, "run", "()I", None);
+        let native = mock.add_native_method(class, "hash", "(J)I");
+        // iload_1, ifeq 8, aload_0, invokevirtual #12, ireturn, iconst_0, ireturn
+        mock.set_bytecodes(run, &[0x1b, 0x99, 0x00, 0x07, 0x2a, 0xb6, 0x00, 0x0c, 0xac, 0x03, 0xac]);
+        let main = mock.add_thread("main", None, false);
+        mock.set_stack(main, &[(run, 5)]);
+        let mut env = JvmtiEnv::cons(mock.env_ptr());
+        let bytecodes = env.get_bytecodes(&JMethodId::cons(run)).unwrap();
+        let disassembly = Disassembly::decode(&bytecodes).unwrap();
+        assert_eq!(disassembly.to_string(), "0: iload_1\n1: ifeq 8\n4: aload_0\n5: invokevirtual #12\n8: ireturn\n\
+                                             9: iconst_0\n10: ireturn\n");
+        // Frame locations are bcis of the instruction being executed
+        let frame = env.get_frame_location(Some(&crate::JThread::cons(main)), 0).unwrap();
+        assert_eq!(disassembly.instruction_at(frame.location).map(|i| i.to_string()), Some("invokevirtual #12".to_string()));
+        match env.get_bytecodes(&JMethodId::cons(native)) {
+            Err(JvmtiError::NativeMethod) => {},
+            other => panic!("Unexpected result {:?}", other),
+        }
+        assert_eq!(mock.outstanding_allocations(), 0);
+    }
+
+}
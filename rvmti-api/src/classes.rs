@@ -0,0 +1,392 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+// Loaded class and class member enumeration, classes and class loaders are returned as local references
+
+use std::ptr;
+
+use thiserror::Error;
+
+use super::{JvmtiEnv, JniEnv, JClass, JObject, JMethodId, JFieldId, JvmtiError, Local, JniReference, StringDecodeError,
+            VmOwnedString, VmOwnedArray};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldName {
+    pub name: String,
+    pub signature: String,
+    pub generic_signature: Option<String>,
+}
+
+// Access flags as defined by the class file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Modifiers {
+    flags: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClassStatus {
+    status: u32,
+}
+
+#[derive(Error, Debug)]
+pub enum GetFieldNameError {
+    #[error("JVMTI method call error: {0}")]
+    VmError(#[source] JvmtiError),
+    #[error("Failed to decode field name: {0}")]
+    NameDecodeError(#[source] StringDecodeError),
+    #[error("Failed to decode field signature: {0}")]
+    SignatureDecodeError(#[source] StringDecodeError),
+    #[error("Failed to decode field generic signature: {0}")]
+    GenericSignatureDecodeError(#[source] StringDecodeError),
+}
+
+impl JvmtiEnv {
+
+    // Every class is a new local reference, large enumerations belong in a local frame of sufficient capacity
+    pub fn get_loaded_classes<'a>(&mut self, jni_env: &'a JniEnv) -> Result<Vec<Local<'a, JClass>>, JvmtiError> {
+        unsafe {
+            let mut class_count: rvmti_sys::jint = 0;
+            let mut classes_ptr: *mut rvmti_sys::jclass = ptr::null_mut();
+            let result = (*(*self.env)).GetLoadedClasses.unwrap()(self.env, &mut class_count, &mut classes_ptr);
+            let classes = VmOwnedArray{ptr: classes_ptr, count: class_count, env: self};
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(classes.as_slice().iter().filter_map(|c| jni_env.local(*c)).collect());
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    // Classes the loader is an initiating loader for, bootstrap loader when not given
+    pub fn get_class_loader_classes<'a>(&mut self, jni_env: &'a JniEnv,
+                                        loader: Option<&JObject>) -> Result<Vec<Local<'a, JClass>>, JvmtiError>
+    {
+        unsafe {
+            let mut class_count: rvmti_sys::jint = 0;
+            let mut classes_ptr: *mut rvmti_sys::jclass = ptr::null_mut();
+            let loader = loader.map(|l| l.as_raw_object()).unwrap_or(ptr::null_mut());
+            let result = (*(*self.env)).GetClassLoaderClasses.unwrap()(self.env, loader, &mut class_count, &mut classes_ptr);
+            let classes = VmOwnedArray{ptr: classes_ptr, count: class_count, env: self};
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(classes.as_slice().iter().filter_map(|c| jni_env.local(*c)).collect());
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    // Constructors and static initializers are included, inherited methods are not
+    pub fn get_class_methods(&mut self, class: &JClass) -> Result<Vec<JMethodId>, JvmtiError> {
+        unsafe {
+            let mut method_count: rvmti_sys::jint = 0;
+            let mut methods_ptr: *mut rvmti_sys::jmethodID = ptr::null_mut();
+            let result = (*(*self.env)).GetClassMethods.unwrap()(self.env, class.class, &mut method_count, &mut methods_ptr);
+            let methods = VmOwnedArray{ptr: methods_ptr, count: method_count, env: self};
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(methods.as_slice().iter().map(|m| JMethodId{method: *m}).collect());
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    pub fn get_class_fields(&mut self, class: &JClass) -> Result<Vec<JFieldId>, JvmtiError> {
+        unsafe {
+            let mut field_count: rvmti_sys::jint = 0;
+            let mut fields_ptr: *mut rvmti_sys::jfieldID = ptr::null_mut();
+            let result = (*(*self.env)).GetClassFields.unwrap()(self.env, class.class, &mut field_count, &mut fields_ptr);
+            let fields = VmOwnedArray{ptr: fields_ptr, count: field_count, env: self};
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(fields.as_slice().iter().map(|f| JFieldId{field: *f}).collect());
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    pub fn get_class_modifiers(&mut self, class: &JClass) -> Result<Modifiers, JvmtiError> {
+        unsafe {
+            let mut modifiers: rvmti_sys::jint = 0;
+            let result = (*(*self.env)).GetClassModifiers.unwrap()(self.env, class.class, &mut modifiers);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(Modifiers{flags: modifiers});
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    pub fn get_method_modifiers(&mut self, method: &JMethodId) -> Result<Modifiers, JvmtiError> {
+        unsafe {
+            let mut modifiers: rvmti_sys::jint = 0;
+            let result = (*(*self.env)).GetMethodModifiers.unwrap()(self.env, method.method, &mut modifiers);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(Modifiers{flags: modifiers});
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    pub fn get_field_modifiers(&mut self, class: &JClass, field: &JFieldId) -> Result<Modifiers, JvmtiError> {
+        unsafe {
+            let mut modifiers: rvmti_sys::jint = 0;
+            let result = (*(*self.env)).GetFieldModifiers.unwrap()(self.env, class.class, field.field, &mut modifiers);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(Modifiers{flags: modifiers});
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    pub fn get_field_name(&mut self, class: &JClass, field: &JFieldId) -> Result<FieldName, GetFieldNameError> {
+        unsafe {
+            let mut name_ptr: *mut ::std::os::raw::c_char = ptr::null_mut();
+            let mut signature_ptr: *mut ::std::os::raw::c_char = ptr::null_mut();
+            let mut generic_signature_ptr: *mut ::std::os::raw::c_char = ptr::null_mut();
+            let result = (*(*self.env)).GetFieldName.unwrap()(self.env, class.class, field.field, &mut name_ptr,
+                                                              &mut signature_ptr, &mut generic_signature_ptr);
+            let name = name_ptr.as_ref().map(|v| VmOwnedString {ptr: v, env: self});
+            let signature = signature_ptr.as_ref().map(|v| VmOwnedString {ptr: v, env: self});
+            let generic_signature = generic_signature_ptr.as_ref().map(|v| VmOwnedString {ptr: v, env: self});
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                let name_string = name.as_ref().map_or_else(|| Ok(None), |s| s.to_string())
+                    .map_err(GetFieldNameError::NameDecodeError)?;
+                let signature_string = signature.as_ref().map_or_else(|| Ok(None), |s| s.to_string())
+                    .map_err(GetFieldNameError::SignatureDecodeError)?;
+                let generic_signature_string = generic_signature.as_ref().map_or_else(|| Ok(None), |s| s.to_string())
+                    .map_err(GetFieldNameError::GenericSignatureDecodeError)?;
+                return Ok(FieldName{name: name_string.unwrap_or_default(), signature: signature_string.unwrap_or_default(),
+                                    generic_signature: generic_signature_string});
+            } else {
+                return Err(GetFieldNameError::VmError(JvmtiError::from(result)));
+            }
+        }
+    }
+
+    pub fn get_class_status(&mut self, class: &JClass) -> Result<ClassStatus, JvmtiError> {
+        unsafe {
+            let mut status: rvmti_sys::jint = 0;
+            let result = (*(*self.env)).GetClassStatus.unwrap()(self.env, class.class, &mut status);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(ClassStatus{status: status as u32});
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    pub fn check_is_interface(&mut self, class: &JClass) -> Result<bool, JvmtiError> {
+        unsafe {
+            let mut is_interface: rvmti_sys::jboolean = 0 as rvmti_sys::jboolean;
+            let result = (*(*self.env)).IsInterface.unwrap()(self.env, class.class, &mut is_interface);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(is_interface != 0);
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    pub fn check_is_array_class(&mut self, class: &JClass) -> Result<bool, JvmtiError> {
+        unsafe {
+            let mut is_array_class: rvmti_sys::jboolean = 0 as rvmti_sys::jboolean;
+            let result = (*(*self.env)).IsArrayClass.unwrap()(self.env, class.class, &mut is_array_class);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(is_array_class != 0);
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    // Classes loaded by the bootstrap loader have no loader object
+    pub fn get_class_loader<'a>(&mut self, jni_env: &'a JniEnv, class: &JClass) -> Result<Option<Local<'a, JObject>>, JvmtiError> {
+        unsafe {
+            let mut loader: rvmti_sys::jobject = ptr::null_mut();
+            let result = (*(*self.env)).GetClassLoader.unwrap()(self.env, class.class, &mut loader);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(jni_env.local(loader));
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+}
+
+impl Modifiers {
+
+    pub fn from_raw(flags: i32) -> Modifiers {
+        Modifiers{flags}
+    }
+
+    pub fn raw(&self) -> i32 {
+        self.flags
+    }
+
+    pub fn is_public(&self) -> bool {
+        self.has(0x0001)
+    }
+
+    pub fn is_private(&self) -> bool {
+        self.has(0x0002)
+    }
+
+    pub fn is_protected(&self) -> bool {
+        self.has(0x0004)
+    }
+
+    pub fn is_static(&self) -> bool {
+        self.has(0x0008)
+    }
+
+    pub fn is_final(&self) -> bool {
+        self.has(0x0010)
+    }
+
+    pub fn is_synchronized(&self) -> bool {
+        self.has(0x0020)
+    }
+
+    pub fn is_volatile(&self) -> bool {
+        self.has(0x0040)
+    }
+
+    pub fn is_transient(&self) -> bool {
+        self.has(0x0080)
+    }
+
+    pub fn is_native(&self) -> bool {
+        self.has(0x0100)
+    }
+
+    pub fn is_interface(&self) -> bool {
+        self.has(0x0200)
+    }
+
+    pub fn is_abstract(&self) -> bool {
+        self.has(0x0400)
+    }
+
+    pub fn is_synthetic(&self) -> bool {
+        self.has(0x1000)
+    }
+
+    fn has(&self, flag: i32) -> bool {
+        self.flags & flag == flag
+    }
+
+}
+
+impl ClassStatus {
+
+    pub fn raw(&self) -> rvmti_sys::jint {
+        self.status as rvmti_sys::jint
+    }
+
+    pub fn is_verified(&self) -> bool {
+        self.has(rvmti_sys::JVMTI_CLASS_STATUS_VERIFIED)
+    }
+
+    pub fn is_prepared(&self) -> bool {
+        self.has(rvmti_sys::JVMTI_CLASS_STATUS_PREPARED)
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.has(rvmti_sys::JVMTI_CLASS_STATUS_INITIALIZED)
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.has(rvmti_sys::JVMTI_CLASS_STATUS_ERROR)
+    }
+
+    pub fn is_array(&self) -> bool {
+        self.has(rvmti_sys::JVMTI_CLASS_STATUS_ARRAY)
+    }
+
+    pub fn is_primitive(&self) -> bool {
+        self.has(rvmti_sys::JVMTI_CLASS_STATUS_PRIMITIVE)
+    }
+
+    fn has(&self, flag: u32) -> bool {
+        self.status & flag == flag
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::mock::MockJvmti;
+
+    #[test]
+    fn test_class_enumeration() {
+        let mock = MockJvmti::new();
+        let loader = mock.add_class_loader();
+        let worker = mock.add_class("Lcom/example/Worker;", Some("Worker.java"));
+        let task = mock.add_class("Lcom/example/Task;", Some("Task.java"));
+        let strings = mock.add_class("[Ljava/lang/String;", None);
+        mock.set_class_details(worker, 0x0011, Some(loader));
+        mock.set_class_details(task, 0x0601, Some(loader));
+        let run = mock.add_method(worker, "run", "()V", None);
+        let hash = mock.add_native_method(worker, "hash", "(J)I");
+        mock.set_method_modifiers(run, 0x0021);
+        mock.add_method(task, "call", "()V", None);
+        let count = mock.add_field(worker, "count", "I", 0x0042);
+        let mut env = crate::JvmtiEnv::cons(mock.env_ptr());
+        let jni_env = crate::JniEnv::cons(mock.jni_env_ptr());
+        {
+            let classes = env.get_loaded_classes(&jni_env).unwrap();
+            let signatures: Vec<String> = classes.iter().map(|c| env.get_class_signature(c).unwrap().signature).collect();
+            assert_eq!(signatures, vec!["Lcom/example/Worker;", "Lcom/example/Task;", "[Ljava/lang/String;"]);
+            let loader_classes: Vec<usize> = env.get_class_loader_classes(&jni_env, Some(&crate::JObject::cons(loader)))
+                .unwrap().iter().map(|c| c.raw() as usize).collect();
+            assert_eq!(loader_classes, vec![worker as usize, task as usize]);
+            let bootstrap_classes: Vec<usize> = env.get_class_loader_classes(&jni_env, None)
+                .unwrap().iter().map(|c| c.raw() as usize).collect();
+            assert_eq!(bootstrap_classes, vec![strings as usize]);
+
+            let worker_class = &classes[0];
+            assert_eq!(env.get_class_loader(&jni_env, worker_class).unwrap().map(|l| l.raw() as usize), Some(loader as usize));
+            assert!(env.get_class_loader(&jni_env, &classes[2]).unwrap().is_none());
+            let modifiers = env.get_class_modifiers(worker_class).unwrap();
+            assert!(modifiers.is_public() && modifiers.is_final() && !modifiers.is_abstract());
+            assert!(env.check_is_interface(&classes[1]).unwrap());
+            assert!(!env.check_is_interface(worker_class).unwrap());
+            assert!(env.check_is_array_class(&classes[2]).unwrap());
+            let status = env.get_class_status(worker_class).unwrap();
+            assert!(status.is_verified() && status.is_prepared() && status.is_initialized() && !status.is_array());
+            assert!(env.get_class_status(&classes[2]).unwrap().is_array());
+
+            // Methods are found by name among the declared ones
+            let methods = env.get_class_methods(worker_class).unwrap();
+            let names: Vec<String> = methods.iter().map(|m| env.get_method_name(m).unwrap().name).collect();
+            assert_eq!(names, vec!["run", "hash"]);
+            let run_method = methods.iter().find(|m| env.get_method_name(m).unwrap().name == "run").unwrap();
+            assert_eq!(run_method.raw() as usize, run as usize);
+            assert!(env.get_method_modifiers(run_method).unwrap().is_synchronized());
+            assert!(env.get_method_modifiers(&methods[1]).unwrap().is_native());
+            assert_eq!(methods[1].raw() as usize, hash as usize);
+
+            let fields = env.get_class_fields(worker_class).unwrap();
+            assert_eq!(fields.len(), 1);
+            assert_eq!(fields[0].raw() as usize, count as usize);
+            let field_name = env.get_field_name(worker_class, &fields[0]).unwrap();
+            assert_eq!(field_name, crate::FieldName{name: "count".to_string(), signature: "I".to_string(),
+                                                        generic_signature: None});
+            let field_modifiers = env.get_field_modifiers(worker_class, &fields[0]).unwrap();
+            assert!(field_modifiers.is_private() && field_modifiers.is_volatile() && !field_modifiers.is_static());
+            assert!(env.get_field_name(&classes[1], &fields[0]).is_err());
+        }
+        assert_eq!(mock.outstanding_allocations(), 0);
+        assert_eq!(mock.outstanding_local_refs(), 0);
+    }
+
+}
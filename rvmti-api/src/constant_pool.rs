@@ -0,0 +1,725 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+// Constant pool of loaded classes and its parsing. Entries are laid out as in the class file format, though the VM
+// may reconstitute them in a different order than the original class file. Constant pool operands of bytecode
+// returned by `get_bytecodes` always refer to the constant pool returned for the declaring class.
+
+use std::fmt;
+
+use thiserror::Error;
+
+use super::{JvmtiEnv, JClass, JvmtiError, VmOwnedArray, to_modified_utf8};
+use super::bytecode::{Instruction, Operands};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constant {
+    Utf8(ModifiedUtf8),
+    Integer(i32),
+    Float(f32),
+    Long(i64),
+    Double(f64),
+    Class { name_index: u16 },
+    String { string_index: u16 },
+    Fieldref { class_index: u16, name_and_type_index: u16 },
+    Methodref { class_index: u16, name_and_type_index: u16 },
+    InterfaceMethodref { class_index: u16, name_and_type_index: u16 },
+    NameAndType { name_index: u16, descriptor_index: u16 },
+    MethodHandle { reference_kind: u8, reference_index: u16 },
+    MethodType { descriptor_index: u16 },
+    Dynamic { bootstrap_method_attr_index: u16, name_and_type_index: u16 },
+    InvokeDynamic { bootstrap_method_attr_index: u16, name_and_type_index: u16 },
+    Module { name_index: u16 },
+    Package { name_index: u16 },
+}
+
+// Contents of a Utf8 constant as stored in the class file. Java strings may hold unpaired surrogates, these have
+// no `str` form but are kept so that the constant is written back unchanged
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModifiedUtf8 {
+    bytes: Vec<u8>,
+    string: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstantPool {
+    // Index zero and the slot following long and double constants are unusable
+    entries: Vec<Option<Constant>>,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ConstantPoolError {
+    #[error("Constant pool entry {index} is truncated")]
+    Truncated { index: u16 },
+    #[error("Unknown tag {tag} of constant pool entry {index}")]
+    UnknownTag { index: u16, tag: u8 },
+    #[error("Constant pool entry {index} is not valid modified UTF-8")]
+    InvalidUtf8 { index: u16 },
+    #[error("Constant pool entry {index} holds unpaired surrogates")]
+    UnpairedSurrogate { index: u16 },
+    #[error("Constant pool is followed by {count} unexpected bytes")]
+    TrailingBytes { count: usize },
+    #[error("Constant pool has no usable entry {index}")]
+    InvalidIndex { index: u16 },
+    #[error("Constant pool entry {index} is not {expected}")]
+    UnexpectedConstant { index: u16, expected: &'static str },
+    #[error("Invalid method handle kind {kind} of constant pool entry {index}")]
+    InvalidReferenceKind { index: u16, kind: u8 },
+    #[error("Constant pool is full")]
+    Full,
+    #[error("String of {length} bytes does not fit a Utf8 constant")]
+    Utf8TooLong { length: usize },
+}
+
+#[derive(Error, Debug)]
+pub enum GetConstantPoolError {
+    #[error("JVMTI method call error: {0}")]
+    VmError(#[source] JvmtiError),
+    #[error("Failed to parse constant pool: {0}")]
+    ParseError(#[source] ConstantPoolError),
+}
+
+impl ModifiedUtf8 {
+
+    pub fn new(value: &str) -> ModifiedUtf8 {
+        ModifiedUtf8{bytes: to_modified_utf8(value).into_bytes(), string: Some(value.to_string())}
+    }
+
+    // None if the bytes are not valid modified UTF-8
+    pub fn from_bytes(bytes: &[u8]) -> Option<ModifiedUtf8> {
+        let units = decode_utf16_units(bytes)?;
+        Some(ModifiedUtf8{bytes: bytes.to_vec(), string: String::from_utf16(&units).ok()})
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    // None if the string holds unpaired surrogates
+    pub fn as_str(&self) -> Option<&str> {
+        self.string.as_deref()
+    }
+
+    pub fn to_utf16(&self) -> Vec<u16> {
+        decode_utf16_units(&self.bytes).expect("Bytes are checked on construction")
+    }
+
+}
+
+// Unpaired surrogates are written as `\u{d800}`
+impl fmt::Display for ModifiedUtf8 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref value) = self.string {
+            return f.write_str(value);
+        }
+        for c in std::char::decode_utf16(self.to_utf16()) {
+            match c {
+                Ok(c) => write!(f, "{}", c)?,
+                Err(e) => write!(f, "\\u{{{:x}}}", e.unpaired_surrogate())?,
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    // Entry being read
+    index: u16,
+}
+
+impl<'a> Reader<'a> {
+
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], ConstantPoolError> {
+        if self.bytes.len() - self.position < count {
+            return Err(ConstantPoolError::Truncated{index: self.index});
+        }
+        let bytes = &self.bytes[self.position..self.position + count];
+        self.position += count;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, ConstantPoolError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, ConstantPoolError> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, ConstantPoolError> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn u64(&mut self) -> Result<u64, ConstantPoolError> {
+        Ok(((self.u32()? as u64) << 32) | self.u32()? as u64)
+    }
+
+}
+
+impl ConstantPool {
+
+    // Count is one more than the number of entries, as the `constant_pool_count` item of class files
+    pub fn parse(count: u16, bytes: &[u8]) -> Result<ConstantPool, ConstantPoolError> {
+        let (pool, length) = ConstantPool::read(count, bytes)?;
+        if length < bytes.len() {
+            return Err(ConstantPoolError::TrailingBytes{count: bytes.len() - length});
+        }
+        Ok(pool)
+    }
+
+    // Pool with the unusable entry zero only
+    pub fn new() -> ConstantPool {
+        ConstantPool{entries: vec![None]}
+    }
+
+    // Parses the entries at the start of bytes, returns the pool and the number of bytes it takes
+    pub fn read(count: u16, bytes: &[u8]) -> Result<(ConstantPool, usize), ConstantPoolError> {
+        let mut reader = Reader{bytes, position: 0, index: 0};
+        let mut entries = Vec::with_capacity(count as usize);
+        entries.push(None);
+        while entries.len() < count as usize {
+            reader.index = entries.len() as u16;
+            let constant = read_constant(&mut reader)?;
+            let wide = matches!(constant, Constant::Long(_) | Constant::Double(_));
+            entries.push(Some(constant));
+            if wide {
+                if entries.len() == count as usize {
+                    return Err(ConstantPoolError::Truncated{index: reader.index});
+                }
+                entries.push(None);
+            }
+        }
+        Ok((ConstantPool{entries}, reader.position))
+    }
+
+    pub fn count(&self) -> u16 {
+        self.entries.len() as u16
+    }
+
+    pub fn get(&self, index: u16) -> Option<&Constant> {
+        self.entries.get(index as usize).and_then(Option::as_ref)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u16, &Constant)> {
+        self.entries.iter().enumerate().filter_map(|(i, c)| c.as_ref().map(|c| (i as u16, c)))
+    }
+
+    // Entries in class file form, as they follow `constant_pool_count`
+    pub fn write(&self, out: &mut Vec<u8>) {
+        for constant in self.entries.iter().flatten() {
+            write_constant(constant, out);
+        }
+    }
+
+    // Appended after existing entries, long and double constants take two indexes
+    pub fn add(&mut self, constant: Constant) -> Result<u16, ConstantPoolError> {
+        if let Constant::Utf8(ref value) = constant {
+            let length = value.as_bytes().len();
+            if length > u16::MAX as usize {
+                return Err(ConstantPoolError::Utf8TooLong{length});
+            }
+        }
+        let wide = matches!(constant, Constant::Long(_) | Constant::Double(_));
+        if self.entries.len() + if wide { 2 } else { 1 } > u16::MAX as usize {
+            return Err(ConstantPoolError::Full);
+        }
+        let index = self.entries.len() as u16;
+        self.entries.push(Some(constant));
+        if wide {
+            self.entries.push(None);
+        }
+        Ok(index)
+    }
+
+    // Index of an equal entry if there is one, the constant is added otherwise. Floating point constants are equal
+    // if their bits are, keeping 0.0 and -0.0 apart and reusing NaN entries
+    pub fn find_or_add(&mut self, constant: Constant) -> Result<u16, ConstantPoolError> {
+        let existing = self.iter().find(|(_, c)| same_constant(c, &constant)).map(|(index, _)| index);
+        match existing {
+            Some(index) => Ok(index),
+            None => self.add(constant),
+        }
+    }
+
+    pub fn add_utf8(&mut self, value: &str) -> Result<u16, ConstantPoolError> {
+        self.find_or_add(Constant::Utf8(ModifiedUtf8::new(value)))
+    }
+
+    // Internal form of the name, `java/lang/String`
+    pub fn add_class(&mut self, name: &str) -> Result<u16, ConstantPoolError> {
+        let name_index = self.add_utf8(name)?;
+        self.find_or_add(Constant::Class{name_index})
+    }
+
+    pub fn add_string(&mut self, value: &str) -> Result<u16, ConstantPoolError> {
+        let string_index = self.add_utf8(value)?;
+        self.find_or_add(Constant::String{string_index})
+    }
+
+    pub fn add_name_and_type(&mut self, name: &str, descriptor: &str) -> Result<u16, ConstantPoolError> {
+        let name_index = self.add_utf8(name)?;
+        let descriptor_index = self.add_utf8(descriptor)?;
+        self.find_or_add(Constant::NameAndType{name_index, descriptor_index})
+    }
+
+    pub fn add_fieldref(&mut self, class: &str, name: &str, descriptor: &str) -> Result<u16, ConstantPoolError> {
+        let class_index = self.add_class(class)?;
+        let name_and_type_index = self.add_name_and_type(name, descriptor)?;
+        self.find_or_add(Constant::Fieldref{class_index, name_and_type_index})
+    }
+
+    pub fn add_methodref(&mut self, class: &str, name: &str, descriptor: &str) -> Result<u16, ConstantPoolError> {
+        let class_index = self.add_class(class)?;
+        let name_and_type_index = self.add_name_and_type(name, descriptor)?;
+        self.find_or_add(Constant::Methodref{class_index, name_and_type_index})
+    }
+
+    pub fn add_interface_methodref(&mut self, class: &str, name: &str, descriptor: &str) -> Result<u16, ConstantPoolError> {
+        let class_index = self.add_class(class)?;
+        let name_and_type_index = self.add_name_and_type(name, descriptor)?;
+        self.find_or_add(Constant::InterfaceMethodref{class_index, name_and_type_index})
+    }
+
+    fn constant(&self, index: u16) -> Result<&Constant, ConstantPoolError> {
+        self.get(index).ok_or(ConstantPoolError::InvalidIndex{index})
+    }
+
+    pub fn utf8(&self, index: u16) -> Result<&str, ConstantPoolError> {
+        self.modified_utf8(index)?.as_str().ok_or(ConstantPoolError::UnpairedSurrogate{index})
+    }
+
+    pub fn modified_utf8(&self, index: u16) -> Result<&ModifiedUtf8, ConstantPoolError> {
+        match self.constant(index)? {
+            Constant::Utf8(value) => Ok(value),
+            _ => Err(ConstantPoolError::UnexpectedConstant{index, expected: "Utf8"}),
+        }
+    }
+
+    // Internal form, `java/lang/String` or a descriptor like `[I` for array classes
+    pub fn class_name(&self, index: u16) -> Result<&str, ConstantPoolError> {
+        match *self.constant(index)? {
+            Constant::Class{name_index} => self.utf8(name_index),
+            _ => Err(ConstantPoolError::UnexpectedConstant{index, expected: "Class"}),
+        }
+    }
+
+    // Name and descriptor
+    pub fn name_and_type(&self, index: u16) -> Result<(&str, &str), ConstantPoolError> {
+        match *self.constant(index)? {
+            Constant::NameAndType{name_index, descriptor_index} => Ok((self.utf8(name_index)?, self.utf8(descriptor_index)?)),
+            _ => Err(ConstantPoolError::UnexpectedConstant{index, expected: "NameAndType"}),
+        }
+    }
+
+    // Symbolic form of the constant, `java/util/List.size()I` for methods, `java/lang/System.out:Ljava/io/PrintStream;`
+    // for fields, `#0:run()Ljava/lang/Runnable;` with the bootstrap method index for dynamically computed call sites
+    pub fn resolve(&self, index: u16) -> Result<String, ConstantPoolError> {
+        let resolved = match *self.constant(index)? {
+            Constant::Utf8(ref value) => value.to_string(),
+            Constant::Integer(value) => value.to_string(),
+            Constant::Float(value) => format!("{}f", value),
+            Constant::Long(value) => format!("{}L", value),
+            Constant::Double(value) => format!("{}d", value),
+            Constant::Class{..} => self.class_name(index)?.to_string(),
+            Constant::String{string_index} => match self.modified_utf8(string_index)?.as_str() {
+                Some(value) => format!("{:?}", value),
+                None => format!("\"{}\"", self.modified_utf8(string_index)?),
+            },
+            Constant::Fieldref{class_index, name_and_type_index} => {
+                let (name, descriptor) = self.name_and_type(name_and_type_index)?;
+                format!("{}.{}:{}", self.class_name(class_index)?, name, descriptor)
+            },
+            Constant::Methodref{class_index, name_and_type_index}
+            | Constant::InterfaceMethodref{class_index, name_and_type_index} => {
+                let (name, descriptor) = self.name_and_type(name_and_type_index)?;
+                format!("{}.{}{}", self.class_name(class_index)?, name, descriptor)
+            },
+            Constant::NameAndType{..} => {
+                let (name, descriptor) = self.name_and_type(index)?;
+                format!("{}:{}", name, descriptor)
+            },
+            Constant::MethodHandle{reference_kind, reference_index} => {
+                let kind = reference_kind_name(reference_kind)
+                    .ok_or(ConstantPoolError::InvalidReferenceKind{index, kind: reference_kind})?;
+                format!("{} {}", kind, self.resolve(reference_index)?)
+            },
+            Constant::MethodType{descriptor_index} => self.utf8(descriptor_index)?.to_string(),
+            Constant::Dynamic{bootstrap_method_attr_index, name_and_type_index} => {
+                let (name, descriptor) = self.name_and_type(name_and_type_index)?;
+                format!("#{}:{}:{}", bootstrap_method_attr_index, name, descriptor)
+            },
+            Constant::InvokeDynamic{bootstrap_method_attr_index, name_and_type_index} => {
+                let (name, descriptor) = self.name_and_type(name_and_type_index)?;
+                format!("#{}:{}{}", bootstrap_method_attr_index, name, descriptor)
+            },
+            Constant::Module{name_index} | Constant::Package{name_index} => self.utf8(name_index)?.to_string(),
+        };
+        Ok(resolved)
+    }
+
+    // Instruction with its constant pool operand resolved, `invokevirtual java/util/List.size()I`
+    pub fn resolve_instruction(&self, instruction: &Instruction) -> Result<String, ConstantPoolError> {
+        let resolved = match instruction.operands {
+            Operands::ConstantPool(index) | Operands::InvokeInterface{index, ..} => {
+                format!("{} {}", instruction.opcode, self.resolve(index)?)
+            },
+            Operands::MultiANewArray{index, dimensions} => {
+                format!("{} {}, {}", instruction.opcode, self.resolve(index)?, dimensions)
+            },
+            _ => instruction.to_string(),
+        };
+        Ok(resolved)
+    }
+
+}
+
+fn read_constant(reader: &mut Reader) -> Result<Constant, ConstantPoolError> {
+    let tag = reader.u8()?;
+    let constant = match tag {
+        1 => {
+            let length = reader.u16()? as usize;
+            let bytes = reader.bytes(length)?;
+            Constant::Utf8(ModifiedUtf8::from_bytes(bytes).ok_or(ConstantPoolError::InvalidUtf8{index: reader.index})?)
+        },
+        3 => Constant::Integer(reader.u32()? as i32),
+        4 => Constant::Float(f32::from_bits(reader.u32()?)),
+        5 => Constant::Long(reader.u64()? as i64),
+        6 => Constant::Double(f64::from_bits(reader.u64()?)),
+        7 => Constant::Class{name_index: reader.u16()?},
+        8 => Constant::String{string_index: reader.u16()?},
+        9 => Constant::Fieldref{class_index: reader.u16()?, name_and_type_index: reader.u16()?},
+        10 => Constant::Methodref{class_index: reader.u16()?, name_and_type_index: reader.u16()?},
+        11 => Constant::InterfaceMethodref{class_index: reader.u16()?, name_and_type_index: reader.u16()?},
+        12 => Constant::NameAndType{name_index: reader.u16()?, descriptor_index: reader.u16()?},
+        15 => Constant::MethodHandle{reference_kind: reader.u8()?, reference_index: reader.u16()?},
+        16 => Constant::MethodType{descriptor_index: reader.u16()?},
+        17 => Constant::Dynamic{bootstrap_method_attr_index: reader.u16()?, name_and_type_index: reader.u16()?},
+        18 => Constant::InvokeDynamic{bootstrap_method_attr_index: reader.u16()?, name_and_type_index: reader.u16()?},
+        19 => Constant::Module{name_index: reader.u16()?},
+        20 => Constant::Package{name_index: reader.u16()?},
+        _ => return Err(ConstantPoolError::UnknownTag{index: reader.index, tag}),
+    };
+    Ok(constant)
+}
+
+fn write_constant(constant: &Constant, out: &mut Vec<u8>) {
+    let u16_pair = |out: &mut Vec<u8>, tag: u8, first: u16, second: u16| {
+        out.push(tag);
+        out.extend_from_slice(&first.to_be_bytes());
+        out.extend_from_slice(&second.to_be_bytes());
+    };
+    match *constant {
+        Constant::Utf8(ref value) => {
+            out.push(1);
+            out.extend_from_slice(&(value.as_bytes().len() as u16).to_be_bytes());
+            out.extend_from_slice(value.as_bytes());
+        },
+        Constant::Integer(value) => {
+            out.push(3);
+            out.extend_from_slice(&value.to_be_bytes());
+        },
+        Constant::Float(value) => {
+            out.push(4);
+            out.extend_from_slice(&value.to_bits().to_be_bytes());
+        },
+        Constant::Long(value) => {
+            out.push(5);
+            out.extend_from_slice(&value.to_be_bytes());
+        },
+        Constant::Double(value) => {
+            out.push(6);
+            out.extend_from_slice(&value.to_bits().to_be_bytes());
+        },
+        Constant::Class{name_index} => {
+            out.push(7);
+            out.extend_from_slice(&name_index.to_be_bytes());
+        },
+        Constant::String{string_index} => {
+            out.push(8);
+            out.extend_from_slice(&string_index.to_be_bytes());
+        },
+        Constant::Fieldref{class_index, name_and_type_index} => u16_pair(out, 9, class_index, name_and_type_index),
+        Constant::Methodref{class_index, name_and_type_index} => u16_pair(out, 10, class_index, name_and_type_index),
+        Constant::InterfaceMethodref{class_index, name_and_type_index} => u16_pair(out, 11, class_index, name_and_type_index),
+        Constant::NameAndType{name_index, descriptor_index} => u16_pair(out, 12, name_index, descriptor_index),
+        Constant::MethodHandle{reference_kind, reference_index} => {
+            out.push(15);
+            out.push(reference_kind);
+            out.extend_from_slice(&reference_index.to_be_bytes());
+        },
+        Constant::MethodType{descriptor_index} => {
+            out.push(16);
+            out.extend_from_slice(&descriptor_index.to_be_bytes());
+        },
+        Constant::Dynamic{bootstrap_method_attr_index, name_and_type_index} => {
+            u16_pair(out, 17, bootstrap_method_attr_index, name_and_type_index)
+        },
+        Constant::InvokeDynamic{bootstrap_method_attr_index, name_and_type_index} => {
+            u16_pair(out, 18, bootstrap_method_attr_index, name_and_type_index)
+        },
+        Constant::Module{name_index} => {
+            out.push(19);
+            out.extend_from_slice(&name_index.to_be_bytes());
+        },
+        Constant::Package{name_index} => {
+            out.push(20);
+            out.extend_from_slice(&name_index.to_be_bytes());
+        },
+    }
+}
+
+fn same_constant(first: &Constant, second: &Constant) -> bool {
+    match (first, second) {
+        (Constant::Float(first), Constant::Float(second)) => first.to_bits() == second.to_bits(),
+        (Constant::Double(first), Constant::Double(second)) => first.to_bits() == second.to_bits(),
+        _ => first == second,
+    }
+}
+
+// Every one, two or three byte sequence encodes a single UTF-16 unit, surrogates included
+fn decode_utf16_units(bytes: &[u8]) -> Option<Vec<u16>> {
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut position = 0;
+    while position < bytes.len() {
+        let first = bytes[position] as u16;
+        let continuation = |offset: usize| {
+            bytes.get(position + offset).filter(|b| **b & 0xc0 == 0x80).map(|b| (*b & 0x3f) as u16)
+        };
+        if first != 0 && first & 0x80 == 0 {
+            units.push(first);
+            position += 1;
+        } else if first & 0xe0 == 0xc0 {
+            let unit = ((first & 0x1f) << 6) | continuation(1)?;
+            if unit != 0 && unit < 0x80 {
+                return None;
+            }
+            units.push(unit);
+            position += 2;
+        } else if first & 0xf0 == 0xe0 {
+            let unit = ((first & 0x0f) << 12) | (continuation(1)? << 6) | continuation(2)?;
+            if unit < 0x800 {
+                return None;
+            }
+            units.push(unit);
+            position += 3;
+        } else {
+            return None;
+        }
+    }
+    Some(units)
+}
+
+fn reference_kind_name(kind: u8) -> Option<&'static str> {
+    match kind {
+        1 => Some("REF_getField"),
+        2 => Some("REF_getStatic"),
+        3 => Some("REF_putField"),
+        4 => Some("REF_putStatic"),
+        5 => Some("REF_invokeVirtual"),
+        6 => Some("REF_invokeStatic"),
+        7 => Some("REF_invokeSpecial"),
+        8 => Some("REF_newInvokeSpecial"),
+        9 => Some("REF_invokeInterface"),
+        _ => None,
+    }
+}
+
+impl Default for ConstantPool {
+
+    fn default() -> ConstantPool {
+        ConstantPool::new()
+    }
+
+}
+
+impl JvmtiEnv {
+
+    // Requires `can_get_constant_pool` capability, not available for array and primitive classes
+    pub fn get_constant_pool(&mut self, class: &JClass) -> Result<ConstantPool, GetConstantPoolError> {
+        unsafe {
+            let mut count: rvmti_sys::jint = 0;
+            let mut byte_count: rvmti_sys::jint = 0;
+            let mut bytes_ptr: *mut u8 = std::ptr::null_mut();
+            let result = (*(*self.env)).GetConstantPool.unwrap()(self.env, class.class, &mut count, &mut byte_count,
+                                                                  &mut bytes_ptr);
+            let bytes = VmOwnedArray{ptr: bytes_ptr, count: byte_count, env: self};
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return ConstantPool::parse(count as u16, bytes.as_slice()).map_err(GetConstantPoolError::ParseError);
+            } else {
+                return Err(GetConstantPoolError::VmError(JvmtiError::from(result)));
+            }
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::bytecode::Disassembly;
+    use crate::mock::MockJvmti;
+
+    // List.size() called on an interface, System.out, a string, a long and an invokedynamic with its bootstrap handle
+    const POOL: [u8; 117] = [
+        0x07, 0x00, 0x02,
+        0x01, 0x00, 0x0e, b'j', b'a', b'v', b'a', b'/', b'u', b't', b'i', b'l', b'/', b'L', b'i', b's', b't',
+        0x0b, 0x00, 0x01, 0x00, 0x04,
+        0x0c, 0x00, 0x05, 0x00, 0x06,
+        0x01, 0x00, 0x04, b's', b'i', b'z', b'e',
+        0x01, 0x00, 0x03, b'(', b')', b'I',
+        0x09, 0x00, 0x08, 0x00, 0x0a,
+        0x07, 0x00, 0x09,
+        0x01, 0x00, 0x06, b'S', b'y', b's', b't', b'e', b'm',
+        0x0c, 0x00, 0x0b, 0x00, 0x0c,
+        0x01, 0x00, 0x03, b'o', b'u', b't',
+        0x01, 0x00, 0x03, b'L', b'S', b';',
+        0x08, 0x00, 0x0e,
+        0x01, 0x00, 0x03, b'h', 0xc3, 0xa4,
+        0x05, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
+        0x12, 0x00, 0x00, 0x00, 0x12,
+        0x0c, 0x00, 0x05, 0x00, 0x06,
+        0x0f, 0x06, 0x00, 0x03,
+        0x03, 0xff, 0xff, 0xff, 0xfe,
+        0x10, 0x00, 0x06,
+    ];
+
+    #[test]
+    fn test_parse() {
+        let pool = ConstantPool::parse(22, &POOL[..POOL.len() - 3]).unwrap_err();
+        assert_eq!(pool, ConstantPoolError::Truncated{index: 21});
+        let pool = ConstantPool::parse(22, &POOL).unwrap();
+        assert_eq!(pool.count(), 22);
+        assert_eq!(pool.get(0), None);
+        assert_eq!(pool.get(3), Some(&Constant::InterfaceMethodref{class_index: 1, name_and_type_index: 4}));
+        assert_eq!(pool.get(15), Some(&Constant::Long(1 << 32)));
+        assert_eq!(pool.get(16), None);
+        assert_eq!(pool.get(22), None);
+        assert_eq!(pool.iter().count(), 20);
+        assert_eq!(pool.utf8(14), Ok("hä"));
+        assert_eq!(pool.class_name(1), Ok("java/util/List"));
+        assert_eq!(pool.name_and_type(4), Ok(("size", "()I")));
+        assert_eq!(pool.class_name(2), Err(ConstantPoolError::UnexpectedConstant{index: 2, expected: "Class"}));
+        assert_eq!(pool.utf8(16), Err(ConstantPoolError::InvalidIndex{index: 16}));
+    }
+
+    #[test]
+    fn test_resolve() {
+        let pool = ConstantPool::parse(22, &POOL).unwrap();
+        let resolved: Vec<String> = [1, 3, 7, 13, 15, 17, 19, 20, 21].iter().map(|i| pool.resolve(*i).unwrap()).collect();
+        assert_eq!(resolved, vec!["java/util/List", "java/util/List.size()I", "System.out:LS;", "\"hä\"", "4294967296L",
+            "#0:size()I", "REF_invokeStatic java/util/List.size()I", "-2", "()I"]
+            .into_iter().map(str::to_string).collect::<Vec<String>>());
+    }
+
+    #[test]
+    fn test_resolve_instructions() {
+        let pool = ConstantPool::parse(22, &POOL).unwrap();
+        // getstatic #7, invokeinterface #3, 1, ldc #13, ldc2_w #15, invokedynamic #17, iadd
+        let code = [0xb2, 0x00, 0x07, 0xb9, 0x00, 0x03, 0x01, 0x00, 0x12, 0x0d, 0x14, 0x00, 0x0f, 0xba, 0x00, 0x11, 0x00,
+            0x00, 0x60];
+        let disassembly = Disassembly::decode(&code).unwrap();
+        let resolved = disassembly.instructions().iter().map(|i| pool.resolve_instruction(i))
+            .collect::<Result<Vec<String>, ConstantPoolError>>().unwrap();
+        assert_eq!(resolved, vec!["getstatic System.out:LS;", "invokeinterface java/util/List.size()I", "ldc \"hä\"",
+            "ldc2_w 4294967296L", "invokedynamic #0:size()I", "iadd"]);
+    }
+
+    #[test]
+    fn test_write() {
+        let mut pool = ConstantPool::parse(22, &POOL).unwrap();
+        let mut written = Vec::new();
+        pool.write(&mut written);
+        assert_eq!(written, POOL);
+        assert_eq!(pool.add_interface_methodref("java/util/List", "size", "()I"), Ok(3));
+        assert_eq!(pool.add_fieldref("java/util/List", "out", "LS;"), Ok(22));
+        assert_eq!(pool.count(), 23);
+        assert_eq!(pool.resolve(22), Ok("java/util/List.out:LS;".to_string()));
+        assert_eq!(pool.add(Constant::Long(7)), Ok(23));
+        assert_eq!(pool.add_string("hä"), Ok(13));
+        assert_eq!(pool.count(), 25);
+        assert_eq!(pool.add_utf8(&"x".repeat(70000)), Err(ConstantPoolError::Utf8TooLong{length: 70000}));
+        written.clear();
+        pool.write(&mut written);
+        assert_eq!(ConstantPool::parse(pool.count(), &written), Ok(pool));
+    }
+
+    #[test]
+    fn test_find_or_add_floating_point() {
+        let mut pool = ConstantPool::new();
+        assert_eq!(pool.find_or_add(Constant::Float(0.0)), Ok(1));
+        assert_eq!(pool.find_or_add(Constant::Float(-0.0)), Ok(2));
+        assert_eq!(pool.find_or_add(Constant::Float(f32::NAN)), Ok(3));
+        assert_eq!(pool.find_or_add(Constant::Float(f32::NAN)), Ok(3));
+        assert_eq!(pool.find_or_add(Constant::Double(0.0)), Ok(4));
+        assert_eq!(pool.find_or_add(Constant::Double(-0.0)), Ok(6));
+        assert_eq!(pool.find_or_add(Constant::Double(f64::NAN)), Ok(8));
+        assert_eq!(pool.find_or_add(Constant::Double(f64::NAN)), Ok(8));
+        assert_eq!(pool.find_or_add(Constant::Float(-0.0)), Ok(2));
+        assert_eq!(pool.count(), 10);
+    }
+
+    #[test]
+    fn test_unpaired_surrogate() {
+        // Utf8 constant of `a` and an unpaired U+D800, followed by a String constant referring to it
+        let bytes = [0x01, 0x00, 0x04, 0x61, 0xed, 0xa0, 0x80, 0x08, 0x00, 0x01];
+        let pool = ConstantPool::parse(3, &bytes).unwrap();
+        assert_eq!(pool.modified_utf8(1).map(ModifiedUtf8::to_utf16), Ok(vec![0x61, 0xd800]));
+        assert_eq!(pool.utf8(1), Err(ConstantPoolError::UnpairedSurrogate{index: 1}));
+        assert_eq!(pool.resolve(1), Ok("a\\u{d800}".to_string()));
+        assert_eq!(pool.resolve(2), Ok("\"a\\u{d800}\"".to_string()));
+        let mut written = Vec::new();
+        pool.write(&mut written);
+        assert_eq!(written, bytes);
+        assert_eq!(ModifiedUtf8::new("a\u{1f600}").as_str(), Some("a\u{1f600}"));
+        assert_eq!(ModifiedUtf8::from_bytes(&[0xc0, 0x81]), None);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(ConstantPool::parse(2, &[0x02, 0x00]), Err(ConstantPoolError::UnknownTag{index: 1, tag: 2}));
+        assert_eq!(ConstantPool::parse(2, &[0x01, 0x00, 0x01, 0xff]), Err(ConstantPoolError::InvalidUtf8{index: 1}));
+        assert_eq!(ConstantPool::parse(2, &[0x06, 0, 0, 0, 0, 0, 0, 0, 0]), Err(ConstantPoolError::Truncated{index: 1}));
+        assert_eq!(ConstantPool::parse(2, &[0x07, 0x00, 0x01, 0x00]), Err(ConstantPoolError::TrailingBytes{count: 1}));
+        let pool = ConstantPool::parse(2, &[0x0f, 0x0a, 0x00, 0x01]).unwrap();
+        assert_eq!(pool.resolve(1), Err(ConstantPoolError::InvalidReferenceKind{index: 1, kind: 10}));
+    }
+
+    #[test]
+    fn test_constant_pool() {
+        let mock = MockJvmti::new();
+        let class = mock.add_class("Lcom/example/Worker;", Some("Worker.java"));
+        let run = mock.add_method(class, "run", "()I", None);
+        // aload_0, invokeinterface #1, 1, ireturn
+        mock.set_bytecodes(run, &[0x2a, 0xb9, 0x00, 0x01, 0x01, 0x00, 0xac]);
+        mock.set_constant_pool(class, 7, &[0x0b, 0x00, 0x02, 0x00, 0x04, 0x07, 0x00, 0x03, 0x01, 0x00, 0x0e, b'j', b'a',
+            b'v', b'a', b'/', b'u', b't', b'i', b'l', b'/', b'L', b'i', b's', b't', 0x0c, 0x00, 0x05, 0x00, 0x06, 0x01,
+            0x00, 0x04, b's', b'i', b'z', b'e', 0x01, 0x00, 0x03, b'(', b')', b'I']);
+        mock.add_class("[I", None);
+        let mut env = JvmtiEnv::cons(mock.env_ptr());
+        let jni_env = crate::JniEnv::cons(mock.jni_env_ptr());
+        {
+            let classes = env.get_loaded_classes(&jni_env).unwrap();
+            let pool = env.get_constant_pool(&classes[0]).unwrap();
+            let bytecodes = env.get_bytecodes(&crate::JMethodId::cons(run)).unwrap();
+            let disassembly = Disassembly::decode(&bytecodes).unwrap();
+            let resolved: Vec<String> = disassembly.instructions().iter().map(|i| pool.resolve_instruction(i).unwrap()).collect();
+            assert_eq!(resolved, vec!["aload_0", "invokeinterface java/util/List.size()I", "ireturn"]);
+            // Array classes have no constant pool
+            match env.get_constant_pool(&classes[1]) {
+                Err(GetConstantPoolError::VmError(JvmtiError::AbsentInformation)) => {},
+                other => panic!("Unexpected result {:?}", other),
+            }
+        }
+        assert_eq!(mock.outstanding_allocations(), 0);
+    }
+
+}
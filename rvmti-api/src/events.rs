@@ -0,0 +1,582 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+// Handler methods mirror callback signatures and trampolines are only called by the JVM with valid pointers
+#![allow(clippy::too_many_arguments)]
+#![allow(clippy::not_unsafe_ptr_arg_deref)]
+
+// Event dispatch, trampolines registered in JVMTI callback table forward events to the handler of their environment
+
+use std::os::raw::{c_char, c_uchar, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+use std::sync::Arc;
+
+use log::warn;
+
+use super::{JvmtiEnv, JniEnv, JThread, JMethodId, JClass, JObject, JFieldId, JLocation, JValue, JvmtiEvent, JvmtiEventMode,
+            JvmtiError, JvmtiEventCallbacksSettings, AddressLocationEntry, CompiledMethodLoadRecord};
+use super::{from_modified_utf8, as_address_location_slice, to_compile_infos};
+
+// Every method does nothing by default, agents override only the events they enable
+#[allow(unused_variables)]
+pub trait AgentEventHandler: Send + Sync {
+
+    fn vm_init(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv, thread: &JThread) {}
+
+    fn vm_death(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv) {}
+
+    fn vm_start(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv) {}
+
+    fn thread_start(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv, thread: &JThread) {}
+
+    fn thread_end(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv, thread: &JThread) {}
+
+    // Returned bytes replace the class data, nothing is replaced when None is returned
+    fn class_file_load_hook(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv, class_being_redefined: Option<&JClass>,
+                            loader: Option<&JObject>, name: &Option<String>, protection_domain: Option<&JObject>,
+                            class_data: &[u8]) -> Option<Vec<u8>> {
+        None
+    }
+
+    fn class_load(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv, thread: &JThread, class: &JClass) {}
+
+    fn class_prepare(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv, thread: &JThread, class: &JClass) {}
+
+    fn exception(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv, thread: &JThread, method: &JMethodId,
+                 location: JLocation, exception: &JObject, catch_method: Option<&JMethodId>, catch_location: JLocation) {}
+
+    fn exception_catch(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv, thread: &JThread, method: &JMethodId,
+                       location: JLocation, exception: &JObject) {}
+
+    fn single_step(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv, thread: &JThread, method: &JMethodId,
+                   location: JLocation) {}
+
+    fn frame_pop(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv, thread: &JThread, method: &JMethodId,
+                 was_popped_by_exception: bool) {}
+
+    fn breakpoint(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv, thread: &JThread, method: &JMethodId,
+                  location: JLocation) {}
+
+    fn field_access(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv, thread: &JThread, method: &JMethodId,
+                    location: JLocation, field_class: &JClass, object: Option<&JObject>, field: &JFieldId) {}
+
+    fn field_modification(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv, thread: &JThread, method: &JMethodId,
+                          location: JLocation, field_class: &JClass, object: Option<&JObject>, field: &JFieldId,
+                          new_value: &JValue) {}
+
+    fn method_entry(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv, thread: &JThread, method: &JMethodId) {}
+
+    // Return value type depends on the method signature, see JValue::from_raw
+    fn method_exit(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv, thread: &JThread, method: &JMethodId,
+                   was_popped_by_exception: bool, return_value: &rvmti_sys::jvalue) {}
+
+    // JNI environment and thread are absent during the primordial phase
+    fn native_method_bind(&self, env: &mut JvmtiEnv, jni_env: Option<&mut JniEnv>, thread: Option<&JThread>,
+                          method: &JMethodId, address: usize) {}
+
+    fn compiled_method_load(&self, env: &mut JvmtiEnv, method: &JMethodId,
+                            address_locations: &Option<Vec<AddressLocationEntry>>,
+                            compile_info: &Option<Vec<CompiledMethodLoadRecord>>, address: usize, length: usize) {}
+
+    fn compiled_method_unload(&self, env: &mut JvmtiEnv, method: &JMethodId, address: usize) {}
+
+    fn dynamic_code_generated(&self, env: &mut JvmtiEnv, name: &Option<String>, address: usize, length: usize) {}
+
+    fn data_dump_request(&self, env: &mut JvmtiEnv) {}
+
+    fn monitor_wait(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv, thread: &JThread, object: &JObject, timeout: i64) {}
+
+    fn monitor_waited(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv, thread: &JThread, object: &JObject,
+                      timed_out: bool) {}
+
+    fn monitor_contended_enter(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv, thread: &JThread, object: &JObject) {}
+
+    fn monitor_contended_entered(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv, thread: &JThread, object: &JObject) {}
+
+    fn resource_exhausted(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv, flags: i32, description: &Option<String>) {}
+
+    fn garbage_collection_start(&self, env: &mut JvmtiEnv) {}
+
+    fn garbage_collection_finish(&self, env: &mut JvmtiEnv) {}
+
+    fn object_free(&self, env: &mut JvmtiEnv, tag: i64) {}
+
+    fn vm_object_alloc(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv, thread: &JThread, object: &JObject,
+                       class: &JClass, size: i64) {}
+
+    fn sampled_object_alloc(&self, env: &mut JvmtiEnv, jni_env: &mut JniEnv, thread: &JThread, object: &JObject,
+                            class: &JClass, size: i64) {}
+
+}
+
+// Each environment has its own handler and callback table, kept in its environment local storage
+pub(crate) struct EnvironmentEvents {
+    handler: Option<Arc<dyn AgentEventHandler>>,
+    settings: JvmtiEventCallbacksSettings,
+}
+
+impl EnvironmentEvents {
+
+    pub(crate) fn new() -> EnvironmentEvents {
+        EnvironmentEvents{handler: None, settings: JvmtiEventCallbacksSettings::new_empty_settings()}
+    }
+
+}
+
+impl JvmtiEnv {
+
+    pub fn set_event_handler(&mut self, handler: Arc<dyn AgentEventHandler>) -> Result<(), JvmtiError> {
+        let data = self.environment_data_or_create()?;
+        let mut events = unsafe { &(*data).events }.write().unwrap_or_else(|e| e.into_inner());
+        events.handler = Some(handler);
+        Ok(())
+    }
+
+    pub fn clear_event_handler(&mut self) {
+        if let Ok(Some(data)) = self.environment_data() {
+            let mut events = unsafe { &(*data).events }.write().unwrap_or_else(|e| e.into_inner());
+            events.handler = None;
+        }
+    }
+
+    // Installs the dispatching callback for the event and enables its notification
+    pub fn enable_event(&mut self, event: JvmtiEvent, event_thread: Option<JThread>) -> Result<(), JvmtiError> {
+        {
+            let data = self.environment_data_or_create()?;
+            let mut events = unsafe { &(*data).events }.write().unwrap_or_else(|e| e.into_inner());
+            events.settings.event_enabled(event);
+            self.set_event_callbacks_settings(&events.settings)?;
+        }
+        self.set_event_notification_mode(JvmtiEventMode::Enable, event, event_thread)
+    }
+
+    // Callback stays installed, it may still be enabled for other threads
+    pub fn disable_event(&mut self, event: JvmtiEvent, event_thread: Option<JThread>) -> Result<(), JvmtiError> {
+        self.set_event_notification_mode(JvmtiEventMode::Disable, event, event_thread)
+    }
+
+}
+
+impl JvmtiEventCallbacksSettings {
+
+    pub fn event_enabled(&mut self, event: JvmtiEvent) {
+        match event {
+            JvmtiEvent::VmInit => self.vm_init_enabled(Some(jvmti_event_vm_init_handler)),
+            JvmtiEvent::VmDeath => self.vm_death_enabled(Some(jvmti_event_vm_death_handler)),
+            JvmtiEvent::ThreadStart => self.thread_start_enabled(Some(jvmti_event_thread_start_handler)),
+            JvmtiEvent::ThreadEnd => self.thread_end_enabled(Some(jvmti_event_thread_end_handler)),
+            JvmtiEvent::ClassFileLoadHook => self.class_file_load_hook_enabled(Some(jvmti_event_class_file_load_hook_handler)),
+            JvmtiEvent::ClassLoad => self.class_load_enabled(Some(jvmti_event_class_load_handler)),
+            JvmtiEvent::ClassPrepare => self.class_prepare_enabled(Some(jvmti_event_class_prepare_handler)),
+            JvmtiEvent::VmStart => self.vm_start_enabled(Some(jvmti_event_vm_start_handler)),
+            JvmtiEvent::Exception => self.exception_enabled(Some(jvmti_event_exception_handler)),
+            JvmtiEvent::ExceptionCatch => self.exception_catch_enabled(Some(jvmti_event_exception_catch_handler)),
+            JvmtiEvent::SingleStep => self.single_step_enabled(Some(jvmti_event_single_step_handler)),
+            JvmtiEvent::FramePop => self.frame_pop_enabled(Some(jvmti_event_frame_pop_handler)),
+            JvmtiEvent::Breakpoint => self.breakpoint_enabled(Some(jvmti_event_breakpoint_handler)),
+            JvmtiEvent::FieldAccess => self.field_access_enabled(Some(jvmti_event_field_access_handler)),
+            JvmtiEvent::FieldModification => self.field_modification_enabled(Some(jvmti_event_field_modification_handler)),
+            JvmtiEvent::MethodEntry => self.method_entry_enabled(Some(jvmti_event_method_entry_handler)),
+            JvmtiEvent::MethodExit => self.method_exit_enabled(Some(jvmti_event_method_exit_handler)),
+            JvmtiEvent::NativeMethodBind => self.native_method_bind_enabled(Some(jvmti_event_native_method_bind_handler)),
+            JvmtiEvent::CompiledMethodLoad => self.compiled_method_load_enabled(Some(jvmti_event_compiled_method_load_handler)),
+            JvmtiEvent::CompiledMethodUnload => self.compiled_method_unload_enabled(Some(jvmti_event_compiled_method_unload_handler)),
+            JvmtiEvent::DynamicCodeGenerated => self.dynamic_code_generated_enabled(Some(jvmti_event_dynamic_code_generated_handler)),
+            JvmtiEvent::DataDumpRequest => self.data_dump_request_enabled(Some(jvmti_event_data_dump_request_handler)),
+            JvmtiEvent::MonitorWait => self.monitor_wait_enabled(Some(jvmti_event_monitor_wait_handler)),
+            JvmtiEvent::MonitorWaited => self.monitor_waited_enabled(Some(jvmti_event_monitor_waited_handler)),
+            JvmtiEvent::MonitorContendedEnter => self.monitor_contended_enter_enabled(Some(jvmti_event_monitor_contended_enter_handler)),
+            JvmtiEvent::MonitorContendedEntered => self.monitor_contended_entered_enabled(Some(jvmti_event_monitor_contended_entered_handler)),
+            JvmtiEvent::ResourceExhausted => self.resource_exhausted_enabled(Some(jvmti_event_resource_exhausted_handler)),
+            JvmtiEvent::GarbageCollectionStart => self.garbage_collection_start_enabled(Some(jvmti_event_garbage_collection_start_handler)),
+            JvmtiEvent::GarbageCollectionFinish => self.garbage_collection_finish_enabled(Some(jvmti_event_garbage_collection_finish_handler)),
+            JvmtiEvent::ObjectFree => self.object_free_enabled(Some(jvmti_event_object_free_handler)),
+            JvmtiEvent::VmObjectAlloc => self.vm_object_alloc_enabled(Some(jvmti_event_vm_object_alloc_handler)),
+            JvmtiEvent::SampledObjectAlloc => self.sampled_object_alloc_enabled(Some(jvmti_event_sampled_object_alloc_handler)),
+        }
+    }
+
+}
+
+fn dispatch<F>(event_name: &str, jvmti_env: *mut rvmti_sys::jvmtiEnv, f: F)
+    where F: FnOnce(&dyn AgentEventHandler, &mut JvmtiEnv)
+{
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        // Handler is cloned out of the environment data, so it may enable or disable events itself
+        let mut env = JvmtiEnv::cons(jvmti_env);
+        let handler = match env.environment_data() {
+            Ok(Some(data)) => unsafe { &(*data).events }.read().unwrap_or_else(|e| e.into_inner()).handler.clone(),
+            _ => None,
+        };
+        if let Some(handler) = handler {
+            f(handler.as_ref(), &mut env);
+        }
+    }));
+    if let Err(e) = result {
+        warn!("Failed to handle '{}' event: {:?}", event_name, e);
+    }
+}
+
+fn optional_object(object: rvmti_sys::jobject) -> Option<JObject> {
+    if object.is_null() { None } else { Some(JObject{object}) }
+}
+
+fn decode_string(event_name: &str, value: *const c_char) -> Option<Option<String>> {
+    match from_modified_utf8(value) {
+        Ok(s) => Some(s),
+        Err(e) => {
+            warn!("Incorrect string for '{}' event: {:?}", event_name, e);
+            None
+        }
+    }
+}
+
+pub extern "C" fn jvmti_event_vm_init_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                              jni_env: *mut rvmti_sys::JNIEnv,
+                                              thread: rvmti_sys::jthread)
+{
+    dispatch("vm init", jvmti_env, |h, env| h.vm_init(env, &mut JniEnv::cons(jni_env), &JThread{thread}));
+}
+
+pub extern "C" fn jvmti_event_vm_death_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                               jni_env: *mut rvmti_sys::JNIEnv)
+{
+    dispatch("vm death", jvmti_env, |h, env| h.vm_death(env, &mut JniEnv::cons(jni_env)));
+}
+
+pub extern "C" fn jvmti_event_vm_start_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                               jni_env: *mut rvmti_sys::JNIEnv)
+{
+    dispatch("vm start", jvmti_env, |h, env| h.vm_start(env, &mut JniEnv::cons(jni_env)));
+}
+
+pub extern "C" fn jvmti_event_thread_start_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                   jni_env: *mut rvmti_sys::JNIEnv,
+                                                   thread: rvmti_sys::jthread)
+{
+    dispatch("thread start", jvmti_env, |h, env| h.thread_start(env, &mut JniEnv::cons(jni_env), &JThread{thread}));
+}
+
+pub extern "C" fn jvmti_event_thread_end_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                 jni_env: *mut rvmti_sys::JNIEnv,
+                                                 thread: rvmti_sys::jthread)
+{
+    dispatch("thread end", jvmti_env, |h, env| h.thread_end(env, &mut JniEnv::cons(jni_env), &JThread{thread}));
+}
+
+pub extern "C" fn jvmti_event_class_file_load_hook_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                           jni_env: *mut rvmti_sys::JNIEnv,
+                                                           class_being_redefined: rvmti_sys::jclass,
+                                                           loader: rvmti_sys::jobject,
+                                                           name: *const c_char,
+                                                           protection_domain: rvmti_sys::jobject,
+                                                           class_data_len: rvmti_sys::jint,
+                                                           class_data: *const c_uchar,
+                                                           new_class_data_len: *mut rvmti_sys::jint,
+                                                           new_class_data: *mut *mut c_uchar)
+{
+    dispatch("class file load hook", jvmti_env, |h, env| {
+        if let Some(name) = decode_string("class file load hook", name) {
+            let class = if class_being_redefined.is_null() { None } else { Some(JClass{class: class_being_redefined}) };
+            let data = if class_data.is_null() || class_data_len <= 0 {
+                &[]
+            } else {
+                unsafe { slice::from_raw_parts(class_data, class_data_len as usize) }
+            };
+            let replacement = h.class_file_load_hook(env, &mut JniEnv::cons(jni_env), class.as_ref(),
+                                                     optional_object(loader).as_ref(), &name,
+                                                     optional_object(protection_domain).as_ref(), data);
+            // VM takes ownership of the replacement, it must come from Allocate
+            if let Some(bytes) = replacement {
+                if new_class_data_len.is_null() || new_class_data.is_null() {
+                    return;
+                }
+                match env.allocate_copy(&bytes) {
+                    Ok(mem) => unsafe {
+                        *new_class_data_len = bytes.len() as rvmti_sys::jint;
+                        *new_class_data = mem;
+                    },
+                    Err(e) => warn!("Failed to allocate replacement class data for {:?}: {}", name, e),
+                }
+            }
+        }
+    });
+}
+
+pub extern "C" fn jvmti_event_class_load_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                 jni_env: *mut rvmti_sys::JNIEnv,
+                                                 thread: rvmti_sys::jthread,
+                                                 klass: rvmti_sys::jclass)
+{
+    dispatch("class load", jvmti_env, |h, env| h.class_load(env, &mut JniEnv::cons(jni_env), &JThread{thread},
+                                                             &JClass{class: klass}));
+}
+
+pub extern "C" fn jvmti_event_class_prepare_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                    jni_env: *mut rvmti_sys::JNIEnv,
+                                                    thread: rvmti_sys::jthread,
+                                                    klass: rvmti_sys::jclass)
+{
+    dispatch("class prepare", jvmti_env, |h, env| h.class_prepare(env, &mut JniEnv::cons(jni_env), &JThread{thread},
+                                                                   &JClass{class: klass}));
+}
+
+pub extern "C" fn jvmti_event_exception_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                jni_env: *mut rvmti_sys::JNIEnv,
+                                                thread: rvmti_sys::jthread,
+                                                method: rvmti_sys::jmethodID,
+                                                location: rvmti_sys::jlocation,
+                                                exception: rvmti_sys::jobject,
+                                                catch_method: rvmti_sys::jmethodID,
+                                                catch_location: rvmti_sys::jlocation)
+{
+    dispatch("exception", jvmti_env, |h, env| {
+        let catch_method = if catch_method.is_null() { None } else { Some(JMethodId{method: catch_method}) };
+        h.exception(env, &mut JniEnv::cons(jni_env), &JThread{thread}, &JMethodId{method}, location,
+                    &JObject{object: exception}, catch_method.as_ref(), catch_location)
+    });
+}
+
+pub extern "C" fn jvmti_event_exception_catch_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                      jni_env: *mut rvmti_sys::JNIEnv,
+                                                      thread: rvmti_sys::jthread,
+                                                      method: rvmti_sys::jmethodID,
+                                                      location: rvmti_sys::jlocation,
+                                                      exception: rvmti_sys::jobject)
+{
+    dispatch("exception catch", jvmti_env, |h, env| h.exception_catch(env, &mut JniEnv::cons(jni_env), &JThread{thread},
+                                                                       &JMethodId{method}, location,
+                                                                       &JObject{object: exception}));
+}
+
+pub extern "C" fn jvmti_event_single_step_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                  jni_env: *mut rvmti_sys::JNIEnv,
+                                                  thread: rvmti_sys::jthread,
+                                                  method: rvmti_sys::jmethodID,
+                                                  location: rvmti_sys::jlocation)
+{
+    dispatch("single step", jvmti_env, |h, env| h.single_step(env, &mut JniEnv::cons(jni_env), &JThread{thread},
+                                                               &JMethodId{method}, location));
+}
+
+pub extern "C" fn jvmti_event_frame_pop_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                jni_env: *mut rvmti_sys::JNIEnv,
+                                                thread: rvmti_sys::jthread,
+                                                method: rvmti_sys::jmethodID,
+                                                was_popped_by_exception: rvmti_sys::jboolean)
+{
+    dispatch("frame pop", jvmti_env, |h, env| h.frame_pop(env, &mut JniEnv::cons(jni_env), &JThread{thread},
+                                                           &JMethodId{method}, was_popped_by_exception != 0));
+}
+
+pub extern "C" fn jvmti_event_breakpoint_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                 jni_env: *mut rvmti_sys::JNIEnv,
+                                                 thread: rvmti_sys::jthread,
+                                                 method: rvmti_sys::jmethodID,
+                                                 location: rvmti_sys::jlocation)
+{
+    dispatch("breakpoint", jvmti_env, |h, env| h.breakpoint(env, &mut JniEnv::cons(jni_env), &JThread{thread},
+                                                             &JMethodId{method}, location));
+}
+
+pub extern "C" fn jvmti_event_field_access_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                   jni_env: *mut rvmti_sys::JNIEnv,
+                                                   thread: rvmti_sys::jthread,
+                                                   method: rvmti_sys::jmethodID,
+                                                   location: rvmti_sys::jlocation,
+                                                   field_klass: rvmti_sys::jclass,
+                                                   object: rvmti_sys::jobject,
+                                                   field: rvmti_sys::jfieldID)
+{
+    dispatch("field access", jvmti_env, |h, env| h.field_access(env, &mut JniEnv::cons(jni_env), &JThread{thread},
+                                                                 &JMethodId{method}, location, &JClass{class: field_klass},
+                                                                 optional_object(object).as_ref(), &JFieldId{field}));
+}
+
+pub extern "C" fn jvmti_event_field_modification_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                         jni_env: *mut rvmti_sys::JNIEnv,
+                                                         thread: rvmti_sys::jthread,
+                                                         method: rvmti_sys::jmethodID,
+                                                         location: rvmti_sys::jlocation,
+                                                         field_klass: rvmti_sys::jclass,
+                                                         object: rvmti_sys::jobject,
+                                                         field: rvmti_sys::jfieldID,
+                                                         signature_type: c_char,
+                                                         new_value: rvmti_sys::jvalue)
+{
+    dispatch("field modification", jvmti_env, |h, env| {
+        match JValue::from_raw(new_value, signature_type as u8 as char) {
+            Some(value) => h.field_modification(env, &mut JniEnv::cons(jni_env), &JThread{thread}, &JMethodId{method},
+                                                location, &JClass{class: field_klass}, optional_object(object).as_ref(),
+                                                &JFieldId{field}, &value),
+            None => warn!("Unknown signature type '{}' for 'field modification' event", signature_type as u8 as char),
+        }
+    });
+}
+
+pub extern "C" fn jvmti_event_method_entry_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                   jni_env: *mut rvmti_sys::JNIEnv,
+                                                   thread: rvmti_sys::jthread,
+                                                   method: rvmti_sys::jmethodID)
+{
+    dispatch("method entry", jvmti_env, |h, env| h.method_entry(env, &mut JniEnv::cons(jni_env), &JThread{thread},
+                                                                 &JMethodId{method}));
+}
+
+pub extern "C" fn jvmti_event_method_exit_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                  jni_env: *mut rvmti_sys::JNIEnv,
+                                                  thread: rvmti_sys::jthread,
+                                                  method: rvmti_sys::jmethodID,
+                                                  was_popped_by_exception: rvmti_sys::jboolean,
+                                                  return_value: rvmti_sys::jvalue)
+{
+    dispatch("method exit", jvmti_env, |h, env| h.method_exit(env, &mut JniEnv::cons(jni_env), &JThread{thread},
+                                                               &JMethodId{method}, was_popped_by_exception != 0,
+                                                               &return_value));
+}
+
+pub extern "C" fn jvmti_event_native_method_bind_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                         jni_env: *mut rvmti_sys::JNIEnv,
+                                                         thread: rvmti_sys::jthread,
+                                                         method: rvmti_sys::jmethodID,
+                                                         address: *mut c_void,
+                                                         _new_address_ptr: *mut *mut c_void)
+{
+    dispatch("native method bind", jvmti_env, |h, env| {
+        let mut jni = if jni_env.is_null() { None } else { Some(JniEnv::cons(jni_env)) };
+        let thread = if thread.is_null() { None } else { Some(JThread{thread}) };
+        h.native_method_bind(env, jni.as_mut(), thread.as_ref(), &JMethodId{method}, address as usize)
+    });
+}
+
+pub extern "C" fn jvmti_event_compiled_method_load_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                           method: rvmti_sys::jmethodID,
+                                                           code_size: rvmti_sys::jint,
+                                                           code_addr: *const c_void,
+                                                           map_length: rvmti_sys::jint,
+                                                           map: *const rvmti_sys::jvmtiAddrLocationMap,
+                                                           compile_info: *const c_void)
+{
+    dispatch("compiled method load", jvmti_env, |h, env| {
+        let address_locations = as_address_location_slice(map_length, map).map(|t| t.iter()
+            .map(|e| AddressLocationEntry{start_address: e.start_address as usize, location: e.location}).collect());
+        let compile_infos = to_compile_infos(compile_info);
+        h.compiled_method_load(env, &JMethodId{method}, &address_locations, &compile_infos, code_addr as usize,
+                               code_size as usize)
+    });
+}
+
+pub extern "C" fn jvmti_event_compiled_method_unload_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                             method: rvmti_sys::jmethodID,
+                                                             code_addr: *const c_void)
+{
+    dispatch("compiled method unload", jvmti_env, |h, env| h.compiled_method_unload(env, &JMethodId{method},
+                                                                                     code_addr as usize));
+}
+
+pub extern "C" fn jvmti_event_dynamic_code_generated_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                             name: *const c_char,
+                                                             address: *const c_void,
+                                                             length: rvmti_sys::jint)
+{
+    dispatch("dynamic code generated", jvmti_env, |h, env| {
+        if let Some(name) = decode_string("dynamic code generated", name) {
+            h.dynamic_code_generated(env, &name, address as usize, length as usize)
+        }
+    });
+}
+
+pub extern "C" fn jvmti_event_data_dump_request_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv) {
+    dispatch("data dump request", jvmti_env, |h, env| h.data_dump_request(env));
+}
+
+pub extern "C" fn jvmti_event_monitor_wait_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                   jni_env: *mut rvmti_sys::JNIEnv,
+                                                   thread: rvmti_sys::jthread,
+                                                   object: rvmti_sys::jobject,
+                                                   timeout: rvmti_sys::jlong)
+{
+    dispatch("monitor wait", jvmti_env, |h, env| h.monitor_wait(env, &mut JniEnv::cons(jni_env), &JThread{thread},
+                                                                 &JObject{object}, timeout));
+}
+
+pub extern "C" fn jvmti_event_monitor_waited_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                     jni_env: *mut rvmti_sys::JNIEnv,
+                                                     thread: rvmti_sys::jthread,
+                                                     object: rvmti_sys::jobject,
+                                                     timed_out: rvmti_sys::jboolean)
+{
+    dispatch("monitor waited", jvmti_env, |h, env| h.monitor_waited(env, &mut JniEnv::cons(jni_env), &JThread{thread},
+                                                                     &JObject{object}, timed_out != 0));
+}
+
+pub extern "C" fn jvmti_event_monitor_contended_enter_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                              jni_env: *mut rvmti_sys::JNIEnv,
+                                                              thread: rvmti_sys::jthread,
+                                                              object: rvmti_sys::jobject)
+{
+    dispatch("monitor contended enter", jvmti_env, |h, env| h.monitor_contended_enter(env, &mut JniEnv::cons(jni_env),
+                                                                                       &JThread{thread}, &JObject{object}));
+}
+
+pub extern "C" fn jvmti_event_monitor_contended_entered_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                                jni_env: *mut rvmti_sys::JNIEnv,
+                                                                thread: rvmti_sys::jthread,
+                                                                object: rvmti_sys::jobject)
+{
+    dispatch("monitor contended entered", jvmti_env, |h, env| h.monitor_contended_entered(env, &mut JniEnv::cons(jni_env),
+                                                                                           &JThread{thread}, &JObject{object}));
+}
+
+pub extern "C" fn jvmti_event_resource_exhausted_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                         jni_env: *mut rvmti_sys::JNIEnv,
+                                                         flags: rvmti_sys::jint,
+                                                         _reserved: *const c_void,
+                                                         description: *const c_char)
+{
+    dispatch("resource exhausted", jvmti_env, |h, env| {
+        if let Some(description) = decode_string("resource exhausted", description) {
+            h.resource_exhausted(env, &mut JniEnv::cons(jni_env), flags, &description)
+        }
+    });
+}
+
+pub extern "C" fn jvmti_event_garbage_collection_start_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv) {
+    dispatch("garbage collection start", jvmti_env, |h, env| h.garbage_collection_start(env));
+}
+
+pub extern "C" fn jvmti_event_garbage_collection_finish_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv) {
+    dispatch("garbage collection finish", jvmti_env, |h, env| h.garbage_collection_finish(env));
+}
+
+pub extern "C" fn jvmti_event_object_free_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv, tag: rvmti_sys::jlong) {
+    dispatch("object free", jvmti_env, |h, env| h.object_free(env, tag));
+}
+
+pub extern "C" fn jvmti_event_vm_object_alloc_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                      jni_env: *mut rvmti_sys::JNIEnv,
+                                                      thread: rvmti_sys::jthread,
+                                                      object: rvmti_sys::jobject,
+                                                      object_klass: rvmti_sys::jclass,
+                                                      size: rvmti_sys::jlong)
+{
+    dispatch("vm object alloc", jvmti_env, |h, env| h.vm_object_alloc(env, &mut JniEnv::cons(jni_env), &JThread{thread},
+                                                                       &JObject{object}, &JClass{class: object_klass}, size));
+}
+
+pub extern "C" fn jvmti_event_sampled_object_alloc_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
+                                                           jni_env: *mut rvmti_sys::JNIEnv,
+                                                           thread: rvmti_sys::jthread,
+                                                           object: rvmti_sys::jobject,
+                                                           object_klass: rvmti_sys::jclass,
+                                                           size: rvmti_sys::jlong)
+{
+    dispatch("sampled object alloc", jvmti_env, |h, env| h.sampled_object_alloc(env, &mut JniEnv::cons(jni_env),
+                                                                                 &JThread{thread}, &JObject{object},
+                                                                                 &JClass{class: object_klass}, size));
+}
@@ -0,0 +1,486 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+// JNI wrappers, JNI environment is only valid on the thread it was passed to
+
+use std::mem;
+use std::ops::Deref;
+use std::os::raw::c_void;
+use std::ptr;
+
+use log::warn;
+use thiserror::Error;
+
+use super::{JniEnv, JClass, JObject, JThread, JThreadGroup, JMethodId, JniError, StringDecodeError, from_modified_utf8, to_modified_utf8};
+
+// Primitive return types, object returning methods are called with `call_object_method`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JavaType {
+    Boolean,
+    Byte,
+    Char,
+    Short,
+    Int,
+    Long,
+    Float,
+    Double,
+}
+
+#[derive(Debug)]
+pub enum JArg<'a> {
+    Boolean(bool),
+    Byte(i8),
+    Char(u16),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    Object(Option<&'a JObject>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JPrimitive {
+    Boolean(bool),
+    Byte(i8),
+    Char(u16),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+}
+
+pub trait JniReference {
+
+    fn as_raw_object(&self) -> rvmti_sys::jobject;
+
+    // Caller must own the reference being wrapped
+    #[allow(clippy::missing_safety_doc)]
+    unsafe fn from_raw_object(object: rvmti_sys::jobject) -> Self;
+
+}
+
+// Deleted when dropped, borrows JNI environment so it can not outlive the native frame it was created in
+#[derive(Debug)]
+pub struct Local<'env, T: JniReference> {
+    reference: T,
+    env: &'env JniEnv,
+}
+
+// Valid on any thread until dropped, deleted using JNI environment of the dropping thread
+#[derive(Debug)]
+pub struct Global<T: JniReference> {
+    reference: T,
+    vm: *mut rvmti_sys::JavaVM,
+}
+
+// Global references are not bound to a thread and invocation interface is thread-safe
+unsafe impl<T: JniReference> Send for Global<T> {}
+unsafe impl<T: JniReference> Sync for Global<T> {}
+
+#[derive(Error, Debug)]
+pub enum JniCallError {
+    // Exception is left pending, caller decides whether to describe or clear it
+    #[error("Java exception is pending")]
+    PendingException,
+    #[error("JNI call unexpectedly returned null")]
+    NullResult,
+    #[error("Failed to create local frame")]
+    LocalFrameError,
+    #[error("Failed to obtain Java VM: {0}")]
+    JavaVmError(#[source] JniError),
+    #[error("Failed to decode string: {0}")]
+    StringDecodeError(#[source] StringDecodeError),
+}
+
+impl JniEnv {
+
+    pub fn find_class(&self, name: &str) -> Result<Local<'_, JClass>, JniCallError> {
+        let name = to_modified_utf8(name);
+        unsafe {
+            let class = (*(*self.env)).FindClass.unwrap()(self.env, name.as_ptr());
+            self.check_local(class)
+        }
+    }
+
+    pub fn get_object_class(&self, object: &JObject) -> Result<Local<'_, JClass>, JniCallError> {
+        unsafe {
+            let class = (*(*self.env)).GetObjectClass.unwrap()(self.env, object.object);
+            self.check_local(class)
+        }
+    }
+
+    pub fn get_method_id(&self, class: &JClass, name: &str, signature: &str) -> Result<JMethodId, JniCallError> {
+        let name = to_modified_utf8(name);
+        let signature = to_modified_utf8(signature);
+        unsafe {
+            let method = (*(*self.env)).GetMethodID.unwrap()(self.env, class.class, name.as_ptr(), signature.as_ptr());
+            self.check_pointer(method).map(|method| JMethodId{method})
+        }
+    }
+
+    pub fn get_static_method_id(&self, class: &JClass, name: &str, signature: &str) -> Result<JMethodId, JniCallError> {
+        let name = to_modified_utf8(name);
+        let signature = to_modified_utf8(signature);
+        unsafe {
+            let method = (*(*self.env)).GetStaticMethodID.unwrap()(self.env, class.class, name.as_ptr(), signature.as_ptr());
+            self.check_pointer(method).map(|method| JMethodId{method})
+        }
+    }
+
+    pub fn call_method(&self, object: &JObject, method: &JMethodId, return_type: JavaType,
+                       args: &[JArg]) -> Result<JPrimitive, JniCallError>
+    {
+        let args: Vec<rvmti_sys::jvalue> = args.iter().map(JArg::to_raw).collect();
+        let (env, object, method, args) = (self.env, object.object, method.method, args.as_ptr());
+        unsafe {
+            let functions = *self.env;
+            let result = match return_type {
+                JavaType::Boolean => JPrimitive::Boolean((*functions).CallBooleanMethodA.unwrap()(env, object, method, args) != 0),
+                JavaType::Byte => JPrimitive::Byte((*functions).CallByteMethodA.unwrap()(env, object, method, args)),
+                JavaType::Char => JPrimitive::Char((*functions).CallCharMethodA.unwrap()(env, object, method, args)),
+                JavaType::Short => JPrimitive::Short((*functions).CallShortMethodA.unwrap()(env, object, method, args)),
+                JavaType::Int => JPrimitive::Int((*functions).CallIntMethodA.unwrap()(env, object, method, args)),
+                JavaType::Long => JPrimitive::Long((*functions).CallLongMethodA.unwrap()(env, object, method, args)),
+                JavaType::Float => JPrimitive::Float((*functions).CallFloatMethodA.unwrap()(env, object, method, args)),
+                JavaType::Double => JPrimitive::Double((*functions).CallDoubleMethodA.unwrap()(env, object, method, args)),
+            };
+            self.check_exception().map(|_| result)
+        }
+    }
+
+    pub fn call_object_method(&self, object: &JObject, method: &JMethodId,
+                              args: &[JArg]) -> Result<Option<Local<'_, JObject>>, JniCallError>
+    {
+        let args: Vec<rvmti_sys::jvalue> = args.iter().map(JArg::to_raw).collect();
+        unsafe {
+            let result = (*(*self.env)).CallObjectMethodA.unwrap()(self.env, object.object, method.method, args.as_ptr());
+            self.check_nullable_local(result)
+        }
+    }
+
+    pub fn call_void_method(&self, object: &JObject, method: &JMethodId, args: &[JArg]) -> Result<(), JniCallError> {
+        let args: Vec<rvmti_sys::jvalue> = args.iter().map(JArg::to_raw).collect();
+        unsafe {
+            (*(*self.env)).CallVoidMethodA.unwrap()(self.env, object.object, method.method, args.as_ptr());
+        }
+        self.check_exception()
+    }
+
+    pub fn call_static_method(&self, class: &JClass, method: &JMethodId, return_type: JavaType,
+                              args: &[JArg]) -> Result<JPrimitive, JniCallError>
+    {
+        let args: Vec<rvmti_sys::jvalue> = args.iter().map(JArg::to_raw).collect();
+        let (env, class, method, args) = (self.env, class.class, method.method, args.as_ptr());
+        unsafe {
+            let functions = *self.env;
+            let result = match return_type {
+                JavaType::Boolean => JPrimitive::Boolean((*functions).CallStaticBooleanMethodA.unwrap()(env, class, method, args) != 0),
+                JavaType::Byte => JPrimitive::Byte((*functions).CallStaticByteMethodA.unwrap()(env, class, method, args)),
+                JavaType::Char => JPrimitive::Char((*functions).CallStaticCharMethodA.unwrap()(env, class, method, args)),
+                JavaType::Short => JPrimitive::Short((*functions).CallStaticShortMethodA.unwrap()(env, class, method, args)),
+                JavaType::Int => JPrimitive::Int((*functions).CallStaticIntMethodA.unwrap()(env, class, method, args)),
+                JavaType::Long => JPrimitive::Long((*functions).CallStaticLongMethodA.unwrap()(env, class, method, args)),
+                JavaType::Float => JPrimitive::Float((*functions).CallStaticFloatMethodA.unwrap()(env, class, method, args)),
+                JavaType::Double => JPrimitive::Double((*functions).CallStaticDoubleMethodA.unwrap()(env, class, method, args)),
+            };
+            self.check_exception().map(|_| result)
+        }
+    }
+
+    pub fn call_static_object_method(&self, class: &JClass, method: &JMethodId,
+                                     args: &[JArg]) -> Result<Option<Local<'_, JObject>>, JniCallError>
+    {
+        let args: Vec<rvmti_sys::jvalue> = args.iter().map(JArg::to_raw).collect();
+        unsafe {
+            let result = (*(*self.env)).CallStaticObjectMethodA.unwrap()(self.env, class.class, method.method, args.as_ptr());
+            self.check_nullable_local(result)
+        }
+    }
+
+    pub fn call_static_void_method(&self, class: &JClass, method: &JMethodId, args: &[JArg]) -> Result<(), JniCallError> {
+        let args: Vec<rvmti_sys::jvalue> = args.iter().map(JArg::to_raw).collect();
+        unsafe {
+            (*(*self.env)).CallStaticVoidMethodA.unwrap()(self.env, class.class, method.method, args.as_ptr());
+        }
+        self.check_exception()
+    }
+
+    pub fn new_string(&self, value: &str) -> Result<Local<'_, JObject>, JniCallError> {
+        let value = to_modified_utf8(value);
+        unsafe {
+            let string = (*(*self.env)).NewStringUTF.unwrap()(self.env, value.as_ptr());
+            self.check_local(string)
+        }
+    }
+
+    pub fn get_string(&self, string: &JObject) -> Result<String, JniCallError> {
+        unsafe {
+            let chars = (*(*self.env)).GetStringUTFChars.unwrap()(self.env, string.object, ptr::null_mut());
+            if chars.is_null() {
+                self.check_exception()?;
+                return Err(JniCallError::NullResult);
+            }
+            let result = from_modified_utf8(chars);
+            (*(*self.env)).ReleaseStringUTFChars.unwrap()(self.env, string.object, chars);
+            match result {
+                Ok(Some(value)) => Ok(value),
+                Ok(None) => Err(JniCallError::NullResult),
+                Err(e) => Err(JniCallError::StringDecodeError(e)),
+            }
+        }
+    }
+
+    pub fn get_array_length(&self, array: &JObject) -> Result<i32, JniCallError> {
+        unsafe {
+            let length = (*(*self.env)).GetArrayLength.unwrap()(self.env, array.object);
+            self.check_exception().map(|_| length)
+        }
+    }
+
+    pub fn get_object_array_element(&self, array: &JObject, index: i32) -> Result<Option<Local<'_, JObject>>, JniCallError> {
+        unsafe {
+            let element = (*(*self.env)).GetObjectArrayElement.unwrap()(self.env, array.object, index);
+            self.check_nullable_local(element)
+        }
+    }
+
+    // References of different kinds may refer to the same object, raw pointers can not be compared
+    pub fn is_same_object<A: JniReference, B: JniReference>(&self, first: &A, second: &B) -> bool {
+        unsafe {
+            (*(*self.env)).IsSameObject.unwrap()(self.env, first.as_raw_object(), second.as_raw_object()) != 0
+        }
+    }
+
+    pub fn exception_check(&self) -> bool {
+        unsafe {
+            (*(*self.env)).ExceptionCheck.unwrap()(self.env) != 0
+        }
+    }
+
+    pub fn exception_occurred(&self) -> Option<Local<'_, JObject>> {
+        unsafe {
+            let exception = (*(*self.env)).ExceptionOccurred.unwrap()(self.env);
+            self.local(exception)
+        }
+    }
+
+    pub fn exception_describe(&self) {
+        unsafe {
+            (*(*self.env)).ExceptionDescribe.unwrap()(self.env)
+        }
+    }
+
+    pub fn exception_clear(&self) {
+        unsafe {
+            (*(*self.env)).ExceptionClear.unwrap()(self.env)
+        }
+    }
+
+    // Local references created inside are freed with the frame, only the returned one is carried over
+    pub fn with_local_frame<F>(&self, capacity: i32, f: F) -> Result<Option<Local<'_, JObject>>, JniCallError>
+        where F: for<'frame> FnOnce(&'frame JniEnv) -> Result<Option<Local<'frame, JObject>>, JniCallError>
+    {
+        unsafe {
+            if (*(*self.env)).PushLocalFrame.unwrap()(self.env, capacity) != rvmti_sys::JNI_OK {
+                return Err(JniCallError::LocalFrameError);
+            }
+            let frame_env = JniEnv{env: self.env};
+            let result = f(&frame_env).map(|r| r.map(Local::into_raw));
+            let object = match result {
+                Ok(Some(object)) => object,
+                _ => ptr::null_mut(),
+            };
+            let object = (*(*self.env)).PopLocalFrame.unwrap()(self.env, object);
+            result.map(|_| self.local(object))
+        }
+    }
+
+    pub fn new_global_ref<T: JniReference>(&self, reference: &T) -> Result<Global<T>, JniCallError> {
+        let vm = self.get_java_vm()?;
+        unsafe {
+            let object = (*(*self.env)).NewGlobalRef.unwrap()(self.env, reference.as_raw_object());
+            self.check_pointer(object).map(|object| Global{reference: T::from_raw_object(object), vm})
+        }
+    }
+
+    fn get_java_vm(&self) -> Result<*mut rvmti_sys::JavaVM, JniCallError> {
+        unsafe {
+            let mut vm: *mut rvmti_sys::JavaVM = ptr::null_mut();
+            let result = (*(*self.env)).GetJavaVM.unwrap()(self.env, &mut vm);
+            if result == rvmti_sys::JNI_OK {
+                Ok(vm)
+            } else {
+                Err(JniCallError::JavaVmError(JniError::from(result)))
+            }
+        }
+    }
+
+    // Wraps local reference owned by the current native frame
+    pub(crate) unsafe fn local<T: JniReference>(&self, object: rvmti_sys::jobject) -> Option<Local<'_, T>> {
+        if object.is_null() {
+            None
+        } else {
+            Some(Local{reference: T::from_raw_object(object), env: self})
+        }
+    }
+
+    fn check_exception(&self) -> Result<(), JniCallError> {
+        if self.exception_check() {
+            return Err(JniCallError::PendingException);
+        }
+        Ok(())
+    }
+
+    fn check_pointer<T>(&self, value: *mut T) -> Result<*mut T, JniCallError> {
+        self.check_exception()?;
+        if value.is_null() {
+            return Err(JniCallError::NullResult);
+        }
+        Ok(value)
+    }
+
+    unsafe fn check_local<T: JniReference>(&self, object: rvmti_sys::jobject) -> Result<Local<'_, T>, JniCallError> {
+        self.check_nullable_local(object)?.ok_or(JniCallError::NullResult)
+    }
+
+    unsafe fn check_nullable_local<T: JniReference>(&self, object: rvmti_sys::jobject) -> Result<Option<Local<'_, T>>, JniCallError> {
+        let local = self.local(object);
+        self.check_exception()?;
+        Ok(local)
+    }
+
+}
+
+impl<'env, T: JniReference> Local<'env, T> {
+
+    pub fn to_global(&self) -> Result<Global<T>, JniCallError> {
+        self.env.new_global_ref(&self.reference)
+    }
+
+    fn into_raw(self) -> rvmti_sys::jobject {
+        let object = self.reference.as_raw_object();
+        mem::forget(self);
+        object
+    }
+
+}
+
+impl<'env, T: JniReference> Deref for Local<'env, T> {
+
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.reference
+    }
+
+}
+
+impl<'env, T: JniReference> Drop for Local<'env, T> {
+
+    fn drop(&mut self) {
+        unsafe {
+            (*(*self.env.env)).DeleteLocalRef.unwrap()(self.env.env, self.reference.as_raw_object());
+        }
+    }
+
+}
+
+impl<T: JniReference> Deref for Global<T> {
+
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.reference
+    }
+
+}
+
+impl<T: JniReference> Drop for Global<T> {
+
+    fn drop(&mut self) {
+        unsafe {
+            let mut env: *mut c_void = ptr::null_mut();
+            let result = (*(*self.vm)).GetEnv.unwrap()(self.vm, &mut env, rvmti_sys::JNI_VERSION_1_8 as rvmti_sys::jint);
+            if result == rvmti_sys::JNI_OK && !env.is_null() {
+                let env = env as *mut rvmti_sys::JNIEnv;
+                (*(*env)).DeleteGlobalRef.unwrap()(env, self.reference.as_raw_object());
+            } else {
+                warn!("Global reference is dropped on a thread detached from VM and leaks: {}", JniError::from(result));
+            }
+        }
+    }
+
+}
+
+impl JniReference for JObject {
+
+    fn as_raw_object(&self) -> rvmti_sys::jobject {
+        self.object
+    }
+
+    unsafe fn from_raw_object(object: rvmti_sys::jobject) -> JObject {
+        JObject{object}
+    }
+
+}
+
+impl JniReference for JClass {
+
+    fn as_raw_object(&self) -> rvmti_sys::jobject {
+        self.class
+    }
+
+    unsafe fn from_raw_object(object: rvmti_sys::jobject) -> JClass {
+        JClass{class: object}
+    }
+
+}
+
+impl JniReference for JThread {
+
+    fn as_raw_object(&self) -> rvmti_sys::jobject {
+        self.thread
+    }
+
+    unsafe fn from_raw_object(object: rvmti_sys::jobject) -> JThread {
+        JThread{thread: object}
+    }
+
+}
+
+impl JniReference for JThreadGroup {
+
+    fn as_raw_object(&self) -> rvmti_sys::jobject {
+        self.group
+    }
+
+    unsafe fn from_raw_object(object: rvmti_sys::jobject) -> JThreadGroup {
+        JThreadGroup{group: object}
+    }
+
+}
+
+impl<'a> JArg<'a> {
+
+    pub fn to_raw(&self) -> rvmti_sys::jvalue {
+        match self {
+            JArg::Boolean(v) => rvmti_sys::jvalue{z: *v as rvmti_sys::jboolean},
+            JArg::Byte(v) => rvmti_sys::jvalue{b: *v},
+            JArg::Char(v) => rvmti_sys::jvalue{c: *v},
+            JArg::Short(v) => rvmti_sys::jvalue{s: *v},
+            JArg::Int(v) => rvmti_sys::jvalue{i: *v},
+            JArg::Long(v) => rvmti_sys::jvalue{j: *v},
+            JArg::Float(v) => rvmti_sys::jvalue{f: *v},
+            JArg::Double(v) => rvmti_sys::jvalue{d: *v},
+            JArg::Object(v) => rvmti_sys::jvalue{l: v.map_or(ptr::null_mut(), |o| o.object)},
+        }
+    }
+
+}
@@ -5,375 +5,72 @@
 //   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
 // THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::ffi::CStr;
+
+// Safe wrappers over JVMTI and JNI invocation interfaces, allows to write JVMTI agents in Rust
+
+// Explicit returns are the style of this code base
+#![allow(clippy::needless_return)]
+
+use std::ffi::{CStr, CString};
 use std::os::raw::{c_char, c_uchar, c_void};
 use std::string::FromUtf8Error;
 use std::str;
 use std::ptr;
-use std::panic;
 use std::slice;
 use std::mem::size_of;
 
-use log::{debug, warn, error};
+use log::{debug, warn};
 use thiserror::Error;
 
-use crate::agent_on_load;
-use crate::agent_on_unload;
-use crate::jvmti_event_dynamic_code_generated;
-use crate::jvmti_event_compiled_method_load;
-
-#[allow(non_snake_case)]
-#[allow(unused_variables)]
-#[no_mangle]
-pub extern "C" fn Agent_OnLoad(vm: *mut rvmti_sys::JavaVM, options: *const c_char, reserved: *const c_void) -> rvmti_sys::jint {
-    let log_init_result = panic::catch_unwind(|| {
-        env_logger::init()
-    });
-    match log_init_result {
-        Ok(r) => {},
-        Err(e) => {
-            println!("Failed to initialize logger: {:?}", e);
-            return -1;
-        }
-    }
-    let result = panic::catch_unwind(|| {
-        debug!("Agent 'on load'");
-        // Options is a platform string, not modified utf-8 string, see https://bugs.openjdk.java.net/browse/JDK-5049313
-        let options_string = from_platform(options);
-        return match options_string {
-            Ok(s) => agent_on_load(&Jvm { vm }, &s),
-            Err(e) => {
-                error!("Failed to process options string {}", e);
-                -1
-            },
-        }
-    });
-    match result {
-        Ok(v) => v,
-        Err(e) => {
-            error!("Failed to load agent: {:?}", e);
-            -1
-        }
-    }
-}
-
-#[allow(non_snake_case)]
-#[no_mangle]
-pub extern "C" fn Agent_OnUnload(vm: *mut rvmti_sys::JavaVM) {
-    debug!("Agent 'on unload'");
-    let result = panic::catch_unwind(|| {
-        agent_on_unload(&Jvm { vm })
-    });
-    match result {
-        Ok(()) => (),
-        Err(e) => {
-            warn!("Failed to unload agent: {:?}", e);
-        }
-    }
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_breakpoint_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                 _jni_env: *mut rvmti_sys::JNIEnv,
-                                                 _thread: rvmti_sys::jthread,
-                                                 _method: rvmti_sys::jmethodID,
-                                                 _location: rvmti_sys::jlocation)
-{
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_class_file_load_hook_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                           _jni_env: *mut rvmti_sys::JNIEnv,
-                                                           _class_being_redefined: rvmti_sys::jclass,
-                                                           _loader: rvmti_sys::jobject,
-                                                           _name: *const ::std::os::raw::c_char,
-                                                           _protection_domain: rvmti_sys::jobject,
-                                                           _class_data_len: rvmti_sys::jint,
-                                                           _class_data: *const ::std::os::raw::c_uchar,
-                                                           _new_class_data_len: *mut rvmti_sys::jint,
-                                                           _new_class_data: *mut *mut ::std::os::raw::c_uchar)
-{
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_class_load_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                 _jni_env: *mut rvmti_sys::JNIEnv,
-                                                 _thread: rvmti_sys::jthread,
-                                                 _klass: rvmti_sys::jclass)
-{
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_class_prepare_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                    _jni_env: *mut rvmti_sys::JNIEnv,
-                                                    _thread: rvmti_sys::jthread,
-                                                    _klass: rvmti_sys::jclass)
-{
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_compiled_method_load_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                           method: rvmti_sys::jmethodID,
-                                                           code_size: rvmti_sys::jint,
-                                                           code_addr: *const ::std::os::raw::c_void,
-                                                           map_length: rvmti_sys::jint,
-                                                           map: *const rvmti_sys::jvmtiAddrLocationMap,
-                                                           compile_info: *const ::std::os::raw::c_void)
-{
-    let result = panic::catch_unwind(|| {
-        let env = &mut JvmtiEnv::cons(jvmti_env);
-        let method_id = JMethodId{method};
-        let address_locations = as_address_location_slice(map_length, map).map(|t| t.iter()
-            .map(|e| AddressLocationEntry{start_address: e.start_address as usize, location: e.location}).collect());
-        let compile_infos = to_compile_infos(compile_info);
-        jvmti_event_compiled_method_load(env, &method_id, &address_locations, &compile_infos, code_addr as usize, code_size as usize)
-    });
-    match result {
-        Ok(_) => (),
-        Err(e) => {
-            warn!("Failed to handle 'compiled method load' event: {:?}", e);
-        }
-    }
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_compiled_method_unload_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                             _method: rvmti_sys::jmethodID,
-                                                             _code_addr: *const ::std::os::raw::c_void)
-{
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_data_dump_request_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv) {
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_dynamic_code_generated_handler(jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                             name: *const ::std::os::raw::c_char,
-                                                             address: *const ::std::os::raw::c_void,
-                                                             length: rvmti_sys::jint)
-{
-    let result = panic::catch_unwind(|| {
-        match from_modified_utf8(name) {
-            Ok(s) => jvmti_event_dynamic_code_generated(&mut JvmtiEnv::cons(jvmti_env), &s, address as usize, length as usize),
-            Err(e) => warn!("Incorrect function name for 'dynamic code generated' event: {:?}", e),
-        }
-    });
-    match result {
-        Ok(_) => (),
-        Err(e) => {
-            warn!("Failed to handle 'dynamic code generated' event: {:?}", e);
-        }
-    }
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_exception_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                _jni_env: *mut rvmti_sys::JNIEnv,
-                                                _thread: rvmti_sys::jthread,
-                                                _method: rvmti_sys::jmethodID,
-                                                _location: rvmti_sys::jlocation,
-                                                _exception: rvmti_sys::jobject,
-                                                _catch_method: rvmti_sys::jmethodID,
-                                                _catch_location: rvmti_sys::jlocation)
-{
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_exception_catch_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                      _jni_env: *mut rvmti_sys::JNIEnv,
-                                                      _thread: rvmti_sys::jthread,
-                                                      _method: rvmti_sys::jmethodID,
-                                                      _location: rvmti_sys::jlocation,
-                                                      _exception: rvmti_sys::jobject)
-{
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_field_access_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                   _jni_env: *mut rvmti_sys::JNIEnv,
-                                                   _thread: rvmti_sys::jthread,
-                                                   _method: rvmti_sys::jmethodID,
-                                                   _location: rvmti_sys::jlocation,
-                                                   _field_klass: rvmti_sys::jclass,
-                                                   _object: rvmti_sys::jobject,
-                                                   _field: rvmti_sys::jfieldID)
-{
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_field_modification_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                         _jni_env: *mut rvmti_sys::JNIEnv,
-                                                         _thread: rvmti_sys::jthread,
-                                                         _method: rvmti_sys::jmethodID,
-                                                         _location: rvmti_sys::jlocation,
-                                                         _field_klass: rvmti_sys::jclass,
-                                                         _object: rvmti_sys::jobject,
-                                                         _field: rvmti_sys::jfieldID,
-                                                         _signature_type: ::std::os::raw::c_char,
-                                                         _new_value: rvmti_sys::jvalue)
-{
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_frame_pop_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                _jni_env: *mut rvmti_sys::JNIEnv,
-                                                _thread: rvmti_sys::jthread,
-                                                _method: rvmti_sys::jmethodID,
-                                                _was_popped_by_exception: rvmti_sys::jboolean)
-{
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_garbage_collection_finish_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv) {
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_garbage_collection_start_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv) {
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_method_entry_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                   _jni_env: *mut rvmti_sys::JNIEnv,
-                                                   _thread: rvmti_sys::jthread,
-                                                   _method: rvmti_sys::jmethodID)
-{
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_method_exit_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                  _jni_env: *mut rvmti_sys::JNIEnv,
-                                                  _thread: rvmti_sys::jthread,
-                                                  _method: rvmti_sys::jmethodID,
-                                                  _was_popped_by_exception: rvmti_sys::jboolean,
-                                                  _return_value: rvmti_sys::jvalue)
-{
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_monitor_contended_enter_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                              _jni_env: *mut rvmti_sys::JNIEnv,
-                                                              _thread: rvmti_sys::jthread,
-                                                              _object: rvmti_sys::jobject)
-{
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_monitor_contended_entered_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                                _jni_env: *mut rvmti_sys::JNIEnv,
-                                                                _thread: rvmti_sys::jthread,
-                                                                _object: rvmti_sys::jobject)
-{
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_monitor_wait_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                   _jni_env: *mut rvmti_sys::JNIEnv,
-                                                   _thread: rvmti_sys::jthread,
-                                                   _object: rvmti_sys::jobject,
-                                                   _timeout: rvmti_sys::jlong)
-{
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_monitor_waited_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                     _jni_env: *mut rvmti_sys::JNIEnv,
-                                                     _thread: rvmti_sys::jthread,
-                                                     _object: rvmti_sys::jobject,
-                                                     _timed_out: rvmti_sys::jboolean)
-{
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_native_method_bind_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                         _jni_env: *mut rvmti_sys::JNIEnv,
-                                                         _thread: rvmti_sys::jthread,
-                                                         _method: rvmti_sys::jmethodID,
-                                                         _address: *mut ::std::os::raw::c_void,
-                                                         _new_address_ptr: *mut *mut ::std::os::raw::c_void)
-{
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_object_free_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv, _tag: rvmti_sys::jlong) {
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_resource_exhausted_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                         _jni_env: *mut rvmti_sys::JNIEnv,
-                                                         _flags: rvmti_sys::jint,
-                                                         _reserved: *const ::std::os::raw::c_void,
-                                                         _description: *const ::std::os::raw::c_char)
-{
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_single_step_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                  _jni_env: *mut rvmti_sys::JNIEnv,
-                                                  _thread: rvmti_sys::jthread,
-                                                  _method: rvmti_sys::jmethodID,
-                                                  _location: rvmti_sys::jlocation)
-{
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_thread_end_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                 _jni_env: *mut rvmti_sys::JNIEnv,
-                                                 _thread: rvmti_sys::jthread)
-{
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_thread_start_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                   _jni_env: *mut rvmti_sys::JNIEnv,
-                                                   _thread: rvmti_sys::jthread)
-{
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_vm_death_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                               _jni_env: *mut rvmti_sys::JNIEnv)
-{
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_vm_init_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                              _jni_env: *mut rvmti_sys::JNIEnv,
-                                              _thread: rvmti_sys::jthread)
-{
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_vm_object_alloc_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                      _jni_env: *mut rvmti_sys::JNIEnv,
-                                                      _thread: rvmti_sys::jthread,
-                                                      _object: rvmti_sys::jobject,
-                                                      _object_klass: rvmti_sys::jclass,
-                                                      _size: rvmti_sys::jlong)
-{
-}
+pub mod agent;
+pub mod breakpoints;
+pub mod bytecode;
+pub mod classes;
+pub mod constant_pool;
+pub mod events;
+pub mod jni;
+// Fake JVMTI environment for tests of agents built on these wrappers
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod monitor;
+pub mod properties;
+pub mod redefine;
+pub mod stack;
+pub mod storage;
+pub mod suspend;
+pub mod threads;
+pub mod time;
+pub mod version;
+
+pub use crate::agent::Agent;
+pub use crate::bytecode::{Opcode, Operands, Instruction, Disassembly, BytecodeError};
+pub use crate::classes::{FieldName, Modifiers, ClassStatus, GetFieldNameError};
+pub use crate::constant_pool::{Constant, ConstantPool, ModifiedUtf8, ConstantPoolError, GetConstantPoolError};
+pub use crate::events::AgentEventHandler;
+pub use crate::jni::{JniCallError, JavaType, JArg, JPrimitive, JniReference, Local, Global};
+pub use crate::monitor::{RawMonitor, RawMonitorGuard};
+pub use crate::properties::GetSystemPropertyError;
+pub use crate::redefine::ClassDefinition;
+pub use crate::stack::{FrameInfo, ThreadStackTrace};
+pub use crate::storage::LocalStorageError;
+pub use crate::suspend::{SuspendedThreads, ThreadSnapshot, SuspendError};
+pub use crate::threads::{ThreadInfo, ThreadGroupInfo, OwnedMonitor, ThreadState, JavaThreadState, GetThreadInfoError};
+pub use crate::time::{TimerInfo, TimerKind};
+pub use crate::version::{JvmtiVersionInfo, JvmtiPhase};
+
+// Used by `jvmti_agent!` expansion
+#[doc(hidden)]
+pub use rvmti_sys;
 
-#[no_mangle]
-pub extern "C" fn jvmti_event_vm_start_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                               _jni_env: *mut rvmti_sys::JNIEnv)
-{
-}
-
-#[no_mangle]
-pub extern "C" fn jvmti_event_sampled_object_alloc_handler(_jvmti_env: *mut rvmti_sys::jvmtiEnv,
-                                                           _jni_env: *mut rvmti_sys::JNIEnv,
-                                                           _thread: rvmti_sys::jthread,
-                                                           _object: rvmti_sys::jobject,
-                                                           _object_klass: rvmti_sys::jclass,
-                                                           _size: rvmti_sys::jlong)
-{
-}
-
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Jvm {
     vm: *mut rvmti_sys::JavaVM,
 }
 
+// Invocation interface may be used from any thread
+unsafe impl Send for Jvm {}
+unsafe impl Sync for Jvm {}
+
 #[derive(Debug)]
 pub struct JvmtiEnv {
     env: *mut rvmti_sys::jvmtiEnv,
@@ -398,6 +95,11 @@ pub struct JThread {
     thread: rvmti_sys::jthread,
 }
 
+#[derive(Debug)]
+pub struct JThreadGroup {
+    group: rvmti_sys::jthreadGroup,
+}
+
 #[derive(Debug)]
 pub struct JMethodId {
     method: rvmti_sys::jmethodID,
@@ -408,6 +110,34 @@ pub struct JClass {
     class: rvmti_sys::jclass,
 }
 
+#[derive(Debug)]
+pub struct JObject {
+    object: rvmti_sys::jobject,
+}
+
+#[derive(Debug)]
+pub struct JFieldId {
+    field: rvmti_sys::jfieldID,
+}
+
+#[derive(Debug)]
+pub enum JValue {
+    Boolean(bool),
+    Byte(i8),
+    Char(u16),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    Object(Option<JObject>),
+}
+
+#[derive(Debug)]
+pub struct JniEnv {
+    env: *mut rvmti_sys::JNIEnv,
+}
+
 pub type JLocation = rvmti_sys::jlocation;
 
 #[derive(Debug)]
@@ -459,7 +189,7 @@ pub enum JvmtiEventMode {
     Disable,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum JvmtiEvent {
     VmInit,
     VmDeath,
@@ -513,13 +243,16 @@ pub enum JniError {
     UnsupportedError(i32),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum JvmtiVersion {
     Version1dot0,
     Version1dot1,
     Version1dot2,
     Version9,
     Version11,
+    Version17,
+    Version21,
+    // Version of the headers bindings were generated from
     CurrentVersion,
 }
 
@@ -625,6 +358,8 @@ pub enum JvmtiError {
     UnattachedThread,
     #[error("Invalid environment")]
     InvalidEnvironment,
+    #[error("{function} requires JVMTI version {required} or later")]
+    UnsupportedJvmtiVersion { function: &'static str, required: JvmtiVersionInfo },
     #[error("Unsupported JVMTI error code: {0}")]
     UnsupportedError(u32),
 }
@@ -671,6 +406,27 @@ pub enum StringDecodeError {
 
 impl Jvm {
 
+    pub fn cons(vm: *mut rvmti_sys::JavaVM) -> Jvm {
+        Jvm{vm}
+    }
+
+    // Current thread must be attached to the VM
+    pub fn get_jni_env(&self) -> Result<JniEnv, JniError> {
+        unsafe {
+            let mut env: *mut c_void = ptr::null_mut();
+            let result = (*(*self.vm)).GetEnv.unwrap()(self.vm, &mut env, rvmti_sys::JNI_VERSION_1_8 as rvmti_sys::jint);
+            if result == rvmti_sys::JNI_OK {
+                if !env.is_null() {
+                    Ok(JniEnv{env: env as *mut rvmti_sys::JNIEnv})
+                } else {
+                    Err(JniError::UnknownError)
+                }
+            } else {
+                Err(JniError::from(result))
+            }
+        }
+    }
+
     pub fn get_jvmti_env(&self, version: JvmtiVersion) -> Result<JvmtiEnv, JniError> {
         unsafe {
             let mut env: *mut c_void = ptr::null_mut();
@@ -691,8 +447,8 @@ impl Jvm {
 
 impl JvmtiEnv {
 
-    fn cons(env: *mut rvmti_sys::jvmtiEnv) -> JvmtiEnv {
-        JvmtiEnv{env: env, owned: false}
+    pub fn cons(env: *mut rvmti_sys::jvmtiEnv) -> JvmtiEnv {
+        JvmtiEnv{env, owned: false}
     }
 
     pub fn add_capabilities(&mut self, capabilities: &JvmtiCapabilities) -> Result<(), JvmtiError> {
@@ -706,6 +462,30 @@ impl JvmtiEnv {
         }
     }
 
+    pub fn get_potential_capabilities(&mut self) -> Result<JvmtiCapabilities, JvmtiError> {
+        unsafe {
+            let mut capabilities = JvmtiCapabilities::new_empty_capabilities();
+            let result = (*(*self.env)).GetPotentialCapabilities.unwrap()(self.env, &mut capabilities.caps);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(capabilities);
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    pub fn get_capabilities(&mut self) -> Result<JvmtiCapabilities, JvmtiError> {
+        unsafe {
+            let mut capabilities = JvmtiCapabilities::new_empty_capabilities();
+            let result = (*(*self.env)).GetCapabilities.unwrap()(self.env, &mut capabilities.caps);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(capabilities);
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
     pub fn relinquish_capabilities(&mut self, capabilities: &JvmtiCapabilities) -> Result<(), JvmtiError> {
         unsafe {
             let result = (*(*self.env)).RelinquishCapabilities.unwrap()(self.env, &capabilities.caps);
@@ -736,7 +516,7 @@ impl JvmtiEnv {
             let result = (*(*self.env)).SetEventNotificationMode.unwrap()(self.env,
                                                                           rvmti_sys::jvmtiEventMode::from(mode),
                                                                           rvmti_sys::jvmtiEvent::from(event_type),
-                                                                          event_thread.map_or_else(|| ptr::null_mut(), |t| t.thread ));
+                                                                          event_thread.map_or_else(ptr::null_mut, |t| t.thread ));
             if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
                 return Ok(());
             } else {
@@ -747,8 +527,9 @@ impl JvmtiEnv {
 
     pub fn set_heap_sampling_interval(&mut self, sampling_interval: i32) -> Result<(), JvmtiError> {
         unsafe {
-            let result = (*(*self.env)).SetHeapSamplingInterval.unwrap()(self.env,
-                                                                         sampling_interval as rvmti_sys::jint);
+            let set_heap_sampling_interval = version::require((*(*self.env)).SetHeapSamplingInterval,
+                                                              "SetHeapSamplingInterval", JvmtiVersion::Version11)?;
+            let result = set_heap_sampling_interval(self.env, sampling_interval as rvmti_sys::jint);
             if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
                 return Ok(());
             } else {
@@ -764,16 +545,16 @@ impl JvmtiEnv {
             let mut generic_signature_ptr: *mut ::std::os::raw::c_char = ptr::null_mut();
             let result = (*(*self.env)).GetMethodName.unwrap()(self.env, method.method, &mut name_ptr,
                                                                &mut signature_ptr, &mut generic_signature_ptr);
-            let name = name_ptr.as_ref().map(|v| VmOwnedString {ptr:v, env: &self});
-            let signature = signature_ptr.as_ref().map(|v| VmOwnedString {ptr: v, env: &self});
-            let generic_signature = generic_signature_ptr.as_ref().map(|v| VmOwnedString {ptr: v, env: &self});
+            let name = name_ptr.as_ref().map(|v| VmOwnedString {ptr:v, env: self});
+            let signature = signature_ptr.as_ref().map(|v| VmOwnedString {ptr: v, env: self});
+            let generic_signature = generic_signature_ptr.as_ref().map(|v| VmOwnedString {ptr: v, env: self});
             if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
                 let name_string = name.as_ref().map_or_else(|| Ok("".to_string()), |s| s.to_string()
-                    .map(|v| v.unwrap_or_else(|| "".to_string()))).map_err(|e| GetMethodNameError::NameDecodeError(e))?;
+                    .map(|v| v.unwrap_or_else(|| "".to_string()))).map_err(GetMethodNameError::NameDecodeError)?;
                 let signature_string = signature.as_ref().map_or_else(|| Ok("".to_string()), |s| s.to_string()
-                    .map(|v| v.unwrap_or_else(|| "".to_string()))).map_err(|e| GetMethodNameError::SignatureDecodeError(e))?;
+                    .map(|v| v.unwrap_or_else(|| "".to_string()))).map_err(GetMethodNameError::SignatureDecodeError)?;
                 let generic_signature_string = generic_signature.as_ref().map_or_else(|| Ok(None), |s| s.to_string()
-                    .map_err(|e| GetMethodNameError::GenericSignatureDecodeError(e)))?;
+                    .map_err(GetMethodNameError::GenericSignatureDecodeError))?;
                 return Ok(MethodName{name: name_string, signature: signature_string, generic_signature: generic_signature_string});
             } else {
                 return Err(GetMethodNameError::VmError(JvmtiError::from(result)));
@@ -781,12 +562,13 @@ impl JvmtiEnv {
         }
     }
 
-    pub fn get_method_declaring_class(&mut self, method: &JMethodId) -> Result<JClass, JvmtiError> {
+    // Class is returned as a local reference of the current thread
+    pub fn get_method_declaring_class<'a>(&mut self, jni_env: &'a JniEnv, method: &JMethodId) -> Result<Local<'a, JClass>, JvmtiError> {
         unsafe {
             let mut class: rvmti_sys::jclass = ptr::null_mut();
             let result = (*(*self.env)).GetMethodDeclaringClass.unwrap()(self.env, method.method, &mut class);
             if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
-                return Ok(JClass{class});
+                return jni_env.local(class).ok_or(JvmtiError::NullPointer);
             } else {
                 return Err(JvmtiError::from(result));
             }
@@ -799,13 +581,13 @@ impl JvmtiEnv {
             let mut generic_signature_ptr: *mut ::std::os::raw::c_char = ptr::null_mut();
             let result = (*(*self.env)).GetClassSignature.unwrap()(self.env, class.class,
                                                                    &mut signature_ptr, &mut generic_signature_ptr);
-            let signature = signature_ptr.as_ref().map(|v| VmOwnedString {ptr: v, env: &self});
-            let generic_signature = generic_signature_ptr.as_ref().map(|v| VmOwnedString {ptr: v, env: &self});
+            let signature = signature_ptr.as_ref().map(|v| VmOwnedString {ptr: v, env: self});
+            let generic_signature = generic_signature_ptr.as_ref().map(|v| VmOwnedString {ptr: v, env: self});
             if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
                 let signature_string = signature.as_ref().map_or_else(|| Ok("".to_string()), |s| s.to_string()
-                    .map(|v| v.unwrap_or_else(|| "".to_string()))).map_err(|e| GetClassSignatureError::SignatureDecodeError(e))?;
+                    .map(|v| v.unwrap_or_else(|| "".to_string()))).map_err(GetClassSignatureError::SignatureDecodeError)?;
                 let generic_signature_string = generic_signature.as_ref().map_or_else(|| Ok(None), |s| s.to_string()
-                    .map_err(|e| GetClassSignatureError::GenericSignatureDecodeError(e)))?;
+                    .map_err(GetClassSignatureError::GenericSignatureDecodeError))?;
                 return Ok(ClassSignature{signature: signature_string, generic_signature: generic_signature_string});
             } else {
                 return Err(GetClassSignatureError::VmError(JvmtiError::from(result)));
@@ -817,10 +599,10 @@ impl JvmtiEnv {
         unsafe {
             let mut source_name_ptr: *mut ::std::os::raw::c_char = ptr::null_mut();
             let result = (*(*self.env)).GetSourceFileName.unwrap()(self.env, class.class, &mut source_name_ptr);
-            let source_name = source_name_ptr.as_ref().map(|v| VmOwnedString {ptr: v, env: &self});
+            let source_name = source_name_ptr.as_ref().map(|v| VmOwnedString {ptr: v, env: self});
             if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
                 let source_name_string = source_name.as_ref().map_or_else(|| Ok(None), |s| s.to_string()
-                    .map_err(|e| GetSourceFileNameError::SourceFileNameDecodeError(e)))?;
+                    .map_err(GetSourceFileNameError::SourceFileNameDecodeError))?;
                 return Ok(source_name_string);
             } else if result == rvmti_sys::jvmtiError_JVMTI_ERROR_ABSENT_INFORMATION {
                 return Ok(None);
@@ -835,7 +617,7 @@ impl JvmtiEnv {
             let mut entry_count: rvmti_sys::jint = 0 as rvmti_sys::jint;
             let mut table_ptr: *mut rvmti_sys::jvmtiLineNumberEntry = ptr::null_mut();
             let result = (*(*self.env)).GetLineNumberTable.unwrap()(self.env, method.method, &mut entry_count, &mut table_ptr);
-            let table = table_ptr.as_ref().map(|v| VmOwnedLineNumberTable{ptr:v, entry_count: entry_count, env: &self});
+            let table = table_ptr.as_ref().map(|v| VmOwnedLineNumberTable{ptr:v, entry_count, env: self});
             if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
                 return Ok(table.as_ref().and_then(|t| t.as_line_number_slice()).map(|t| t.iter()
                     .map(|e| LineNumberEntry{start_location: e.start_location, line_number: e.line_number}).collect()));
@@ -867,6 +649,7 @@ impl Drop for JvmtiEnv {
         if !self.owned {
             return;
         }
+        self.release_environment_data();
         unsafe {
             let result = (*(*self.env)).DisposeEnvironment.unwrap()(self.env);
             if result != rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
@@ -879,6 +662,100 @@ impl Drop for JvmtiEnv {
 
 }
 
+impl JThread {
+
+    pub fn cons(thread: rvmti_sys::jthread) -> JThread {
+        JThread{thread}
+    }
+
+    pub fn raw(&self) -> rvmti_sys::jthread {
+        self.thread
+    }
+
+}
+
+impl JThreadGroup {
+
+    pub fn raw(&self) -> rvmti_sys::jthreadGroup {
+        self.group
+    }
+
+}
+
+impl JMethodId {
+
+    pub fn cons(method: rvmti_sys::jmethodID) -> JMethodId {
+        JMethodId{method}
+    }
+
+    pub fn raw(&self) -> rvmti_sys::jmethodID {
+        self.method
+    }
+
+}
+
+impl JClass {
+
+    pub fn raw(&self) -> rvmti_sys::jclass {
+        self.class
+    }
+
+}
+
+impl JObject {
+
+    pub fn cons(object: rvmti_sys::jobject) -> JObject {
+        JObject{object}
+    }
+
+    pub fn raw(&self) -> rvmti_sys::jobject {
+        self.object
+    }
+
+}
+
+impl JFieldId {
+
+    pub fn raw(&self) -> rvmti_sys::jfieldID {
+        self.field
+    }
+
+}
+
+impl JValue {
+
+    // Signature type is the first character of the field descriptor
+    pub fn from_raw(value: rvmti_sys::jvalue, signature_type: char) -> Option<JValue> {
+        unsafe {
+            match signature_type {
+                'Z' => Some(JValue::Boolean(value.z != 0)),
+                'B' => Some(JValue::Byte(value.b)),
+                'C' => Some(JValue::Char(value.c)),
+                'S' => Some(JValue::Short(value.s)),
+                'I' => Some(JValue::Int(value.i)),
+                'J' => Some(JValue::Long(value.j)),
+                'F' => Some(JValue::Float(value.f)),
+                'D' => Some(JValue::Double(value.d)),
+                'L' | '[' => Some(JValue::Object(if value.l.is_null() { None } else { Some(JObject{object: value.l}) })),
+                _ => None,
+            }
+        }
+    }
+
+}
+
+impl JniEnv {
+
+    pub fn cons(env: *mut rvmti_sys::JNIEnv) -> JniEnv {
+        JniEnv{env}
+    }
+
+    pub fn raw(&self) -> *mut rvmti_sys::JNIEnv {
+        self.env
+    }
+
+}
+
 impl JvmtiCapabilities {
 
     pub fn new_empty_capabilities() -> JvmtiCapabilities {
@@ -1085,132 +962,132 @@ impl JvmtiEventCallbacksSettings {
             VMObjectAlloc: None, reserved85: None, SampledObjectAlloc: None }}
     }
 
-    pub fn vm_init_enabled(&mut self) {
-        self.settings.VMInit = Some(jvmti_event_vm_init_handler)
+    pub fn vm_init_enabled(&mut self, callback: rvmti_sys::jvmtiEventVMInit) {
+        self.settings.VMInit = callback
     }
 
-    pub fn vm_death_enabled(&mut self) {
-        self.settings.VMDeath = Some(jvmti_event_vm_death_handler)
+    pub fn vm_death_enabled(&mut self, callback: rvmti_sys::jvmtiEventVMDeath) {
+        self.settings.VMDeath = callback
     }
 
-    pub fn thread_start_enabled(&mut self) {
-        self.settings.ThreadStart = Some(jvmti_event_thread_start_handler)
+    pub fn thread_start_enabled(&mut self, callback: rvmti_sys::jvmtiEventThreadStart) {
+        self.settings.ThreadStart = callback
     }
 
-    pub fn thread_end_enabled(&mut self) {
-        self.settings.ThreadEnd = Some(jvmti_event_thread_end_handler)
+    pub fn thread_end_enabled(&mut self, callback: rvmti_sys::jvmtiEventThreadEnd) {
+        self.settings.ThreadEnd = callback
     }
 
-    pub fn class_file_load_hook_enabled(&mut self) {
-        self.settings.ClassFileLoadHook = Some(jvmti_event_class_file_load_hook_handler)
+    pub fn class_file_load_hook_enabled(&mut self, callback: rvmti_sys::jvmtiEventClassFileLoadHook) {
+        self.settings.ClassFileLoadHook = callback
     }
 
-    pub fn class_load_enabled(&mut self) {
-        self.settings.ClassLoad = Some(jvmti_event_class_load_handler)
+    pub fn class_load_enabled(&mut self, callback: rvmti_sys::jvmtiEventClassLoad) {
+        self.settings.ClassLoad = callback
     }
 
-    pub fn class_prepare_enabled(&mut self) {
-        self.settings.ClassPrepare = Some(jvmti_event_class_prepare_handler)
+    pub fn class_prepare_enabled(&mut self, callback: rvmti_sys::jvmtiEventClassPrepare) {
+        self.settings.ClassPrepare = callback
     }
 
-    pub fn vm_start_enabled(&mut self) {
-        self.settings.VMStart = Some(jvmti_event_vm_start_handler)
+    pub fn vm_start_enabled(&mut self, callback: rvmti_sys::jvmtiEventVMStart) {
+        self.settings.VMStart = callback
     }
 
-    pub fn exception_enabled(&mut self) {
-        self.settings.Exception = Some(jvmti_event_exception_handler)
+    pub fn exception_enabled(&mut self, callback: rvmti_sys::jvmtiEventException) {
+        self.settings.Exception = callback
     }
 
-    pub fn exception_catch_enabled(&mut self) {
-        self.settings.ExceptionCatch = Some(jvmti_event_exception_catch_handler)
+    pub fn exception_catch_enabled(&mut self, callback: rvmti_sys::jvmtiEventExceptionCatch) {
+        self.settings.ExceptionCatch = callback
     }
 
-    pub fn single_step_enabled(&mut self) {
-        self.settings.SingleStep = Some(jvmti_event_single_step_handler)
+    pub fn single_step_enabled(&mut self, callback: rvmti_sys::jvmtiEventSingleStep) {
+        self.settings.SingleStep = callback
     }
 
-    pub fn frame_pop_enabled(&mut self) {
-        self.settings.FramePop = Some(jvmti_event_frame_pop_handler)
+    pub fn frame_pop_enabled(&mut self, callback: rvmti_sys::jvmtiEventFramePop) {
+        self.settings.FramePop = callback
     }
 
-    pub fn breakpoint_enabled(&mut self) {
-        self.settings.Breakpoint = Some(jvmti_event_breakpoint_handler)
+    pub fn breakpoint_enabled(&mut self, callback: rvmti_sys::jvmtiEventBreakpoint) {
+        self.settings.Breakpoint = callback
     }
 
-    pub fn field_access_enabled(&mut self) {
-        self.settings.FieldAccess = Some(jvmti_event_field_access_handler)
+    pub fn field_access_enabled(&mut self, callback: rvmti_sys::jvmtiEventFieldAccess) {
+        self.settings.FieldAccess = callback
     }
 
-    pub fn field_modification_enabled(&mut self) {
-        self.settings.FieldModification = Some(jvmti_event_field_modification_handler)
+    pub fn field_modification_enabled(&mut self, callback: rvmti_sys::jvmtiEventFieldModification) {
+        self.settings.FieldModification = callback
     }
 
-    pub fn method_entry_enabled(&mut self) {
-        self.settings.MethodEntry = Some(jvmti_event_method_entry_handler)
+    pub fn method_entry_enabled(&mut self, callback: rvmti_sys::jvmtiEventMethodEntry) {
+        self.settings.MethodEntry = callback
     }
 
-    pub fn method_exit_enabled(&mut self) {
-        self.settings.MethodExit = Some(jvmti_event_method_exit_handler)
+    pub fn method_exit_enabled(&mut self, callback: rvmti_sys::jvmtiEventMethodExit) {
+        self.settings.MethodExit = callback
     }
 
-    pub fn native_method_bind_enabled(&mut self) {
-        self.settings.NativeMethodBind = Some(jvmti_event_native_method_bind_handler)
+    pub fn native_method_bind_enabled(&mut self, callback: rvmti_sys::jvmtiEventNativeMethodBind) {
+        self.settings.NativeMethodBind = callback
     }
 
-    pub fn compiled_method_load_enabled(&mut self) {
-        self.settings.CompiledMethodLoad = Some(jvmti_event_compiled_method_load_handler)
+    pub fn compiled_method_load_enabled(&mut self, callback: rvmti_sys::jvmtiEventCompiledMethodLoad) {
+        self.settings.CompiledMethodLoad = callback
     }
 
-    pub fn compiled_method_unload_enabled(&mut self) {
-        self.settings.CompiledMethodUnload = Some(jvmti_event_compiled_method_unload_handler)
+    pub fn compiled_method_unload_enabled(&mut self, callback: rvmti_sys::jvmtiEventCompiledMethodUnload) {
+        self.settings.CompiledMethodUnload = callback
     }
 
-    pub fn dynamic_code_generated_enabled(&mut self) {
-        self.settings.DynamicCodeGenerated = Some(jvmti_event_dynamic_code_generated_handler)
+    pub fn dynamic_code_generated_enabled(&mut self, callback: rvmti_sys::jvmtiEventDynamicCodeGenerated) {
+        self.settings.DynamicCodeGenerated = callback
     }
 
-    pub fn data_dump_request_enabled(&mut self) {
-        self.settings.DataDumpRequest = Some(jvmti_event_data_dump_request_handler)
+    pub fn data_dump_request_enabled(&mut self, callback: rvmti_sys::jvmtiEventDataDumpRequest) {
+        self.settings.DataDumpRequest = callback
     }
 
-    pub fn monitor_wait_enabled(&mut self) {
-        self.settings.MonitorWait = Some(jvmti_event_monitor_wait_handler)
+    pub fn monitor_wait_enabled(&mut self, callback: rvmti_sys::jvmtiEventMonitorWait) {
+        self.settings.MonitorWait = callback
     }
 
-    pub fn monitor_waited_enabled(&mut self) {
-        self.settings.MonitorWaited = Some(jvmti_event_monitor_waited_handler)
+    pub fn monitor_waited_enabled(&mut self, callback: rvmti_sys::jvmtiEventMonitorWaited) {
+        self.settings.MonitorWaited = callback
     }
 
-    pub fn monitor_contended_enter_enabled(&mut self) {
-        self.settings.MonitorContendedEnter = Some(jvmti_event_monitor_contended_enter_handler)
+    pub fn monitor_contended_enter_enabled(&mut self, callback: rvmti_sys::jvmtiEventMonitorContendedEnter) {
+        self.settings.MonitorContendedEnter = callback
     }
 
-    pub fn monitor_contended_entered_enabled(&mut self) {
-        self.settings.MonitorContendedEntered = Some(jvmti_event_monitor_contended_entered_handler)
+    pub fn monitor_contended_entered_enabled(&mut self, callback: rvmti_sys::jvmtiEventMonitorContendedEntered) {
+        self.settings.MonitorContendedEntered = callback
     }
 
-    pub fn resource_exhausted_enabled(&mut self) {
-        self.settings.ResourceExhausted = Some(jvmti_event_resource_exhausted_handler)
+    pub fn resource_exhausted_enabled(&mut self, callback: rvmti_sys::jvmtiEventResourceExhausted) {
+        self.settings.ResourceExhausted = callback
     }
 
-    pub fn garbage_collection_start_enabled(&mut self) {
-        self.settings.GarbageCollectionStart = Some(jvmti_event_garbage_collection_start_handler)
+    pub fn garbage_collection_start_enabled(&mut self, callback: rvmti_sys::jvmtiEventGarbageCollectionStart) {
+        self.settings.GarbageCollectionStart = callback
     }
 
-    pub fn garbage_collection_finish_enabled(&mut self) {
-        self.settings.GarbageCollectionFinish = Some(jvmti_event_garbage_collection_finish_handler)
+    pub fn garbage_collection_finish_enabled(&mut self, callback: rvmti_sys::jvmtiEventGarbageCollectionFinish) {
+        self.settings.GarbageCollectionFinish = callback
     }
 
-    pub fn object_free_enabled(&mut self) {
-        self.settings.ObjectFree = Some(jvmti_event_object_free_handler)
+    pub fn object_free_enabled(&mut self, callback: rvmti_sys::jvmtiEventObjectFree) {
+        self.settings.ObjectFree = callback
     }
 
-    pub fn vm_object_alloc_enabled(&mut self) {
-        self.settings.VMObjectAlloc = Some(jvmti_event_vm_object_alloc_handler)
+    pub fn vm_object_alloc_enabled(&mut self, callback: rvmti_sys::jvmtiEventVMObjectAlloc) {
+        self.settings.VMObjectAlloc = callback
     }
 
-    pub fn sampled_object_alloc_enabled(&mut self) {
-        self.settings.SampledObjectAlloc = Some(jvmti_event_sampled_object_alloc_handler)
+    pub fn sampled_object_alloc_enabled(&mut self, callback: rvmti_sys::jvmtiEventSampledObjectAlloc) {
+        self.settings.SampledObjectAlloc = callback
     }
 }
 
@@ -1223,6 +1100,9 @@ impl From<JvmtiVersion> for rvmti_sys::jint {
             JvmtiVersion::Version1dot2 => rvmti_sys::JVMTI_VERSION_1_2 as rvmti_sys::jint,
             JvmtiVersion::Version9 => rvmti_sys::JVMTI_VERSION_9 as rvmti_sys::jint,
             JvmtiVersion::Version11 => rvmti_sys::JVMTI_VERSION_11 as rvmti_sys::jint,
+            // Not every supported JDK has headers defining these
+            JvmtiVersion::Version17 => (rvmti_sys::JVMTI_VERSION_INTERFACE_JVMTI | (17 << rvmti_sys::JVMTI_VERSION_SHIFT_MAJOR)) as rvmti_sys::jint,
+            JvmtiVersion::Version21 => (rvmti_sys::JVMTI_VERSION_INTERFACE_JVMTI | (21 << rvmti_sys::JVMTI_VERSION_SHIFT_MAJOR)) as rvmti_sys::jint,
             JvmtiVersion::CurrentVersion => rvmti_sys::JVMTI_VERSION as rvmti_sys::jint,
         }
     }
@@ -1231,7 +1111,8 @@ impl From<JvmtiVersion> for rvmti_sys::jint {
 
 // TODO Support platform encodings other than utf-8
 #[cfg(not(target_os = "windows"))]
-fn from_platform(input: *const c_char) -> Result<Option<String>, StringDecodeError> {
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn from_platform(input: *const c_char) -> Result<Option<String>, StringDecodeError> {
     unsafe {
         if input.is_null() {
             return Ok(None);
@@ -1240,130 +1121,183 @@ fn from_platform(input: *const c_char) -> Result<Option<String>, StringDecodeErr
     }
 }
 
-fn from_modified_utf8(input: *const c_char) -> Result<Option<String>, StringDecodeError> {
+pub(crate) fn from_modified_utf8(input: *const c_char) -> Result<Option<String>, StringDecodeError> {
     unsafe {
         if input.is_null() {
             return Ok(None);
         }
-        let bytes = CStr::from_ptr(input).to_bytes();
-        let mut converted: Vec<u8> = Vec::new();
-        let mut state: ModifiedUtf8DecoderState = ModifiedUtf8DecoderState::OneByte;
-        let mut accumulator: u32 = 0u32;
-        for b in bytes.into_iter() {
-            match state {
-                ModifiedUtf8DecoderState::OneByte => {
-                    if (*b & 0x80u8) == 0x00u8 {
-                        converted.push(*b & 0x7Fu8);
-                    } else if (*b & 0xe0u8) == 0xC0u8 {
-                        state = ModifiedUtf8DecoderState::TwoBytes;
-                        accumulator += ((*b & 0x1fu8) as u32) << 6;
-                    } else if (*b & 0xf0u8) == 0x70u8 {
-                        state = ModifiedUtf8DecoderState::ThreeBytesOne;
-                        accumulator += ((*b & 0x0fu8) as u32) << 12;
-                    } else if *b == 0xedu8 {
-                        state = ModifiedUtf8DecoderState::SixBytesOne;
-                        accumulator += 0x10000u32;
-                    } else {
-                        return Err(StringDecodeError::ModifiedUtf8Error);
-                    }
-                },
-                ModifiedUtf8DecoderState::TwoBytes => {
-                    if (*b & 0xc0u8) == 0x80u8 {
-                        state = ModifiedUtf8DecoderState::OneByte;
-                        accumulator += (*b & 0x3fu8) as u32;
-                        if accumulator == 0u32 {
-                            converted.push(0u8);
-                        } else if accumulator >= 0x80u32 && accumulator <= 0x7ffu32 {
-                            converted.push(0xc0u8 | ((accumulator >> 6) & 0xffu32) as u8);
-                            converted.push(0x80u8 | (accumulator & 0x3fu32) as u8);
-                        } else {
-                            return Err(StringDecodeError::ModifiedUtf8Error);
-                        }
-                        accumulator = 0u32;
-                    } else {
-                        return Err(StringDecodeError::ModifiedUtf8Error);
-                    }
-                },
-                ModifiedUtf8DecoderState::ThreeBytesOne => {
-                    if (*b & 0xc0u8) == 0x80u8 {
-                        state = ModifiedUtf8DecoderState::ThreeBytesTwo;
-                        accumulator += ((*b & 0x3fu8) as u32) << 6;
-                    } else {
-                        return Err(StringDecodeError::ModifiedUtf8Error);
-                    }
-                },
-                ModifiedUtf8DecoderState::ThreeBytesTwo => {
-                    if (*b & 0xc0u8) == 0x80u8 {
-                        state = ModifiedUtf8DecoderState::OneByte;
-                        accumulator += (*b & 0x3fu8) as u32;
-                        if accumulator >= 0x800u32 && accumulator <= 0xffffu32 {
-                            converted.push(0xe0u8 | ((accumulator >> 12) & 0xffu32) as u8);
-                            converted.push(0x80u8 | ((accumulator >> 6) & 0x3fu32) as u8);
-                            converted.push(0x80u8 | (accumulator & 0x3fu32) as u8);
-                        } else {
-                            return Err(StringDecodeError::ModifiedUtf8Error);
-                        }
-                        accumulator = 0u32;
-                    } else {
-                        return Err(StringDecodeError::ModifiedUtf8Error);
-                    }
-                },
-                ModifiedUtf8DecoderState::SixBytesOne => {
-                    if *b & 0xf0u8 == 0xa0u8 {
-                        state = ModifiedUtf8DecoderState::SixBytesTwo;
-                        accumulator += ((*b & 0x0f) as u32) << 16;
-                    } else {
-                        return Err(StringDecodeError::ModifiedUtf8Error);
-                    }
-                },
-                ModifiedUtf8DecoderState::SixBytesTwo => {
-                    if *b & 0xc0u8 == 0x80u8 {
-                        state = ModifiedUtf8DecoderState::SixBytesThree;
-                        accumulator += ((*b & 0x3fu8) as u32) << 10;
-                    } else {
-                        return Err(StringDecodeError::ModifiedUtf8Error);
-                    }
-                },
-                ModifiedUtf8DecoderState::SixBytesThree => {
-                    if *b == 0xedu8 {
-                        state = ModifiedUtf8DecoderState::SixBytesFour;
+        return decode_modified_utf8(CStr::from_ptr(input).to_bytes()).map(Option::Some);
+    }
+}
+
+// Class files store modified UTF-8 without terminating zero
+pub(crate) fn decode_modified_utf8(bytes: &[u8]) -> Result<String, StringDecodeError> {
+    let mut converted: Vec<u8> = Vec::new();
+    let mut state: ModifiedUtf8DecoderState = ModifiedUtf8DecoderState::OneByte;
+    let mut accumulator: u32 = 0u32;
+    for b in bytes.iter() {
+        match state {
+            ModifiedUtf8DecoderState::OneByte => {
+                if (*b & 0x80u8) == 0x00u8 {
+                    converted.push(*b & 0x7Fu8);
+                } else if (*b & 0xe0u8) == 0xC0u8 {
+                    state = ModifiedUtf8DecoderState::TwoBytes;
+                    accumulator += ((*b & 0x1fu8) as u32) << 6;
+                } else if *b == 0xedu8 {
+                    state = ModifiedUtf8DecoderState::SixBytesOne;
+                    accumulator += 0x10000u32;
+                } else if (*b & 0xf0u8) == 0xe0u8 {
+                    state = ModifiedUtf8DecoderState::ThreeBytesOne;
+                    accumulator += ((*b & 0x0fu8) as u32) << 12;
+                } else {
+                    return Err(StringDecodeError::ModifiedUtf8Error);
+                }
+            },
+            ModifiedUtf8DecoderState::TwoBytes => {
+                if (*b & 0xc0u8) == 0x80u8 {
+                    state = ModifiedUtf8DecoderState::OneByte;
+                    accumulator += (*b & 0x3fu8) as u32;
+                    if accumulator == 0u32 {
+                        converted.push(0u8);
+                    } else if (0x80u32..=0x7ffu32).contains(&accumulator) {
+                        converted.push(0xc0u8 | ((accumulator >> 6) & 0xffu32) as u8);
+                        converted.push(0x80u8 | (accumulator & 0x3fu32) as u8);
                     } else {
                         return Err(StringDecodeError::ModifiedUtf8Error);
                     }
-                },
-                ModifiedUtf8DecoderState::SixBytesFour => {
-                    if *b & 0xf0u8 == 0xb0u8 {
-                        state = ModifiedUtf8DecoderState::SixBytesFive;
-                        accumulator += ((*b & 0x0fu8) as u32) << 6;
+                    accumulator = 0u32;
+                } else {
+                    return Err(StringDecodeError::ModifiedUtf8Error);
+                }
+            },
+            ModifiedUtf8DecoderState::ThreeBytesOne => {
+                if (*b & 0xc0u8) == 0x80u8 {
+                    state = ModifiedUtf8DecoderState::ThreeBytesTwo;
+                    accumulator += ((*b & 0x3fu8) as u32) << 6;
+                } else {
+                    return Err(StringDecodeError::ModifiedUtf8Error);
+                }
+            },
+            ModifiedUtf8DecoderState::ThreeBytesTwo => {
+                if (*b & 0xc0u8) == 0x80u8 {
+                    state = ModifiedUtf8DecoderState::OneByte;
+                    accumulator += (*b & 0x3fu8) as u32;
+                    if (0x800u32..=0xffffu32).contains(&accumulator) {
+                        converted.push(0xe0u8 | ((accumulator >> 12) & 0xffu32) as u8);
+                        converted.push(0x80u8 | ((accumulator >> 6) & 0x3fu32) as u8);
+                        converted.push(0x80u8 | (accumulator & 0x3fu32) as u8);
                     } else {
                         return Err(StringDecodeError::ModifiedUtf8Error);
                     }
-                },
-                ModifiedUtf8DecoderState::SixBytesFive => {
-                    if *b & 0xc0u8 == 0x80u8 {
-                        state = ModifiedUtf8DecoderState::OneByte;
-                        accumulator += (*b & 0x3fu8) as u32;
-                        if accumulator > 0xffffu32 {
-                            converted.push(0xf0u8 | ((accumulator >> 18) & 0xffu32) as u8);
-                            converted.push(0x80u8 | ((accumulator >> 12) & 0x3fu32) as u8);
-                            converted.push(0x80u8 | ((accumulator >> 6) & 0x3fu32) as u8);
-                            converted.push(0x80u8 | (accumulator & 0x3fu32) as u8);
-                        } else {
-                            return Err(StringDecodeError::ModifiedUtf8Error);
-                        }
-                        accumulator = 0u32;
+                    accumulator = 0u32;
+                } else {
+                    return Err(StringDecodeError::ModifiedUtf8Error);
+                }
+            },
+            ModifiedUtf8DecoderState::SixBytesOne => {
+                if *b & 0xf0u8 == 0xa0u8 {
+                    state = ModifiedUtf8DecoderState::SixBytesTwo;
+                    accumulator += ((*b & 0x0f) as u32) << 16;
+                } else if *b & 0xe0u8 == 0x80u8 {
+                    // Not a surrogate pair, just a three byte character starting with 0xed
+                    state = ModifiedUtf8DecoderState::ThreeBytesTwo;
+                    accumulator = 0xd000u32 + (((*b & 0x3fu8) as u32) << 6);
+                } else {
+                    return Err(StringDecodeError::ModifiedUtf8Error);
+                }
+            },
+            ModifiedUtf8DecoderState::SixBytesTwo => {
+                if *b & 0xc0u8 == 0x80u8 {
+                    state = ModifiedUtf8DecoderState::SixBytesThree;
+                    accumulator += ((*b & 0x3fu8) as u32) << 10;
+                } else {
+                    return Err(StringDecodeError::ModifiedUtf8Error);
+                }
+            },
+            ModifiedUtf8DecoderState::SixBytesThree => {
+                if *b == 0xedu8 {
+                    state = ModifiedUtf8DecoderState::SixBytesFour;
+                } else {
+                    return Err(StringDecodeError::ModifiedUtf8Error);
+                }
+            },
+            ModifiedUtf8DecoderState::SixBytesFour => {
+                if *b & 0xf0u8 == 0xb0u8 {
+                    state = ModifiedUtf8DecoderState::SixBytesFive;
+                    accumulator += ((*b & 0x0fu8) as u32) << 6;
+                } else {
+                    return Err(StringDecodeError::ModifiedUtf8Error);
+                }
+            },
+            ModifiedUtf8DecoderState::SixBytesFive => {
+                if *b & 0xc0u8 == 0x80u8 {
+                    state = ModifiedUtf8DecoderState::OneByte;
+                    accumulator += (*b & 0x3fu8) as u32;
+                    if accumulator > 0xffffu32 {
+                        converted.push(0xf0u8 | ((accumulator >> 18) & 0xffu32) as u8);
+                        converted.push(0x80u8 | ((accumulator >> 12) & 0x3fu32) as u8);
+                        converted.push(0x80u8 | ((accumulator >> 6) & 0x3fu32) as u8);
+                        converted.push(0x80u8 | (accumulator & 0x3fu32) as u8);
                     } else {
                         return Err(StringDecodeError::ModifiedUtf8Error);
                     }
-                },
-            }
+                    accumulator = 0u32;
+                } else {
+                    return Err(StringDecodeError::ModifiedUtf8Error);
+                }
+            },
         }
-        String::from_utf8(converted).map_err(StringDecodeError::from).map(Option::Some)
     }
+    match state {
+        ModifiedUtf8DecoderState::OneByte => {},
+        _ => return Err(StringDecodeError::ModifiedUtf8Error),
+    }
+    String::from_utf8(converted).map_err(StringDecodeError::from)
+}
+
+// Null thread stands for the current thread in JVMTI functions
+fn raw_thread(thread: Option<&JThread>) -> rvmti_sys::jthread {
+    thread.map(|t| t.raw()).unwrap_or(ptr::null_mut())
+}
+
+pub(crate) fn to_modified_utf8(input: &str) -> CString {
+    let mut converted: Vec<u8> = Vec::with_capacity(input.len() + 1);
+    for c in input.chars() {
+        let code = c as u32;
+        if code == 0u32 {
+            converted.push(0xc0u8);
+            converted.push(0x80u8);
+        } else if code < 0x80u32 {
+            converted.push(code as u8);
+        } else if code < 0x10000u32 {
+            let mut units = [0u16; 1];
+            c.encode_utf16(&mut units);
+            push_modified_utf8_unit(&mut converted, units[0]);
+        } else {
+            // Supplementary characters are encoded as surrogate pairs, three bytes each
+            let mut units = [0u16; 2];
+            c.encode_utf16(&mut units);
+            push_modified_utf8_unit(&mut converted, units[0]);
+            push_modified_utf8_unit(&mut converted, units[1]);
+        }
+    }
+    CString::new(converted).expect("Modified UTF-8 never contains zero bytes")
 }
 
-fn as_address_location_slice<'a>(map_length: rvmti_sys::jint,
-                                 map: *const rvmti_sys::jvmtiAddrLocationMap) -> Option<&'a [rvmti_sys::jvmtiAddrLocationMap]>
+fn push_modified_utf8_unit(converted: &mut Vec<u8>, unit: u16) {
+    let unit = unit as u32;
+    if unit < 0x800u32 {
+        converted.push(0xc0u8 | (unit >> 6) as u8);
+        converted.push(0x80u8 | (unit & 0x3fu32) as u8);
+    } else {
+        converted.push(0xe0u8 | (unit >> 12) as u8);
+        converted.push(0x80u8 | ((unit >> 6) & 0x3fu32) as u8);
+        converted.push(0x80u8 | (unit & 0x3fu32) as u8);
+    }
+}
+
+pub(crate) fn as_address_location_slice<'a>(map_length: rvmti_sys::jint,
+                                     map: *const rvmti_sys::jvmtiAddrLocationMap) -> Option<&'a [rvmti_sys::jvmtiAddrLocationMap]>
 {
     unsafe {
         if map_length == 0 || map.is_null() {
@@ -1373,7 +1307,7 @@ fn as_address_location_slice<'a>(map_length: rvmti_sys::jint,
     }
 }
 
-fn to_compile_infos(compile_info: *const ::std::os::raw::c_void) -> Option<Vec<CompiledMethodLoadRecord>> {
+pub(crate) fn to_compile_infos(compile_info: *const ::std::os::raw::c_void) -> Option<Vec<CompiledMethodLoadRecord>> {
     unsafe {
         if compile_info.is_null() {
             return None;
@@ -1402,9 +1336,9 @@ fn to_compile_infos(compile_info: *const ::std::os::raw::c_void) -> Option<Vec<C
                                                 byte_code_index: byte_code_indices[i]});
                                         }
                                     }
-                                    StackInfo{pc_address: e.pc as usize, stack_frames: stack_frames}
+                                    StackInfo{pc_address: e.pc as usize, stack_frames}
                                 }).collect();
-                            result.push(CompiledMethodLoadRecord::Inline {stack_infos: stack_infos})
+                            result.push(CompiledMethodLoadRecord::Inline {stack_infos})
                         },
                         _ => {},
                     }
@@ -1471,6 +1405,13 @@ struct VmOwnedLineNumberTable<'a> {
     env: &'a JvmtiEnv,
 }
 
+// Array allocated by the VM, elements are copied out before the array is deallocated
+struct VmOwnedArray<'a, T> {
+    ptr: *const T,
+    count: rvmti_sys::jint,
+    env: &'a JvmtiEnv,
+}
+
 impl<'a> VmOwnedString<'a> {
 
     fn to_string(&self) -> Result<Option<String>, StringDecodeError> {
@@ -1491,6 +1432,19 @@ impl<'a> VmOwnedLineNumberTable<'a> {
     }
 }
 
+impl<'a, T> VmOwnedArray<'a, T> {
+
+    fn as_slice(&self) -> &[T] {
+        unsafe {
+            if self.count <= 0 || self.ptr.is_null() {
+                return &[];
+            }
+            slice::from_raw_parts(self.ptr, self.count as usize)
+        }
+    }
+
+}
+
 impl<'a> Drop for VmOwnedString<'a> {
 
     fn drop(&mut self) {
@@ -1525,6 +1479,23 @@ impl<'a> Drop for VmOwnedLineNumberTable<'a> {
 
 }
 
+impl<'a, T> Drop for VmOwnedArray<'a, T> {
+
+    fn drop(&mut self) {
+        unsafe {
+            if !self.ptr.is_null() {
+                let result = (*(*(self.env.env))).Deallocate.unwrap()(self.env.env, self.ptr as *mut c_uchar);
+                if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                    debug!("VM owned array is deallocated");
+                } else {
+                    warn!("Failed to deallocate VM owned array {}", JvmtiError::from(result));
+                }
+            }
+        }
+    }
+
+}
+
 impl From<FromUtf8Error> for StringDecodeError {
 
     fn from(error: FromUtf8Error) -> StringDecodeError {
@@ -1668,3 +1639,57 @@ impl From<JvmtiEvent> for rvmti_sys::jvmtiEvent {
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn decode(bytes: &[u8]) -> Result<Option<String>, StringDecodeError> {
+        let mut input = bytes.to_vec();
+        input.push(0u8);
+        from_modified_utf8(input.as_ptr() as *const c_char)
+    }
+
+    #[test]
+    fn test_jvmti_version_info() {
+        assert_eq!(JvmtiVersionInfo::from(JvmtiVersion::Version1dot2), JvmtiVersionInfo{major: 1, minor: 2, micro: 0});
+        assert_eq!(JvmtiVersionInfo::from(JvmtiVersion::Version11), JvmtiVersionInfo{major: 11, minor: 0, micro: 0});
+        assert_eq!(JvmtiVersionInfo::from(JvmtiVersion::Version17), JvmtiVersionInfo{major: 17, minor: 0, micro: 0});
+        assert_eq!(JvmtiVersionInfo::from(JvmtiVersion::Version21), JvmtiVersionInfo{major: 21, minor: 0, micro: 0});
+        assert!(JvmtiVersionInfo::from(JvmtiVersion::Version1dot2) < JvmtiVersionInfo::from(JvmtiVersion::Version9));
+        assert!(JvmtiVersionInfo{major: 11, minor: 0, micro: 1} > JvmtiVersionInfo::from(JvmtiVersion::Version11));
+    }
+
+    #[test]
+    fn test_to_modified_utf8() {
+        assert_eq!(to_modified_utf8("java/lang/String").as_bytes(), b"java/lang/String");
+        assert_eq!(to_modified_utf8("a\0b").as_bytes(), &[0x61, 0xc0, 0x80, 0x62]);
+        assert_eq!(to_modified_utf8("\u{e9}").as_bytes(), &[0xc3, 0xa9]);
+        assert_eq!(to_modified_utf8("\u{20ac}").as_bytes(), &[0xe2, 0x82, 0xac]);
+        assert_eq!(to_modified_utf8("\u{d55c}").as_bytes(), &[0xed, 0x95, 0x9c]);
+        assert_eq!(to_modified_utf8("\u{1f600}").as_bytes(), &[0xed, 0xa0, 0xbd, 0xed, 0xb8, 0x80]);
+    }
+
+    #[test]
+    fn test_from_modified_utf8() {
+        assert_eq!(from_modified_utf8(ptr::null()).unwrap(), None);
+        assert_eq!(decode(b"Lcom/example/Worker;").unwrap().unwrap(), "Lcom/example/Worker;");
+        assert_eq!(decode(&[0x61, 0xc0, 0x80, 0x62]).unwrap().unwrap(), "a\0b");
+        assert_eq!(decode(&[0xe2, 0x82, 0xac]).unwrap().unwrap(), "\u{20ac}");
+        assert_eq!(decode(&[0xed, 0x95, 0x9c]).unwrap().unwrap(), "\u{d55c}");
+        assert_eq!(decode(&[0xed, 0xa0, 0xbd, 0xed, 0xb8, 0x80]).unwrap().unwrap(), "\u{1f600}");
+        assert!(decode(&[0xe2, 0x82]).is_err());
+        assert!(decode(&[0xed, 0xa0, 0xbd, 0x61]).is_err());
+        assert!(decode(&[0xf0, 0x9f, 0x98, 0x80]).is_err());
+    }
+
+    #[test]
+    fn test_modified_utf8_round_trip() {
+        for value in ["", "plain", "\u{0}", "caf\u{e9}", "\u{4e2d}\u{6587}", "\u{d7ff}\u{e000}\u{ffff}", "x\u{10000}y\u{10ffff}"].iter() {
+            let encoded = to_modified_utf8(value);
+            assert_eq!(from_modified_utf8(encoded.as_ptr()).unwrap().unwrap(), *value);
+        }
+    }
+
+}
@@ -0,0 +1,1454 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+// Fake JVMTI environment serving scripted answers, allows to drive event handlers without a JVM
+
+use std::collections::{HashMap, HashSet};
+use std::ffi::CString;
+use std::os::raw::{c_char, c_uchar, c_void};
+use std::ptr;
+use std::sync::Mutex;
+
+#[repr(C)]
+pub struct MockJvmti {
+    // Must stay the first field, callbacks cast the environment pointer back to `MockJvmti`
+    env: rvmti_sys::jvmtiEnv,
+    functions: Box<rvmti_sys::jvmtiInterface_1_>,
+    jni_functions: Box<rvmti_sys::JNINativeInterface_>,
+    vm_functions: Box<rvmti_sys::JNIInvokeInterface_>,
+    jni: MockJni,
+    vm: MockVm,
+    state: Mutex<MockState>,
+}
+
+// JNI environment and VM point back to the owning `MockJvmti`, interface pointers must stay the first fields
+#[repr(C)]
+struct MockJni {
+    env: rvmti_sys::JNIEnv,
+    owner: *const MockJvmti,
+}
+
+#[repr(C)]
+struct MockVm {
+    vm: rvmti_sys::JavaVM,
+    owner: *const MockJvmti,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    classes: HashMap<usize, MockClass>,
+    methods: HashMap<usize, MockMethod>,
+    fields: HashMap<usize, MockField>,
+    threads: HashMap<usize, MockThread>,
+    current_thread: usize,
+    thread_groups: HashMap<usize, MockThreadGroup>,
+    allocations: HashSet<usize>,
+    local_refs: HashMap<usize, usize>,
+    invalid_local_ref_deletions: usize,
+    next_id: usize,
+    callbacks: Option<rvmti_sys::jvmtiEventCallbacks>,
+    enabled_events: HashSet<rvmti_sys::jvmtiEvent>,
+    environment_storage: usize,
+    // Keyed by thread, null thread is the current one
+    thread_storage: HashMap<usize, usize>,
+    raw_monitors: HashMap<usize, MockRawMonitor>,
+    // Method and location
+    breakpoints: HashSet<(usize, rvmti_sys::jlocation)>,
+    time: rvmti_sys::jlong,
+    // Keyed by thread, null thread is the current one
+    thread_cpu_times: HashMap<usize, rvmti_sys::jlong>,
+    system_properties: Vec<(String, String)>,
+    pending_exception: bool,
+    version: rvmti_sys::jint,
+    phase: rvmti_sys::jvmtiPhase,
+}
+
+#[derive(Debug, Clone)]
+struct MockClass {
+    signature: String,
+    generic_signature: Option<String>,
+    source_file_name: Option<String>,
+    modifiers: i32,
+    loader: Option<usize>,
+    // Count and entry bytes
+    constant_pool: Option<(u16, Vec<u8>)>,
+    // Class file the class was defined or last redefined with
+    class_bytes: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+struct MockField {
+    class: usize,
+    name: String,
+    signature: String,
+    modifiers: i32,
+}
+
+#[derive(Debug, Clone)]
+struct MockMethod {
+    class: usize,
+    name: String,
+    signature: String,
+    generic_signature: Option<String>,
+    native: bool,
+    modifiers: i32,
+    line_numbers: Option<Vec<(rvmti_sys::jlocation, i32)>>,
+    bytecodes: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+struct MockThread {
+    name: String,
+    group: Option<usize>,
+    daemon: bool,
+    // Top frame first
+    frames: Vec<rvmti_sys::jvmtiFrameInfo>,
+    alive: bool,
+    suspended: bool,
+    // Monitor objects and stack depths
+    owned_monitors: Vec<(usize, rvmti_sys::jint)>,
+    contended_monitor: Option<usize>,
+}
+
+// Single threaded, waits return at once and only count
+#[derive(Debug, Clone)]
+struct MockRawMonitor {
+    name: String,
+    entries: usize,
+    waits: usize,
+    notifications: usize,
+}
+
+#[derive(Debug, Clone)]
+struct MockThreadGroup {
+    name: String,
+    parent: Option<usize>,
+}
+
+impl MockThread {
+
+    fn state(&self) -> u32 {
+        if !self.alive {
+            return rvmti_sys::JVMTI_THREAD_STATE_TERMINATED;
+        }
+        let state = rvmti_sys::JVMTI_THREAD_STATE_ALIVE | rvmti_sys::JVMTI_THREAD_STATE_RUNNABLE;
+        if self.suspended { state | rvmti_sys::JVMTI_THREAD_STATE_SUSPENDED } else { state }
+    }
+
+}
+
+impl MockJvmti {
+
+    pub fn new() -> Box<MockJvmti> {
+        let mut functions: Box<rvmti_sys::jvmtiInterface_1_> = Box::new(unsafe { std::mem::zeroed() });
+        functions.Allocate = Some(mock_allocate);
+        functions.Deallocate = Some(mock_deallocate);
+        functions.DisposeEnvironment = Some(mock_dispose_environment);
+        functions.GetMethodName = Some(mock_get_method_name);
+        functions.GetMethodDeclaringClass = Some(mock_get_method_declaring_class);
+        functions.GetClassSignature = Some(mock_get_class_signature);
+        functions.GetSourceFileName = Some(mock_get_source_file_name);
+        functions.IsMethodNative = Some(mock_is_method_native);
+        functions.GetLineNumberTable = Some(mock_get_line_number_table);
+        functions.GetBytecodes = Some(mock_get_bytecodes);
+        functions.GetConstantPool = Some(mock_get_constant_pool);
+        functions.RedefineClasses = Some(mock_redefine_classes);
+        functions.RetransformClasses = Some(mock_retransform_classes);
+        functions.IsModifiableClass = Some(mock_is_modifiable_class);
+        functions.SetBreakpoint = Some(mock_set_breakpoint);
+        functions.ClearBreakpoint = Some(mock_clear_breakpoint);
+        functions.SetEventCallbacks = Some(mock_set_event_callbacks);
+        functions.GetThreadInfo = Some(mock_get_thread_info);
+        functions.GetThreadGroupInfo = Some(mock_get_thread_group_info);
+        functions.GetStackTrace = Some(mock_get_stack_trace);
+        functions.GetLoadedClasses = Some(mock_get_loaded_classes);
+        functions.GetClassLoaderClasses = Some(mock_get_class_loader_classes);
+        functions.GetClassMethods = Some(mock_get_class_methods);
+        functions.GetClassFields = Some(mock_get_class_fields);
+        functions.GetClassModifiers = Some(mock_get_class_modifiers);
+        functions.GetMethodModifiers = Some(mock_get_method_modifiers);
+        functions.GetFieldModifiers = Some(mock_get_field_modifiers);
+        functions.GetFieldName = Some(mock_get_field_name);
+        functions.GetClassStatus = Some(mock_get_class_status);
+        functions.IsInterface = Some(mock_is_interface);
+        functions.IsArrayClass = Some(mock_is_array_class);
+        functions.GetClassLoader = Some(mock_get_class_loader);
+        functions.GetAllStackTraces = Some(mock_get_all_stack_traces);
+        functions.GetThreadListStackTraces = Some(mock_get_thread_list_stack_traces);
+        functions.GetFrameCount = Some(mock_get_frame_count);
+        functions.GetCurrentThread = Some(mock_get_current_thread);
+        functions.SuspendThread = Some(mock_suspend_thread);
+        functions.ResumeThread = Some(mock_resume_thread);
+        functions.SuspendThreadList = Some(mock_suspend_thread_list);
+        functions.ResumeThreadList = Some(mock_resume_thread_list);
+        functions.GetOwnedMonitorStackDepthInfo = Some(mock_get_owned_monitor_stack_depth_info);
+        functions.GetCurrentContendedMonitor = Some(mock_get_current_contended_monitor);
+        functions.GetFrameLocation = Some(mock_get_frame_location);
+        functions.GetEnvironmentLocalStorage = Some(mock_get_environment_local_storage);
+        functions.SetEnvironmentLocalStorage = Some(mock_set_environment_local_storage);
+        functions.GetThreadLocalStorage = Some(mock_get_thread_local_storage);
+        functions.SetThreadLocalStorage = Some(mock_set_thread_local_storage);
+        functions.GetVersionNumber = Some(mock_get_version_number);
+        functions.GetPhase = Some(mock_get_phase);
+        functions.GetSystemProperties = Some(mock_get_system_properties);
+        functions.GetSystemProperty = Some(mock_get_system_property);
+        functions.GetTime = Some(mock_get_time);
+        functions.GetTimerInfo = Some(mock_get_elapsed_timer_info);
+        functions.GetCurrentThreadCpuTime = Some(mock_get_current_thread_cpu_time);
+        functions.GetCurrentThreadCpuTimerInfo = Some(mock_get_cpu_timer_info);
+        functions.GetThreadCpuTime = Some(mock_get_thread_cpu_time);
+        functions.GetThreadCpuTimerInfo = Some(mock_get_cpu_timer_info);
+        functions.CreateRawMonitor = Some(mock_create_raw_monitor);
+        functions.DestroyRawMonitor = Some(mock_destroy_raw_monitor);
+        functions.RawMonitorEnter = Some(mock_raw_monitor_enter);
+        functions.RawMonitorExit = Some(mock_raw_monitor_exit);
+        functions.RawMonitorWait = Some(mock_raw_monitor_wait);
+        functions.RawMonitorNotify = Some(mock_raw_monitor_notify);
+        functions.RawMonitorNotifyAll = Some(mock_raw_monitor_notify);
+        // Declared variadic in jvmti.h, the extra arguments are never passed so a fixed arity function is call compatible
+        functions.SetEventNotificationMode = Some(unsafe { std::mem::transmute::<
+            unsafe extern "C" fn(*mut rvmti_sys::jvmtiEnv, rvmti_sys::jvmtiEventMode, rvmti_sys::jvmtiEvent,
+                                 rvmti_sys::jthread) -> rvmti_sys::jvmtiError,
+            unsafe extern "C" fn(*mut rvmti_sys::jvmtiEnv, rvmti_sys::jvmtiEventMode, rvmti_sys::jvmtiEvent,
+                                 rvmti_sys::jthread, ...) -> rvmti_sys::jvmtiError>(mock_set_event_notification_mode) });
+        let mut jni_functions: Box<rvmti_sys::JNINativeInterface_> = Box::new(unsafe { std::mem::zeroed() });
+        jni_functions.DeleteLocalRef = Some(mock_delete_local_ref);
+        jni_functions.IsSameObject = Some(mock_is_same_object);
+        jni_functions.FindClass = Some(mock_find_class);
+        jni_functions.ExceptionCheck = Some(mock_exception_check);
+        jni_functions.ExceptionClear = Some(mock_exception_clear);
+        jni_functions.PushLocalFrame = Some(mock_push_local_frame);
+        jni_functions.PopLocalFrame = Some(mock_pop_local_frame);
+        let mut vm_functions: Box<rvmti_sys::JNIInvokeInterface_> = Box::new(unsafe { std::mem::zeroed() });
+        vm_functions.GetEnv = Some(mock_get_env);
+        let env = &*functions as *const rvmti_sys::jvmtiInterface_1_;
+        let jni = MockJni{env: &*jni_functions, owner: ptr::null()};
+        let vm = MockVm{vm: &*vm_functions, owner: ptr::null()};
+        let mut mock = Box::new(MockJvmti{env, functions, jni_functions, vm_functions, jni, vm,
+            state: Mutex::new(MockState{next_id: 0x1000, version: rvmti_sys::JVMTI_VERSION as rvmti_sys::jint,
+                phase: rvmti_sys::jvmtiPhase_JVMTI_PHASE_LIVE, ..Default::default()})});
+        let owner = &*mock as *const MockJvmti;
+        mock.jni.owner = owner;
+        mock.vm.owner = owner;
+        mock
+    }
+
+    pub fn vm_ptr(&self) -> *mut rvmti_sys::JavaVM {
+        &self.vm.vm as *const rvmti_sys::JavaVM as *mut rvmti_sys::JavaVM
+    }
+
+    pub fn env_ptr(&self) -> *mut rvmti_sys::jvmtiEnv {
+        &self.env as *const rvmti_sys::jvmtiEnv as *mut rvmti_sys::jvmtiEnv
+    }
+
+    pub fn jni_env_ptr(&self) -> *mut rvmti_sys::JNIEnv {
+        &self.jni.env as *const rvmti_sys::JNIEnv as *mut rvmti_sys::JNIEnv
+    }
+
+    pub fn add_thread_group(&self, name: &str, parent: Option<rvmti_sys::jthreadGroup>) -> rvmti_sys::jthreadGroup {
+        let mut state = self.state.lock().unwrap();
+        let id = state.allocate_id();
+        state.thread_groups.insert(id, MockThreadGroup{name: name.to_string(), parent: parent.map(|p| p as usize)});
+        id as rvmti_sys::jthreadGroup
+    }
+
+    pub fn add_thread(&self, name: &str, group: Option<rvmti_sys::jthreadGroup>, daemon: bool) -> rvmti_sys::jthread {
+        let mut state = self.state.lock().unwrap();
+        let id = state.allocate_id();
+        state.threads.insert(id, MockThread{name: name.to_string(), group: group.map(|g| g as usize), daemon,
+            frames: Vec::new(), alive: true, suspended: false, owned_monitors: Vec::new(), contended_monitor: None});
+        id as rvmti_sys::jthread
+    }
+
+    pub fn set_stack(&self, thread: rvmti_sys::jthread, frames: &[(rvmti_sys::jmethodID, rvmti_sys::jlocation)]) {
+        let mut state = self.state.lock().unwrap();
+        state.threads.get_mut(&(thread as usize)).unwrap().frames = frames.iter()
+            .map(|&(method, location)| rvmti_sys::jvmtiFrameInfo{method, location})
+            .collect();
+    }
+
+    pub fn add_object(&self) -> rvmti_sys::jobject {
+        self.state.lock().unwrap().allocate_id() as rvmti_sys::jobject
+    }
+
+    pub fn set_monitors(&self, thread: rvmti_sys::jthread, owned: &[(rvmti_sys::jobject, rvmti_sys::jint)],
+                        contended: Option<rvmti_sys::jobject>)
+    {
+        let mut state = self.state.lock().unwrap();
+        let thread = state.threads.get_mut(&(thread as usize)).unwrap();
+        thread.owned_monitors = owned.iter().map(|&(monitor, depth)| (monitor as usize, depth)).collect();
+        thread.contended_monitor = contended.map(|m| m as usize);
+    }
+
+    pub fn terminate_thread(&self, thread: rvmti_sys::jthread) {
+        let mut state = self.state.lock().unwrap();
+        let thread = state.threads.get_mut(&(thread as usize)).unwrap();
+        thread.alive = false;
+        thread.frames.clear();
+    }
+
+    pub fn is_suspended(&self, thread: rvmti_sys::jthread) -> bool {
+        self.state.lock().unwrap().threads[&(thread as usize)].suspended
+    }
+
+    // Thread standing for the current one when JVMTI functions get null thread
+    pub fn set_current_thread(&self, thread: rvmti_sys::jthread) {
+        self.state.lock().unwrap().current_thread = thread as usize;
+    }
+
+    pub fn add_class(&self, signature: &str, source_file_name: Option<&str>) -> rvmti_sys::jclass {
+        let mut state = self.state.lock().unwrap();
+        let id = state.allocate_id();
+        state.classes.insert(id, MockClass{signature: signature.to_string(), generic_signature: None,
+            source_file_name: source_file_name.map(str::to_string), modifiers: 0x0001, loader: None,
+            constant_pool: None, class_bytes: Vec::new()});
+        id as rvmti_sys::jclass
+    }
+
+    pub fn add_method(&self, class: rvmti_sys::jclass, name: &str, signature: &str,
+                      line_numbers: Option<Vec<(rvmti_sys::jlocation, i32)>>) -> rvmti_sys::jmethodID
+    {
+        self.insert_method(MockMethod{class: class as usize, name: name.to_string(), signature: signature.to_string(),
+            generic_signature: None, native: false, modifiers: 0x0001, line_numbers, bytecodes: Vec::new()})
+    }
+
+    pub fn add_native_method(&self, class: rvmti_sys::jclass, name: &str, signature: &str) -> rvmti_sys::jmethodID {
+        self.insert_method(MockMethod{class: class as usize, name: name.to_string(), signature: signature.to_string(),
+            generic_signature: None, native: true, modifiers: 0x0101, line_numbers: None, bytecodes: Vec::new()})
+    }
+
+    pub fn add_field(&self, class: rvmti_sys::jclass, name: &str, signature: &str, modifiers: i32) -> rvmti_sys::jfieldID {
+        let mut state = self.state.lock().unwrap();
+        let id = state.allocate_id();
+        state.fields.insert(id, MockField{class: class as usize, name: name.to_string(), signature: signature.to_string(),
+            modifiers});
+        id as rvmti_sys::jfieldID
+    }
+
+    pub fn add_class_loader(&self) -> rvmti_sys::jobject {
+        self.state.lock().unwrap().allocate_id() as rvmti_sys::jobject
+    }
+
+    // Classes are public and loaded by the bootstrap loader unless changed
+    pub fn set_class_details(&self, class: rvmti_sys::jclass, modifiers: i32, loader: Option<rvmti_sys::jobject>) {
+        let mut state = self.state.lock().unwrap();
+        let class = state.classes.get_mut(&(class as usize)).unwrap();
+        class.modifiers = modifiers;
+        class.loader = loader.map(|l| l as usize);
+    }
+
+    pub fn set_bytecodes(&self, method: rvmti_sys::jmethodID, bytecodes: &[u8]) {
+        self.state.lock().unwrap().methods.get_mut(&(method as usize)).unwrap().bytecodes = bytecodes.to_vec();
+    }
+
+    pub fn set_constant_pool(&self, class: rvmti_sys::jclass, count: u16, bytes: &[u8]) {
+        self.state.lock().unwrap().classes.get_mut(&(class as usize)).unwrap().constant_pool = Some((count, bytes.to_vec()));
+    }
+
+    pub fn class_bytes(&self, class: rvmti_sys::jclass) -> Vec<u8> {
+        self.state.lock().unwrap().classes[&(class as usize)].class_bytes.clone()
+    }
+
+    pub fn set_method_modifiers(&self, method: rvmti_sys::jmethodID, modifiers: i32) {
+        self.state.lock().unwrap().methods.get_mut(&(method as usize)).unwrap().modifiers = modifiers;
+    }
+
+    pub fn outstanding_allocations(&self) -> usize {
+        self.state.lock().unwrap().allocations.len()
+    }
+
+    // Local references handed out and not deleted yet, deleting unknown references counts as a leak too
+    pub fn outstanding_local_refs(&self) -> usize {
+        let state = self.state.lock().unwrap();
+        state.local_refs.values().sum::<usize>() + state.invalid_local_ref_deletions
+    }
+
+    pub fn set_version(&self, version: rvmti_sys::jint) {
+        self.state.lock().unwrap().version = version;
+    }
+
+    pub fn set_phase(&self, phase: rvmti_sys::jvmtiPhase) {
+        self.state.lock().unwrap().phase = phase;
+    }
+
+    pub fn set_system_property(&self, key: &str, value: &str) {
+        self.state.lock().unwrap().system_properties.push((key.to_string(), value.to_string()));
+    }
+
+    pub fn has_pending_exception(&self) -> bool {
+        self.state.lock().unwrap().pending_exception
+    }
+
+    pub fn set_time(&self, nanos: rvmti_sys::jlong) {
+        self.state.lock().unwrap().time = nanos;
+    }
+
+    pub fn set_thread_cpu_time(&self, thread: Option<rvmti_sys::jthread>, nanos: rvmti_sys::jlong) {
+        self.state.lock().unwrap().thread_cpu_times.insert(thread.map(|t| t as usize).unwrap_or(0), nanos);
+    }
+
+    pub fn outstanding_raw_monitors(&self) -> usize {
+        self.state.lock().unwrap().raw_monitors.len()
+    }
+
+    // Entry count, waits and notifications of a live raw monitor
+    pub fn raw_monitor_state(&self, name: &str) -> Option<(usize, usize, usize)> {
+        self.state.lock().unwrap().raw_monitors.values().find(|m| m.name == name)
+            .map(|m| (m.entries, m.waits, m.notifications))
+    }
+
+    pub fn breakpoints(&self) -> Vec<(rvmti_sys::jmethodID, rvmti_sys::jlocation)> {
+        let mut breakpoints: Vec<(usize, rvmti_sys::jlocation)> = self.state.lock().unwrap().breakpoints.iter().copied().collect();
+        breakpoints.sort();
+        breakpoints.into_iter().map(|(m, l)| (m as rvmti_sys::jmethodID, l)).collect()
+    }
+
+    pub fn callbacks(&self) -> Option<rvmti_sys::jvmtiEventCallbacks> {
+        self.state.lock().unwrap().callbacks
+    }
+
+    pub fn is_event_enabled(&self, event: rvmti_sys::jvmtiEvent) -> bool {
+        self.state.lock().unwrap().enabled_events.contains(&event)
+    }
+
+    fn insert_method(&self, method: MockMethod) -> rvmti_sys::jmethodID {
+        let mut state = self.state.lock().unwrap();
+        let id = state.allocate_id();
+        state.methods.insert(id, method);
+        id as rvmti_sys::jmethodID
+    }
+
+}
+
+impl MockState {
+
+    fn allocate_id(&mut self) -> usize {
+        let id = self.next_id;
+        self.next_id += 0x10;
+        id
+    }
+
+    // Handed out objects are local references, they must be deleted by the caller
+    fn new_local_ref(&mut self, id: Option<usize>) -> rvmti_sys::jobject {
+        match id {
+            Some(id) => {
+                *self.local_refs.entry(id).or_insert(0) += 1;
+                id as rvmti_sys::jobject
+            },
+            None => ptr::null_mut(),
+        }
+    }
+
+    fn thread(&self, thread: rvmti_sys::jthread) -> Option<&MockThread> {
+        let id = if thread.is_null() { self.current_thread } else { thread as usize };
+        self.threads.get(&id)
+    }
+
+    // Stack infos and their frame buffers share one allocation, as in HotSpot
+    unsafe fn allocate_stack_infos(&mut self, threads: &[(usize, rvmti_sys::jthread)],
+                                   max_frame_count: rvmti_sys::jint) -> *mut rvmti_sys::jvmtiStackInfo
+    {
+        let frames: Vec<Vec<rvmti_sys::jvmtiFrameInfo>> = threads.iter()
+            .map(|&(id, _)| self.threads[&id].frames.iter().take(max_frame_count as usize).cloned().collect())
+            .collect();
+        let infos_size = threads.len() * std::mem::size_of::<rvmti_sys::jvmtiStackInfo>();
+        let frames_size = frames.iter().map(|f| f.len()).sum::<usize>() * std::mem::size_of::<rvmti_sys::jvmtiFrameInfo>();
+        let mem = self.allocate(infos_size + frames_size);
+        let infos = mem as *mut rvmti_sys::jvmtiStackInfo;
+        let mut frame_buffer = mem.add(infos_size) as *mut rvmti_sys::jvmtiFrameInfo;
+        for (i, (&(id, thread), thread_frames)) in threads.iter().zip(frames.iter()).enumerate() {
+            ptr::copy_nonoverlapping(thread_frames.as_ptr(), frame_buffer, thread_frames.len());
+            *infos.add(i) = rvmti_sys::jvmtiStackInfo{
+                thread,
+                state: self.threads[&id].state() as rvmti_sys::jint,
+                frame_buffer,
+                frame_count: thread_frames.len() as rvmti_sys::jint,
+            };
+            frame_buffer = frame_buffer.add(thread_frames.len());
+        }
+        infos
+    }
+
+    // Ids sorted to keep enumeration order stable
+    unsafe fn allocate_ids<T>(&mut self, ids: &[usize], count_ptr: *mut rvmti_sys::jint, array_ptr: *mut *mut T) {
+        let mut ids = ids.to_vec();
+        ids.sort();
+        let array = self.allocate(ids.len() * std::mem::size_of::<usize>()) as *mut usize;
+        ptr::copy_nonoverlapping(ids.as_ptr(), array, ids.len());
+        *count_ptr = ids.len() as rvmti_sys::jint;
+        *array_ptr = array as *mut T;
+    }
+
+    fn allocate(&mut self, size: usize) -> *mut c_uchar {
+        let mem = unsafe { libc::malloc(size.max(1)) } as *mut c_uchar;
+        self.allocations.insert(mem as usize);
+        mem
+    }
+
+    fn allocate_string(&mut self, value: &str) -> *mut c_char {
+        let bytes = CString::new(value).unwrap().into_bytes_with_nul();
+        let mem = self.allocate(bytes.len());
+        unsafe {
+            ptr::copy_nonoverlapping(bytes.as_ptr(), mem, bytes.len());
+        }
+        mem as *mut c_char
+    }
+
+}
+
+impl Drop for MockJvmti {
+
+    fn drop(&mut self) {
+        let state = self.state.get_mut().unwrap();
+        for mem in state.allocations.drain() {
+            unsafe { libc::free(mem as *mut c_void) };
+        }
+    }
+
+}
+
+// Records are boxed, their addresses must stay stable while the chain is being built
+#[allow(clippy::vec_box)]
+#[derive(Default)]
+pub struct MockCompileInfo {
+    inline_records: Vec<Box<rvmti_sys::jvmtiCompiledMethodLoadInlineRecord>>,
+    dummy_records: Vec<Box<rvmti_sys::jvmtiCompiledMethodLoadDummyRecord>>,
+    order: Vec<*mut rvmti_sys::jvmtiCompiledMethodLoadRecordHeader>,
+    pc_infos: Vec<Vec<rvmti_sys::PCStackInfo>>,
+    methods: Vec<Vec<rvmti_sys::jmethodID>>,
+    bcis: Vec<Vec<rvmti_sys::jint>>,
+}
+
+impl MockCompileInfo {
+
+    pub fn new() -> MockCompileInfo {
+        Default::default()
+    }
+
+    // Frames of each pc are (method, bci) pairs, innermost first
+    pub fn inline_record(mut self, pcs: Vec<(usize, Vec<(rvmti_sys::jmethodID, i32)>)>) -> MockCompileInfo {
+        let mut pc_infos = Vec::new();
+        for (pc, frames) in pcs {
+            let methods: Vec<rvmti_sys::jmethodID> = frames.iter().map(|f| f.0).collect();
+            let bcis: Vec<rvmti_sys::jint> = frames.iter().map(|f| f.1).collect();
+            pc_infos.push(rvmti_sys::PCStackInfo{pc: pc as *mut c_void, numstackframes: frames.len() as rvmti_sys::jint,
+                methods: methods.as_ptr() as *mut rvmti_sys::jmethodID, bcis: bcis.as_ptr() as *mut rvmti_sys::jint});
+            self.methods.push(methods);
+            self.bcis.push(bcis);
+        }
+        let mut record = Box::new(rvmti_sys::jvmtiCompiledMethodLoadInlineRecord{
+            header: record_header(rvmti_sys::jvmtiCMLRKind_JVMTI_CMLR_INLINE_INFO),
+            numpcs: pc_infos.len() as rvmti_sys::jint, pcinfo: pc_infos.as_ptr() as *mut rvmti_sys::PCStackInfo});
+        self.order.push(&mut record.header);
+        self.inline_records.push(record);
+        self.pc_infos.push(pc_infos);
+        self
+    }
+
+    pub fn dummy_record(mut self) -> MockCompileInfo {
+        let mut record = Box::new(rvmti_sys::jvmtiCompiledMethodLoadDummyRecord{
+            header: record_header(rvmti_sys::jvmtiCMLRKind_JVMTI_CMLR_DUMMY), message: [0; 50]});
+        self.order.push(&mut record.header);
+        self.dummy_records.push(record);
+        self
+    }
+
+    pub fn as_ptr(&mut self) -> *const c_void {
+        for i in 0..self.order.len() {
+            let next = self.order.get(i + 1).cloned().unwrap_or(ptr::null_mut());
+            unsafe { (*self.order[i]).next = next };
+        }
+        self.order.first().map_or(ptr::null(), |h| *h as *const c_void)
+    }
+
+}
+
+fn record_header(kind: rvmti_sys::jvmtiCMLRKind) -> rvmti_sys::jvmtiCompiledMethodLoadRecordHeader {
+    rvmti_sys::jvmtiCompiledMethodLoadRecordHeader{kind,
+        majorinfoversion: rvmti_sys::JVMTI_CMLR_MAJOR_VERSION as rvmti_sys::jint,
+        minorinfoversion: rvmti_sys::JVMTI_CMLR_MINOR_VERSION as rvmti_sys::jint,
+        next: ptr::null_mut()}
+}
+
+pub fn address_location_map(entries: &[(usize, rvmti_sys::jlocation)]) -> Vec<rvmti_sys::jvmtiAddrLocationMap> {
+    entries.iter().map(|e| rvmti_sys::jvmtiAddrLocationMap{start_address: e.0 as *const c_void, location: e.1}).collect()
+}
+
+unsafe fn state<'a>(env: *mut rvmti_sys::jvmtiEnv) -> std::sync::MutexGuard<'a, MockState> {
+    (*(env as *const MockJvmti)).state.lock().unwrap()
+}
+
+unsafe fn jni_state<'a>(env: *mut rvmti_sys::JNIEnv) -> std::sync::MutexGuard<'a, MockState> {
+    (*(*(env as *const MockJni)).owner).state.lock().unwrap()
+}
+
+unsafe fn store_string(state: &mut MockState, target: *mut *mut c_char, value: Option<&String>) {
+    if !target.is_null() {
+        *target = value.map_or(ptr::null_mut(), |v| state.allocate_string(v));
+    }
+}
+
+unsafe extern "C" fn mock_allocate(env: *mut rvmti_sys::jvmtiEnv, size: rvmti_sys::jlong,
+                                   mem_ptr: *mut *mut c_uchar) -> rvmti_sys::jvmtiError
+{
+    if mem_ptr.is_null() {
+        return rvmti_sys::jvmtiError_JVMTI_ERROR_NULL_POINTER;
+    }
+    if size < 0 {
+        return rvmti_sys::jvmtiError_JVMTI_ERROR_ILLEGAL_ARGUMENT;
+    }
+    *mem_ptr = state(env).allocate(size as usize);
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_deallocate(env: *mut rvmti_sys::jvmtiEnv, mem: *mut c_uchar) -> rvmti_sys::jvmtiError {
+    if mem.is_null() {
+        return rvmti_sys::jvmtiError_JVMTI_ERROR_NONE;
+    }
+    if !state(env).allocations.remove(&(mem as usize)) {
+        return rvmti_sys::jvmtiError_JVMTI_ERROR_ILLEGAL_ARGUMENT;
+    }
+    libc::free(mem as *mut c_void);
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_dispose_environment(_env: *mut rvmti_sys::jvmtiEnv) -> rvmti_sys::jvmtiError {
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_get_method_name(env: *mut rvmti_sys::jvmtiEnv, method: rvmti_sys::jmethodID,
+                                          name_ptr: *mut *mut c_char, signature_ptr: *mut *mut c_char,
+                                          generic_ptr: *mut *mut c_char) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    let method = match state.methods.get(&(method as usize)) {
+        Some(m) => m.clone(),
+        None => return rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_METHODID,
+    };
+    store_string(&mut state, name_ptr, Some(&method.name));
+    store_string(&mut state, signature_ptr, Some(&method.signature));
+    store_string(&mut state, generic_ptr, method.generic_signature.as_ref());
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_get_method_declaring_class(env: *mut rvmti_sys::jvmtiEnv, method: rvmti_sys::jmethodID,
+                                                     declaring_class_ptr: *mut rvmti_sys::jclass) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    match state.methods.get(&(method as usize)).map(|m| m.class) {
+        Some(class) => {
+            *declaring_class_ptr = state.new_local_ref(Some(class));
+            rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+        },
+        None => rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_METHODID,
+    }
+}
+
+unsafe extern "C" fn mock_get_class_signature(env: *mut rvmti_sys::jvmtiEnv, klass: rvmti_sys::jclass,
+                                              signature_ptr: *mut *mut c_char,
+                                              generic_ptr: *mut *mut c_char) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    let class = match state.classes.get(&(klass as usize)) {
+        Some(c) => c.clone(),
+        None => return rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_CLASS,
+    };
+    store_string(&mut state, signature_ptr, Some(&class.signature));
+    store_string(&mut state, generic_ptr, class.generic_signature.as_ref());
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_get_source_file_name(env: *mut rvmti_sys::jvmtiEnv, klass: rvmti_sys::jclass,
+                                               source_name_ptr: *mut *mut c_char) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    let class = match state.classes.get(&(klass as usize)) {
+        Some(c) => c.clone(),
+        None => return rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_CLASS,
+    };
+    match class.source_file_name {
+        Some(ref name) => {
+            store_string(&mut state, source_name_ptr, Some(name));
+            rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+        },
+        None => rvmti_sys::jvmtiError_JVMTI_ERROR_ABSENT_INFORMATION,
+    }
+}
+
+unsafe extern "C" fn mock_is_method_native(env: *mut rvmti_sys::jvmtiEnv, method: rvmti_sys::jmethodID,
+                                           is_native_ptr: *mut rvmti_sys::jboolean) -> rvmti_sys::jvmtiError
+{
+    match state(env).methods.get(&(method as usize)) {
+        Some(m) => {
+            *is_native_ptr = m.native as rvmti_sys::jboolean;
+            rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+        },
+        None => rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_METHODID,
+    }
+}
+
+unsafe extern "C" fn mock_get_bytecodes(env: *mut rvmti_sys::jvmtiEnv, method: rvmti_sys::jmethodID,
+                                        bytecode_count_ptr: *mut rvmti_sys::jint,
+                                        bytecodes_ptr: *mut *mut c_uchar) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    let bytecodes = match state.methods.get(&(method as usize)) {
+        Some(m) if m.native => return rvmti_sys::jvmtiError_JVMTI_ERROR_NATIVE_METHOD,
+        Some(m) => m.bytecodes.clone(),
+        None => return rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_METHODID,
+    };
+    let mem = state.allocate(bytecodes.len());
+    ptr::copy_nonoverlapping(bytecodes.as_ptr(), mem, bytecodes.len());
+    *bytecode_count_ptr = bytecodes.len() as rvmti_sys::jint;
+    *bytecodes_ptr = mem;
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_get_constant_pool(env: *mut rvmti_sys::jvmtiEnv, klass: rvmti_sys::jclass,
+                                            constant_pool_count_ptr: *mut rvmti_sys::jint,
+                                            constant_pool_byte_count_ptr: *mut rvmti_sys::jint,
+                                            constant_pool_bytes_ptr: *mut *mut c_uchar) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    let (count, bytes) = match state.classes.get(&(klass as usize)) {
+        Some(c) => match c.constant_pool {
+            Some(ref pool) => pool.clone(),
+            None => return rvmti_sys::jvmtiError_JVMTI_ERROR_ABSENT_INFORMATION,
+        },
+        None => return rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_CLASS,
+    };
+    let mem = state.allocate(bytes.len());
+    ptr::copy_nonoverlapping(bytes.as_ptr(), mem, bytes.len());
+    *constant_pool_count_ptr = count as rvmti_sys::jint;
+    *constant_pool_byte_count_ptr = bytes.len() as rvmti_sys::jint;
+    *constant_pool_bytes_ptr = mem;
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_redefine_classes(env: *mut rvmti_sys::jvmtiEnv, class_count: rvmti_sys::jint,
+                                           class_definitions: *const rvmti_sys::jvmtiClassDefinition) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    let definitions = std::slice::from_raw_parts(class_definitions, class_count as usize);
+    if definitions.iter().any(|d| !state.classes.contains_key(&(d.klass as usize))) {
+        return rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_CLASS;
+    }
+    for definition in definitions {
+        let bytes = std::slice::from_raw_parts(definition.class_bytes, definition.class_byte_count as usize).to_vec();
+        state.classes.get_mut(&(definition.klass as usize)).unwrap().class_bytes = bytes;
+    }
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+// Runs the enabled ClassFileLoadHook over current class bytes and keeps the replacement, as the VM would
+unsafe extern "C" fn mock_retransform_classes(env: *mut rvmti_sys::jvmtiEnv, class_count: rvmti_sys::jint,
+                                              classes: *const rvmti_sys::jclass) -> rvmti_sys::jvmtiError
+{
+    let classes = std::slice::from_raw_parts(classes, class_count as usize);
+    for &class in classes {
+        let (name, bytes, hook) = {
+            let state = state(env);
+            let hook = state.callbacks.and_then(|c| c.ClassFileLoadHook)
+                .filter(|_| state.enabled_events.contains(&rvmti_sys::jvmtiEvent_JVMTI_EVENT_CLASS_FILE_LOAD_HOOK));
+            match state.classes.get(&(class as usize)) {
+                Some(c) if !c.signature.starts_with('[') => {
+                    let name = c.signature.trim_start_matches('L').trim_end_matches(';').to_string();
+                    (CString::new(name).unwrap(), c.class_bytes.clone(), hook)
+                },
+                Some(_) => return rvmti_sys::jvmtiError_JVMTI_ERROR_UNMODIFIABLE_CLASS,
+                None => return rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_CLASS,
+            }
+        };
+        if let Some(hook) = hook {
+            let jni_env = &(*(env as *const MockJvmti)).jni.env as *const rvmti_sys::JNIEnv as *mut rvmti_sys::JNIEnv;
+            let mut new_class_data_len: rvmti_sys::jint = 0;
+            let mut new_class_data: *mut c_uchar = ptr::null_mut();
+            hook(env, jni_env, class, ptr::null_mut(), name.as_ptr(), ptr::null_mut(), bytes.len() as rvmti_sys::jint,
+                 bytes.as_ptr(), &mut new_class_data_len, &mut new_class_data);
+            if !new_class_data.is_null() {
+                let replacement = std::slice::from_raw_parts(new_class_data, new_class_data_len as usize).to_vec();
+                state(env).classes.get_mut(&(class as usize)).unwrap().class_bytes = replacement;
+                mock_deallocate(env, new_class_data);
+            }
+        }
+    }
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_is_modifiable_class(env: *mut rvmti_sys::jvmtiEnv, klass: rvmti_sys::jclass,
+                                              is_modifiable_class_ptr: *mut rvmti_sys::jboolean) -> rvmti_sys::jvmtiError
+{
+    match state(env).classes.get(&(klass as usize)) {
+        Some(c) => {
+            *is_modifiable_class_ptr = !c.signature.starts_with('[') as rvmti_sys::jboolean;
+            rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+        },
+        None => rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_CLASS,
+    }
+}
+
+unsafe extern "C" fn mock_set_breakpoint(env: *mut rvmti_sys::jvmtiEnv, method: rvmti_sys::jmethodID,
+                                         location: rvmti_sys::jlocation) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    if !state.methods.contains_key(&(method as usize)) {
+        return rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_METHODID;
+    }
+    if state.breakpoints.insert((method as usize, location)) {
+        rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+    } else {
+        rvmti_sys::jvmtiError_JVMTI_ERROR_DUPLICATE
+    }
+}
+
+unsafe extern "C" fn mock_clear_breakpoint(env: *mut rvmti_sys::jvmtiEnv, method: rvmti_sys::jmethodID,
+                                           location: rvmti_sys::jlocation) -> rvmti_sys::jvmtiError
+{
+    if state(env).breakpoints.remove(&(method as usize, location)) {
+        rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+    } else {
+        rvmti_sys::jvmtiError_JVMTI_ERROR_NOT_FOUND
+    }
+}
+
+unsafe extern "C" fn mock_get_line_number_table(env: *mut rvmti_sys::jvmtiEnv, method: rvmti_sys::jmethodID,
+                                                entry_count_ptr: *mut rvmti_sys::jint,
+                                                table_ptr: *mut *mut rvmti_sys::jvmtiLineNumberEntry) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    let method = match state.methods.get(&(method as usize)) {
+        Some(m) => m.clone(),
+        None => return rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_METHODID,
+    };
+    if method.native {
+        return rvmti_sys::jvmtiError_JVMTI_ERROR_NATIVE_METHOD;
+    }
+    match method.line_numbers {
+        Some(ref lines) => {
+            let table = state.allocate(lines.len() * std::mem::size_of::<rvmti_sys::jvmtiLineNumberEntry>())
+                as *mut rvmti_sys::jvmtiLineNumberEntry;
+            for (i, line) in lines.iter().enumerate() {
+                *table.add(i) = rvmti_sys::jvmtiLineNumberEntry{start_location: line.0, line_number: line.1};
+            }
+            *entry_count_ptr = lines.len() as rvmti_sys::jint;
+            *table_ptr = table;
+            rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+        },
+        None => rvmti_sys::jvmtiError_JVMTI_ERROR_ABSENT_INFORMATION,
+    }
+}
+
+unsafe extern "C" fn mock_set_event_callbacks(env: *mut rvmti_sys::jvmtiEnv,
+                                              callbacks: *const rvmti_sys::jvmtiEventCallbacks,
+                                              size_of_callbacks: rvmti_sys::jint) -> rvmti_sys::jvmtiError
+{
+    if size_of_callbacks as usize != std::mem::size_of::<rvmti_sys::jvmtiEventCallbacks>() {
+        return rvmti_sys::jvmtiError_JVMTI_ERROR_ILLEGAL_ARGUMENT;
+    }
+    state(env).callbacks = if callbacks.is_null() { None } else { Some(*callbacks) };
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_get_thread_info(env: *mut rvmti_sys::jvmtiEnv, thread: rvmti_sys::jthread,
+                                          info_ptr: *mut rvmti_sys::jvmtiThreadInfo) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    match state.threads.get(&(thread as usize)).cloned() {
+        Some(t) => {
+            (*info_ptr).name = state.allocate_string(&t.name);
+            (*info_ptr).priority = 5;
+            (*info_ptr).is_daemon = t.daemon as rvmti_sys::jboolean;
+            (*info_ptr).thread_group = state.new_local_ref(t.group);
+            (*info_ptr).context_class_loader = ptr::null_mut();
+            rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+        },
+        None => rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_THREAD,
+    }
+}
+
+unsafe extern "C" fn mock_get_thread_group_info(env: *mut rvmti_sys::jvmtiEnv, group: rvmti_sys::jthreadGroup,
+                                                info_ptr: *mut rvmti_sys::jvmtiThreadGroupInfo) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    match state.thread_groups.get(&(group as usize)).cloned() {
+        Some(g) => {
+            (*info_ptr).parent = state.new_local_ref(g.parent);
+            (*info_ptr).name = state.allocate_string(&g.name);
+            (*info_ptr).max_priority = 10;
+            (*info_ptr).is_daemon = 0;
+            rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+        },
+        None => rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_THREAD_GROUP,
+    }
+}
+
+unsafe extern "C" fn mock_get_stack_trace(env: *mut rvmti_sys::jvmtiEnv, thread: rvmti_sys::jthread,
+                                          start_depth: rvmti_sys::jint, max_frame_count: rvmti_sys::jint,
+                                          frame_buffer: *mut rvmti_sys::jvmtiFrameInfo,
+                                          count_ptr: *mut rvmti_sys::jint) -> rvmti_sys::jvmtiError
+{
+    let state = state(env);
+    match state.thread(thread) {
+        Some(t) => {
+            if start_depth < 0 || start_depth as usize > t.frames.len() {
+                return rvmti_sys::jvmtiError_JVMTI_ERROR_ILLEGAL_ARGUMENT;
+            }
+            let frames: Vec<_> = t.frames.iter().skip(start_depth as usize).take(max_frame_count as usize).cloned().collect();
+            ptr::copy_nonoverlapping(frames.as_ptr(), frame_buffer, frames.len());
+            *count_ptr = frames.len() as rvmti_sys::jint;
+            rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+        },
+        None => rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_THREAD,
+    }
+}
+
+unsafe extern "C" fn mock_get_all_stack_traces(env: *mut rvmti_sys::jvmtiEnv, max_frame_count: rvmti_sys::jint,
+                                               stack_info_ptr: *mut *mut rvmti_sys::jvmtiStackInfo,
+                                               thread_count_ptr: *mut rvmti_sys::jint) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    let mut ids: Vec<usize> = state.threads.keys().cloned().collect();
+    ids.sort();
+    // Every thread gets a new local reference
+    let threads: Vec<(usize, rvmti_sys::jthread)> = ids.iter().map(|&id| (id, state.new_local_ref(Some(id)))).collect();
+    *stack_info_ptr = state.allocate_stack_infos(&threads, max_frame_count);
+    *thread_count_ptr = threads.len() as rvmti_sys::jint;
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_get_thread_list_stack_traces(env: *mut rvmti_sys::jvmtiEnv, thread_count: rvmti_sys::jint,
+                                                       thread_list: *const rvmti_sys::jthread,
+                                                       max_frame_count: rvmti_sys::jint,
+                                                       stack_info_ptr: *mut *mut rvmti_sys::jvmtiStackInfo) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    let thread_list = std::slice::from_raw_parts(thread_list, thread_count as usize);
+    if thread_list.iter().any(|t| !state.threads.contains_key(&(*t as usize))) {
+        return rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_THREAD;
+    }
+    let threads: Vec<(usize, rvmti_sys::jthread)> = thread_list.iter().map(|&t| (t as usize, t)).collect();
+    *stack_info_ptr = state.allocate_stack_infos(&threads, max_frame_count);
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_get_frame_count(env: *mut rvmti_sys::jvmtiEnv, thread: rvmti_sys::jthread,
+                                          count_ptr: *mut rvmti_sys::jint) -> rvmti_sys::jvmtiError
+{
+    match state(env).thread(thread) {
+        Some(t) => {
+            *count_ptr = t.frames.len() as rvmti_sys::jint;
+            rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+        },
+        None => rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_THREAD,
+    }
+}
+
+unsafe extern "C" fn mock_get_frame_location(env: *mut rvmti_sys::jvmtiEnv, thread: rvmti_sys::jthread, depth: rvmti_sys::jint,
+                                             method_ptr: *mut rvmti_sys::jmethodID,
+                                             location_ptr: *mut rvmti_sys::jlocation) -> rvmti_sys::jvmtiError
+{
+    match state(env).thread(thread) {
+        Some(t) => match t.frames.get(depth as usize) {
+            Some(frame) if depth >= 0 => {
+                *method_ptr = frame.method;
+                *location_ptr = frame.location;
+                rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+            },
+            _ => rvmti_sys::jvmtiError_JVMTI_ERROR_NO_MORE_FRAMES,
+        },
+        None => rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_THREAD,
+    }
+}
+
+unsafe extern "C" fn mock_get_current_thread(env: *mut rvmti_sys::jvmtiEnv, thread_ptr: *mut rvmti_sys::jthread) -> rvmti_sys::jvmtiError {
+    let mut state = state(env);
+    let current = Some(state.current_thread).filter(|&id| id != 0);
+    *thread_ptr = state.new_local_ref(current);
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+// Suspension only flips the flag, the current thread suspending itself would never return in a real VM
+fn set_suspended(state: &mut MockState, thread: rvmti_sys::jthread, suspended: bool) -> rvmti_sys::jvmtiError {
+    let id = if thread.is_null() { state.current_thread } else { thread as usize };
+    match state.threads.get_mut(&id) {
+        Some(t) if !t.alive => rvmti_sys::jvmtiError_JVMTI_ERROR_THREAD_NOT_ALIVE,
+        Some(t) if t.suspended == suspended => if suspended {
+            rvmti_sys::jvmtiError_JVMTI_ERROR_THREAD_SUSPENDED
+        } else {
+            rvmti_sys::jvmtiError_JVMTI_ERROR_THREAD_NOT_SUSPENDED
+        },
+        Some(t) => {
+            t.suspended = suspended;
+            rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+        },
+        None => rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_THREAD,
+    }
+}
+
+unsafe extern "C" fn mock_suspend_thread(env: *mut rvmti_sys::jvmtiEnv, thread: rvmti_sys::jthread) -> rvmti_sys::jvmtiError {
+    set_suspended(&mut state(env), thread, true)
+}
+
+unsafe extern "C" fn mock_resume_thread(env: *mut rvmti_sys::jvmtiEnv, thread: rvmti_sys::jthread) -> rvmti_sys::jvmtiError {
+    set_suspended(&mut state(env), thread, false)
+}
+
+unsafe extern "C" fn mock_suspend_thread_list(env: *mut rvmti_sys::jvmtiEnv, request_count: rvmti_sys::jint,
+                                              request_list: *const rvmti_sys::jthread,
+                                              results: *mut rvmti_sys::jvmtiError) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    for (i, thread) in std::slice::from_raw_parts(request_list, request_count as usize).iter().enumerate() {
+        *results.add(i) = set_suspended(&mut state, *thread, true);
+    }
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_resume_thread_list(env: *mut rvmti_sys::jvmtiEnv, request_count: rvmti_sys::jint,
+                                             request_list: *const rvmti_sys::jthread,
+                                             results: *mut rvmti_sys::jvmtiError) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    for (i, thread) in std::slice::from_raw_parts(request_list, request_count as usize).iter().enumerate() {
+        *results.add(i) = set_suspended(&mut state, *thread, false);
+    }
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_get_owned_monitor_stack_depth_info(env: *mut rvmti_sys::jvmtiEnv, thread: rvmti_sys::jthread,
+                                                             monitor_info_count_ptr: *mut rvmti_sys::jint,
+                                                             monitor_info_ptr: *mut *mut rvmti_sys::jvmtiMonitorStackDepthInfo)
+                                                             -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    let monitors = match state.thread(thread) {
+        Some(t) if !t.alive => return rvmti_sys::jvmtiError_JVMTI_ERROR_THREAD_NOT_ALIVE,
+        Some(t) => t.owned_monitors.clone(),
+        None => return rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_THREAD,
+    };
+    let infos = state.allocate(monitors.len() * std::mem::size_of::<rvmti_sys::jvmtiMonitorStackDepthInfo>())
+        as *mut rvmti_sys::jvmtiMonitorStackDepthInfo;
+    for (i, &(monitor, stack_depth)) in monitors.iter().enumerate() {
+        *infos.add(i) = rvmti_sys::jvmtiMonitorStackDepthInfo{monitor: state.new_local_ref(Some(monitor)), stack_depth};
+    }
+    *monitor_info_count_ptr = monitors.len() as rvmti_sys::jint;
+    *monitor_info_ptr = infos;
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_get_current_contended_monitor(env: *mut rvmti_sys::jvmtiEnv, thread: rvmti_sys::jthread,
+                                                        monitor_ptr: *mut rvmti_sys::jobject) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    let monitor = match state.thread(thread) {
+        Some(t) if !t.alive => return rvmti_sys::jvmtiError_JVMTI_ERROR_THREAD_NOT_ALIVE,
+        Some(t) => t.contended_monitor,
+        None => return rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_THREAD,
+    };
+    *monitor_ptr = state.new_local_ref(monitor);
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_get_loaded_classes(env: *mut rvmti_sys::jvmtiEnv, class_count_ptr: *mut rvmti_sys::jint,
+                                             classes_ptr: *mut *mut rvmti_sys::jclass) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    let ids: Vec<usize> = state.classes.keys().cloned().collect();
+    for id in ids.iter() {
+        state.new_local_ref(Some(*id));
+    }
+    state.allocate_ids(&ids, class_count_ptr, classes_ptr);
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_get_class_loader_classes(env: *mut rvmti_sys::jvmtiEnv, initiating_loader: rvmti_sys::jobject,
+                                                   class_count_ptr: *mut rvmti_sys::jint,
+                                                   classes_ptr: *mut *mut rvmti_sys::jclass) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    let loader = if initiating_loader.is_null() { None } else { Some(initiating_loader as usize) };
+    let ids: Vec<usize> = state.classes.iter().filter(|(_, c)| c.loader == loader).map(|(id, _)| *id).collect();
+    for id in ids.iter() {
+        state.new_local_ref(Some(*id));
+    }
+    state.allocate_ids(&ids, class_count_ptr, classes_ptr);
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_get_class_methods(env: *mut rvmti_sys::jvmtiEnv, klass: rvmti_sys::jclass,
+                                            method_count_ptr: *mut rvmti_sys::jint,
+                                            methods_ptr: *mut *mut rvmti_sys::jmethodID) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    if !state.classes.contains_key(&(klass as usize)) {
+        return rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_CLASS;
+    }
+    let ids: Vec<usize> = state.methods.iter().filter(|(_, m)| m.class == klass as usize).map(|(id, _)| *id).collect();
+    state.allocate_ids(&ids, method_count_ptr, methods_ptr);
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_get_class_fields(env: *mut rvmti_sys::jvmtiEnv, klass: rvmti_sys::jclass,
+                                           field_count_ptr: *mut rvmti_sys::jint,
+                                           fields_ptr: *mut *mut rvmti_sys::jfieldID) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    if !state.classes.contains_key(&(klass as usize)) {
+        return rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_CLASS;
+    }
+    let ids: Vec<usize> = state.fields.iter().filter(|(_, f)| f.class == klass as usize).map(|(id, _)| *id).collect();
+    state.allocate_ids(&ids, field_count_ptr, fields_ptr);
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_get_class_modifiers(env: *mut rvmti_sys::jvmtiEnv, klass: rvmti_sys::jclass,
+                                              modifiers_ptr: *mut rvmti_sys::jint) -> rvmti_sys::jvmtiError
+{
+    match state(env).classes.get(&(klass as usize)) {
+        Some(c) => {
+            *modifiers_ptr = c.modifiers;
+            rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+        },
+        None => rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_CLASS,
+    }
+}
+
+unsafe extern "C" fn mock_get_method_modifiers(env: *mut rvmti_sys::jvmtiEnv, method: rvmti_sys::jmethodID,
+                                               modifiers_ptr: *mut rvmti_sys::jint) -> rvmti_sys::jvmtiError
+{
+    match state(env).methods.get(&(method as usize)) {
+        Some(m) => {
+            *modifiers_ptr = m.modifiers;
+            rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+        },
+        None => rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_METHODID,
+    }
+}
+
+unsafe extern "C" fn mock_get_field_modifiers(env: *mut rvmti_sys::jvmtiEnv, klass: rvmti_sys::jclass, field: rvmti_sys::jfieldID,
+                                              modifiers_ptr: *mut rvmti_sys::jint) -> rvmti_sys::jvmtiError
+{
+    match state(env).fields.get(&(field as usize)) {
+        Some(f) if f.class == klass as usize => {
+            *modifiers_ptr = f.modifiers;
+            rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+        },
+        _ => rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_FIELDID,
+    }
+}
+
+unsafe extern "C" fn mock_get_field_name(env: *mut rvmti_sys::jvmtiEnv, klass: rvmti_sys::jclass, field: rvmti_sys::jfieldID,
+                                         name_ptr: *mut *mut c_char, signature_ptr: *mut *mut c_char,
+                                         generic_ptr: *mut *mut c_char) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    match state.fields.get(&(field as usize)).cloned() {
+        Some(f) if f.class == klass as usize => {
+            store_string(&mut state, name_ptr, Some(&f.name));
+            store_string(&mut state, signature_ptr, Some(&f.signature));
+            store_string(&mut state, generic_ptr, None);
+            rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+        },
+        _ => rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_FIELDID,
+    }
+}
+
+unsafe extern "C" fn mock_get_class_status(env: *mut rvmti_sys::jvmtiEnv, klass: rvmti_sys::jclass,
+                                           status_ptr: *mut rvmti_sys::jint) -> rvmti_sys::jvmtiError
+{
+    match state(env).classes.get(&(klass as usize)) {
+        Some(c) => {
+            *status_ptr = if c.signature.starts_with('[') {
+                rvmti_sys::JVMTI_CLASS_STATUS_ARRAY
+            } else {
+                rvmti_sys::JVMTI_CLASS_STATUS_VERIFIED | rvmti_sys::JVMTI_CLASS_STATUS_PREPARED
+                    | rvmti_sys::JVMTI_CLASS_STATUS_INITIALIZED
+            } as rvmti_sys::jint;
+            rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+        },
+        None => rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_CLASS,
+    }
+}
+
+unsafe extern "C" fn mock_is_interface(env: *mut rvmti_sys::jvmtiEnv, klass: rvmti_sys::jclass,
+                                       is_interface_ptr: *mut rvmti_sys::jboolean) -> rvmti_sys::jvmtiError
+{
+    match state(env).classes.get(&(klass as usize)) {
+        Some(c) => {
+            *is_interface_ptr = (c.modifiers & 0x0200 != 0) as rvmti_sys::jboolean;
+            rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+        },
+        None => rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_CLASS,
+    }
+}
+
+unsafe extern "C" fn mock_is_array_class(env: *mut rvmti_sys::jvmtiEnv, klass: rvmti_sys::jclass,
+                                         is_array_class_ptr: *mut rvmti_sys::jboolean) -> rvmti_sys::jvmtiError
+{
+    match state(env).classes.get(&(klass as usize)) {
+        Some(c) => {
+            *is_array_class_ptr = c.signature.starts_with('[') as rvmti_sys::jboolean;
+            rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+        },
+        None => rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_CLASS,
+    }
+}
+
+unsafe extern "C" fn mock_get_class_loader(env: *mut rvmti_sys::jvmtiEnv, klass: rvmti_sys::jclass,
+                                           classloader_ptr: *mut rvmti_sys::jobject) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    match state.classes.get(&(klass as usize)).map(|c| c.loader) {
+        Some(loader) => {
+            *classloader_ptr = state.new_local_ref(loader);
+            rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+        },
+        None => rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_CLASS,
+    }
+}
+
+unsafe extern "C" fn mock_get_environment_local_storage(env: *mut rvmti_sys::jvmtiEnv,
+                                                        data_ptr: *mut *mut c_void) -> rvmti_sys::jvmtiError
+{
+    *data_ptr = state(env).environment_storage as *mut c_void;
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_set_environment_local_storage(env: *mut rvmti_sys::jvmtiEnv,
+                                                        data: *const c_void) -> rvmti_sys::jvmtiError
+{
+    state(env).environment_storage = data as usize;
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_get_thread_local_storage(env: *mut rvmti_sys::jvmtiEnv, thread: rvmti_sys::jthread,
+                                                   data_ptr: *mut *mut c_void) -> rvmti_sys::jvmtiError
+{
+    *data_ptr = state(env).thread_storage.get(&(thread as usize)).cloned().unwrap_or(0) as *mut c_void;
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_set_thread_local_storage(env: *mut rvmti_sys::jvmtiEnv, thread: rvmti_sys::jthread,
+                                                   data: *const c_void) -> rvmti_sys::jvmtiError
+{
+    state(env).thread_storage.insert(thread as usize, data as usize);
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_get_version_number(env: *mut rvmti_sys::jvmtiEnv,
+                                             version_ptr: *mut rvmti_sys::jint) -> rvmti_sys::jvmtiError
+{
+    *version_ptr = state(env).version;
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_get_phase(env: *mut rvmti_sys::jvmtiEnv, phase_ptr: *mut rvmti_sys::jvmtiPhase) -> rvmti_sys::jvmtiError {
+    *phase_ptr = state(env).phase;
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_get_system_properties(env: *mut rvmti_sys::jvmtiEnv, count_ptr: *mut rvmti_sys::jint,
+                                                property_ptr: *mut *mut *mut c_char) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    let keys: Vec<String> = state.system_properties.iter().map(|(k, _)| k.clone()).collect();
+    let array = state.allocate(keys.len() * std::mem::size_of::<*mut c_char>()) as *mut *mut c_char;
+    for (index, key) in keys.iter().enumerate() {
+        *array.add(index) = state.allocate_string(key);
+    }
+    *count_ptr = keys.len() as rvmti_sys::jint;
+    *property_ptr = array;
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_get_system_property(env: *mut rvmti_sys::jvmtiEnv, property: *const c_char,
+                                              value_ptr: *mut *mut c_char) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    let property = std::ffi::CStr::from_ptr(property).to_string_lossy().into_owned();
+    let value = state.system_properties.iter().find(|(k, _)| *k == property).map(|(_, v)| v.clone());
+    match value {
+        Some(value) => {
+            *value_ptr = state.allocate_string(&value);
+            rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+        },
+        None => rvmti_sys::jvmtiError_JVMTI_ERROR_NOT_AVAILABLE,
+    }
+}
+
+unsafe extern "C" fn mock_get_time(env: *mut rvmti_sys::jvmtiEnv, nanos_ptr: *mut rvmti_sys::jlong) -> rvmti_sys::jvmtiError {
+    *nanos_ptr = state(env).time;
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_get_elapsed_timer_info(_env: *mut rvmti_sys::jvmtiEnv,
+                                                 info_ptr: *mut rvmti_sys::jvmtiTimerInfo) -> rvmti_sys::jvmtiError
+{
+    *info_ptr = rvmti_sys::jvmtiTimerInfo{max_value: rvmti_sys::jlong::MAX, may_skip_forward: 0, may_skip_backward: 0,
+        kind: rvmti_sys::jvmtiTimerKind_JVMTI_TIMER_ELAPSED, reserved1: 0, reserved2: 0};
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_get_cpu_timer_info(_env: *mut rvmti_sys::jvmtiEnv,
+                                             info_ptr: *mut rvmti_sys::jvmtiTimerInfo) -> rvmti_sys::jvmtiError
+{
+    *info_ptr = rvmti_sys::jvmtiTimerInfo{max_value: rvmti_sys::jlong::MAX, may_skip_forward: 0, may_skip_backward: 0,
+        kind: rvmti_sys::jvmtiTimerKind_JVMTI_TIMER_TOTAL_CPU, reserved1: 0, reserved2: 0};
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_get_current_thread_cpu_time(env: *mut rvmti_sys::jvmtiEnv,
+                                                      nanos_ptr: *mut rvmti_sys::jlong) -> rvmti_sys::jvmtiError
+{
+    mock_get_thread_cpu_time(env, ptr::null_mut(), nanos_ptr)
+}
+
+unsafe extern "C" fn mock_get_thread_cpu_time(env: *mut rvmti_sys::jvmtiEnv, thread: rvmti_sys::jthread,
+                                              nanos_ptr: *mut rvmti_sys::jlong) -> rvmti_sys::jvmtiError
+{
+    match state(env).thread_cpu_times.get(&(thread as usize)) {
+        Some(nanos) => {
+            *nanos_ptr = *nanos;
+            rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+        },
+        None => rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_THREAD,
+    }
+}
+
+unsafe extern "C" fn mock_create_raw_monitor(env: *mut rvmti_sys::jvmtiEnv, name: *const c_char,
+                                             monitor_ptr: *mut rvmti_sys::jrawMonitorID) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    let id = state.allocate_id();
+    let name = std::ffi::CStr::from_ptr(name).to_string_lossy().into_owned();
+    state.raw_monitors.insert(id, MockRawMonitor{name, entries: 0, waits: 0, notifications: 0});
+    *monitor_ptr = id as rvmti_sys::jrawMonitorID;
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_destroy_raw_monitor(env: *mut rvmti_sys::jvmtiEnv,
+                                              monitor: rvmti_sys::jrawMonitorID) -> rvmti_sys::jvmtiError
+{
+    match state(env).raw_monitors.remove(&(monitor as usize)) {
+        Some(_) => rvmti_sys::jvmtiError_JVMTI_ERROR_NONE,
+        None => rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_MONITOR,
+    }
+}
+
+unsafe extern "C" fn mock_raw_monitor_enter(env: *mut rvmti_sys::jvmtiEnv,
+                                            monitor: rvmti_sys::jrawMonitorID) -> rvmti_sys::jvmtiError
+{
+    match state(env).raw_monitors.get_mut(&(monitor as usize)) {
+        Some(monitor) => {
+            monitor.entries += 1;
+            rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+        },
+        None => rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_MONITOR,
+    }
+}
+
+unsafe extern "C" fn mock_raw_monitor_exit(env: *mut rvmti_sys::jvmtiEnv,
+                                           monitor: rvmti_sys::jrawMonitorID) -> rvmti_sys::jvmtiError
+{
+    match state(env).raw_monitors.get_mut(&(monitor as usize)) {
+        Some(monitor) if monitor.entries > 0 => {
+            monitor.entries -= 1;
+            rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+        },
+        Some(_) => rvmti_sys::jvmtiError_JVMTI_ERROR_NOT_MONITOR_OWNER,
+        None => rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_MONITOR,
+    }
+}
+
+unsafe extern "C" fn mock_raw_monitor_wait(env: *mut rvmti_sys::jvmtiEnv, monitor: rvmti_sys::jrawMonitorID,
+                                           _millis: rvmti_sys::jlong) -> rvmti_sys::jvmtiError
+{
+    match state(env).raw_monitors.get_mut(&(monitor as usize)) {
+        Some(monitor) if monitor.entries > 0 => {
+            monitor.waits += 1;
+            rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+        },
+        Some(_) => rvmti_sys::jvmtiError_JVMTI_ERROR_NOT_MONITOR_OWNER,
+        None => rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_MONITOR,
+    }
+}
+
+unsafe extern "C" fn mock_raw_monitor_notify(env: *mut rvmti_sys::jvmtiEnv,
+                                             monitor: rvmti_sys::jrawMonitorID) -> rvmti_sys::jvmtiError
+{
+    match state(env).raw_monitors.get_mut(&(monitor as usize)) {
+        Some(monitor) if monitor.entries > 0 => {
+            monitor.notifications += 1;
+            rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+        },
+        Some(_) => rvmti_sys::jvmtiError_JVMTI_ERROR_NOT_MONITOR_OWNER,
+        None => rvmti_sys::jvmtiError_JVMTI_ERROR_INVALID_MONITOR,
+    }
+}
+
+unsafe extern "C" fn mock_set_event_notification_mode(env: *mut rvmti_sys::jvmtiEnv, mode: rvmti_sys::jvmtiEventMode,
+                                                      event_type: rvmti_sys::jvmtiEvent,
+                                                      _event_thread: rvmti_sys::jthread) -> rvmti_sys::jvmtiError
+{
+    let mut state = state(env);
+    if mode == rvmti_sys::jvmtiEventMode_JVMTI_ENABLE {
+        state.enabled_events.insert(event_type);
+    } else {
+        state.enabled_events.remove(&event_type);
+    }
+    rvmti_sys::jvmtiError_JVMTI_ERROR_NONE
+}
+
+unsafe extern "C" fn mock_get_env(vm: *mut rvmti_sys::JavaVM, penv: *mut *mut c_void,
+                                  version: rvmti_sys::jint) -> rvmti_sys::jint
+{
+    let owner = (*(vm as *const MockVm)).owner;
+    *penv = if version as u32 & rvmti_sys::JVMTI_VERSION_INTERFACE_JVMTI != 0 {
+        (*owner).env_ptr() as *mut c_void
+    } else {
+        &(*owner).jni.env as *const rvmti_sys::JNIEnv as *mut c_void
+    };
+    rvmti_sys::JNI_OK as rvmti_sys::jint
+}
+
+unsafe extern "C" fn mock_delete_local_ref(env: *mut rvmti_sys::JNIEnv, obj: rvmti_sys::jobject) {
+    let mut state = jni_state(env);
+    match state.local_refs.get_mut(&(obj as usize)) {
+        Some(count) if *count > 0 => *count -= 1,
+        _ => state.invalid_local_ref_deletions += 1,
+    }
+}
+
+// References are the object ids themselves
+unsafe extern "C" fn mock_is_same_object(_env: *mut rvmti_sys::JNIEnv, obj1: rvmti_sys::jobject,
+                                         obj2: rvmti_sys::jobject) -> rvmti_sys::jboolean
+{
+    (obj1 == obj2) as rvmti_sys::jboolean
+}
+
+// No Java classes in the mock, lookups fail like for a missing class
+unsafe extern "C" fn mock_find_class(env: *mut rvmti_sys::JNIEnv, _name: *const c_char) -> rvmti_sys::jclass {
+    jni_state(env).pending_exception = true;
+    ptr::null_mut()
+}
+
+unsafe extern "C" fn mock_exception_check(env: *mut rvmti_sys::JNIEnv) -> rvmti_sys::jboolean {
+    jni_state(env).pending_exception as rvmti_sys::jboolean
+}
+
+unsafe extern "C" fn mock_exception_clear(env: *mut rvmti_sys::JNIEnv) {
+    jni_state(env).pending_exception = false;
+}
+
+// Frames are not tracked, references handed out are deleted one by one and counted as outstanding otherwise
+unsafe extern "C" fn mock_push_local_frame(_env: *mut rvmti_sys::JNIEnv, _capacity: rvmti_sys::jint) -> rvmti_sys::jint {
+    rvmti_sys::JNI_OK as rvmti_sys::jint
+}
+
+unsafe extern "C" fn mock_pop_local_frame(_env: *mut rvmti_sys::JNIEnv, result: rvmti_sys::jobject) -> rvmti_sys::jobject {
+    result
+}
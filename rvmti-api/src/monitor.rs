@@ -0,0 +1,170 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+
+// Raw monitors are usable in any phase including callbacks running before VM initialization, unlike Java monitors.
+// Monitor keeps the raw environment pointer, it must be dropped before the environment is disposed.
+
+use std::time::Duration;
+
+use log::{debug, warn};
+
+use super::{JvmtiEnv, JvmtiError, to_modified_utf8};
+
+#[derive(Debug)]
+pub struct RawMonitor {
+    env: *mut rvmti_sys::jvmtiEnv,
+    monitor: rvmti_sys::jrawMonitorID,
+}
+
+// Raw monitors are intended to be shared between threads, environment functions are callable from any thread
+unsafe impl Send for RawMonitor {}
+unsafe impl Sync for RawMonitor {}
+
+// Monitor is entered while the guard lives, raw monitors are reentrant
+#[derive(Debug)]
+pub struct RawMonitorGuard<'a> {
+    monitor: &'a RawMonitor,
+}
+
+impl JvmtiEnv {
+
+    pub fn create_raw_monitor(&mut self, name: &str) -> Result<RawMonitor, JvmtiError> {
+        let name = to_modified_utf8(name);
+        let mut monitor: rvmti_sys::jrawMonitorID = std::ptr::null_mut();
+        unsafe {
+            let result = (*(*self.env)).CreateRawMonitor.unwrap()(self.env, name.as_ptr(), &mut monitor);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(RawMonitor{env: self.env, monitor});
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+}
+
+impl RawMonitor {
+
+    pub fn raw(&self) -> rvmti_sys::jrawMonitorID {
+        self.monitor
+    }
+
+    pub fn enter(&self) -> Result<RawMonitorGuard<'_>, JvmtiError> {
+        unsafe {
+            let result = (*(*self.env)).RawMonitorEnter.unwrap()(self.env, self.monitor);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(RawMonitorGuard{monitor: self});
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+}
+
+impl<'a> RawMonitorGuard<'a> {
+
+    // Waits until notified or the timeout elapses, without timeout waits forever. Interrupted wait returns
+    // `JvmtiError::Interrupt`, monitor is entered again in any case
+    pub fn wait(&self, timeout: Option<Duration>) -> Result<(), JvmtiError> {
+        // Zero means no timeout for the VM, shorter timeouts are rounded up to a millisecond
+        let millis = match timeout {
+            Some(timeout) => std::cmp::max(1, std::cmp::min(timeout.as_millis(), rvmti_sys::jlong::MAX as u128) as rvmti_sys::jlong),
+            None => 0,
+        };
+        unsafe {
+            let result = (*(*self.monitor.env)).RawMonitorWait.unwrap()(self.monitor.env, self.monitor.monitor, millis);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(());
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    pub fn notify(&self) -> Result<(), JvmtiError> {
+        unsafe {
+            let result = (*(*self.monitor.env)).RawMonitorNotify.unwrap()(self.monitor.env, self.monitor.monitor);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(());
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    pub fn notify_all(&self) -> Result<(), JvmtiError> {
+        unsafe {
+            let result = (*(*self.monitor.env)).RawMonitorNotifyAll.unwrap()(self.monitor.env, self.monitor.monitor);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(());
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+}
+
+impl<'a> Drop for RawMonitorGuard<'a> {
+
+    fn drop(&mut self) {
+        unsafe {
+            let result = (*(*self.monitor.env)).RawMonitorExit.unwrap()(self.monitor.env, self.monitor.monitor);
+            if result != rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                warn!("Failed to exit raw monitor: {}", JvmtiError::from(result));
+            }
+        }
+    }
+
+}
+
+impl Drop for RawMonitor {
+
+    fn drop(&mut self) {
+        unsafe {
+            let result = (*(*self.env)).DestroyRawMonitor.unwrap()(self.env, self.monitor);
+            if result != rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                warn!("Failed to destroy raw monitor: {}", JvmtiError::from(result));
+            } else {
+                debug!("Destroyed raw monitor");
+            }
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::mock::MockJvmti;
+
+    #[test]
+    fn test_raw_monitor() {
+        let mock = MockJvmti::new();
+        let mut env = crate::JvmtiEnv::cons(mock.env_ptr());
+        let monitor = env.create_raw_monitor("rvmti test monitor").unwrap();
+        assert_eq!(mock.raw_monitor_state("rvmti test monitor"), Some((0, 0, 0)));
+        {
+            let guard = monitor.enter().unwrap();
+            // Raw monitors are reentrant
+            let nested = monitor.enter().unwrap();
+            assert_eq!(mock.raw_monitor_state("rvmti test monitor"), Some((2, 0, 0)));
+            drop(nested);
+            guard.notify().unwrap();
+            guard.notify_all().unwrap();
+            guard.wait(Some(std::time::Duration::from_millis(1))).unwrap();
+            assert_eq!(mock.raw_monitor_state("rvmti test monitor"), Some((1, 1, 2)));
+        }
+        assert_eq!(mock.raw_monitor_state("rvmti test monitor"), Some((0, 1, 2)));
+        drop(monitor);
+        assert_eq!(mock.outstanding_raw_monitors(), 0);
+    }
+
+}
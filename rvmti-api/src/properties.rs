@@ -0,0 +1,98 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+
+// System properties as seen by the VM. The VM side set is initialized before `java.lang.System`, it does not
+// reflect properties changed by Java code later on. HotSpot hands out properties given on the command line as
+// platform strings instead of modified UTF-8, both encodings are accepted.
+
+use std::os::raw::c_char;
+use std::ptr;
+
+use thiserror::Error;
+
+use super::{JvmtiEnv, JvmtiError, StringDecodeError, VmOwnedString, VmOwnedArray, from_platform, to_modified_utf8};
+
+#[derive(Error, Debug)]
+pub enum GetSystemPropertyError {
+    #[error("JVMTI method call error: {0}")]
+    VmError(#[source] JvmtiError),
+    #[error("Failed to decode property key: {0}")]
+    KeyDecodeError(#[source] StringDecodeError),
+    #[error("Failed to decode property value: {0}")]
+    ValueDecodeError(#[source] StringDecodeError),
+}
+
+impl JvmtiEnv {
+
+    // Keys of properties available through `get_system_property`
+    pub fn get_system_properties(&mut self) -> Result<Vec<String>, GetSystemPropertyError> {
+        unsafe {
+            let mut count: rvmti_sys::jint = 0;
+            let mut keys_ptr: *mut *mut c_char = ptr::null_mut();
+            let result = (*(*self.env)).GetSystemProperties.unwrap()(self.env, &mut count, &mut keys_ptr);
+            let keys = VmOwnedArray{ptr: keys_ptr, count, env: self};
+            // Every key is allocated separately and must be released even if another one fails to decode
+            let keys: Vec<VmOwnedString> = keys.as_slice().iter().map(|k| VmOwnedString{ptr: *k, env: self}).collect();
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return keys.iter().filter_map(|k| decode(k).transpose())
+                    .collect::<Result<Vec<String>, StringDecodeError>>()
+                    .map_err(GetSystemPropertyError::KeyDecodeError);
+            } else {
+                return Err(GetSystemPropertyError::VmError(JvmtiError::from(result)));
+            }
+        }
+    }
+
+    // Unknown property is `None`
+    pub fn get_system_property(&mut self, key: &str) -> Result<Option<String>, GetSystemPropertyError> {
+        let key = to_modified_utf8(key);
+        unsafe {
+            let mut value_ptr: *mut c_char = ptr::null_mut();
+            let result = (*(*self.env)).GetSystemProperty.unwrap()(self.env, key.as_ptr(), &mut value_ptr);
+            let value = VmOwnedString{ptr: value_ptr, env: self};
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return decode(&value).map_err(GetSystemPropertyError::ValueDecodeError);
+            } else if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NOT_AVAILABLE {
+                return Ok(None);
+            } else {
+                return Err(GetSystemPropertyError::VmError(JvmtiError::from(result)));
+            }
+        }
+    }
+
+}
+
+// Supplementary characters encoded as surrogate pairs are not valid UTF-8, so modified UTF-8 goes first
+fn decode(value: &VmOwnedString) -> Result<Option<String>, StringDecodeError> {
+    match value.to_string() {
+        Err(StringDecodeError::ModifiedUtf8Error) => from_platform(value.ptr),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::mock::MockJvmti;
+
+    #[test]
+    fn test_system_properties() {
+        let mock = MockJvmti::new();
+        mock.set_system_property("java.vm.name", "OpenJDK 64-Bit Server VM");
+        mock.set_system_property("java.home", "/usr/lib/jvm/java-17");
+        mock.set_system_property("user.name", "v\u{e4}rde");
+        let mut env = crate::JvmtiEnv::cons(mock.env_ptr());
+        assert_eq!(env.get_system_properties().unwrap(), vec!["java.vm.name", "java.home", "user.name"]);
+        assert_eq!(env.get_system_property("java.home").unwrap(), Some("/usr/lib/jvm/java-17".to_string()));
+        assert_eq!(env.get_system_property("user.name").unwrap(), Some("v\u{e4}rde".to_string()));
+        assert_eq!(env.get_system_property("missing").unwrap(), None);
+        assert_eq!(mock.outstanding_allocations(), 0);
+    }
+
+}
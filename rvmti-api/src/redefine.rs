@@ -0,0 +1,147 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+// Class redefinition and retransformation. Redefinition installs given class bytes, retransformation reruns
+// ClassFileLoadHook of retransformation capable environments over the original bytes of loaded classes.
+
+use std::os::raw::c_uchar;
+use std::ptr;
+
+use super::{JvmtiEnv, JClass, JvmtiError, JvmtiVersion, version};
+
+#[derive(Debug)]
+pub struct ClassDefinition<'a> {
+    pub class: &'a JClass,
+    pub class_bytes: &'a [u8],
+}
+
+impl JvmtiEnv {
+
+    // Requires `can_redefine_classes` capability, `can_redefine_any_class` for classes not otherwise modifiable
+    pub fn redefine_classes(&mut self, definitions: &[ClassDefinition]) -> Result<(), JvmtiError> {
+        let class_definitions: Vec<rvmti_sys::jvmtiClassDefinition> = definitions.iter()
+            .map(|d| rvmti_sys::jvmtiClassDefinition{klass: d.class.class, class_byte_count: d.class_bytes.len() as rvmti_sys::jint,
+                                                     class_bytes: d.class_bytes.as_ptr()})
+            .collect();
+        unsafe {
+            let result = (*(*self.env)).RedefineClasses.unwrap()(self.env, class_definitions.len() as rvmti_sys::jint,
+                                                                 class_definitions.as_ptr());
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(());
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    // Requires `can_retransform_classes` capability, `can_retransform_any_class` for classes not otherwise modifiable
+    pub fn retransform_classes(&mut self, classes: &[&JClass]) -> Result<(), JvmtiError> {
+        let class_list: Vec<rvmti_sys::jclass> = classes.iter().map(|c| c.class).collect();
+        unsafe {
+            let retransform_classes = version::require((*(*self.env)).RetransformClasses, "RetransformClasses",
+                                                       JvmtiVersion::Version1dot1)?;
+            let result = retransform_classes(self.env, class_list.len() as rvmti_sys::jint, class_list.as_ptr());
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(());
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    // Primitive and array classes are never modifiable, other classes depend on capabilities possessed
+    pub fn is_modifiable_class(&mut self, class: &JClass) -> Result<bool, JvmtiError> {
+        let mut is_modifiable: rvmti_sys::jboolean = 0;
+        unsafe {
+            let is_modifiable_class = version::require((*(*self.env)).IsModifiableClass, "IsModifiableClass",
+                                                       JvmtiVersion::Version1dot1)?;
+            let result = is_modifiable_class(self.env, class.class, &mut is_modifiable);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(is_modifiable != 0);
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    // Copy owned by the VM afterwards, used for buffers the VM deallocates itself such as replacement class data
+    pub(crate) fn allocate_copy(&mut self, bytes: &[u8]) -> Result<*mut c_uchar, JvmtiError> {
+        let mut mem: *mut c_uchar = ptr::null_mut();
+        unsafe {
+            let result = (*(*self.env)).Allocate.unwrap()(self.env, bytes.len() as rvmti_sys::jlong, &mut mem);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                ptr::copy_nonoverlapping(bytes.as_ptr(), mem, bytes.len());
+                return Ok(mem);
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use crate::mock::MockJvmti;
+
+    // Appends a marker to the classes it is asked about, leaves the others alone
+    struct MarkingHandler;
+
+    impl crate::AgentEventHandler for MarkingHandler {
+
+        fn class_file_load_hook(&self, _env: &mut crate::JvmtiEnv, _jni_env: &mut crate::JniEnv,
+                                class_being_redefined: Option<&crate::JClass>, _loader: Option<&crate::JObject>,
+                                name: &Option<String>, _protection_domain: Option<&crate::JObject>,
+                                class_data: &[u8]) -> Option<Vec<u8>>
+        {
+            if class_being_redefined.is_some() && name.as_deref() == Some("com/example/Worker") {
+                let mut bytes = class_data.to_vec();
+                bytes.push(0xff);
+                return Some(bytes);
+            }
+            None
+        }
+
+    }
+
+    #[test]
+    fn test_class_redefinition_and_retransformation() {
+        let mock = MockJvmti::new();
+        let worker = mock.add_class("Lcom/example/Worker;", None);
+        let other = mock.add_class("Lcom/example/Other;", None);
+        let array = mock.add_class("[I", None);
+        let mut env = crate::JvmtiEnv::cons(mock.env_ptr());
+        let jni_env = crate::JniEnv::cons(mock.jni_env_ptr());
+        // Loaded classes are listed in the order they were added
+        let classes = env.get_loaded_classes(&jni_env).unwrap();
+        let (worker_class, other_class, array_class) = (&*classes[0], &*classes[1], &*classes[2]);
+        assert_eq!(array_class.raw(), array);
+        assert!(env.is_modifiable_class(worker_class).unwrap());
+        assert!(!env.is_modifiable_class(array_class).unwrap());
+
+        env.redefine_classes(&[crate::ClassDefinition{class: worker_class, class_bytes: &[0xca, 0xfe]},
+                               crate::ClassDefinition{class: other_class, class_bytes: &[0xba, 0xbe]}]).unwrap();
+        assert_eq!(mock.class_bytes(worker), vec![0xca, 0xfe]);
+
+        // Replacement is allocated through the environment and released by the VM
+        env.set_event_handler(Arc::new(MarkingHandler)).unwrap();
+        env.enable_event(crate::JvmtiEvent::ClassFileLoadHook, None).unwrap();
+        env.retransform_classes(&[worker_class, other_class]).unwrap();
+        assert_eq!(mock.class_bytes(worker), vec![0xca, 0xfe, 0xff]);
+        assert_eq!(mock.class_bytes(other), vec![0xba, 0xbe]);
+        assert!(matches!(env.retransform_classes(&[array_class]), Err(crate::JvmtiError::UnmodifiableClass)));
+        env.clear_event_handler();
+        drop(classes);
+        assert_eq!(mock.outstanding_allocations(), 0);
+        assert_eq!(mock.outstanding_local_refs(), 0);
+    }
+
+}
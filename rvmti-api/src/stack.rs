@@ -0,0 +1,174 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+// Java stack walking, frames are copied out of VM allocated buffers
+
+use std::ptr;
+
+use super::{JvmtiEnv, JniEnv, JThread, JMethodId, JLocation, JvmtiError, Local, ThreadState, VmOwnedArray, raw_thread};
+
+#[derive(Debug)]
+pub struct FrameInfo {
+    pub method: JMethodId,
+    pub location: JLocation,
+}
+
+#[derive(Debug)]
+pub struct ThreadStackTrace {
+    pub state: ThreadState,
+    pub frames: Vec<FrameInfo>,
+}
+
+impl JvmtiEnv {
+
+    // Frames are ordered from the top of the stack, thread is the current thread when not given
+    pub fn get_stack_trace(&mut self, thread: Option<&JThread>, start_depth: i32,
+                           max_frame_count: i32) -> Result<Vec<FrameInfo>, JvmtiError>
+    {
+        unsafe {
+            let mut frame_buffer: Vec<rvmti_sys::jvmtiFrameInfo> = Vec::with_capacity(max_frame_count.max(0) as usize);
+            let mut count: rvmti_sys::jint = 0;
+            let result = (*(*self.env)).GetStackTrace.unwrap()(self.env, raw_thread(thread), start_depth, max_frame_count,
+                                                               frame_buffer.as_mut_ptr(), &mut count);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                frame_buffer.set_len(count.max(0).min(max_frame_count) as usize);
+                return Ok(to_frame_infos(&frame_buffer));
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    pub fn get_all_stack_traces<'a>(&mut self, jni_env: &'a JniEnv,
+                                    max_frame_count: i32) -> Result<Vec<(Local<'a, JThread>, ThreadStackTrace)>, JvmtiError>
+    {
+        unsafe {
+            let mut stack_info_ptr: *mut rvmti_sys::jvmtiStackInfo = ptr::null_mut();
+            let mut thread_count: rvmti_sys::jint = 0;
+            let result = (*(*self.env)).GetAllStackTraces.unwrap()(self.env, max_frame_count, &mut stack_info_ptr,
+                                                                   &mut thread_count);
+            // Frame buffers are part of the same allocation
+            let stack_infos = VmOwnedArray{ptr: stack_info_ptr, count: thread_count, env: self};
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(stack_infos.as_slice().iter()
+                    .filter_map(|info| jni_env.local(info.thread).map(|thread| (thread, to_thread_stack_trace(info))))
+                    .collect());
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    // Stack traces are in the order of given threads
+    pub fn get_thread_list_stack_traces(&mut self, threads: &[&JThread],
+                                        max_frame_count: i32) -> Result<Vec<ThreadStackTrace>, JvmtiError>
+    {
+        unsafe {
+            let thread_list: Vec<rvmti_sys::jthread> = threads.iter().map(|t| t.raw()).collect();
+            let mut stack_info_ptr: *mut rvmti_sys::jvmtiStackInfo = ptr::null_mut();
+            let result = (*(*self.env)).GetThreadListStackTraces.unwrap()(self.env, thread_list.len() as rvmti_sys::jint,
+                                                                          thread_list.as_ptr(), max_frame_count,
+                                                                          &mut stack_info_ptr);
+            let stack_infos = VmOwnedArray{ptr: stack_info_ptr, count: thread_list.len() as rvmti_sys::jint, env: self};
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(stack_infos.as_slice().iter().map(to_thread_stack_trace).collect());
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    pub fn get_frame_count(&mut self, thread: Option<&JThread>) -> Result<i32, JvmtiError> {
+        unsafe {
+            let mut count: rvmti_sys::jint = 0;
+            let result = (*(*self.env)).GetFrameCount.unwrap()(self.env, raw_thread(thread), &mut count);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(count);
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    // Depth zero is the current frame
+    pub fn get_frame_location(&mut self, thread: Option<&JThread>, depth: i32) -> Result<FrameInfo, JvmtiError> {
+        unsafe {
+            let mut method: rvmti_sys::jmethodID = ptr::null_mut();
+            let mut location: rvmti_sys::jlocation = 0;
+            let result = (*(*self.env)).GetFrameLocation.unwrap()(self.env, raw_thread(thread), depth, &mut method, &mut location);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(FrameInfo{method: JMethodId{method}, location});
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+}
+
+fn to_frame_infos(frames: &[rvmti_sys::jvmtiFrameInfo]) -> Vec<FrameInfo> {
+    frames.iter().map(|f| FrameInfo{method: JMethodId{method: f.method}, location: f.location}).collect()
+}
+
+fn to_thread_stack_trace(info: &rvmti_sys::jvmtiStackInfo) -> ThreadStackTrace {
+    let frames = if info.frame_count <= 0 || info.frame_buffer.is_null() {
+        Vec::new()
+    } else {
+        to_frame_infos(unsafe { std::slice::from_raw_parts(info.frame_buffer, info.frame_count as usize) })
+    };
+    ThreadStackTrace{state: ThreadState::from_raw(info.state), frames}
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::mock::MockJvmti;
+
+    #[test]
+    fn test_stack_traces() {
+        let mock = MockJvmti::new();
+        let class = mock.add_class("Lcom/example/Worker;", Some("Worker.java"));
+        let run = mock.add_method(class, "run", "()V", Some(vec![(0, 10), (6, 11)]));
+        let process = mock.add_method(class, "process", "(I)J", Some(vec![(0, 20), (3, 21)]));
+        let native = mock.add_native_method(class, "hash", "(J)I");
+        let main = mock.add_thread("main", None, false);
+        let worker = mock.add_thread("worker", None, true);
+        mock.set_stack(main, &[(native, -1), (process, 3), (run, 6)]);
+        mock.set_stack(worker, &[(run, 0)]);
+        mock.set_current_thread(main);
+        let mut env = crate::JvmtiEnv::cons(mock.env_ptr());
+        let jni_env = crate::JniEnv::cons(mock.jni_env_ptr());
+        let worker_thread = crate::JThread::cons(worker);
+        let locations = |frames: &[crate::FrameInfo]| -> Vec<(usize, i64)> {
+            frames.iter().map(|f| (f.method.raw() as usize, f.location)).collect()
+        };
+        let frames = env.get_stack_trace(None, 0, 10).unwrap();
+        assert_eq!(locations(&frames), vec![(native as usize, -1), (process as usize, 3), (run as usize, 6)]);
+        let frames = env.get_stack_trace(None, 1, 1).unwrap();
+        assert_eq!(locations(&frames), vec![(process as usize, 3)]);
+        assert_eq!(env.get_frame_count(Some(&worker_thread)).unwrap(), 1);
+        let frame = env.get_frame_location(None, 2).unwrap();
+        assert_eq!((frame.method.raw() as usize, frame.location), (run as usize, 6));
+        assert!(env.get_frame_location(None, 3).is_err());
+
+        // Thread references are local and deleted as soon as traces are dropped
+        let all: Vec<(usize, Vec<(usize, i64)>)> = env.get_all_stack_traces(&jni_env, 2).unwrap().iter()
+            .map(|(t, s)| (t.raw() as usize, locations(&s.frames)))
+            .collect();
+        assert_eq!(all, vec![(main as usize, vec![(native as usize, -1), (process as usize, 3)]),
+                             (worker as usize, vec![(run as usize, 0)])]);
+        let list = env.get_thread_list_stack_traces(&[&worker_thread], 10).unwrap();
+        assert_eq!(list.len(), 1);
+        assert!(list[0].state.is_alive());
+        assert_eq!(locations(&list[0].frames), vec![(run as usize, 0)]);
+        drop(list);
+        assert_eq!(mock.outstanding_allocations(), 0);
+        assert_eq!(mock.outstanding_local_refs(), 0);
+    }
+
+}
@@ -0,0 +1,272 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+// Environment and thread local storage. JVMTI keeps one raw pointer per environment and per thread. Pointer of the
+// environment holds everything this crate keeps per environment, event handler and callback table next to the stored
+// value, so events reach their handler and agent state without a global lock. Pointers are created once under a lock,
+// so concurrent setters do not both allocate, and are never freed, so readers racing with writers or with disposal on
+// other threads never see a dangling pointer. Disposal drops the stored value and the handler but leaks the data of
+// the environment, and every thread that ever stored a value leaks its slot.
+
+use std::any::Any;
+use std::os::raw::c_void;
+use std::ptr;
+use std::sync::{Arc, Mutex, RwLock};
+
+use log::warn;
+use thiserror::Error;
+
+use super::{JvmtiEnv, JThread, JvmtiError, JvmtiEventCallbacksSettings, raw_thread};
+use super::events::EnvironmentEvents;
+
+type StorageSlot = RwLock<Option<Arc<dyn Any + Send + Sync>>>;
+
+pub(crate) struct EnvironmentData {
+    storage: StorageSlot,
+    pub(crate) events: RwLock<EnvironmentEvents>,
+}
+
+// Held from reading a missing pointer until the new one is set
+static SLOT_CREATION: Mutex<()> = Mutex::new(());
+
+#[derive(Error, Debug)]
+pub enum LocalStorageError {
+    #[error("JVMTI method call error: {0}")]
+    VmError(#[source] JvmtiError),
+    #[error("Local storage holds a value of another type")]
+    TypeMismatch,
+}
+
+impl JvmtiEnv {
+
+    // Replaces previously stored value, it is dropped once the last reader releases it
+    pub fn set_environment_local_storage<T: Any + Send + Sync>(&mut self, value: T) -> Result<(), LocalStorageError> {
+        let data = self.environment_data_or_create().map_err(LocalStorageError::VmError)?;
+        store(unsafe { &(*data).storage }, Arc::new(value));
+        Ok(())
+    }
+
+    pub fn get_environment_local_storage<T: Any + Send + Sync>(&mut self) -> Result<Option<Arc<T>>, LocalStorageError> {
+        match self.environment_data().map_err(LocalStorageError::VmError)? {
+            Some(data) => load(unsafe { &(*data).storage }),
+            None => Ok(None),
+        }
+    }
+
+    // Value of another type is left in place
+    pub fn take_environment_local_storage<T: Any + Send + Sync>(&mut self) -> Result<Option<Arc<T>>, LocalStorageError> {
+        match self.environment_data().map_err(LocalStorageError::VmError)? {
+            Some(data) => take(unsafe { &(*data).storage }),
+            None => Ok(None),
+        }
+    }
+
+    // Thread is the current thread when not given
+    pub fn set_thread_local_storage<T: Any + Send + Sync>(&mut self, thread: Option<&JThread>,
+                                                            value: T) -> Result<(), LocalStorageError>
+    {
+        let slot = {
+            let _creation = SLOT_CREATION.lock().unwrap_or_else(|e| e.into_inner());
+            match self.thread_storage_slot(thread)? {
+                Some(slot) => slot,
+                None => {
+                    let slot = Box::into_raw(Box::new(StorageSlot::new(None)));
+                    if let Err(e) = self.set_thread_storage_slot(thread, slot) {
+                        unsafe {
+                            drop(Box::from_raw(slot));
+                        }
+                        return Err(e);
+                    }
+                    slot
+                },
+            }
+        };
+        store(unsafe { &*slot }, Arc::new(value));
+        Ok(())
+    }
+
+    pub fn get_thread_local_storage<T: Any + Send + Sync>(&mut self,
+                                                            thread: Option<&JThread>) -> Result<Option<Arc<T>>, LocalStorageError>
+    {
+        match self.thread_storage_slot(thread)? {
+            Some(slot) => load(unsafe { &*slot }),
+            None => Ok(None),
+        }
+    }
+
+    // Should be called on thread end, VM does not release thread local values. Slot of the thread stays allocated,
+    // as readers on other threads may still hold it, so it leaks once the thread ends
+    pub fn take_thread_local_storage<T: Any + Send + Sync>(&mut self,
+                                                             thread: Option<&JThread>) -> Result<Option<Arc<T>>, LocalStorageError>
+    {
+        match self.thread_storage_slot(thread)? {
+            Some(slot) => take(unsafe { &*slot }),
+            None => Ok(None),
+        }
+    }
+
+    // Environment is being disposed of. Callbacks are removed so no new events arrive, events already being
+    // dispatched on other threads may still read the data, so it is leaked and only its contents are dropped
+    pub(crate) fn release_environment_data(&mut self) {
+        let _creation = SLOT_CREATION.lock().unwrap_or_else(|e| e.into_inner());
+        if let Ok(Some(data)) = self.environment_data() {
+            let settings = JvmtiEventCallbacksSettings::new_empty_settings();
+            if let Err(e) = self.set_event_callbacks_settings(&settings) {
+                warn!("Failed to remove event callbacks: {}", e);
+            }
+            unsafe {
+                *(*data).events.write().unwrap_or_else(|e| e.into_inner()) = EnvironmentEvents::new();
+                drop(take_value(&(*data).storage));
+            }
+        }
+    }
+
+    pub(crate) fn environment_data_or_create(&mut self) -> Result<*mut EnvironmentData, JvmtiError> {
+        let _creation = SLOT_CREATION.lock().unwrap_or_else(|e| e.into_inner());
+        match self.environment_data()? {
+            Some(data) => Ok(data),
+            None => {
+                let data = Box::into_raw(Box::new(EnvironmentData{storage: StorageSlot::new(None),
+                    events: RwLock::new(EnvironmentEvents::new())}));
+                if let Err(e) = self.set_environment_data(data) {
+                    unsafe {
+                        drop(Box::from_raw(data));
+                    }
+                    return Err(e);
+                }
+                Ok(data)
+            },
+        }
+    }
+
+    pub(crate) fn environment_data(&mut self) -> Result<Option<*mut EnvironmentData>, JvmtiError> {
+        unsafe {
+            let mut data: *mut c_void = ptr::null_mut();
+            let result = (*(*self.env)).GetEnvironmentLocalStorage.unwrap()(self.env, &mut data);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(if data.is_null() { None } else { Some(data as *mut EnvironmentData) });
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    fn set_environment_data(&mut self, data: *mut EnvironmentData) -> Result<(), JvmtiError> {
+        unsafe {
+            let result = (*(*self.env)).SetEnvironmentLocalStorage.unwrap()(self.env, data as *const c_void);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(());
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    fn thread_storage_slot(&mut self, thread: Option<&JThread>) -> Result<Option<*mut StorageSlot>, LocalStorageError> {
+        unsafe {
+            let mut data: *mut c_void = ptr::null_mut();
+            let result = (*(*self.env)).GetThreadLocalStorage.unwrap()(self.env, raw_thread(thread), &mut data);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(if data.is_null() { None } else { Some(data as *mut StorageSlot) });
+            } else {
+                return Err(LocalStorageError::VmError(JvmtiError::from(result)));
+            }
+        }
+    }
+
+    fn set_thread_storage_slot(&mut self, thread: Option<&JThread>, slot: *mut StorageSlot) -> Result<(), LocalStorageError> {
+        unsafe {
+            let result = (*(*self.env)).SetThreadLocalStorage.unwrap()(self.env, raw_thread(thread), slot as *const c_void);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(());
+            } else {
+                return Err(LocalStorageError::VmError(JvmtiError::from(result)));
+            }
+        }
+    }
+
+}
+
+fn store(slot: &StorageSlot, value: Arc<dyn Any + Send + Sync>) {
+    *slot.write().unwrap_or_else(|e| e.into_inner()) = Some(value);
+}
+
+fn load<T: Any + Send + Sync>(slot: &StorageSlot) -> Result<Option<Arc<T>>, LocalStorageError> {
+    let value = slot.read().unwrap_or_else(|e| e.into_inner()).clone();
+    match value {
+        Some(value) => value.downcast::<T>().map(Some).map_err(|_| LocalStorageError::TypeMismatch),
+        None => Ok(None),
+    }
+}
+
+fn take_value(slot: &StorageSlot) -> Option<Arc<dyn Any + Send + Sync>> {
+    slot.write().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+fn take<T: Any + Send + Sync>(slot: &StorageSlot) -> Result<Option<Arc<T>>, LocalStorageError> {
+    let mut guard = slot.write().unwrap_or_else(|e| e.into_inner());
+    match guard.as_ref() {
+        Some(value) if !value.is::<T>() => Err(LocalStorageError::TypeMismatch),
+        _ => Ok(guard.take().map(|value| value.downcast::<T>().unwrap())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use crate::mock::MockJvmti;
+
+    #[test]
+    fn test_local_storage() {
+        let mock = MockJvmti::new();
+        let other_mock = MockJvmti::new();
+        let mut env = crate::JvmtiEnv::cons(mock.env_ptr());
+        let mut other_env = crate::JvmtiEnv::cons(other_mock.env_ptr());
+        assert!(env.get_environment_local_storage::<String>().unwrap().is_none());
+        env.set_environment_local_storage("first".to_string()).unwrap();
+        let first = env.get_environment_local_storage::<String>().unwrap().unwrap();
+        env.set_environment_local_storage("second".to_string()).unwrap();
+        // Readers keep replaced value alive
+        assert_eq!(*first, "first");
+        assert_eq!(*env.get_environment_local_storage::<String>().unwrap().unwrap(), "second");
+        assert!(other_env.get_environment_local_storage::<String>().unwrap().is_none());
+        match env.get_environment_local_storage::<u32>() {
+            Err(crate::LocalStorageError::TypeMismatch) => {},
+            other => panic!("Unexpected result {:?}", other),
+        }
+        assert!(env.take_environment_local_storage::<u32>().is_err());
+        assert_eq!(*env.take_environment_local_storage::<String>().unwrap().unwrap(), "second");
+        assert!(env.get_environment_local_storage::<String>().unwrap().is_none());
+        let thread = crate::JThread::cons(0x2000 as rvmti_sys::jthread);
+        env.set_thread_local_storage(None, 1u64).unwrap();
+        env.set_thread_local_storage(Some(&thread), 2u64).unwrap();
+        assert_eq!(*env.get_thread_local_storage::<u64>(None).unwrap().unwrap(), 1);
+        assert_eq!(*env.take_thread_local_storage::<u64>(Some(&thread)).unwrap().unwrap(), 2);
+        assert!(env.get_thread_local_storage::<u64>(Some(&thread)).unwrap().is_none());
+        assert_eq!(*env.get_thread_local_storage::<u64>(None).unwrap().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_dispose_environment() {
+        let mock = MockJvmti::new();
+        let vm = crate::Jvm::cons(mock.vm_ptr());
+        let mut env = vm.get_jvmti_env(crate::JvmtiVersion::CurrentVersion).unwrap();
+        let value = Arc::new(1u64);
+        env.set_environment_local_storage(value.clone()).unwrap();
+        env.enable_event(crate::JvmtiEvent::VmInit, None).unwrap();
+        assert!(mock.callbacks().is_some());
+        drop(env);
+        // Value is dropped and no callback is left to reach the leaked data
+        assert_eq!(Arc::strong_count(&value), 1);
+        let callbacks = mock.callbacks().unwrap();
+        assert!(callbacks.VMInit.is_none());
+    }
+
+}
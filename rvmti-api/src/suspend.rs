@@ -0,0 +1,317 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+// Thread suspension, requires `can_suspend` capability. Threads suspended through `SuspendedThreads` are resumed when
+// it is dropped, also while unwinding from a panic, so an agent can not leave application threads stopped by mistake.
+
+use std::mem;
+use std::os::raw::c_void;
+
+use log::warn;
+use thiserror::Error;
+
+use super::{JvmtiEnv, JniEnv, JThread, JObject, JvmtiError, JvmtiVersion, Local, OwnedMonitor, ThreadStackTrace, raw_thread,
+            version};
+
+// SuspendAllVirtualThreads and ResumeAllVirtualThreads were added in JVMTI 21 as function table entries 118 and 119,
+// headers of older versions declare them reserved, so the slots are read by position
+const SUSPEND_ALL_VIRTUAL_THREADS_SLOT: usize = 118;
+const RESUME_ALL_VIRTUAL_THREADS_SLOT: usize = 119;
+
+type AllVirtualThreadsFunction = unsafe extern "C" fn(*mut rvmti_sys::jvmtiEnv, rvmti_sys::jint,
+                                                      *const rvmti_sys::jthread) -> rvmti_sys::jvmtiError;
+
+// Threads suspended by this environment, resumed on drop
+#[derive(Debug)]
+pub struct SuspendedThreads<'t> {
+    env: *mut rvmti_sys::jvmtiEnv,
+    threads: Vec<&'t JThread>,
+}
+
+// State of a thread captured while it was suspended, monitors are empty for threads not alive
+#[derive(Debug)]
+pub struct ThreadSnapshot<'a> {
+    pub stack: ThreadStackTrace,
+    pub owned_monitors: Vec<OwnedMonitor<'a>>,
+    pub contended_monitor: Option<Local<'a, JObject>>,
+}
+
+#[derive(Error, Debug)]
+pub enum SuspendError {
+    #[error("JVMTI method call error: {0}")]
+    VmError(#[source] JvmtiError),
+    #[error("Failed to get current thread: {0}")]
+    CurrentThreadError(#[source] JvmtiError),
+    #[error("Failed to suspend thread {index}: {source}")]
+    SuspendThreadError { index: usize, #[source] source: JvmtiError },
+    #[error("Failed to get stack traces: {0}")]
+    StackTraceError(#[source] JvmtiError),
+    #[error("Failed to get monitors of thread {index}: {source}")]
+    MonitorInfoError { index: usize, #[source] source: JvmtiError },
+}
+
+impl JvmtiEnv {
+
+    // Thread is the current thread when not given, current thread stays suspended until another thread resumes it
+    pub fn suspend_thread(&mut self, thread: Option<&JThread>) -> Result<(), JvmtiError> {
+        unsafe {
+            let result = (*(*self.env)).SuspendThread.unwrap()(self.env, raw_thread(thread));
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(());
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    pub fn resume_thread(&mut self, thread: &JThread) -> Result<(), JvmtiError> {
+        unsafe {
+            let result = (*(*self.env)).ResumeThread.unwrap()(self.env, thread.raw());
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(());
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    // Outer error is about the call itself, inner ones are per thread in the order of given threads
+    pub fn suspend_thread_list(&mut self, threads: &[&JThread]) -> Result<Vec<Result<(), JvmtiError>>, JvmtiError> {
+        unsafe {
+            let thread_list: Vec<rvmti_sys::jthread> = threads.iter().map(|t| t.raw()).collect();
+            let mut results: Vec<rvmti_sys::jvmtiError> = vec![rvmti_sys::jvmtiError_JVMTI_ERROR_NONE; thread_list.len()];
+            let result = (*(*self.env)).SuspendThreadList.unwrap()(self.env, thread_list.len() as rvmti_sys::jint,
+                                                                   thread_list.as_ptr(), results.as_mut_ptr());
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(to_thread_results(&results));
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    pub fn resume_thread_list(&mut self, threads: &[&JThread]) -> Result<Vec<Result<(), JvmtiError>>, JvmtiError> {
+        unsafe {
+            let thread_list: Vec<rvmti_sys::jthread> = threads.iter().map(|t| t.raw()).collect();
+            let mut results: Vec<rvmti_sys::jvmtiError> = vec![rvmti_sys::jvmtiError_JVMTI_ERROR_NONE; thread_list.len()];
+            let result = (*(*self.env)).ResumeThreadList.unwrap()(self.env, thread_list.len() as rvmti_sys::jint,
+                                                                  thread_list.as_ptr(), results.as_mut_ptr());
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(to_thread_results(&results));
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    // Requires `can_support_virtual_threads` capability as well, which bundled JDK 17 bindings can not request yet
+    pub fn suspend_all_virtual_threads(&mut self, except: &[&JThread]) -> Result<(), JvmtiError> {
+        unsafe {
+            let suspend_all = version::require(self.function_slot(SUSPEND_ALL_VIRTUAL_THREADS_SLOT),
+                                               "SuspendAllVirtualThreads", JvmtiVersion::Version21)?;
+            let except_list: Vec<rvmti_sys::jthread> = except.iter().map(|t| t.raw()).collect();
+            let result = suspend_all(self.env, except_list.len() as rvmti_sys::jint, except_list.as_ptr());
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(());
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    pub fn resume_all_virtual_threads(&mut self, except: &[&JThread]) -> Result<(), JvmtiError> {
+        unsafe {
+            let resume_all = version::require(self.function_slot(RESUME_ALL_VIRTUAL_THREADS_SLOT),
+                                              "ResumeAllVirtualThreads", JvmtiVersion::Version21)?;
+            let except_list: Vec<rvmti_sys::jthread> = except.iter().map(|t| t.raw()).collect();
+            let result = resume_all(self.env, except_list.len() as rvmti_sys::jint, except_list.as_ptr());
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(());
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    // Threads no longer alive or already suspended by someone else are left out of the guard, on any other failure
+    // threads suspended so far are resumed before returning. Current thread must not be among the given ones
+    pub fn suspend_threads<'t>(&mut self, threads: &[&'t JThread]) -> Result<SuspendedThreads<'t>, SuspendError> {
+        let mut suspended = SuspendedThreads{env: self.env, threads: Vec::with_capacity(threads.len())};
+        if threads.is_empty() {
+            return Ok(suspended);
+        }
+        let results = self.suspend_thread_list(threads).map_err(SuspendError::VmError)?;
+        let mut failure = None;
+        for (index, (thread, result)) in threads.iter().zip(results).enumerate() {
+            match result {
+                Ok(()) => suspended.threads.push(*thread),
+                Err(JvmtiError::ThreadNotAlive) | Err(JvmtiError::ThreadSuspended) => {},
+                Err(e) => if failure.is_none() {
+                    failure = Some(SuspendError::SuspendThreadError{index, source: e});
+                },
+            }
+        }
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(suspended),
+        }
+    }
+
+    // Suspends the threads, captures their stacks and monitors and passes them to `f` in the order of given threads.
+    // Threads are resumed once `f` returns or panics. The current thread is never suspended, it is captured as is
+    pub fn with_threads_suspended<'a, F, R>(&mut self, jni_env: &'a JniEnv, threads: &[&JThread], max_frame_count: i32,
+                                            f: F) -> Result<R, SuspendError>
+        where F: FnOnce(&[ThreadSnapshot<'a>]) -> R
+    {
+        let current = self.get_current_thread(jni_env).map_err(SuspendError::CurrentThreadError)?;
+        let is_current = |thread: &JThread| current.as_ref().is_some_and(|c| jni_env.is_same_object(&**c, thread));
+        let (indices, others): (Vec<usize>, Vec<&JThread>) = threads.iter().copied().enumerate()
+            .filter(|(_, t)| !is_current(t))
+            .unzip();
+        let suspended = self.suspend_threads(&others)
+            .map_err(|e| match e {
+                SuspendError::SuspendThreadError{index, source} => SuspendError::SuspendThreadError{index: indices[index], source},
+                other => other,
+            })?;
+        let stacks = self.get_thread_list_stack_traces(threads, max_frame_count).map_err(SuspendError::StackTraceError)?;
+        let mut snapshots = Vec::with_capacity(stacks.len());
+        for (index, (thread, stack)) in threads.iter().zip(stacks).enumerate() {
+            let (owned_monitors, contended_monitor) = if stack.state.is_alive() {
+                let owned = self.get_owned_monitor_stack_depth_info(jni_env, Some(thread))
+                    .map_err(|e| SuspendError::MonitorInfoError{index, source: e})?;
+                let contended = self.get_current_contended_monitor(jni_env, Some(thread))
+                    .map_err(|e| SuspendError::MonitorInfoError{index, source: e})?;
+                (owned, contended)
+            } else {
+                (Vec::new(), None)
+            };
+            snapshots.push(ThreadSnapshot{stack, owned_monitors, contended_monitor});
+        }
+        let result = f(&snapshots);
+        drop(suspended);
+        Ok(result)
+    }
+
+    unsafe fn function_slot(&self, slot: usize) -> Option<AllVirtualThreadsFunction> {
+        // Function table is a sequence of pointers, entry numbers start from one
+        let table = *self.env as *const *mut c_void;
+        let function = *table.add(slot - 1);
+        if function.is_null() {
+            None
+        } else {
+            Some(mem::transmute::<*mut c_void, AllVirtualThreadsFunction>(function))
+        }
+    }
+
+}
+
+impl<'t> SuspendedThreads<'t> {
+
+    pub fn threads(&self) -> &[&'t JThread] {
+        &self.threads
+    }
+
+}
+
+impl<'t> Drop for SuspendedThreads<'t> {
+
+    fn drop(&mut self) {
+        if self.threads.is_empty() {
+            return;
+        }
+        let mut env = JvmtiEnv::cons(self.env);
+        match env.resume_thread_list(&self.threads) {
+            Ok(results) => for (thread, result) in self.threads.iter().zip(results) {
+                if let Err(e) = result {
+                    warn!("Failed to resume thread {:?}: {}", thread.raw(), e);
+                }
+            },
+            Err(e) => warn!("Failed to resume threads: {}", e),
+        }
+    }
+
+}
+
+fn to_thread_results(results: &[rvmti_sys::jvmtiError]) -> Vec<Result<(), JvmtiError>> {
+    results.iter().map(|&r| if r == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE { Ok(()) } else { Err(JvmtiError::from(r)) }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::mem::{offset_of, size_of};
+    use std::os::raw::c_void;
+
+    use crate::mock::MockJvmti;
+
+    // Entries around the virtual thread ones are present in headers of every version
+    #[test]
+    fn test_virtual_thread_function_slots() {
+        let entry_size = size_of::<*mut c_void>();
+        assert_eq!(offset_of!(rvmti_sys::jvmtiInterface_1_, IterateThroughHeap) / entry_size + 1,
+                   super::SUSPEND_ALL_VIRTUAL_THREADS_SLOT - 2);
+        assert_eq!(offset_of!(rvmti_sys::jvmtiInterface_1_, SetJNIFunctionTable) / entry_size + 1,
+                   super::RESUME_ALL_VIRTUAL_THREADS_SLOT + 1);
+    }
+
+    #[test]
+    fn test_threads_suspended_for_snapshot() {
+        let mock = MockJvmti::new();
+        let class = mock.add_class("Lcom/example/Worker;", Some("Worker.java"));
+        let run = mock.add_method(class, "run", "()V", None);
+        let main = mock.add_thread("main", None, false);
+        let worker = mock.add_thread("worker", None, false);
+        let finished = mock.add_thread("finished", None, false);
+        let lock = mock.add_object();
+        let queue = mock.add_object();
+        mock.set_stack(main, &[(run, 4)]);
+        mock.set_stack(worker, &[(run, 0)]);
+        mock.set_monitors(worker, &[(lock, 0)], Some(queue));
+        mock.terminate_thread(finished);
+        mock.set_current_thread(main);
+        let mut env = crate::JvmtiEnv::cons(mock.env_ptr());
+        let jni_env = crate::JniEnv::cons(mock.jni_env_ptr());
+        let threads: Vec<crate::JThread> = [main, worker, finished].iter().map(|&t| crate::JThread::cons(t)).collect();
+        let thread_refs: Vec<&crate::JThread> = threads.iter().collect();
+
+        // Current thread is captured without being suspended, terminated one has nothing to suspend
+        let captured = env.with_threads_suspended(&jni_env, &thread_refs, 10, |snapshots| {
+            assert!(!mock.is_suspended(main));
+            assert!(mock.is_suspended(worker));
+            snapshots.iter()
+                .map(|s| (s.stack.state.is_suspended(), s.stack.frames.len(),
+                          s.owned_monitors.iter().map(|m| (m.monitor.raw() as usize, m.stack_depth)).collect::<Vec<_>>(),
+                          s.contended_monitor.as_ref().map(|m| m.raw() as usize)))
+                .collect::<Vec<_>>()
+        }).unwrap();
+        assert_eq!(captured, vec![(false, 1, vec![], None), (true, 1, vec![(lock as usize, 0)], Some(queue as usize)),
+                                  (false, 0, vec![], None)]);
+        assert!(!mock.is_suspended(worker));
+
+        // Threads are resumed while unwinding
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            env.with_threads_suspended(&jni_env, &thread_refs[1..2], 10, |_| panic!("snapshot failed")).unwrap();
+        }));
+        assert!(panicked.is_err());
+        assert!(!mock.is_suspended(worker));
+
+        // Threads suspended elsewhere are left suspended
+        env.suspend_thread(Some(&threads[1])).unwrap();
+        let guard = env.suspend_threads(&thread_refs[1..2]).unwrap();
+        assert!(guard.threads().is_empty());
+        drop(guard);
+        assert!(mock.is_suspended(worker));
+        env.resume_thread(&threads[1]).unwrap();
+        assert!(matches!(env.resume_thread(&threads[1]), Err(crate::JvmtiError::ThreadNotSuspended)));
+
+        assert!(matches!(env.suspend_all_virtual_threads(&[]), Err(crate::JvmtiError::UnsupportedJvmtiVersion{..})));
+        assert_eq!(mock.outstanding_allocations(), 0);
+        assert_eq!(mock.outstanding_local_refs(), 0);
+    }
+
+}
@@ -0,0 +1,252 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+// Thread enumeration and inspection, references returned by JVMTI are local and owned by the caller
+
+use std::ptr;
+
+use thiserror::Error;
+
+use super::{JvmtiEnv, JniEnv, JThread, JThreadGroup, JObject, JvmtiError, JvmtiVersion, Local, StringDecodeError, VmOwnedString,
+            VmOwnedArray, raw_thread, version};
+
+#[derive(Debug)]
+pub struct ThreadInfo<'a> {
+    pub name: String,
+    pub priority: i32,
+    pub is_daemon: bool,
+    pub thread_group: Option<Local<'a, JThreadGroup>>,
+    pub context_class_loader: Option<Local<'a, JObject>>,
+}
+
+// Stack depth is -1 for monitors entered by native code with JNI MonitorEnter
+#[derive(Debug)]
+pub struct OwnedMonitor<'a> {
+    pub monitor: Local<'a, JObject>,
+    pub stack_depth: i32,
+}
+
+#[derive(Debug)]
+pub struct ThreadGroupInfo<'a> {
+    pub parent: Option<Local<'a, JThreadGroup>>,
+    pub name: String,
+    pub max_priority: i32,
+    pub is_daemon: bool,
+}
+
+// Bit set as returned by GetThreadState
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThreadState {
+    state: u32,
+}
+
+// Same states as java.lang.Thread.State
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JavaThreadState {
+    New,
+    Runnable,
+    Blocked,
+    Waiting,
+    TimedWaiting,
+    Terminated,
+}
+
+#[derive(Error, Debug)]
+pub enum GetThreadInfoError {
+    #[error("JVMTI method call error: {0}")]
+    VmError(#[source] JvmtiError),
+    #[error("Failed to decode thread name: {0}")]
+    NameDecodeError(#[source] StringDecodeError),
+}
+
+impl JvmtiEnv {
+
+    pub fn get_all_threads<'a>(&mut self, jni_env: &'a JniEnv) -> Result<Vec<Local<'a, JThread>>, JvmtiError> {
+        unsafe {
+            let mut threads_count: rvmti_sys::jint = 0;
+            let mut threads_ptr: *mut rvmti_sys::jthread = ptr::null_mut();
+            let result = (*(*self.env)).GetAllThreads.unwrap()(self.env, &mut threads_count, &mut threads_ptr);
+            let threads = VmOwnedArray{ptr: threads_ptr, count: threads_count, env: self};
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(threads.as_slice().iter().filter_map(|t| jni_env.local(*t)).collect());
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    // None when called from a thread not attached as a Java thread
+    pub fn get_current_thread<'a>(&mut self, jni_env: &'a JniEnv) -> Result<Option<Local<'a, JThread>>, JvmtiError> {
+        unsafe {
+            let get_current_thread = version::require((*(*self.env)).GetCurrentThread, "GetCurrentThread",
+                                                      JvmtiVersion::Version1dot1)?;
+            let mut thread: rvmti_sys::jthread = ptr::null_mut();
+            let result = get_current_thread(self.env, &mut thread);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(jni_env.local(thread));
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    // Thread is the current thread when not given
+    pub fn get_thread_info<'a>(&mut self, jni_env: &'a JniEnv,
+                               thread: Option<&JThread>) -> Result<ThreadInfo<'a>, GetThreadInfoError>
+    {
+        unsafe {
+            let mut info: rvmti_sys::jvmtiThreadInfo = std::mem::zeroed();
+            let result = (*(*self.env)).GetThreadInfo.unwrap()(self.env, raw_thread(thread), &mut info);
+            let name = info.name.as_ref().map(|v| VmOwnedString {ptr: v, env: self});
+            let thread_group = jni_env.local(info.thread_group);
+            let context_class_loader = jni_env.local(info.context_class_loader);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                let name_string = name.as_ref().map_or_else(|| Ok(None), |s| s.to_string())
+                    .map_err(GetThreadInfoError::NameDecodeError)?;
+                return Ok(ThreadInfo{name: name_string.unwrap_or_default(), priority: info.priority, is_daemon: info.is_daemon != 0,
+                                     thread_group, context_class_loader});
+            } else {
+                return Err(GetThreadInfoError::VmError(JvmtiError::from(result)));
+            }
+        }
+    }
+
+    pub fn get_thread_group_info<'a>(&mut self, jni_env: &'a JniEnv,
+                                     group: &JThreadGroup) -> Result<ThreadGroupInfo<'a>, GetThreadInfoError>
+    {
+        unsafe {
+            let mut info: rvmti_sys::jvmtiThreadGroupInfo = std::mem::zeroed();
+            let result = (*(*self.env)).GetThreadGroupInfo.unwrap()(self.env, group.group, &mut info);
+            let name = info.name.as_ref().map(|v| VmOwnedString {ptr: v, env: self});
+            let parent = jni_env.local(info.parent);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                let name_string = name.as_ref().map_or_else(|| Ok(None), |s| s.to_string())
+                    .map_err(GetThreadInfoError::NameDecodeError)?;
+                return Ok(ThreadGroupInfo{parent, name: name_string.unwrap_or_default(), max_priority: info.max_priority,
+                                          is_daemon: info.is_daemon != 0});
+            } else {
+                return Err(GetThreadInfoError::VmError(JvmtiError::from(result)));
+            }
+        }
+    }
+
+    // Thread must be suspended or the current one, requires `can_get_owned_monitor_stack_depth_info` capability
+    pub fn get_owned_monitor_stack_depth_info<'a>(&mut self, jni_env: &'a JniEnv,
+                                                  thread: Option<&JThread>) -> Result<Vec<OwnedMonitor<'a>>, JvmtiError>
+    {
+        unsafe {
+            let mut monitor_count: rvmti_sys::jint = 0;
+            let mut monitors_ptr: *mut rvmti_sys::jvmtiMonitorStackDepthInfo = ptr::null_mut();
+            let result = (*(*self.env)).GetOwnedMonitorStackDepthInfo.unwrap()(self.env, raw_thread(thread), &mut monitor_count,
+                                                                               &mut monitors_ptr);
+            let monitors = VmOwnedArray{ptr: monitors_ptr, count: monitor_count, env: self};
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(monitors.as_slice().iter()
+                    .filter_map(|m| jni_env.local(m.monitor).map(|monitor| OwnedMonitor{monitor, stack_depth: m.stack_depth}))
+                    .collect());
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    // Monitor the thread is blocked on entering or waiting for, requires `can_get_current_contended_monitor` capability
+    pub fn get_current_contended_monitor<'a>(&mut self, jni_env: &'a JniEnv,
+                                             thread: Option<&JThread>) -> Result<Option<Local<'a, JObject>>, JvmtiError>
+    {
+        unsafe {
+            let mut monitor: rvmti_sys::jobject = ptr::null_mut();
+            let result = (*(*self.env)).GetCurrentContendedMonitor.unwrap()(self.env, raw_thread(thread), &mut monitor);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(jni_env.local(monitor));
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    pub fn get_thread_state(&mut self, thread: Option<&JThread>) -> Result<ThreadState, JvmtiError> {
+        unsafe {
+            let mut state: rvmti_sys::jint = 0;
+            let result = (*(*self.env)).GetThreadState.unwrap()(self.env, raw_thread(thread), &mut state);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(ThreadState{state: state as u32});
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+}
+
+impl ThreadState {
+
+    pub fn from_raw(state: rvmti_sys::jint) -> ThreadState {
+        ThreadState{state: state as u32}
+    }
+
+    pub fn raw(&self) -> rvmti_sys::jint {
+        self.state as rvmti_sys::jint
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.has(rvmti_sys::JVMTI_THREAD_STATE_ALIVE)
+    }
+
+    pub fn is_terminated(&self) -> bool {
+        self.has(rvmti_sys::JVMTI_THREAD_STATE_TERMINATED)
+    }
+
+    pub fn is_runnable(&self) -> bool {
+        self.has(rvmti_sys::JVMTI_THREAD_STATE_RUNNABLE)
+    }
+
+    pub fn is_blocked_on_monitor_enter(&self) -> bool {
+        self.has(rvmti_sys::JVMTI_THREAD_STATE_BLOCKED_ON_MONITOR_ENTER)
+    }
+
+    pub fn is_waiting(&self) -> bool {
+        self.has(rvmti_sys::JVMTI_THREAD_STATE_WAITING)
+    }
+
+    pub fn is_sleeping(&self) -> bool {
+        self.has(rvmti_sys::JVMTI_THREAD_STATE_SLEEPING)
+    }
+
+    pub fn is_parked(&self) -> bool {
+        self.has(rvmti_sys::JVMTI_THREAD_STATE_PARKED)
+    }
+
+    pub fn is_suspended(&self) -> bool {
+        self.has(rvmti_sys::JVMTI_THREAD_STATE_SUSPENDED)
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        self.has(rvmti_sys::JVMTI_THREAD_STATE_INTERRUPTED)
+    }
+
+    pub fn is_in_native(&self) -> bool {
+        self.has(rvmti_sys::JVMTI_THREAD_STATE_IN_NATIVE)
+    }
+
+    pub fn java_state(&self) -> JavaThreadState {
+        match self.state & rvmti_sys::JVMTI_JAVA_LANG_THREAD_STATE_MASK {
+            rvmti_sys::JVMTI_JAVA_LANG_THREAD_STATE_NEW => JavaThreadState::New,
+            rvmti_sys::JVMTI_JAVA_LANG_THREAD_STATE_TERMINATED => JavaThreadState::Terminated,
+            rvmti_sys::JVMTI_JAVA_LANG_THREAD_STATE_BLOCKED => JavaThreadState::Blocked,
+            rvmti_sys::JVMTI_JAVA_LANG_THREAD_STATE_WAITING => JavaThreadState::Waiting,
+            rvmti_sys::JVMTI_JAVA_LANG_THREAD_STATE_TIMED_WAITING => JavaThreadState::TimedWaiting,
+            _ => JavaThreadState::Runnable,
+        }
+    }
+
+    fn has(&self, flag: u32) -> bool {
+        self.state & flag == flag
+    }
+
+}
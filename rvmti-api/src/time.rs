@@ -0,0 +1,172 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+
+// Timers and thread CPU time. Values are nanoseconds counted from an arbitrary origin, only differences between
+// readings of the same timer are meaningful. CPU time wrappers require `can_get_current_thread_cpu_time` or
+// `can_get_thread_cpu_time` capabilities.
+
+use std::time::Duration;
+
+use super::{JvmtiEnv, JThread, JvmtiError, raw_thread};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerKind {
+    UserCpu,
+    TotalCpu,
+    Elapsed,
+    Unknown(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimerInfo {
+    // Timer wraps around to zero after reaching this value
+    pub max_value: Duration,
+    pub may_skip_forward: bool,
+    pub may_skip_backward: bool,
+    pub kind: TimerKind,
+}
+
+impl TimerKind {
+
+    fn from_raw(kind: rvmti_sys::jvmtiTimerKind) -> TimerKind {
+        match kind {
+            rvmti_sys::jvmtiTimerKind_JVMTI_TIMER_USER_CPU => TimerKind::UserCpu,
+            rvmti_sys::jvmtiTimerKind_JVMTI_TIMER_TOTAL_CPU => TimerKind::TotalCpu,
+            rvmti_sys::jvmtiTimerKind_JVMTI_TIMER_ELAPSED => TimerKind::Elapsed,
+            other => TimerKind::Unknown(other),
+        }
+    }
+
+}
+
+impl TimerInfo {
+
+    fn from_raw(info: &rvmti_sys::jvmtiTimerInfo) -> TimerInfo {
+        TimerInfo {
+            max_value: nanos(info.max_value),
+            may_skip_forward: info.may_skip_forward != 0,
+            may_skip_backward: info.may_skip_backward != 0,
+            kind: TimerKind::from_raw(info.kind),
+        }
+    }
+
+}
+
+impl JvmtiEnv {
+
+    pub fn get_time(&mut self) -> Result<Duration, JvmtiError> {
+        let mut value: rvmti_sys::jlong = 0;
+        unsafe {
+            let result = (*(*self.env)).GetTime.unwrap()(self.env, &mut value);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(nanos(value));
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    pub fn get_timer_info(&mut self) -> Result<TimerInfo, JvmtiError> {
+        let mut info: rvmti_sys::jvmtiTimerInfo = unsafe { std::mem::zeroed() };
+        unsafe {
+            let result = (*(*self.env)).GetTimerInfo.unwrap()(self.env, &mut info);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(TimerInfo::from_raw(&info));
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    pub fn get_current_thread_cpu_time(&mut self) -> Result<Duration, JvmtiError> {
+        let mut value: rvmti_sys::jlong = 0;
+        unsafe {
+            let result = (*(*self.env)).GetCurrentThreadCpuTime.unwrap()(self.env, &mut value);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(nanos(value));
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    pub fn get_current_thread_cpu_timer_info(&mut self) -> Result<TimerInfo, JvmtiError> {
+        let mut info: rvmti_sys::jvmtiTimerInfo = unsafe { std::mem::zeroed() };
+        unsafe {
+            let result = (*(*self.env)).GetCurrentThreadCpuTimerInfo.unwrap()(self.env, &mut info);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(TimerInfo::from_raw(&info));
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    // Thread is the current thread when not given
+    pub fn get_thread_cpu_time(&mut self, thread: Option<&JThread>) -> Result<Duration, JvmtiError> {
+        let mut value: rvmti_sys::jlong = 0;
+        unsafe {
+            let result = (*(*self.env)).GetThreadCpuTime.unwrap()(self.env, raw_thread(thread), &mut value);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(nanos(value));
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    pub fn get_thread_cpu_timer_info(&mut self) -> Result<TimerInfo, JvmtiError> {
+        let mut info: rvmti_sys::jvmtiTimerInfo = unsafe { std::mem::zeroed() };
+        unsafe {
+            let result = (*(*self.env)).GetThreadCpuTimerInfo.unwrap()(self.env, &mut info);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(TimerInfo::from_raw(&info));
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+}
+
+// Timers are not supposed to go negative, clamp rather than wrap if one does
+fn nanos(value: rvmti_sys::jlong) -> Duration {
+    Duration::from_nanos(std::cmp::max(value, 0) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::mock::MockJvmti;
+
+    #[test]
+    fn test_timers() {
+        let mock = MockJvmti::new();
+        let thread = mock.add_thread("worker", None, false);
+        mock.set_time(1_500_000_000);
+        mock.set_thread_cpu_time(None, 2_000);
+        mock.set_thread_cpu_time(Some(thread), 3_000);
+        let mut env = crate::JvmtiEnv::cons(mock.env_ptr());
+        assert_eq!(env.get_time().unwrap(), std::time::Duration::from_millis(1500));
+        assert_eq!(env.get_timer_info().unwrap().kind, crate::TimerKind::Elapsed);
+        assert_eq!(env.get_current_thread_cpu_time().unwrap(), std::time::Duration::from_micros(2));
+        assert_eq!(env.get_thread_cpu_time(None).unwrap(), std::time::Duration::from_micros(2));
+        assert_eq!(env.get_thread_cpu_time(Some(&crate::JThread::cons(thread))).unwrap(),
+                   std::time::Duration::from_micros(3));
+        let info = env.get_thread_cpu_timer_info().unwrap();
+        assert_eq!(info.kind, crate::TimerKind::TotalCpu);
+        assert_eq!(info.max_value, std::time::Duration::from_nanos(i64::MAX as u64));
+        assert!(!info.may_skip_backward);
+        assert_eq!(env.get_current_thread_cpu_timer_info().unwrap(), info);
+        // Negative readings are clamped
+        mock.set_time(-1);
+        assert_eq!(env.get_time().unwrap(), std::time::Duration::from_nanos(0));
+    }
+
+}
@@ -0,0 +1,134 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+
+// JVMTI version and phase of the VM. Function table slots of functions added in later versions are null in older
+// VMs, wrappers of such functions check the slot and report the version required instead of crashing.
+
+use std::fmt;
+
+use super::{JvmtiEnv, JvmtiError, JvmtiVersion};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct JvmtiVersionInfo {
+    pub major: u32,
+    pub minor: u32,
+    pub micro: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JvmtiPhase {
+    OnLoad,
+    Primordial,
+    Start,
+    Live,
+    Dead,
+    Unknown(u32),
+}
+
+impl JvmtiVersionInfo {
+
+    pub fn from_raw(version: rvmti_sys::jint) -> JvmtiVersionInfo {
+        let version = version as u32;
+        JvmtiVersionInfo {
+            major: (version & rvmti_sys::JVMTI_VERSION_MASK_MAJOR) >> rvmti_sys::JVMTI_VERSION_SHIFT_MAJOR,
+            minor: (version & rvmti_sys::JVMTI_VERSION_MASK_MINOR) >> rvmti_sys::JVMTI_VERSION_SHIFT_MINOR,
+            micro: (version & rvmti_sys::JVMTI_VERSION_MASK_MICRO) >> rvmti_sys::JVMTI_VERSION_SHIFT_MICRO,
+        }
+    }
+
+}
+
+impl From<JvmtiVersion> for JvmtiVersionInfo {
+
+    fn from(version: JvmtiVersion) -> JvmtiVersionInfo {
+        JvmtiVersionInfo::from_raw(rvmti_sys::jint::from(version))
+    }
+
+}
+
+impl fmt::Display for JvmtiVersionInfo {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.micro)
+    }
+
+}
+
+impl JvmtiPhase {
+
+    fn from_raw(phase: rvmti_sys::jvmtiPhase) -> JvmtiPhase {
+        match phase {
+            rvmti_sys::jvmtiPhase_JVMTI_PHASE_ONLOAD => JvmtiPhase::OnLoad,
+            rvmti_sys::jvmtiPhase_JVMTI_PHASE_PRIMORDIAL => JvmtiPhase::Primordial,
+            rvmti_sys::jvmtiPhase_JVMTI_PHASE_START => JvmtiPhase::Start,
+            rvmti_sys::jvmtiPhase_JVMTI_PHASE_LIVE => JvmtiPhase::Live,
+            rvmti_sys::jvmtiPhase_JVMTI_PHASE_DEAD => JvmtiPhase::Dead,
+            other => JvmtiPhase::Unknown(other),
+        }
+    }
+
+}
+
+impl JvmtiEnv {
+
+    pub fn get_version_number(&mut self) -> Result<JvmtiVersionInfo, JvmtiError> {
+        let mut version: rvmti_sys::jint = 0;
+        unsafe {
+            let result = (*(*self.env)).GetVersionNumber.unwrap()(self.env, &mut version);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(JvmtiVersionInfo::from_raw(version));
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+    pub fn get_phase(&mut self) -> Result<JvmtiPhase, JvmtiError> {
+        let mut phase: rvmti_sys::jvmtiPhase = 0;
+        unsafe {
+            let result = (*(*self.env)).GetPhase.unwrap()(self.env, &mut phase);
+            if result == rvmti_sys::jvmtiError_JVMTI_ERROR_NONE {
+                return Ok(JvmtiPhase::from_raw(phase));
+            } else {
+                return Err(JvmtiError::from(result));
+            }
+        }
+    }
+
+}
+
+// Function pointer of a function introduced in the given version, missing in VMs implementing an older one
+pub(crate) fn require<F>(function: Option<F>, name: &'static str, since: JvmtiVersion) -> Result<F, JvmtiError> {
+    function.ok_or(JvmtiError::UnsupportedJvmtiVersion{function: name, required: JvmtiVersionInfo::from(since)})
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::mock::MockJvmti;
+
+    #[test]
+    fn test_version_and_phase() {
+        let mock = MockJvmti::new();
+        let mut env = crate::JvmtiEnv::cons(mock.env_ptr());
+        mock.set_version(0x300b0102);
+        assert_eq!(env.get_version_number().unwrap(), crate::JvmtiVersionInfo{major: 11, minor: 1, micro: 2});
+        assert!(env.get_version_number().unwrap() > crate::JvmtiVersionInfo::from(crate::JvmtiVersion::Version9));
+        assert_eq!(env.get_phase().unwrap(), crate::JvmtiPhase::Live);
+        mock.set_phase(rvmti_sys::jvmtiPhase_JVMTI_PHASE_ONLOAD);
+        assert_eq!(env.get_phase().unwrap(), crate::JvmtiPhase::OnLoad);
+        // Mock leaves slots of functions it does not implement null, like a VM of an older version
+        match env.set_heap_sampling_interval(1024) {
+            Err(crate::JvmtiError::UnsupportedJvmtiVersion{function: "SetHeapSamplingInterval", required}) =>
+                assert_eq!(required.to_string(), "11.0.0"),
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+}
@@ -0,0 +1,64 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+// Loads example agents into a real JVM and checks what they print
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn java() -> PathBuf {
+    match env::var_os("JAVA_HOME") {
+        Some(home) => Path::new(&home).join("bin").join("java"),
+        None => PathBuf::from("java"),
+    }
+}
+
+fn java_available() -> bool {
+    Command::new(java()).arg("-version").output().map(|o| o.status.success()).unwrap_or(false)
+}
+
+fn example_path(name: &str) -> PathBuf {
+    // Test binary lives in target/<profile>/deps, examples are built into target/<profile>/examples
+    let exe = env::current_exe().unwrap();
+    exe.parent().and_then(|p| p.parent()).unwrap().join("examples").join(format!("lib{}.so", name))
+}
+
+fn run_example(name: &str, jvm_args: &[&str]) -> Vec<String> {
+    // Command line is decoded with the locale charset
+    let output = Command::new(java())
+        .env("LC_ALL", "C.UTF-8")
+        .arg(format!("-agentpath:{}", example_path(name).display()))
+        .args(jvm_args)
+        .arg("-version")
+        .output().unwrap();
+    assert!(output.status.success(), "JVM failed: {}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap().lines().map(str::to_string).collect()
+}
+
+#[test]
+fn test_jni_calls_from_vm_init() {
+    if !java_available() {
+        eprintln!("JDK is not available, skipping");
+        return;
+    }
+    let lines = run_example("jni_agent", &["-Xcheck:jni", "-Drvmti.test.property=v\u{e4}rde \u{1f600}"]);
+    assert_eq!(lines, vec![
+        "property: v\u{e4}rde \u{1f600}".to_string(),
+        "length: 8".to_string(),
+        "missing class: true".to_string(),
+        "frame: kept".to_string(),
+        "current thread: main Runnable".to_string(),
+        "stack traces: true".to_string(),
+        "suspended snapshot: true true true".to_string(),
+        "raw monitor: 42".to_string(),
+        "string length references: [\"getfield java/lang/String.value:[B\", \"invokevirtual java/lang/String.coder()B\"]".to_string(),
+        "loaded methods decoded: []".to_string(),
+        "retransform: modifiable true array modifiable false hooked 1".to_string(),
+    ]);
+}
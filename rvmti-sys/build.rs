@@ -6,18 +6,27 @@
 // THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::env;
+#[cfg(all(feature = "bundled-bindings", not(feature = "generate-bindings")))]
+use std::fs;
 use std::path::PathBuf;
+#[cfg(feature = "generate-bindings")]
 use std::process::Command;
+#[cfg(feature = "generate-bindings")]
 use std::process::Stdio;
+#[cfg(feature = "generate-bindings")]
 use std::io::Read;
 use std::path::Path;
+#[cfg(feature = "generate-bindings")]
 use bindgen::callbacks::ParseCallbacks;
+#[cfg(feature = "generate-bindings")]
 use bindgen::callbacks::IntKind;
 
+#[cfg(feature = "generate-bindings")]
 #[derive(Debug)]
 struct MacroCallback {
 }
 
+#[cfg(feature = "generate-bindings")]
 impl ParseCallbacks for MacroCallback {
     fn int_macro(&self, _name: &str, _value: i64) -> Option<IntKind> {
         if _name.starts_with("JNI_OK") {
@@ -27,10 +36,89 @@ impl ParseCallbacks for MacroCallback {
     }
 }
 
+#[cfg(not(any(feature = "bundled-bindings", feature = "generate-bindings")))]
+compile_error!("Either 'bundled-bindings' or 'generate-bindings' feature must be enabled");
+
+// Bundled bindings, keyed by target os and arch
+#[cfg(all(feature = "bundled-bindings", not(feature = "generate-bindings")))]
+const BUNDLED_BINDINGS: &[(&str, &str, &str)] = &[
+    ("linux", "x86_64", "src/bundled/jdk17_x86_64_linux.rs"),
+];
+
 fn main() {
     env_logger::init();
     println!("cargo:rerun-if-changed=src/wrapper.h");
+    println!("cargo:rerun-if-changed=src/bundled");
+    println!("cargo:rerun-if-env-changed=RVMTI_JDK_INCLUDE");
+    println!("cargo:rerun-if-env-changed=JAVA_HOME");
+
+    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap()).join("bindings.rs");
+    #[cfg(feature = "generate-bindings")]
+    generate_bindings(&out_path);
+    #[cfg(all(feature = "bundled-bindings", not(feature = "generate-bindings")))]
+    copy_bundled_bindings(&out_path);
+}
+
+#[cfg(all(feature = "bundled-bindings", not(feature = "generate-bindings")))]
+fn copy_bundled_bindings(out_path: &Path) {
+    let target_os = env::var("CARGO_CFG_TARGET_OS").expect("Target os is unknown");
+    let target_arch = env::var("CARGO_CFG_TARGET_ARCH").expect("Target arch is unknown");
+    let bundled = BUNDLED_BINDINGS.iter().find(|(os, arch, _)| *os == target_os && *arch == target_arch)
+        .map(|(_, _, path)| path)
+        .unwrap_or_else(|| panic!("No bundled bindings for {}-{}, enable 'generate-bindings' feature instead",
+                                  target_arch, target_os));
+    if let Err(why) = fs::copy(bundled, out_path) {
+        panic!("Couldn't copy bundled bindings: {}", why);
+    }
+}
+
+#[cfg(feature = "generate-bindings")]
+fn generate_bindings(out_path: &Path) {
+    let java_include_path = find_jdk_include();
+    if !java_include_path.join("jni.h").exists() {
+        panic!("jni.h not found in {:?}, set RVMTI_JDK_INCLUDE or JAVA_HOME", java_include_path);
+    }
+    let java_include_path_str = java_include_path.to_str().expect("Couldn't convert include path to string").to_owned();
+    let md_dir = match env::var("CARGO_CFG_TARGET_OS").expect("Target os is unknown").as_str() {
+        "linux" => "linux",
+        "windows" => "win32",
+        "macos" => "darwin",
+        "freebsd" => "freebsd",
+        "openbsd" => "openbsd",
+        _ => panic!("Path to include files is unknown for the current platform"),
+    };
+    let java_md_include_path = java_include_path.join(md_dir);
+    let java_md_include_path_str = java_md_include_path.to_str().expect("Couldn't convert include path to string").to_owned();
+
+    let bindings = bindgen::Builder::default()
+        .clang_arg(format!("-I{}", &java_include_path_str))
+        .clang_arg(format!("-I{}", &java_md_include_path_str))
+        .header("src/wrapper.h")
+        .with_codegen_config(bindgen::CodegenConfig::all())
+        .whitelist_recursively(true)
+        .parse_callbacks(Box::new(MacroCallback {}))
+        .generate()
+        .expect("Unable to generate bindings");
+
+    bindings
+        .write_to_file(out_path)
+        .expect("Couldn't write bindings!");
+}
 
+// Explicit include directory wins, then JAVA_HOME, then java.home reported by java from PATH
+#[cfg(feature = "generate-bindings")]
+fn find_jdk_include() -> PathBuf {
+    if let Some(include) = env::var_os("RVMTI_JDK_INCLUDE") {
+        return PathBuf::from(include);
+    }
+    if let Some(java_home) = env::var_os("JAVA_HOME") {
+        return Path::new(&java_home).join("include");
+    }
+    Path::new(&java_home_from_java()).join("include")
+}
+
+#[cfg(feature = "generate-bindings")]
+fn java_home_from_java() -> String {
     let mut java_process = match Command::new("java")
         .arg("-XshowSettings:properties")
         .arg("-version")
@@ -39,7 +127,7 @@ fn main() {
         .stderr(Stdio::piped())
         .spawn()
         {
-            Err(why) => panic!("Couldn't spawn process: {}", why),
+            Err(why) => panic!("Couldn't spawn java, set RVMTI_JDK_INCLUDE or JAVA_HOME: {}", why),
             Ok(process) => process,
         };
     match java_process.wait()
@@ -52,41 +140,6 @@ fn main() {
         Err(why) => panic!("Couldn't read process stderr: {}", why),
         Ok(_) => (),
     }
-    let java_home = java_out.lines().find(|line| line.contains("java.home")).and_then(|line| line.split(" = ").nth(1))
-        .expect("java.home not found");
-    let java_include_path = Path::new(java_home).join("include");
-    let java_include_path_str = java_include_path.to_str().expect("Couldn't convert include path to string").to_owned();
-    let java_md_include_path_str = if cfg!(target_os = "linux") {
-        let java_md_include_path = java_include_path.join("linux");
-        java_md_include_path.to_str().expect("Couldn't convert include path to string").to_owned()
-    } else if cfg!(target_os = "windows") {
-        let java_md_include_path = java_include_path.join("win32");
-        java_md_include_path.to_str().expect("Couldn't convert include path to string").to_owned()
-    } else if cfg!(target_os = "macos") {
-        let java_md_include_path = java_include_path.join("darwin");
-        java_md_include_path.to_str().expect("Couldn't convert include path to string").to_owned()
-    } else if cfg!(target_os = "freebsd") {
-        let java_md_include_path = java_include_path.join("freebsd");
-        java_md_include_path.to_str().expect("Couldn't convert include path to string").to_owned()
-    } else if cfg!(target_os = "openbsd") {
-        let java_md_include_path = java_include_path.join("openbsd");
-        java_md_include_path.to_str().expect("Couldn't convert include path to string").to_owned()
-    } else {
-        panic!("Path to include files is unknown for the current platform");
-    };
-
-    let bindings = bindgen::Builder::default()
-        .clang_arg(format!("-I{}", &java_include_path_str))
-        .clang_arg(format!("-I{}", &java_md_include_path_str))
-        .header("src/wrapper.h")
-        .with_codegen_config(bindgen::CodegenConfig::all())
-        .whitelist_recursively(true)
-        .parse_callbacks(Box::new(MacroCallback {}))
-        .generate()
-        .expect("Unable to generate bindings");
-
-    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
-    bindings
-        .write_to_file(out_path.join("bindings.rs"))
-        .expect("Couldn't write bindings!");
+    java_out.lines().find(|line| line.contains("java.home")).and_then(|line| line.split(" = ").nth(1))
+        .expect("java.home not found").to_owned()
 }
@@ -0,0 +1,1011 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+// Class file model that writes unchanged classes back byte for byte, and inserts probes at method entry and exit
+
+use byteorder::{BigEndian, ByteOrder};
+use thiserror::Error;
+
+use rvmti_api::{Constant, ConstantPool, ConstantPoolError, Disassembly, Instruction, Opcode, Operands, BytecodeError};
+
+use crate::demangle::{FieldType, MethodType};
+use crate::frames::{self, FrameState};
+
+const CLASS_FILE_MAGIC: u32 = 0xcafebabe;
+const MAX_CODE_LENGTH: usize = 65535;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassFile {
+    pub minor_version: u16,
+    pub major_version: u16,
+    constant_pool: ConstantPool,
+    // Pool as it was read, written back until the pool is borrowed mutably
+    constant_pool_bytes: Option<Vec<u8>>,
+    pub access_flags: u16,
+    pub this_class: u16,
+    pub super_class: u16,
+    pub interfaces: Vec<u16>,
+    pub fields: Vec<Member>,
+    pub methods: Vec<Member>,
+    pub attributes: Vec<Attribute>,
+}
+
+// Field or method
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    pub access_flags: u16,
+    pub name_index: u16,
+    pub descriptor_index: u16,
+    pub attributes: Vec<Attribute>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute {
+    pub name_index: u16,
+    pub body: AttributeBody,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeBody {
+    Code(Code),
+    StackMapTable(Vec<StackMapFrame>),
+    LineNumberTable(Vec<LineNumber>),
+    LocalVariableTable(Vec<LocalVariable>),
+    // Same layout as the local variable table, with signatures in place of descriptors
+    LocalVariableTypeTable(Vec<LocalVariable>),
+    SourceFile { source_file_index: u16 },
+    Raw(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Code {
+    pub max_stack: u16,
+    pub max_locals: u16,
+    pub code: Vec<u8>,
+    pub exception_table: Vec<ExceptionHandler>,
+    pub attributes: Vec<Attribute>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExceptionHandler {
+    pub start_pc: u16,
+    pub end_pc: u16,
+    pub handler_pc: u16,
+    pub catch_type: u16,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineNumber {
+    pub start_pc: u16,
+    pub line_number: u16,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalVariable {
+    pub start_pc: u16,
+    pub length: u16,
+    pub name_index: u16,
+    pub descriptor_index: u16,
+    pub index: u16,
+}
+
+// Variants follow the frame encodings, so that frames are written back as they were read
+#[derive(Debug, Clone, PartialEq)]
+pub enum StackMapFrame {
+    Same { offset_delta: u16 },
+    SameLocals1StackItem { offset_delta: u16, stack: VerificationType },
+    SameLocals1StackItemExtended { offset_delta: u16, stack: VerificationType },
+    Chop { offset_delta: u16, count: u8 },
+    SameExtended { offset_delta: u16 },
+    Append { offset_delta: u16, locals: Vec<VerificationType> },
+    Full { offset_delta: u16, locals: Vec<VerificationType>, stack: Vec<VerificationType> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerificationType {
+    Top,
+    Integer,
+    Float,
+    Double,
+    Long,
+    Null,
+    UninitializedThis,
+    Object { class_index: u16 },
+    // Offset of the `new` instruction creating the object
+    Uninitialized { offset: u16 },
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ClassFileError {
+    #[error("Class file is truncated")]
+    Truncated,
+    #[error("Invalid class file magic 0x{magic:08x}")]
+    InvalidMagic { magic: u32 },
+    #[error("Class file is followed by {count} unexpected bytes")]
+    TrailingBytes { count: usize },
+    #[error("Invalid {name} attribute")]
+    InvalidAttribute { name: String },
+    #[error("Invalid stack map frame type {frame_type}")]
+    InvalidFrameType { frame_type: u8 },
+    #[error("Invalid verification type tag {tag}")]
+    InvalidVerificationType { tag: u8 },
+    #[error("Invalid constant pool: {0}")]
+    ConstantPoolError(#[source] ConstantPoolError),
+    #[error("Invalid bytecode: {0}")]
+    BytecodeError(#[source] BytecodeError),
+    #[error("Invalid descriptor {descriptor}")]
+    InvalidDescriptor { descriptor: String },
+    #[error("Method {name}{descriptor} not found")]
+    MethodNotFound { name: String, descriptor: String },
+    #[error("Method {name}{descriptor} has no code")]
+    MissingCode { name: String, descriptor: String },
+    #[error("Instruction {opcode} is not supported in probes")]
+    UnsupportedProbeInstruction { opcode: Opcode },
+    #[error("Probe does not leave the operand stack as it found it")]
+    UnbalancedProbe,
+    #[error("Code offset {offset} is not an instruction start")]
+    InvalidCodeOffset { offset: u32 },
+    #[error("Instrumented code of {length} bytes is too long")]
+    CodeTooLong { length: usize },
+    #[error("Invalid stack map frame at code offset {offset}")]
+    InvalidFrame { offset: u32 },
+    #[error("Stack map frame does not match {opcode} at bci {bci}")]
+    FrameMismatch { bci: u32, opcode: Opcode },
+    #[error("Instruction {opcode} is not supported when computing stack map frames")]
+    UnsupportedFrameInstruction { opcode: Opcode },
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+
+    fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader{bytes, position: 0}
+    }
+
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], ClassFileError> {
+        if self.remaining() < count {
+            return Err(ClassFileError::Truncated);
+        }
+        let bytes = &self.bytes[self.position..self.position + count];
+        self.position += count;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, ClassFileError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, ClassFileError> {
+        Ok(BigEndian::read_u16(self.bytes(2)?))
+    }
+
+    fn u32(&mut self) -> Result<u32, ClassFileError> {
+        Ok(BigEndian::read_u32(self.bytes(4)?))
+    }
+
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.position
+    }
+
+    // Items preceded by a u16 count
+    fn table<T, F>(&mut self, mut read: F) -> Result<Vec<T>, ClassFileError>
+        where F: FnMut(&mut Reader<'a>) -> Result<T, ClassFileError> {
+        let count = self.u16()?;
+        (0..count).map(|_| read(self)).collect()
+    }
+
+}
+
+impl ClassFile {
+
+    pub fn parse(bytes: &[u8]) -> Result<ClassFile, ClassFileError> {
+        let mut reader = Reader::new(bytes);
+        let magic = reader.u32()?;
+        if magic != CLASS_FILE_MAGIC {
+            return Err(ClassFileError::InvalidMagic{magic});
+        }
+        let minor_version = reader.u16()?;
+        let major_version = reader.u16()?;
+        let constant_pool_count = reader.u16()?;
+        let (constant_pool, length) = ConstantPool::read(constant_pool_count, &bytes[reader.position..])
+            .map_err(ClassFileError::ConstantPoolError)?;
+        let constant_pool_bytes = reader.bytes(length)?.to_vec();
+        let access_flags = reader.u16()?;
+        let this_class = reader.u16()?;
+        let super_class = reader.u16()?;
+        let interfaces = reader.table(|r| r.u16())?;
+        let fields = reader.table(|r| read_member(r, &constant_pool))?;
+        let methods = reader.table(|r| read_member(r, &constant_pool))?;
+        let attributes = reader.table(|r| read_attribute(r, &constant_pool))?;
+        if reader.remaining() > 0 {
+            return Err(ClassFileError::TrailingBytes{count: reader.remaining()});
+        }
+        Ok(ClassFile{minor_version, major_version, constant_pool, constant_pool_bytes: Some(constant_pool_bytes),
+                     access_flags, this_class, super_class, interfaces, fields, methods, attributes})
+    }
+
+    pub fn write(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        put_u32(&mut out, CLASS_FILE_MAGIC);
+        put_u16(&mut out, self.minor_version);
+        put_u16(&mut out, self.major_version);
+        put_u16(&mut out, self.constant_pool.count());
+        match self.constant_pool_bytes {
+            Some(ref bytes) => out.extend_from_slice(bytes),
+            None => self.constant_pool.write(&mut out),
+        }
+        put_u16(&mut out, self.access_flags);
+        put_u16(&mut out, self.this_class);
+        put_u16(&mut out, self.super_class);
+        put_u16(&mut out, self.interfaces.len() as u16);
+        for interface in self.interfaces.iter() {
+            put_u16(&mut out, *interface);
+        }
+        for members in [&self.fields, &self.methods].iter() {
+            put_u16(&mut out, members.len() as u16);
+            for member in members.iter() {
+                put_u16(&mut out, member.access_flags);
+                put_u16(&mut out, member.name_index);
+                put_u16(&mut out, member.descriptor_index);
+                write_attributes(&member.attributes, &mut out);
+            }
+        }
+        write_attributes(&self.attributes, &mut out);
+        out
+    }
+
+    pub fn constant_pool(&self) -> &ConstantPool {
+        &self.constant_pool
+    }
+
+    // Probes reference constants added here
+    pub fn constant_pool_mut(&mut self) -> &mut ConstantPool {
+        self.constant_pool_bytes = None;
+        &mut self.constant_pool
+    }
+
+    pub fn class_name(&self) -> Result<&str, ClassFileError> {
+        self.constant_pool.class_name(self.this_class).map_err(ClassFileError::ConstantPoolError)
+    }
+
+    pub fn method(&self, name: &str, descriptor: &str) -> Option<&Member> {
+        let pool = &self.constant_pool;
+        self.methods.iter().find(|m| pool.utf8(m.name_index) == Ok(name) && pool.utf8(m.descriptor_index) == Ok(descriptor))
+    }
+
+    // Entry probe runs before the first instruction, exit probe before every return. Exits by exception are not covered
+    pub fn instrument_method(&mut self, name: &str, descriptor: &str, entry: &[Instruction], exit: &[Instruction])
+                             -> Result<(), ClassFileError> {
+        // Frames may need classes added to the pool, which is only taken over once the probes are in
+        let mut pool = self.constant_pool.clone();
+        let method = self.methods.iter_mut()
+            .find(|m| pool.utf8(m.name_index) == Ok(name) && pool.utf8(m.descriptor_index) == Ok(descriptor))
+            .ok_or_else(|| ClassFileError::MethodNotFound{name: name.to_string(), descriptor: descriptor.to_string()})?;
+        let access_flags = method.access_flags;
+        let code = method.code_mut()
+            .ok_or_else(|| ClassFileError::MissingCode{name: name.to_string(), descriptor: descriptor.to_string()})?;
+        let initial_locals = if code.attributes.iter().any(|a| matches!(a.body, AttributeBody::StackMapTable(_))) {
+            frames::initial_state(&mut pool, self.this_class, access_flags, name, descriptor)?.locals
+        } else {
+            Vec::new()
+        };
+        code.insert_probes(&mut pool, self.this_class, &initial_locals, entry, exit)?;
+        if pool.count() != self.constant_pool.count() {
+            self.constant_pool = pool;
+            self.constant_pool_bytes = None;
+        }
+        Ok(())
+    }
+
+}
+
+impl Member {
+
+    pub fn code(&self) -> Option<&Code> {
+        self.attributes.iter().find_map(|a| match a.body {
+            AttributeBody::Code(ref code) => Some(code),
+            _ => None,
+        })
+    }
+
+    pub fn code_mut(&mut self) -> Option<&mut Code> {
+        self.attributes.iter_mut().find_map(|a| match a.body {
+            AttributeBody::Code(ref mut code) => Some(code),
+            _ => None,
+        })
+    }
+
+}
+
+impl Code {
+
+    // Probes are straight line code that leaves the operand stack as it found it and stores no locals. Stack map
+    // frames are recomputed from the declared ones and the locals on method entry, the only new ones are needed where
+    // a conditional branch pushed out of range by the probes is rewritten to jump over a goto_w. Type annotations of
+    // the code refer to instructions by offset and are dropped. Other raw attributes are copied unchanged, which is
+    // only correct for attributes without code offsets
+    pub fn insert_probes(&mut self, constant_pool: &mut ConstantPool, this_class: u16, initial_locals: &[VerificationType],
+                         entry: &[Instruction], exit: &[Instruction]) -> Result<(), ClassFileError> {
+        let entry_stack = probe_max_stack(constant_pool, entry)?;
+        let exit_stack = probe_max_stack(constant_pool, exit)?;
+        let disassembly = Disassembly::decode(&self.code).map_err(ClassFileError::BytecodeError)?;
+        let entry_length: u32 = entry.iter().map(|i| i.encoded_length(0)).sum();
+        let exit_length: u32 = exit.iter().map(|i| i.encoded_length(0)).sum();
+        // Short branches rewritten to goto_w by old bci. Widening one moves code, which may push others out of range
+        let mut widened = vec![false; self.code.len()];
+        let (targets, starts) = loop {
+            let (targets, starts) = layout(&disassembly, self.code.len(), entry_length, exit_length, &widened);
+            let mut changed = false;
+            for instruction in disassembly.instructions() {
+                let bci = instruction.bci as usize;
+                if let Operands::Branch(target) = instruction.operands {
+                    if !widened[bci] && is_short_branch(instruction.opcode) {
+                        let offset = relocate(&targets, target)? as i64 - relocate(&targets, instruction.bci)? as i64;
+                        if offset < i16::MIN as i64 || offset > i16::MAX as i64 {
+                            widened[bci] = true;
+                            changed = true;
+                        }
+                    }
+                }
+            }
+            if !changed {
+                break (targets, starts);
+            }
+        };
+        let length = starts[self.code.len()].unwrap_or(0);
+        if length as usize > MAX_CODE_LENGTH {
+            return Err(ClassFileError::CodeTooLong{length: length as usize});
+        }
+
+        let mut code = Vec::with_capacity(length as usize);
+        for instruction in entry.iter() {
+            instruction.encode(&mut code).map_err(ClassFileError::BytecodeError)?;
+        }
+        for instruction in disassembly.instructions() {
+            if is_return(instruction.opcode) {
+                for probe_instruction in exit.iter() {
+                    probe_instruction.encode(&mut code).map_err(ClassFileError::BytecodeError)?;
+                }
+            }
+            let operands = match instruction.operands {
+                Operands::Branch(target) => Operands::Branch(relocate(&targets, target)?),
+                Operands::TableSwitch{default, low, targets: ref switch_targets} => Operands::TableSwitch{
+                    default: relocate(&targets, default)?,
+                    low,
+                    targets: switch_targets.iter().map(|t| relocate(&targets, *t)).collect::<Result<_, _>>()?,
+                },
+                Operands::LookupSwitch{default, ref pairs} => Operands::LookupSwitch{
+                    default: relocate(&targets, default)?,
+                    pairs: pairs.iter().map(|&(k, t)| Ok((k, relocate(&targets, t)?))).collect::<Result<_, ClassFileError>>()?,
+                },
+                ref operands => operands.clone(),
+            };
+            if widened[instruction.bci as usize] {
+                // Conditional branch skips the goto_w when its inverted condition holds
+                if let Some(inverted) = inverted_condition(instruction.opcode) {
+                    let skip = code.len() as u32 + 8;
+                    Instruction::new(inverted, Operands::Branch(skip)).encode(&mut code).map_err(ClassFileError::BytecodeError)?;
+                }
+                let opcode = if instruction.opcode == Opcode::Jsr { Opcode::JsrW } else { Opcode::GotoW };
+                Instruction::new(opcode, operands).encode(&mut code).map_err(ClassFileError::BytecodeError)?;
+                continue;
+            }
+            let relocated = Instruction{operands, ..instruction.clone()};
+            relocated.encode(&mut code).map_err(ClassFileError::BytecodeError)?;
+        }
+
+        let mut frames = None;
+        for attribute in self.attributes.iter() {
+            if let AttributeBody::StackMapTable(ref declared) = attribute.body {
+                frames = Some(recompute_frames(constant_pool, this_class, &disassembly, initial_locals, declared,
+                                               &widened, &targets)?);
+            }
+        }
+        let mut exception_table = self.exception_table.clone();
+        for handler in exception_table.iter_mut() {
+            handler.start_pc = relocate(&targets, handler.start_pc as u32)? as u16;
+            handler.end_pc = relocate(&targets, handler.end_pc as u32)? as u16;
+            handler.handler_pc = relocate(&targets, handler.handler_pc as u32)? as u16;
+        }
+        let mut attributes: Vec<Attribute> = self.attributes.iter()
+            .filter(|a| !is_type_annotations(constant_pool, a.name_index))
+            .cloned()
+            .collect();
+        for attribute in attributes.iter_mut() {
+            match attribute.body {
+                AttributeBody::StackMapTable(ref mut declared) => {
+                    if let Some(ref frames) = frames {
+                        *declared = frames.clone();
+                    }
+                },
+                AttributeBody::LineNumberTable(ref mut line_numbers) => {
+                    for line_number in line_numbers.iter_mut() {
+                        line_number.start_pc = relocate(&starts, line_number.start_pc as u32)? as u16;
+                    }
+                },
+                AttributeBody::LocalVariableTable(ref mut variables) | AttributeBody::LocalVariableTypeTable(ref mut variables) => {
+                    for variable in variables.iter_mut() {
+                        let start = relocate(&starts, variable.start_pc as u32)?;
+                        let end = relocate(&starts, variable.start_pc as u32 + variable.length as u32)?;
+                        variable.start_pc = start as u16;
+                        variable.length = (end - start) as u16;
+                    }
+                },
+                _ => {},
+            }
+        }
+        self.code = code;
+        self.exception_table = exception_table;
+        self.attributes = attributes;
+        self.max_stack = self.max_stack.max(entry_stack);
+        if !exit.is_empty() {
+            self.max_stack = self.max_stack.saturating_add(exit_stack);
+        }
+        Ok(())
+    }
+
+}
+
+impl StackMapFrame {
+
+    pub fn offset_delta(&self) -> u16 {
+        match *self {
+            StackMapFrame::Same{offset_delta} | StackMapFrame::SameLocals1StackItem{offset_delta, ..}
+            | StackMapFrame::SameLocals1StackItemExtended{offset_delta, ..} | StackMapFrame::Chop{offset_delta, ..}
+            | StackMapFrame::SameExtended{offset_delta} | StackMapFrame::Append{offset_delta, ..}
+            | StackMapFrame::Full{offset_delta, ..} => offset_delta,
+        }
+    }
+
+}
+
+fn is_return(opcode: Opcode) -> bool {
+    matches!(opcode, Opcode::Ireturn | Opcode::Lreturn | Opcode::Freturn | Opcode::Dreturn | Opcode::Areturn | Opcode::Return)
+}
+
+fn is_type_annotations(constant_pool: &ConstantPool, name_index: u16) -> bool {
+    matches!(constant_pool.utf8(name_index), Ok("RuntimeVisibleTypeAnnotations") | Ok("RuntimeInvisibleTypeAnnotations"))
+}
+
+fn relocate(offsets: &[Option<u32>], offset: u32) -> Result<u32, ClassFileError> {
+    offsets.get(offset as usize).cloned().flatten().ok_or(ClassFileError::InvalidCodeOffset{offset})
+}
+
+// New offsets by old bci. Branches skip the entry probe and land on the exit probe in front of a return, line numbers
+// and local variable scopes cover both probes
+fn layout(disassembly: &Disassembly, code_length: usize, entry_length: u32, exit_length: u32, widened: &[bool])
+          -> (Vec<Option<u32>>, Vec<Option<u32>>) {
+    let mut targets = vec![None; code_length + 1];
+    let mut starts = vec![None; code_length + 1];
+    let mut position = entry_length;
+    for instruction in disassembly.instructions() {
+        let bci = instruction.bci as usize;
+        targets[bci] = Some(position);
+        starts[bci] = Some(if bci == 0 { 0 } else { position });
+        if is_return(instruction.opcode) {
+            position += exit_length;
+        }
+        position += match (widened[bci], inverted_condition(instruction.opcode)) {
+            (true, Some(_)) => 8,
+            (true, None) => 5,
+            (false, _) => instruction.encoded_length(position),
+        };
+    }
+    targets[code_length] = Some(position);
+    starts[code_length] = Some(position);
+    (targets, starts)
+}
+
+fn is_short_branch(opcode: Opcode) -> bool {
+    opcode == Opcode::Goto || opcode == Opcode::Jsr || inverted_condition(opcode).is_some()
+}
+
+fn inverted_condition(opcode: Opcode) -> Option<Opcode> {
+    match opcode {
+        Opcode::Ifeq => Some(Opcode::Ifne),
+        Opcode::Ifne => Some(Opcode::Ifeq),
+        Opcode::Iflt => Some(Opcode::Ifge),
+        Opcode::Ifge => Some(Opcode::Iflt),
+        Opcode::Ifgt => Some(Opcode::Ifle),
+        Opcode::Ifle => Some(Opcode::Ifgt),
+        Opcode::IfIcmpeq => Some(Opcode::IfIcmpne),
+        Opcode::IfIcmpne => Some(Opcode::IfIcmpeq),
+        Opcode::IfIcmplt => Some(Opcode::IfIcmpge),
+        Opcode::IfIcmpge => Some(Opcode::IfIcmplt),
+        Opcode::IfIcmpgt => Some(Opcode::IfIcmple),
+        Opcode::IfIcmple => Some(Opcode::IfIcmpgt),
+        Opcode::IfAcmpeq => Some(Opcode::IfAcmpne),
+        Opcode::IfAcmpne => Some(Opcode::IfAcmpeq),
+        Opcode::Ifnull => Some(Opcode::Ifnonnull),
+        Opcode::Ifnonnull => Some(Opcode::Ifnull),
+        _ => None,
+    }
+}
+
+// Declared frames are moved to the new offsets, and instructions following a widened conditional branch get a frame
+// as targets of the inverted branch
+fn recompute_frames(constant_pool: &mut ConstantPool, this_class: u16, disassembly: &Disassembly,
+                    initial_locals: &[VerificationType], declared: &[StackMapFrame], widened: &[bool],
+                    targets: &[Option<u32>]) -> Result<Vec<StackMapFrame>, ClassFileError> {
+    let initial = FrameState{locals: initial_locals.to_vec(), stack: Vec::new()};
+    let mut states = frames::expand(&initial, declared)?;
+    let fallthroughs: Vec<u32> = disassembly.instructions().iter()
+        .filter(|i| widened[i.bci as usize] && inverted_condition(i.opcode).is_some())
+        .map(|i| i.bci + i.length)
+        .filter(|bci| states.iter().all(|(offset, _)| offset != bci))
+        .collect();
+    if !fallthroughs.is_empty() {
+        let mut computed = frames::simulate(constant_pool, this_class, disassembly, &initial, &states, &fallthroughs)?;
+        for bci in fallthroughs.iter() {
+            let state = computed.remove(bci).ok_or(ClassFileError::InvalidCodeOffset{offset: *bci})?;
+            states.push((*bci, state));
+        }
+    }
+    let mut relocated = Vec::with_capacity(states.len());
+    for (offset, mut state) in states.into_iter() {
+        for verification_type in state.locals.iter_mut().chain(state.stack.iter_mut()) {
+            if let VerificationType::Uninitialized{ref mut offset} = *verification_type {
+                *offset = relocate(targets, *offset as u32)? as u16;
+            }
+        }
+        relocated.push((relocate(targets, offset)?, state));
+    }
+    relocated.sort_by_key(|&(offset, _)| offset);
+    Ok(frames::encode(&initial, &relocated))
+}
+
+fn member_descriptor(constant_pool: &ConstantPool, index: u16) -> Result<&str, ClassFileError> {
+    let name_and_type_index = match constant_pool.get(index) {
+        Some(&Constant::Fieldref{name_and_type_index, ..}) | Some(&Constant::Methodref{name_and_type_index, ..})
+        | Some(&Constant::InterfaceMethodref{name_and_type_index, ..})
+        | Some(&Constant::InvokeDynamic{name_and_type_index, ..}) => name_and_type_index,
+        _ => return Err(ClassFileError::ConstantPoolError(
+            ConstantPoolError::UnexpectedConstant{index, expected: "a member reference"})),
+    };
+    let (_, descriptor) = constant_pool.name_and_type(name_and_type_index).map_err(ClassFileError::ConstantPoolError)?;
+    Ok(descriptor)
+}
+
+fn field_slots(descriptor: &str) -> Result<usize, ClassFileError> {
+    FieldType::new(descriptor).map(|t| t.slots())
+        .map_err(|_| ClassFileError::InvalidDescriptor{descriptor: descriptor.to_string()})
+}
+
+// Parameter and return slots
+fn method_slots(descriptor: &str) -> Result<(usize, usize), ClassFileError> {
+    MethodType::new(descriptor).map(|t| (t.parameter_slots(), t.return_slots()))
+        .map_err(|_| ClassFileError::InvalidDescriptor{descriptor: descriptor.to_string()})
+}
+
+// Slots popped and pushed. Probes are limited to instructions that neither branch nor store locals
+fn stack_effect(constant_pool: &ConstantPool, instruction: &Instruction) -> Result<(usize, usize), ClassFileError> {
+    let unsupported = ClassFileError::UnsupportedProbeInstruction{opcode: instruction.opcode};
+    let index = match instruction.operands {
+        Operands::ConstantPool(index) | Operands::InvokeInterface{index, ..} => Some(index),
+        _ => None,
+    };
+    let effect = match instruction.opcode {
+        Opcode::Nop => (0, 0),
+        Opcode::AconstNull | Opcode::IconstM1 | Opcode::Iconst0 | Opcode::Iconst1 | Opcode::Iconst2 | Opcode::Iconst3
+        | Opcode::Iconst4 | Opcode::Iconst5 | Opcode::Fconst0 | Opcode::Fconst1 | Opcode::Fconst2 | Opcode::Bipush
+        | Opcode::Sipush | Opcode::Ldc | Opcode::LdcW => (0, 1),
+        Opcode::Lconst0 | Opcode::Lconst1 | Opcode::Dconst0 | Opcode::Dconst1 | Opcode::Ldc2W => (0, 2),
+        Opcode::Iload | Opcode::Fload | Opcode::Aload | Opcode::Iload0 | Opcode::Iload1 | Opcode::Iload2 | Opcode::Iload3
+        | Opcode::Fload0 | Opcode::Fload1 | Opcode::Fload2 | Opcode::Fload3 | Opcode::Aload0 | Opcode::Aload1
+        | Opcode::Aload2 | Opcode::Aload3 => (0, 1),
+        Opcode::Lload | Opcode::Dload | Opcode::Lload0 | Opcode::Lload1 | Opcode::Lload2 | Opcode::Lload3
+        | Opcode::Dload0 | Opcode::Dload1 | Opcode::Dload2 | Opcode::Dload3 => (0, 2),
+        Opcode::Pop => (1, 0),
+        Opcode::Pop2 => (2, 0),
+        Opcode::Dup => (1, 2),
+        Opcode::Dup2 => (2, 4),
+        Opcode::Swap => (2, 2),
+        Opcode::Iadd | Opcode::Isub | Opcode::Imul | Opcode::Iand | Opcode::Ior | Opcode::Ixor => (2, 1),
+        Opcode::Ladd | Opcode::Lsub | Opcode::Lmul | Opcode::Land | Opcode::Lor | Opcode::Lxor => (4, 2),
+        Opcode::Arraylength | Opcode::Checkcast | Opcode::Instanceof => (1, 1),
+        Opcode::Getstatic | Opcode::Putstatic | Opcode::Getfield | Opcode::Putfield => {
+            let slots = field_slots(member_descriptor(constant_pool, index.ok_or(unsupported)?)?)?;
+            match instruction.opcode {
+                Opcode::Getstatic => (0, slots),
+                Opcode::Putstatic => (slots, 0),
+                Opcode::Getfield => (1, slots),
+                _ => (1 + slots, 0),
+            }
+        },
+        Opcode::Invokevirtual | Opcode::Invokeinterface | Opcode::Invokestatic | Opcode::Invokedynamic => {
+            let (parameters, result) = method_slots(member_descriptor(constant_pool, index.ok_or(unsupported)?)?)?;
+            match instruction.opcode {
+                Opcode::Invokevirtual | Opcode::Invokeinterface => (1 + parameters, result),
+                _ => (parameters, result),
+            }
+        },
+        _ => return Err(unsupported),
+    };
+    Ok(effect)
+}
+
+// Deepest operand stack the probe builds on top of the stack it finds
+fn probe_max_stack(constant_pool: &ConstantPool, probe: &[Instruction]) -> Result<u16, ClassFileError> {
+    let mut depth = 0;
+    let mut max_depth = 0;
+    for instruction in probe.iter() {
+        let (pops, pushes) = stack_effect(constant_pool, instruction)?;
+        if pops > depth {
+            return Err(ClassFileError::UnbalancedProbe);
+        }
+        depth = depth - pops + pushes;
+        max_depth = max_depth.max(depth);
+    }
+    if depth != 0 {
+        return Err(ClassFileError::UnbalancedProbe);
+    }
+    Ok(max_depth as u16)
+}
+
+fn read_member(reader: &mut Reader, constant_pool: &ConstantPool) -> Result<Member, ClassFileError> {
+    let access_flags = reader.u16()?;
+    let name_index = reader.u16()?;
+    let descriptor_index = reader.u16()?;
+    let attributes = reader.table(|r| read_attribute(r, constant_pool))?;
+    Ok(Member{access_flags, name_index, descriptor_index, attributes})
+}
+
+fn read_attribute(reader: &mut Reader, constant_pool: &ConstantPool) -> Result<Attribute, ClassFileError> {
+    let name_index = reader.u16()?;
+    let length = reader.u32()? as usize;
+    let bytes = reader.bytes(length)?;
+    let name = constant_pool.utf8(name_index).map_err(ClassFileError::ConstantPoolError)?;
+    let invalid = || ClassFileError::InvalidAttribute{name: name.to_string()};
+    let mut body_reader = Reader::new(bytes);
+    let body = match read_attribute_body(name, &mut body_reader, constant_pool) {
+        Ok(_) if body_reader.remaining() > 0 => return Err(invalid()),
+        Ok(body) => body,
+        Err(ClassFileError::Truncated) => return Err(invalid()),
+        Err(e) => return Err(e),
+    };
+    Ok(Attribute{name_index, body})
+}
+
+fn read_attribute_body(name: &str, reader: &mut Reader, constant_pool: &ConstantPool) -> Result<AttributeBody, ClassFileError> {
+    let body = match name {
+        "Code" => {
+            let max_stack = reader.u16()?;
+            let max_locals = reader.u16()?;
+            let code_length = reader.u32()? as usize;
+            let code = reader.bytes(code_length)?.to_vec();
+            let exception_table = reader.table(|r| Ok(ExceptionHandler{start_pc: r.u16()?, end_pc: r.u16()?,
+                                                                       handler_pc: r.u16()?, catch_type: r.u16()?}))?;
+            let attributes = reader.table(|r| read_attribute(r, constant_pool))?;
+            AttributeBody::Code(Code{max_stack, max_locals, code, exception_table, attributes})
+        },
+        "StackMapTable" => AttributeBody::StackMapTable(reader.table(read_frame)?),
+        "LineNumberTable" => {
+            AttributeBody::LineNumberTable(reader.table(|r| Ok(LineNumber{start_pc: r.u16()?, line_number: r.u16()?}))?)
+        },
+        "LocalVariableTable" => AttributeBody::LocalVariableTable(reader.table(read_local_variable)?),
+        "LocalVariableTypeTable" => AttributeBody::LocalVariableTypeTable(reader.table(read_local_variable)?),
+        "SourceFile" => AttributeBody::SourceFile{source_file_index: reader.u16()?},
+        _ => AttributeBody::Raw(reader.bytes(reader.remaining())?.to_vec()),
+    };
+    Ok(body)
+}
+
+fn read_local_variable(reader: &mut Reader) -> Result<LocalVariable, ClassFileError> {
+    Ok(LocalVariable{start_pc: reader.u16()?, length: reader.u16()?, name_index: reader.u16()?,
+                     descriptor_index: reader.u16()?, index: reader.u16()?})
+}
+
+fn read_frame(reader: &mut Reader) -> Result<StackMapFrame, ClassFileError> {
+    let frame_type = reader.u8()?;
+    let frame = match frame_type {
+        0..=63 => StackMapFrame::Same{offset_delta: frame_type as u16},
+        64..=127 => StackMapFrame::SameLocals1StackItem{offset_delta: frame_type as u16 - 64,
+                                                        stack: read_verification_type(reader)?},
+        247 => StackMapFrame::SameLocals1StackItemExtended{offset_delta: reader.u16()?,
+                                                           stack: read_verification_type(reader)?},
+        248..=250 => StackMapFrame::Chop{offset_delta: reader.u16()?, count: 251 - frame_type},
+        251 => StackMapFrame::SameExtended{offset_delta: reader.u16()?},
+        252..=254 => {
+            let offset_delta = reader.u16()?;
+            let locals = (0..frame_type - 251).map(|_| read_verification_type(reader)).collect::<Result<_, _>>()?;
+            StackMapFrame::Append{offset_delta, locals}
+        },
+        255 => {
+            let offset_delta = reader.u16()?;
+            let locals = reader.table(read_verification_type)?;
+            let stack = reader.table(read_verification_type)?;
+            StackMapFrame::Full{offset_delta, locals, stack}
+        },
+        _ => return Err(ClassFileError::InvalidFrameType{frame_type}),
+    };
+    Ok(frame)
+}
+
+fn read_verification_type(reader: &mut Reader) -> Result<VerificationType, ClassFileError> {
+    let tag = reader.u8()?;
+    let verification_type = match tag {
+        0 => VerificationType::Top,
+        1 => VerificationType::Integer,
+        2 => VerificationType::Float,
+        3 => VerificationType::Double,
+        4 => VerificationType::Long,
+        5 => VerificationType::Null,
+        6 => VerificationType::UninitializedThis,
+        7 => VerificationType::Object{class_index: reader.u16()?},
+        8 => VerificationType::Uninitialized{offset: reader.u16()?},
+        _ => return Err(ClassFileError::InvalidVerificationType{tag}),
+    };
+    Ok(verification_type)
+}
+
+fn put_u16(out: &mut Vec<u8>, value: u16) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+fn write_attributes(attributes: &[Attribute], out: &mut Vec<u8>) {
+    put_u16(out, attributes.len() as u16);
+    for attribute in attributes.iter() {
+        let mut body = Vec::new();
+        write_attribute_body(&attribute.body, &mut body);
+        put_u16(out, attribute.name_index);
+        put_u32(out, body.len() as u32);
+        out.extend_from_slice(&body);
+    }
+}
+
+fn write_attribute_body(body: &AttributeBody, out: &mut Vec<u8>) {
+    match *body {
+        AttributeBody::Code(ref code) => {
+            put_u16(out, code.max_stack);
+            put_u16(out, code.max_locals);
+            put_u32(out, code.code.len() as u32);
+            out.extend_from_slice(&code.code);
+            put_u16(out, code.exception_table.len() as u16);
+            for handler in code.exception_table.iter() {
+                for value in [handler.start_pc, handler.end_pc, handler.handler_pc, handler.catch_type].iter() {
+                    put_u16(out, *value);
+                }
+            }
+            write_attributes(&code.attributes, out);
+        },
+        AttributeBody::StackMapTable(ref frames) => {
+            put_u16(out, frames.len() as u16);
+            for frame in frames.iter() {
+                write_frame(frame, out);
+            }
+        },
+        AttributeBody::LineNumberTable(ref line_numbers) => {
+            put_u16(out, line_numbers.len() as u16);
+            for line_number in line_numbers.iter() {
+                put_u16(out, line_number.start_pc);
+                put_u16(out, line_number.line_number);
+            }
+        },
+        AttributeBody::LocalVariableTable(ref variables) | AttributeBody::LocalVariableTypeTable(ref variables) => {
+            put_u16(out, variables.len() as u16);
+            for variable in variables.iter() {
+                for value in [variable.start_pc, variable.length, variable.name_index, variable.descriptor_index,
+                              variable.index].iter() {
+                    put_u16(out, *value);
+                }
+            }
+        },
+        AttributeBody::SourceFile{source_file_index} => put_u16(out, source_file_index),
+        AttributeBody::Raw(ref bytes) => out.extend_from_slice(bytes),
+    }
+}
+
+fn write_frame(frame: &StackMapFrame, out: &mut Vec<u8>) {
+    match *frame {
+        StackMapFrame::Same{offset_delta} => out.push(offset_delta as u8),
+        StackMapFrame::SameLocals1StackItem{offset_delta, ref stack} => {
+            out.push(64 + offset_delta as u8);
+            write_verification_type(stack, out);
+        },
+        StackMapFrame::SameLocals1StackItemExtended{offset_delta, ref stack} => {
+            out.push(247);
+            put_u16(out, offset_delta);
+            write_verification_type(stack, out);
+        },
+        StackMapFrame::Chop{offset_delta, count} => {
+            out.push(251 - count);
+            put_u16(out, offset_delta);
+        },
+        StackMapFrame::SameExtended{offset_delta} => {
+            out.push(251);
+            put_u16(out, offset_delta);
+        },
+        StackMapFrame::Append{offset_delta, ref locals} => {
+            out.push(251 + locals.len() as u8);
+            put_u16(out, offset_delta);
+            for local in locals.iter() {
+                write_verification_type(local, out);
+            }
+        },
+        StackMapFrame::Full{offset_delta, ref locals, ref stack} => {
+            out.push(255);
+            put_u16(out, offset_delta);
+            for types in [locals, stack].iter() {
+                put_u16(out, types.len() as u16);
+                for verification_type in types.iter() {
+                    write_verification_type(verification_type, out);
+                }
+            }
+        },
+    }
+}
+
+fn write_verification_type(verification_type: &VerificationType, out: &mut Vec<u8>) {
+    match *verification_type {
+        VerificationType::Top => out.push(0),
+        VerificationType::Integer => out.push(1),
+        VerificationType::Float => out.push(2),
+        VerificationType::Double => out.push(3),
+        VerificationType::Long => out.push(4),
+        VerificationType::Null => out.push(5),
+        VerificationType::UninitializedThis => out.push(6),
+        VerificationType::Object{class_index} => {
+            out.push(7);
+            put_u16(out, class_index);
+        },
+        VerificationType::Uninitialized{offset} => {
+            out.push(8);
+            put_u16(out, offset);
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // static int f(int x) { return x == 0 ? 1 : 0; } with a handler over the body for relocation only
+    fn test_class() -> ClassFile {
+        let mut constant_pool = ConstantPool::new();
+        let this_class = constant_pool.add_class("T").unwrap();
+        let super_class = constant_pool.add_class("java/lang/Object").unwrap();
+        let code_name = constant_pool.add_utf8("Code").unwrap();
+        let frames_name = constant_pool.add_utf8("StackMapTable").unwrap();
+        let lines_name = constant_pool.add_utf8("LineNumberTable").unwrap();
+        let source_name = constant_pool.add_utf8("SourceFile").unwrap();
+        let source_file_index = constant_pool.add_utf8("T.java").unwrap();
+        let code = Code{
+            max_stack: 1,
+            max_locals: 1,
+            code: vec![0x1a, 0x99, 0x00, 0x07, 0x04, 0xa7, 0x00, 0x04, 0x03, 0xac],
+            exception_table: vec![ExceptionHandler{start_pc: 0, end_pc: 9, handler_pc: 8, catch_type: 0}],
+            attributes: vec![
+                Attribute{name_index: frames_name, body: AttributeBody::StackMapTable(vec![
+                    StackMapFrame::Same{offset_delta: 8},
+                    StackMapFrame::SameLocals1StackItem{offset_delta: 0, stack: VerificationType::Integer},
+                ])},
+                Attribute{name_index: lines_name, body: AttributeBody::LineNumberTable(vec![
+                    LineNumber{start_pc: 0, line_number: 1}, LineNumber{start_pc: 9, line_number: 2},
+                ])},
+            ],
+        };
+        let method = Member{access_flags: 0x0008, name_index: constant_pool.add_utf8("f").unwrap(),
+                            descriptor_index: constant_pool.add_utf8("(I)I").unwrap(),
+                            attributes: vec![Attribute{name_index: code_name, body: AttributeBody::Code(code)}]};
+        ClassFile{minor_version: 0, major_version: 61, constant_pool, constant_pool_bytes: None, access_flags: 0x0021,
+                  this_class, super_class, interfaces: Vec::new(), fields: Vec::new(), methods: vec![method],
+                  attributes: vec![Attribute{name_index: source_name, body: AttributeBody::SourceFile{source_file_index}}]}
+    }
+
+    #[test]
+    fn test_write_parse() {
+        let class = test_class();
+        let bytes = class.write();
+        assert_eq!(bytes[..8], [0xca, 0xfe, 0xba, 0xbe, 0x00, 0x00, 0x00, 0x3d]);
+        let parsed = ClassFile::parse(&bytes).unwrap();
+        assert_eq!(parsed.class_name(), Ok("T"));
+        assert_eq!(parsed.methods, class.methods);
+        assert_eq!(parsed.attributes, class.attributes);
+        assert_eq!(parsed.write(), bytes);
+        assert_eq!(ClassFile::parse(&bytes[..bytes.len() - 1]), Err(ClassFileError::Truncated));
+        assert_eq!(ClassFile::parse(&[0xca, 0xfe, 0xd0, 0x0d]), Err(ClassFileError::InvalidMagic{magic: 0xcafed00d}));
+    }
+
+    #[test]
+    fn test_insert_probes() {
+        let mut class = test_class();
+        let counter = class.constant_pool_mut().add_fieldref("T", "counter", "J").unwrap();
+        // Type annotation targeting code offset 0, stale once the code moves
+        let annotations_name = class.constant_pool_mut().add_utf8("RuntimeVisibleTypeAnnotations").unwrap();
+        let annotation_type = class.constant_pool_mut().add_utf8("LA;").unwrap();
+        let [type_high, type_low] = annotation_type.to_be_bytes();
+        class.methods[0].code_mut().unwrap().attributes.push(Attribute{name_index: annotations_name,
+            body: AttributeBody::Raw(vec![0x00, 0x01, 0x43, 0x00, 0x00, 0x00, type_high, type_low, 0x00, 0x00])});
+        let entry = [Instruction::new(Opcode::Getstatic, Operands::ConstantPool(counter)),
+                     Instruction::new(Opcode::Lconst1, Operands::None), Instruction::new(Opcode::Ladd, Operands::None),
+                     Instruction::new(Opcode::Putstatic, Operands::ConstantPool(counter))];
+        let exit = [Instruction::new(Opcode::Iload0, Operands::None), Instruction::new(Opcode::Pop, Operands::None)];
+        class.instrument_method("f", "(I)I", &entry, &exit).unwrap();
+        let code = class.method("f", "(I)I").unwrap().code().unwrap();
+        let [high, low] = counter.to_be_bytes();
+        // Branch to the return lands on the exit probe
+        assert_eq!(code.code, vec![0xb2, high, low, 0x0a, 0x61, 0xb3, high, low, 0x1a, 0x99, 0x00, 0x07, 0x04, 0xa7, 0x00,
+                                   0x04, 0x03, 0x1a, 0x57, 0xac]);
+        assert_eq!(code.max_stack, 5);
+        assert_eq!(code.exception_table, vec![ExceptionHandler{start_pc: 8, end_pc: 17, handler_pc: 16, catch_type: 0}]);
+        assert_eq!(code.attributes[0].body, AttributeBody::StackMapTable(vec![
+            StackMapFrame::Same{offset_delta: 16},
+            StackMapFrame::SameLocals1StackItem{offset_delta: 0, stack: VerificationType::Integer},
+        ]));
+        assert_eq!(code.attributes[1].body, AttributeBody::LineNumberTable(vec![
+            LineNumber{start_pc: 0, line_number: 1}, LineNumber{start_pc: 17, line_number: 2},
+        ]));
+        assert_eq!(code.attributes.len(), 2);
+        let bytes = class.write();
+        assert_eq!(ClassFile::parse(&bytes).unwrap().write(), bytes);
+    }
+
+    #[test]
+    fn test_insert_probes_extends_frames() {
+        let mut class = test_class();
+        let entry = vec![Instruction::new(Opcode::Nop, Operands::None); 60];
+        class.instrument_method("f", "(I)I", &entry, &[]).unwrap();
+        let code = class.method("f", "(I)I").unwrap().code().unwrap();
+        assert_eq!(code.code.len(), 70);
+        assert_eq!(code.max_stack, 1);
+        assert_eq!(code.attributes[0].body, AttributeBody::StackMapTable(vec![
+            StackMapFrame::SameExtended{offset_delta: 68},
+            StackMapFrame::SameLocals1StackItem{offset_delta: 0, stack: VerificationType::Integer},
+        ]));
+    }
+
+    #[test]
+    fn test_insert_probes_widens_branches() {
+        let mut class = test_class();
+        // static int f(int x) { int y; while ((y = x) != 0) { x--; } return 1; }
+        let code = class.methods[0].code_mut().unwrap();
+        code.max_locals = 2;
+        code.code = vec![0x1a, 0x3c, 0x1b, 0x9a, 0x00, 0x05, 0x04, 0xac, 0x84, 0x00, 0xff, 0xa7, 0xff, 0xf5];
+        code.exception_table.clear();
+        code.attributes = vec![Attribute{name_index: code.attributes[0].name_index, body: AttributeBody::StackMapTable(vec![
+            StackMapFrame::Same{offset_delta: 0},
+            StackMapFrame::Append{offset_delta: 7, locals: vec![VerificationType::Integer]},
+        ])}];
+        let exit = vec![Instruction::new(Opcode::Nop, Operands::None); 32765];
+        class.instrument_method("f", "(I)I", &[], &exit).unwrap();
+        let code = class.method("f", "(I)I").unwrap().code().unwrap();
+        assert_eq!(code.code.len(), 32786);
+        // ifne becomes ifeq over a goto_w, and the goto back to the start becomes a goto_w
+        assert_eq!(code.code[..11], [0x1a, 0x3c, 0x1b, 0x99, 0x00, 0x08, 0xc8, 0x00, 0x00, 0x80, 0x04]);
+        assert_eq!(code.code[32777..], [0xac, 0x84, 0x00, 0xff, 0xc8, 0xff, 0xff, 0x7f, 0xf3]);
+        // Instruction after the goto_w is a branch target now and gets a frame
+        assert_eq!(code.attributes[0].body, AttributeBody::StackMapTable(vec![
+            StackMapFrame::Same{offset_delta: 0},
+            StackMapFrame::Append{offset_delta: 10, locals: vec![VerificationType::Integer]},
+            StackMapFrame::SameExtended{offset_delta: 32766},
+        ]));
+    }
+
+    #[test]
+    fn test_invalid_probes() {
+        let mut class = test_class();
+        let push = [Instruction::new(Opcode::Iconst0, Operands::None)];
+        assert_eq!(class.instrument_method("f", "(I)I", &push, &[]), Err(ClassFileError::UnbalancedProbe));
+        let store = [Instruction::new(Opcode::Iconst0, Operands::None), Instruction::new(Opcode::Istore0, Operands::None)];
+        assert_eq!(class.instrument_method("f", "(I)I", &[], &store),
+                   Err(ClassFileError::UnsupportedProbeInstruction{opcode: Opcode::Istore0}));
+        assert_eq!(class.instrument_method("g", "()V", &[], &[]),
+                   Err(ClassFileError::MethodNotFound{name: "g".to_string(), descriptor: "()V".to_string()}));
+        assert_eq!(class, test_class());
+    }
+
+}
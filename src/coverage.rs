@@ -0,0 +1,168 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+// Line coverage collected with breakpoints. Every line of a selected class gets a breakpoint at its first bci, the
+// breakpoint is cleared on its first hit, so covered code runs without overhead afterwards. Lines are only known as
+// executed or not, no hit counts are kept.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+use std::sync::Mutex;
+
+#[derive(Debug)]
+pub struct Coverage {
+    // Class name prefixes in internal form, `com/example/`
+    prefixes: Vec<String>,
+    state: Mutex<CoverageState>,
+}
+
+#[derive(Debug, Default)]
+struct CoverageState {
+    // Lines and whether they were executed keyed by source path
+    files: BTreeMap<String, BTreeMap<i32, bool>>,
+    // Breakpoints not hit yet keyed by method and location, with source path and line they stand for
+    breakpoints: HashMap<(usize, i64), (String, i32)>,
+    methods: HashSet<usize>,
+}
+
+impl Coverage {
+
+    pub fn new(prefixes: &[String]) -> Coverage {
+        Coverage{prefixes: prefixes.iter().map(|p| p.replace('.', "/")).collect(), state: Default::default()}
+    }
+
+    // Signature of the class, `Lcom/example/Main;`
+    pub fn is_selected(&self, class_signature: &str) -> bool {
+        match internal_name(class_signature) {
+            Some(name) => self.prefixes.iter().any(|p| name.starts_with(p.as_str())),
+            None => false,
+        }
+    }
+
+    // Registers lines of the method and returns locations to set breakpoints at, nothing if method is known already
+    pub fn add_method(&self, method: usize, source_path: &str, line_numbers: &[rvmti_api::LineNumberEntry]) -> Vec<i64> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if !state.methods.insert(method) {
+            return Vec::new();
+        }
+        let mut first_locations: BTreeMap<i32, i64> = BTreeMap::new();
+        for entry in line_numbers {
+            let location = first_locations.entry(entry.line_number).or_insert(entry.start_location);
+            *location = (*location).min(entry.start_location);
+        }
+        let lines = state.files.entry(source_path.to_string()).or_default();
+        for line in first_locations.keys() {
+            lines.entry(*line).or_insert(false);
+        }
+        for (line, location) in first_locations.iter() {
+            state.breakpoints.insert((method, *location), (source_path.to_string(), *line));
+        }
+        first_locations.values().copied().collect()
+    }
+
+    // Returns false for breakpoints set by someone else or hit already, only the first hit clears the breakpoint
+    pub fn hit(&self, method: usize, location: i64) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        match state.breakpoints.remove(&(method, location)) {
+            Some((source_path, line)) => {
+                if let Some(hit) = state.files.get_mut(&source_path).and_then(|lines| lines.get_mut(&line)) {
+                    *hit = true;
+                }
+                true
+            },
+            None => false,
+        }
+    }
+
+    // Line whose breakpoint could not be set is left out of the report unless known as executed otherwise
+    pub fn breakpoint_failed(&self, method: usize, location: i64) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((source_path, line)) = state.breakpoints.remove(&(method, location)) {
+            let pending = state.breakpoints.values().any(|(p, l)| *p == source_path && *l == line);
+            if let Some(lines) = state.files.get_mut(&source_path) {
+                if !pending && lines.get(&line) == Some(&false) {
+                    lines.remove(&line);
+                }
+            }
+        }
+    }
+
+    // One record per source file, see `geninfo(1)`
+    pub fn lcov(&self) -> String {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut result = String::new();
+        for (source_path, lines) in state.files.iter().filter(|(_, lines)| !lines.is_empty()) {
+            let _ = writeln!(result, "TN:\nSF:{}", source_path);
+            for (line, hit) in lines.iter() {
+                let _ = writeln!(result, "DA:{},{}", line, *hit as u32);
+            }
+            let _ = writeln!(result, "LF:{}\nLH:{}\nend_of_record", lines.len(), lines.values().filter(|h| **h).count());
+        }
+        result
+    }
+
+}
+
+// Path relative to the source root, nested classes share the source file of the outermost class
+pub fn source_path(class_signature: &str, source_file_name: Option<&str>) -> String {
+    let name = internal_name(class_signature).unwrap_or(class_signature);
+    let (package, simple_name) = match name.rfind('/') {
+        Some(index) => name.split_at(index + 1),
+        None => ("", name),
+    };
+    match source_file_name {
+        Some(file_name) => format!("{}{}", package, file_name),
+        None => format!("{}{}.java", package, simple_name.split('$').next().unwrap_or(simple_name)),
+    }
+}
+
+fn internal_name(class_signature: &str) -> Option<&str> {
+    class_signature.strip_prefix('L').and_then(|s| s.strip_suffix(';'))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn line(start_location: i64, line_number: i32) -> rvmti_api::LineNumberEntry {
+        rvmti_api::LineNumberEntry{start_location, line_number}
+    }
+
+    #[test]
+    fn test_selection_and_source_path() {
+        let coverage = Coverage::new(&["com.example.".to_string(), "org.example.Main".to_string()]);
+        assert!(coverage.is_selected("Lcom/example/Worker;"));
+        assert!(coverage.is_selected("Lorg/example/Main$1;"));
+        assert!(!coverage.is_selected("Lorg/example/Other;"));
+        assert!(!coverage.is_selected("[Lcom/example/Worker;"));
+        assert_eq!(source_path("Lcom/example/Worker$Task;", Some("Worker.java")), "com/example/Worker.java");
+        assert_eq!(source_path("Lcom/example/Worker$Task;", None), "com/example/Worker.java");
+        assert_eq!(source_path("LMain;", Some("Main.kt")), "Main.kt");
+    }
+
+    #[test]
+    fn test_breakpoints_cleared_on_first_hit() {
+        let coverage = Coverage::new(&["com.example.".to_string()]);
+        // Loop condition at the end of the method belongs to the line of the loop
+        let run = [line(0, 10), line(4, 11), line(9, 12), line(15, 11), line(20, 13)];
+        assert_eq!(coverage.add_method(1, "com/example/Worker.java", &run), vec![0, 4, 9, 20]);
+        assert_eq!(coverage.add_method(1, "com/example/Worker.java", &run), Vec::<i64>::new());
+        assert_eq!(coverage.add_method(2, "com/example/Worker.java", &[line(0, 20), line(3, 21)]), vec![0, 3]);
+        assert_eq!(coverage.add_method(3, "com/example/Task.java", &[line(0, 5)]), vec![0]);
+        assert!(coverage.hit(1, 0));
+        assert!(coverage.hit(1, 4));
+        assert!(!coverage.hit(1, 4));
+        assert!(!coverage.hit(1, 15));
+        assert!(coverage.hit(2, 3));
+        coverage.breakpoint_failed(1, 20);
+        assert_eq!(coverage.lcov(), "TN:\nSF:com/example/Task.java\nDA:5,0\nLF:1\nLH:0\nend_of_record\n\
+                                     TN:\nSF:com/example/Worker.java\nDA:10,1\nDA:11,1\nDA:12,0\nDA:20,0\nDA:21,1\n\
+                                     LF:5\nLH:3\nend_of_record\n");
+    }
+
+}
@@ -9,18 +9,18 @@ use std::fmt;
 
 use thiserror::Error;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct BinaryName {
     packages: Vec<String>,
     class: String,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ClassType {
     name: BinaryName,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ScalarFieldType {
     Byte,
     Char,
@@ -33,18 +33,27 @@ pub enum ScalarFieldType {
     Class{name: BinaryName},
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct FieldType {
     scalar_type: ScalarFieldType,
     dimensions: usize,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct MethodType {
     parameter_types: Vec<FieldType>,
     return_type: Option<FieldType>,
 }
 
+// Native method symbol, `Java_<class>_<method>` or `Java_<class>_<method>__<parameters>` for overloaded methods.
+// Return type is not part of the symbol, method type of a demangled long name returns void
+#[derive(Debug, Clone, PartialEq)]
+pub struct JniSymbol {
+    class_type: ClassType,
+    method_name: String,
+    method_type: Option<MethodType>,
+}
+
 impl BinaryName {
 
     pub fn new(mangled_name: &str) -> Result<BinaryName, DemangleError> {
@@ -59,6 +68,15 @@ impl BinaryName {
         return Ok(BinaryName{packages: head.iter().map(|s| s.to_string()).collect(), class: last.to_string()});
     }
 
+    // Name with slashes, as in descriptors
+    pub fn internal_name(&self) -> String {
+        if self.packages.is_empty() {
+            self.class.clone()
+        } else {
+            format!("{}/{}", self.packages.join("/"), self.class)
+        }
+    }
+
     pub fn package_as_file_path(&self, source_file_name: &str) -> String {
         if self.packages.is_empty() {
             source_file_name.to_string()
@@ -109,7 +127,7 @@ impl fmt::Display for ClassType {
 impl FieldType {
 
     pub fn new(mangled_field_type: &str) -> Result<FieldType, DemangleError> {
-        let mut dimensions = 0 as usize;
+        let mut dimensions = 0usize;
         let mut state = FieldTypeParserState::Dimensions;
         let mut char_iter = mangled_field_type.chars();
         let mut class_name = String::from("");
@@ -120,7 +138,7 @@ impl FieldType {
                     match state {
                         FieldTypeParserState::Dimensions => {
                             match c {
-                                '[' => dimensions += 1 as usize,
+                                '[' => dimensions += 1usize,
                                 'B' => state = FieldTypeParserState::TypeTagByte,
                                 'C' => state = FieldTypeParserState::TypeTagChar,
                                 'D' => state = FieldTypeParserState::TypeTagDouble,
@@ -166,6 +184,29 @@ impl FieldType {
         }
     }
 
+    pub fn descriptor(&self) -> String {
+        let element = match self.scalar_type {
+            ScalarFieldType::Byte => "B".to_string(),
+            ScalarFieldType::Char => "C".to_string(),
+            ScalarFieldType::Double => "D".to_string(),
+            ScalarFieldType::Float => "F".to_string(),
+            ScalarFieldType::Integer => "I".to_string(),
+            ScalarFieldType::Long => "J".to_string(),
+            ScalarFieldType::Short => "S".to_string(),
+            ScalarFieldType::Boolean => "Z".to_string(),
+            ScalarFieldType::Class{ref name} => format!("L{};", name.internal_name()),
+        };
+        format!("{}{}", "[".repeat(self.dimensions), element)
+    }
+
+    // Operand stack and local variable slots, long and double take two
+    pub fn slots(&self) -> usize {
+        match self.scalar_type {
+            ScalarFieldType::Long | ScalarFieldType::Double if self.dimensions == 0 => 2,
+            _ => 1,
+        }
+    }
+
 }
 
 enum FieldTypeParserState {
@@ -227,8 +268,7 @@ impl MethodType {
 
     pub fn new(mangled_method_type: &str) -> Result<MethodType, DemangleError> {
         let mut parameter_types: Vec<FieldType> = Vec::new();
-        let return_type: Option<FieldType>;
-        let mut dimensions = 0 as usize;
+        let mut dimensions = 0usize;
         let mut state = MethodTypeParserState::ParametersStart;
         let mut char_iter = mangled_method_type.chars();
         let mut class_name = String::from("");
@@ -245,7 +285,7 @@ impl MethodType {
                         },
                         MethodTypeParserState::ParameterDimensions => {
                             match c {
-                                '[' => dimensions += 1 as usize,
+                                '[' => dimensions += 1usize,
                                 'B' => {
                                     parameter_types.push(FieldType{scalar_type: ScalarFieldType::Byte, dimensions});
                                     dimensions = 0;
@@ -279,13 +319,7 @@ impl MethodType {
                                     dimensions = 0;
                                 },
                                 'L' => state = MethodTypeParserState::ParameterTypeTagClass,
-                                ')' => {
-                                    if dimensions == 0 {
-                                        state = MethodTypeParserState::ReturnTypeDimensions
-                                    } else {
-                                        return Err(DemangleError::DemangleFailed)
-                                    }
-                                },
+                                ')' if dimensions == 0 => state = MethodTypeParserState::ReturnTypeDimensions,
                                 _ => return Err(DemangleError::DemangleFailed),
                             }
                         },
@@ -307,7 +341,7 @@ impl MethodType {
                         },
                         MethodTypeParserState::ReturnTypeDimensions => {
                             match c {
-                                '[' => dimensions += 1 as usize,
+                                '[' => dimensions += 1usize,
                                 'B' => state = MethodTypeParserState::ReturnTypeTagByte,
                                 'C' => state = MethodTypeParserState::ReturnTypeTagChar,
                                 'D' => state = MethodTypeParserState::ReturnTypeTagDouble,
@@ -317,13 +351,7 @@ impl MethodType {
                                 'S' => state = MethodTypeParserState::ReturnTypeTagShort,
                                 'Z' => state = MethodTypeParserState::ReturnTypeTagBoolean,
                                 'L' => state = MethodTypeParserState::ReturnTypeTagClass,
-                                'V' => {
-                                    if dimensions == 0 {
-                                        state = MethodTypeParserState::ReturnTypeTagVoid
-                                    } else {
-                                        return Err(DemangleError::DemangleFailed)
-                                    }
-                                },
+                                'V' if dimensions == 0 => state = MethodTypeParserState::ReturnTypeTagVoid,
                                 _ => return Err(DemangleError::DemangleFailed),
                             }
                         },
@@ -340,28 +368,45 @@ impl MethodType {
                 None => break,
             };
         }
-        match state {
-            MethodTypeParserState::ReturnTypeTagByte => return_type = Some(FieldType{scalar_type: ScalarFieldType::Byte, dimensions}),
-            MethodTypeParserState::ReturnTypeTagChar => return_type = Some(FieldType{scalar_type: ScalarFieldType::Char, dimensions}),
-            MethodTypeParserState::ReturnTypeTagDouble => return_type = Some(FieldType{scalar_type: ScalarFieldType::Double, dimensions}),
-            MethodTypeParserState::ReturnTypeTagFloat => return_type = Some(FieldType{scalar_type: ScalarFieldType::Float, dimensions}),
-            MethodTypeParserState::ReturnTypeTagInteger => return_type = Some(FieldType{scalar_type: ScalarFieldType::Integer, dimensions}),
-            MethodTypeParserState::ReturnTypeTagLong => return_type = Some(FieldType{scalar_type: ScalarFieldType::Long, dimensions}),
-            MethodTypeParserState::ReturnTypeTagShort => return_type = Some(FieldType{scalar_type: ScalarFieldType::Short, dimensions}),
-            MethodTypeParserState::ReturnTypeTagBoolean => return_type = Some(FieldType{scalar_type: ScalarFieldType::Boolean, dimensions}),
-            MethodTypeParserState::ReturnTypeTagVoid => return_type = None,
+        let return_type = match state {
+            MethodTypeParserState::ReturnTypeTagByte => Some(FieldType{scalar_type: ScalarFieldType::Byte, dimensions}),
+            MethodTypeParserState::ReturnTypeTagChar => Some(FieldType{scalar_type: ScalarFieldType::Char, dimensions}),
+            MethodTypeParserState::ReturnTypeTagDouble => Some(FieldType{scalar_type: ScalarFieldType::Double, dimensions}),
+            MethodTypeParserState::ReturnTypeTagFloat => Some(FieldType{scalar_type: ScalarFieldType::Float, dimensions}),
+            MethodTypeParserState::ReturnTypeTagInteger => Some(FieldType{scalar_type: ScalarFieldType::Integer, dimensions}),
+            MethodTypeParserState::ReturnTypeTagLong => Some(FieldType{scalar_type: ScalarFieldType::Long, dimensions}),
+            MethodTypeParserState::ReturnTypeTagShort => Some(FieldType{scalar_type: ScalarFieldType::Short, dimensions}),
+            MethodTypeParserState::ReturnTypeTagBoolean => Some(FieldType{scalar_type: ScalarFieldType::Boolean, dimensions}),
+            MethodTypeParserState::ReturnTypeTagVoid => None,
             MethodTypeParserState::ReturnClassNameEnd => {
                 if class_name.is_empty() {
                     return Err(DemangleError::DemangleFailed);
                 }
                 let name = BinaryName::new(&class_name)?;
-                return_type = Some(FieldType{scalar_type: ScalarFieldType::Class {name}, dimensions});
+                Some(FieldType{scalar_type: ScalarFieldType::Class {name}, dimensions})
             },
             _ => return Err(DemangleError::DemangleFailed),
-        }
+        };
         return Ok(MethodType{parameter_types, return_type});
     }
 
+    pub fn parameter_types(&self) -> &[FieldType] {
+        &self.parameter_types
+    }
+
+    // None for void methods
+    pub fn return_type(&self) -> Option<&FieldType> {
+        self.return_type.as_ref()
+    }
+
+    pub fn parameter_slots(&self) -> usize {
+        return self.parameter_types.iter().map(FieldType::slots).sum();
+    }
+
+    pub fn return_slots(&self) -> usize {
+        return self.return_type.as_ref().map_or(0, FieldType::slots);
+    }
+
     pub fn display_as_method_definition<T: fmt::Display>(&self, method_name: &str, class_name: &T) -> String {
         let return_type = match self.return_type {
             Some(ref t) => format!("{}", t),
@@ -405,6 +450,168 @@ impl fmt::Display for MethodType {
 
 }
 
+impl JniSymbol {
+
+    pub fn new(mangled_symbol: &str) -> Result<JniSymbol, DemangleError> {
+        let body = match mangled_symbol.strip_prefix("Java_") {
+            Some(body) => body,
+            None => return Err(DemangleError::DemangleFailed),
+        };
+        let (name, parameters) = match jni_parameters_start(body) {
+            Some(index) => (&body[..index], Some(&body[index + 2..])),
+            None => (body, None),
+        };
+        let mut segments = unmangle_jni(name)?;
+        if segments.len() < 2 || segments.iter().any(String::is_empty) {
+            return Err(DemangleError::DemangleFailed);
+        }
+        let method_name = segments.pop().unwrap();
+        let class_type = ClassType{name: BinaryName::new(&segments.join("/"))?};
+        let method_type = match parameters {
+            Some(parameters) => Some(MethodType::new(&format!("({})V", unmangle_jni(parameters)?.join("/")))?),
+            None => None,
+        };
+        return Ok(JniSymbol{class_type, method_name, method_type});
+    }
+
+    // Short name without method type, long name needed for overloaded native methods otherwise
+    pub fn for_method(class_type: ClassType, method_name: &str, method_type: Option<MethodType>) -> JniSymbol {
+        JniSymbol{class_type, method_name: method_name.to_string(), method_type}
+    }
+
+    pub fn class_type(&self) -> &ClassType {
+        &self.class_type
+    }
+
+    pub fn method_name(&self) -> &str {
+        &self.method_name
+    }
+
+    pub fn method_type(&self) -> Option<&MethodType> {
+        self.method_type.as_ref()
+    }
+
+    // Short name stands for every overload of the method. `is_none_or` would need a newer toolchain
+    #[allow(clippy::unnecessary_map_or)]
+    pub fn matches(&self, class_type: &ClassType, method_name: &str, method_type: &MethodType) -> bool {
+        self.class_type == *class_type && self.method_name == method_name
+            && self.method_type.as_ref().map_or(true, |t| t.parameter_types == method_type.parameter_types)
+    }
+
+    // JVM looks a native method up by its short name first, then by its long name
+    pub fn is_bound_by(&self, symbol: &str) -> bool {
+        let short_name = JniSymbol{method_type: None, ..self.clone()};
+        symbol == short_name.mangle() || symbol == self.mangle()
+    }
+
+    pub fn mangle(&self) -> String {
+        let mut symbol = String::from("Java_");
+        mangle_jni(&self.class_type.name.internal_name(), &mut symbol);
+        symbol.push('_');
+        mangle_jni(&self.method_name, &mut symbol);
+        if let Some(ref method_type) = self.method_type {
+            symbol.push_str("__");
+            for parameter_type in method_type.parameter_types.iter() {
+                mangle_jni(&parameter_type.descriptor(), &mut symbol);
+            }
+        }
+        symbol
+    }
+
+}
+
+impl fmt::Display for JniSymbol {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.method_type {
+            Some(ref method_type) => {
+                let parameter_types: Vec<String> = method_type.parameter_types.iter().map(|t| format!("{}", t)).collect();
+                write!(f, "{}.{}({})", self.class_type, self.method_name, parameter_types.join(", "))
+            },
+            None => write!(f, "{}.{}", self.class_type, self.method_name),
+        }
+    }
+
+}
+
+// Slashes become underscores, other characters but ASCII letters and digits are escaped, outside the BMP as surrogates
+fn mangle_jni(name: &str, symbol: &mut String) {
+    for unit in name.encode_utf16() {
+        match char::from_u32(unit as u32) {
+            Some('/') => symbol.push('_'),
+            Some('_') => symbol.push_str("_1"),
+            Some(';') => symbol.push_str("_2"),
+            Some('[') => symbol.push_str("_3"),
+            Some(c) if c.is_ascii_alphanumeric() => symbol.push(c),
+            _ => symbol.push_str(&format!("_0{:04x}", unit)),
+        }
+    }
+}
+
+// Underscores of names are escaped, so two in a row separate the parameters unless the second one starts an escape
+fn jni_parameters_start(mangled: &str) -> Option<usize> {
+    let bytes = mangled.as_bytes();
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] != b'_' {
+            index += 1;
+            continue;
+        }
+        match bytes.get(index + 1) {
+            Some(b'_') if !matches!(bytes.get(index + 2), Some(b'0'..=b'3')) => return Some(index),
+            Some(b'0') => index += 6,
+            Some(b'1'..=b'3') => index += 2,
+            _ => index += 1,
+        }
+    }
+    None
+}
+
+// Splits at unescaped underscores, which stand for slashes
+fn unmangle_jni(mangled: &str) -> Result<Vec<String>, DemangleError> {
+    let mut segments = Vec::new();
+    let mut units: Vec<u16> = Vec::new();
+    let mut chars = mangled.chars().peekable();
+    loop {
+        match chars.next() {
+            Some('_') => {
+                match chars.peek() {
+                    Some('0') => {
+                        chars.next();
+                        let hex: String = chars.by_ref().take(4).collect();
+                        if hex.len() != 4 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                            return Err(DemangleError::DemangleFailed);
+                        }
+                        units.push(u16::from_str_radix(&hex, 16).map_err(|_| DemangleError::DemangleFailed)?);
+                    },
+                    Some('1') => {
+                        chars.next();
+                        units.push('_' as u16);
+                    },
+                    Some('2') => {
+                        chars.next();
+                        units.push(';' as u16);
+                    },
+                    Some('3') => {
+                        chars.next();
+                        units.push('[' as u16);
+                    },
+                    _ => {
+                        segments.push(String::from_utf16(&units).map_err(|_| DemangleError::DemangleFailed)?);
+                        units.clear();
+                    },
+                }
+            },
+            Some(c) if c.is_ascii_alphanumeric() => units.push(c as u16),
+            Some(_) => return Err(DemangleError::DemangleFailed),
+            None => {
+                segments.push(String::from_utf16(&units).map_err(|_| DemangleError::DemangleFailed)?);
+                return Ok(segments);
+            },
+        }
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum DemangleError {
     #[error("Failed to demangle")]
@@ -643,6 +850,14 @@ mod tests {
         assert!(super::MethodType::new("(IDLjava/lang/Thread;)Ljava/lang/Object;B").is_err());
     }
 
+    #[test]
+    fn test_slots() {
+        let method_type = super::MethodType::new("(JI[DLjava/lang/Object;D)J").unwrap();
+        assert_eq!((method_type.parameter_slots(), method_type.return_slots()), (7, 2));
+        assert_eq!(super::MethodType::new("()V").unwrap().return_slots(), 0);
+        assert_eq!(super::FieldType::new("[J").unwrap().slots(), 1);
+    }
+
     #[test]
     fn test_display_as_method_definition() {
         assert_eq!(super::MethodType::new("(IDLjava/lang/Thread;)Ljava/lang/Object;").unwrap()
@@ -665,4 +880,86 @@ mod tests {
                    "java.lang.Object[] java.lang.Thread.test(int[][] p0, double[] p1, java.lang.Thread p2)");
     }
 
+    #[test]
+    fn test_demangle_jni_symbol_valid() {
+        assert_eq!(super::JniSymbol::new("Java_java_lang_ClassLoader_findLoadedClass0").unwrap(),
+                   super::JniSymbol{class_type: super::ClassType::new("Ljava/lang/ClassLoader;").unwrap(),
+                   method_name: "findLoadedClass0".to_owned(), method_type: None});
+        assert_eq!(super::JniSymbol::new("Java_java_util_zip_CRC32_update__II").unwrap(),
+                   super::JniSymbol{class_type: super::ClassType::new("Ljava/util/zip/CRC32;").unwrap(),
+                   method_name: "update".to_owned(), method_type: Some(super::MethodType::new("(II)V").unwrap())});
+        assert_eq!(super::JniSymbol::new("Java_com_example_My_1Class_run_1now__ILjava_lang_String_2_3_3J").unwrap(),
+                   super::JniSymbol{class_type: super::ClassType::new("Lcom/example/My_Class;").unwrap(),
+                   method_name: "run_now".to_owned(),
+                   method_type: Some(super::MethodType::new("(ILjava/lang/String;[[J)V").unwrap())});
+        assert_eq!(super::JniSymbol::new("Java_Native_run__").unwrap(),
+                   super::JniSymbol{class_type: super::ClassType::new("LNative;").unwrap(),
+                   method_name: "run".to_owned(), method_type: Some(super::MethodType::new("()V").unwrap())});
+        assert_eq!(super::JniSymbol::new("Java_caf_000e9_Na_0d835_0dcb3ve_m").unwrap(),
+                   super::JniSymbol{class_type: super::ClassType::new("Lcafé/Na\u{1d4b3}ve;").unwrap(),
+                   method_name: "m".to_owned(), method_type: None});
+    }
+
+    #[test]
+    fn test_demangle_jni_symbol_invalid() {
+        assert!(super::JniSymbol::new("").is_err());
+        assert!(super::JniSymbol::new("JVM_GetClassName").is_err());
+        assert!(super::JniSymbol::new("Java_Native").is_err());
+        assert!(super::JniSymbol::new("Java_Native_").is_err());
+        assert!(super::JniSymbol::new("Java__run").is_err());
+        assert!(super::JniSymbol::new("Java_com__Native_run").is_err());
+        assert!(super::JniSymbol::new("Java_Native_run__Q").is_err());
+        assert!(super::JniSymbol::new("Java_Native_run__Ljava_lang_String").is_err());
+        assert!(super::JniSymbol::new("Java_Native_r$n").is_err());
+        assert!(super::JniSymbol::new("Java_Native_r_00e").is_err());
+        assert!(super::JniSymbol::new("Java_Native_r_0zzzz").is_err());
+        assert!(super::JniSymbol::new("Java_Native_r_0d835").is_err());
+    }
+
+    #[test]
+    fn test_mangle_jni_symbol() {
+        let symbols = [
+            "Java_java_lang_ClassLoader_findLoadedClass0",
+            "Java_java_util_zip_CRC32_update__II",
+            "Java_com_example_My_1Class_run_1now__ILjava_lang_String_2_3_3J",
+            "Java_Native_run__",
+            "Java_caf_000e9_Na_0d835_0dcb3ve_m",
+            "Java_pkg__1Foo_run",
+            "Java_pkg__1Foo_run__I",
+            "Java_pkg__000e9t_m",
+            "Java_pkg_Foo__1run___3I",
+        ];
+        for symbol in symbols.iter() {
+            assert_eq!(super::JniSymbol::new(symbol).unwrap().mangle(), *symbol);
+        }
+        let symbol = super::JniSymbol::for_method(super::ClassType::new("Lcom/example/Caf\u{e9};").unwrap(), "hash$",
+                                                  Some(super::MethodType::new("([BLjava/lang/Object;)J").unwrap()));
+        assert_eq!(symbol.mangle(), "Java_com_example_Caf_000e9_hash_00024___3BLjava_lang_Object_2");
+        assert_eq!(format!("{}", symbol), "com.example.Caf\u{e9}.hash$(byte[], java.lang.Object)");
+        assert_eq!(format!("{}", super::JniSymbol::new("Java_pkg__1Foo_run").unwrap()), "pkg._Foo.run");
+        assert_eq!(format!("{}", super::JniSymbol::new("Java_pkg__000e9t_m__I").unwrap()), "pkg.\u{e9}t.m(int)");
+    }
+
+    #[test]
+    fn test_jni_symbol_is_bound_by() {
+        let symbol = super::JniSymbol::for_method(super::ClassType::new("Ljava/util/zip/CRC32;").unwrap(), "update",
+                                                  Some(super::MethodType::new("(II)I").unwrap()));
+        assert!(symbol.is_bound_by("Java_java_util_zip_CRC32_update"));
+        assert!(symbol.is_bound_by("Java_java_util_zip_CRC32_update__II"));
+        assert!(!symbol.is_bound_by("Java_java_util_zip_CRC32_update__I"));
+        assert!(!symbol.is_bound_by("Java_java_util_zip_CRC32_reset"));
+        assert!(!symbol.is_bound_by("update"));
+    }
+
+    #[test]
+    fn test_jni_symbol_matches() {
+        let class_type = super::ClassType::new("Ljava/util/zip/CRC32;").unwrap();
+        let method_type = super::MethodType::new("(II)I").unwrap();
+        assert!(super::JniSymbol::new("Java_java_util_zip_CRC32_update").unwrap().matches(&class_type, "update", &method_type));
+        assert!(super::JniSymbol::new("Java_java_util_zip_CRC32_update__II").unwrap().matches(&class_type, "update", &method_type));
+        assert!(!super::JniSymbol::new("Java_java_util_zip_CRC32_update__I").unwrap().matches(&class_type, "update", &method_type));
+        assert!(!super::JniSymbol::new("Java_java_util_zip_CRC32_reset").unwrap().matches(&class_type, "update", &method_type));
+        assert!(!super::JniSymbol::new("Java_java_util_zip_Adler32_update").unwrap().matches(&class_type, "update", &method_type));
+    }
+
 }
@@ -0,0 +1,541 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+// Stack map frames of instrumented code. Frames of the original code are expanded to the states they describe, states
+// at instructions that need a new frame are computed by simulating verification types from the closest frame before
+// them, and the table is encoded again from these states. Types at existing frames are taken as declared, so no types
+// need merging, which would take the class hierarchy
+
+use std::collections::HashMap;
+
+use rvmti_api::{Constant, ConstantPool, ConstantPoolError, Disassembly, Instruction, Opcode, Operands};
+
+use crate::classfile::{ClassFileError, StackMapFrame, VerificationType};
+use crate::demangle::MethodType;
+
+const ACC_STATIC: u16 = 0x0008;
+
+// Locals and operand stack as frames list them, long and double values take one entry
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FrameState {
+    pub(crate) locals: Vec<VerificationType>,
+    pub(crate) stack: Vec<VerificationType>,
+}
+
+// State during simulation, long and double locals are followed by a Top slot for their second half
+struct Slots {
+    locals: Vec<VerificationType>,
+    stack: Vec<VerificationType>,
+}
+
+impl FrameState {
+
+    fn to_slots(&self) -> Slots {
+        let mut locals = Vec::with_capacity(self.locals.len());
+        for local in self.locals.iter() {
+            locals.push(local.clone());
+            if is_wide(local) {
+                locals.push(VerificationType::Top);
+            }
+        }
+        Slots{locals, stack: self.stack.clone()}
+    }
+
+}
+
+impl Slots {
+
+    // Trailing unusable locals are left out, as frames do
+    fn to_state(&self) -> FrameState {
+        let mut locals = Vec::with_capacity(self.locals.len());
+        let mut index = 0;
+        while index < self.locals.len() {
+            locals.push(self.locals[index].clone());
+            index += if is_wide(&self.locals[index]) { 2 } else { 1 };
+        }
+        while locals.last() == Some(&VerificationType::Top) {
+            locals.pop();
+        }
+        FrameState{locals, stack: self.stack.clone()}
+    }
+
+    fn pop(&mut self, count: usize) -> Option<Vec<VerificationType>> {
+        if count > self.stack.len() {
+            return None;
+        }
+        Some(self.stack.split_off(self.stack.len() - count))
+    }
+
+    // Entries taking the given number of slots, a long or double value may not be split
+    fn pop_slots(&mut self, slots: usize) -> Option<Vec<VerificationType>> {
+        let mut popped = Vec::new();
+        let mut taken = 0;
+        while taken < slots {
+            let value = self.stack.pop()?;
+            taken += if is_wide(&value) { 2 } else { 1 };
+            popped.insert(0, value);
+        }
+        if taken == slots { Some(popped) } else { None }
+    }
+
+    fn load(&self, index: u16) -> Option<VerificationType> {
+        self.locals.get(index as usize).cloned()
+    }
+
+    fn store(&mut self, index: u16, value: VerificationType) {
+        let index = index as usize;
+        let end = index + if is_wide(&value) { 2 } else { 1 };
+        if self.locals.len() < end {
+            self.locals.resize(end, VerificationType::Top);
+        }
+        // Overwriting the second half of a long or double leaves the first half unusable
+        if index > 0 && is_wide(&self.locals[index - 1]) {
+            self.locals[index - 1] = VerificationType::Top;
+        }
+        if end < self.locals.len() && is_wide(&self.locals[end - 1]) {
+            self.locals[end] = VerificationType::Top;
+        }
+        if is_wide(&value) {
+            self.locals[index + 1] = VerificationType::Top;
+        }
+        self.locals[index] = value;
+    }
+
+    // Constructor call turns every reference to the object into the class
+    fn initialize(&mut self, object: &VerificationType, class_index: u16) {
+        for value in self.locals.iter_mut().chain(self.stack.iter_mut()) {
+            if value == object {
+                *value = VerificationType::Object{class_index};
+            }
+        }
+    }
+
+}
+
+// Locals on method entry, the receiver of a constructor is uninitialized until the super constructor is called
+pub(crate) fn initial_state(constant_pool: &mut ConstantPool, this_class: u16, access_flags: u16, name: &str,
+                            descriptor: &str) -> Result<FrameState, ClassFileError> {
+    let mut locals = Vec::new();
+    if access_flags & ACC_STATIC == 0 {
+        let this_name = constant_pool.class_name(this_class).map_err(ClassFileError::ConstantPoolError)?;
+        if name == "<init>" && this_name != "java/lang/Object" {
+            locals.push(VerificationType::UninitializedThis);
+        } else {
+            locals.push(VerificationType::Object{class_index: this_class});
+        }
+    }
+    let method_type = MethodType::new(descriptor)
+        .map_err(|_| ClassFileError::InvalidDescriptor{descriptor: descriptor.to_string()})?;
+    for parameter_type in method_type.parameter_types() {
+        locals.push(verification_type(constant_pool, &parameter_type.descriptor())?);
+    }
+    Ok(FrameState{locals, stack: Vec::new()})
+}
+
+// States by bci the frames stand for
+pub(crate) fn expand(initial: &FrameState, frames: &[StackMapFrame]) -> Result<Vec<(u32, FrameState)>, ClassFileError> {
+    let mut states = Vec::with_capacity(frames.len());
+    let mut locals = initial.locals.clone();
+    let mut offset: i64 = -1;
+    for frame in frames.iter() {
+        offset += frame.offset_delta() as i64 + 1;
+        let stack = match *frame {
+            StackMapFrame::Same{..} | StackMapFrame::SameExtended{..} => Vec::new(),
+            StackMapFrame::SameLocals1StackItem{ref stack, ..}
+            | StackMapFrame::SameLocals1StackItemExtended{ref stack, ..} => vec![stack.clone()],
+            StackMapFrame::Chop{count, ..} => {
+                if count as usize > locals.len() {
+                    return Err(ClassFileError::InvalidFrame{offset: offset as u32});
+                }
+                locals.truncate(locals.len() - count as usize);
+                Vec::new()
+            },
+            StackMapFrame::Append{locals: ref appended, ..} => {
+                locals.extend_from_slice(appended);
+                Vec::new()
+            },
+            StackMapFrame::Full{locals: ref full_locals, ref stack, ..} => {
+                locals = full_locals.clone();
+                stack.clone()
+            },
+        };
+        states.push((offset as u32, FrameState{locals: locals.clone(), stack}));
+    }
+    Ok(states)
+}
+
+// Frames for states sorted by bci, each in the shortest form relative to the previous one
+pub(crate) fn encode(initial: &FrameState, states: &[(u32, FrameState)]) -> Vec<StackMapFrame> {
+    let mut frames = Vec::with_capacity(states.len());
+    let mut previous_locals = &initial.locals;
+    let mut previous_offset = None;
+    for (offset, state) in states.iter() {
+        let offset_delta = match previous_offset {
+            Some(previous_offset) => (offset - previous_offset - 1) as u16,
+            None => *offset as u16,
+        };
+        frames.push(compact_frame(previous_locals, state, offset_delta));
+        previous_locals = &state.locals;
+        previous_offset = Some(*offset);
+    }
+    frames
+}
+
+fn compact_frame(previous_locals: &[VerificationType], state: &FrameState, offset_delta: u16) -> StackMapFrame {
+    let locals = &state.locals;
+    if locals.as_slice() == previous_locals && state.stack.len() <= 1 {
+        return match (state.stack.first(), offset_delta) {
+            (None, 0..=63) => StackMapFrame::Same{offset_delta},
+            (None, _) => StackMapFrame::SameExtended{offset_delta},
+            (Some(stack), 0..=63) => StackMapFrame::SameLocals1StackItem{offset_delta, stack: stack.clone()},
+            (Some(stack), _) => StackMapFrame::SameLocals1StackItemExtended{offset_delta, stack: stack.clone()},
+        };
+    }
+    if state.stack.is_empty() && locals.len() < previous_locals.len() && previous_locals.len() - locals.len() <= 3
+        && previous_locals.starts_with(locals) {
+        return StackMapFrame::Chop{offset_delta, count: (previous_locals.len() - locals.len()) as u8};
+    }
+    if state.stack.is_empty() && locals.len() > previous_locals.len() && locals.len() - previous_locals.len() <= 3
+        && locals.starts_with(previous_locals) {
+        return StackMapFrame::Append{offset_delta, locals: locals[previous_locals.len()..].to_vec()};
+    }
+    StackMapFrame::Full{offset_delta, locals: locals.clone(), stack: state.stack.clone()}
+}
+
+// States before the instructions at the given bcis. Frames are taken as declared where there are any, code only reached
+// by branches must have a frame
+pub(crate) fn simulate(constant_pool: &mut ConstantPool, this_class: u16, disassembly: &Disassembly, initial: &FrameState,
+                       frames: &[(u32, FrameState)], bcis: &[u32]) -> Result<HashMap<u32, FrameState>, ClassFileError> {
+    let mut states = HashMap::new();
+    let mut frames = frames.iter().peekable();
+    let mut slots = Some(initial.to_slots());
+    for instruction in disassembly.instructions() {
+        while let Some((offset, frame)) = frames.peek() {
+            if *offset > instruction.bci {
+                break;
+            }
+            if *offset == instruction.bci {
+                slots = Some(frame.to_slots());
+            }
+            frames.next();
+        }
+        if bcis.contains(&instruction.bci) {
+            let state = slots.as_ref().ok_or(ClassFileError::InvalidFrame{offset: instruction.bci})?.to_state();
+            states.insert(instruction.bci, state);
+        }
+        slots = match slots {
+            Some(slots) => step(constant_pool, this_class, disassembly, instruction, slots)?,
+            None => None,
+        };
+    }
+    Ok(states)
+}
+
+// State after the instruction, none if execution does not fall through to the next one
+fn step(constant_pool: &mut ConstantPool, this_class: u16, disassembly: &Disassembly, instruction: &Instruction,
+        mut slots: Slots) -> Result<Option<Slots>, ClassFileError> {
+    let mismatch = || ClassFileError::FrameMismatch{bci: instruction.bci, opcode: instruction.opcode};
+    let index = match instruction.operands {
+        Operands::ConstantPool(index) | Operands::InvokeInterface{index, ..}
+        | Operands::MultiANewArray{index, ..} => Some(index),
+        _ => None,
+    };
+    let local = match instruction.operands {
+        Operands::Local(local) => Some(local),
+        _ => short_form_local(instruction.opcode),
+    };
+    let pushed = match instruction.opcode {
+        Opcode::Nop | Opcode::Iinc => None,
+        Opcode::AconstNull => Some(VerificationType::Null),
+        Opcode::IconstM1 | Opcode::Iconst0 | Opcode::Iconst1 | Opcode::Iconst2 | Opcode::Iconst3 | Opcode::Iconst4
+        | Opcode::Iconst5 | Opcode::Bipush | Opcode::Sipush => Some(VerificationType::Integer),
+        Opcode::Lconst0 | Opcode::Lconst1 => Some(VerificationType::Long),
+        Opcode::Fconst0 | Opcode::Fconst1 | Opcode::Fconst2 => Some(VerificationType::Float),
+        Opcode::Dconst0 | Opcode::Dconst1 => Some(VerificationType::Double),
+        Opcode::Ldc | Opcode::LdcW | Opcode::Ldc2W => Some(constant_type(constant_pool, index.ok_or_else(mismatch)?)?),
+        Opcode::Iload | Opcode::Iload0 | Opcode::Iload1 | Opcode::Iload2 | Opcode::Iload3 => Some(VerificationType::Integer),
+        Opcode::Lload | Opcode::Lload0 | Opcode::Lload1 | Opcode::Lload2 | Opcode::Lload3 => Some(VerificationType::Long),
+        Opcode::Fload | Opcode::Fload0 | Opcode::Fload1 | Opcode::Fload2 | Opcode::Fload3 => Some(VerificationType::Float),
+        Opcode::Dload | Opcode::Dload0 | Opcode::Dload1 | Opcode::Dload2 | Opcode::Dload3 => Some(VerificationType::Double),
+        Opcode::Aload | Opcode::Aload0 | Opcode::Aload1 | Opcode::Aload2 | Opcode::Aload3 => {
+            Some(slots.load(local.ok_or_else(mismatch)?).ok_or_else(mismatch)?)
+        },
+        Opcode::Iaload | Opcode::Baload | Opcode::Caload | Opcode::Saload => {
+            slots.pop(2).ok_or_else(mismatch)?;
+            Some(VerificationType::Integer)
+        },
+        Opcode::Laload => Some(slots.pop(2).map(|_| VerificationType::Long).ok_or_else(mismatch)?),
+        Opcode::Faload => Some(slots.pop(2).map(|_| VerificationType::Float).ok_or_else(mismatch)?),
+        Opcode::Daload => Some(slots.pop(2).map(|_| VerificationType::Double).ok_or_else(mismatch)?),
+        Opcode::Aaload => {
+            let array = slots.pop(2).ok_or_else(mismatch)?.remove(0);
+            Some(component_type(constant_pool, &array)?.ok_or_else(mismatch)?)
+        },
+        Opcode::Istore | Opcode::Istore0 | Opcode::Istore1 | Opcode::Istore2 | Opcode::Istore3 | Opcode::Lstore
+        | Opcode::Lstore0 | Opcode::Lstore1 | Opcode::Lstore2 | Opcode::Lstore3 | Opcode::Fstore | Opcode::Fstore0
+        | Opcode::Fstore1 | Opcode::Fstore2 | Opcode::Fstore3 | Opcode::Dstore | Opcode::Dstore0 | Opcode::Dstore1
+        | Opcode::Dstore2 | Opcode::Dstore3 | Opcode::Astore | Opcode::Astore0 | Opcode::Astore1 | Opcode::Astore2
+        | Opcode::Astore3 => {
+            let value = slots.pop(1).ok_or_else(mismatch)?.remove(0);
+            slots.store(local.ok_or_else(mismatch)?, value);
+            None
+        },
+        Opcode::Iastore | Opcode::Lastore | Opcode::Fastore | Opcode::Dastore | Opcode::Aastore | Opcode::Bastore
+        | Opcode::Castore | Opcode::Sastore => {
+            slots.pop(3).ok_or_else(mismatch)?;
+            None
+        },
+        Opcode::Pop => {
+            slots.pop_slots(1).ok_or_else(mismatch)?;
+            None
+        },
+        Opcode::Pop2 => {
+            slots.pop_slots(2).ok_or_else(mismatch)?;
+            None
+        },
+        Opcode::Dup | Opcode::DupX1 | Opcode::DupX2 | Opcode::Dup2 | Opcode::Dup2X1 | Opcode::Dup2X2 => {
+            let (copied, skipped) = match instruction.opcode {
+                Opcode::Dup => (1, 0),
+                Opcode::DupX1 => (1, 1),
+                Opcode::DupX2 => (1, 2),
+                Opcode::Dup2 => (2, 0),
+                Opcode::Dup2X1 => (2, 1),
+                _ => (2, 2),
+            };
+            let top = slots.pop_slots(copied).ok_or_else(mismatch)?;
+            let below = slots.pop_slots(skipped).ok_or_else(mismatch)?;
+            slots.stack.extend(top.iter().cloned().chain(below).chain(top.iter().cloned()));
+            None
+        },
+        Opcode::Swap => {
+            let mut values = slots.pop_slots(2).ok_or_else(mismatch)?;
+            if values.len() != 2 {
+                return Err(mismatch());
+            }
+            values.swap(0, 1);
+            slots.stack.extend(values);
+            None
+        },
+        Opcode::Iadd | Opcode::Isub | Opcode::Imul | Opcode::Idiv | Opcode::Irem | Opcode::Ishl | Opcode::Ishr
+        | Opcode::Iushr | Opcode::Iand | Opcode::Ior | Opcode::Ixor | Opcode::Lcmp | Opcode::Fcmpl | Opcode::Fcmpg
+        | Opcode::Dcmpl | Opcode::Dcmpg => Some(slots.pop(2).map(|_| VerificationType::Integer).ok_or_else(mismatch)?),
+        Opcode::Ladd | Opcode::Lsub | Opcode::Lmul | Opcode::Ldiv | Opcode::Lrem | Opcode::Lshl | Opcode::Lshr
+        | Opcode::Lushr | Opcode::Land | Opcode::Lor | Opcode::Lxor => Some(slots.pop(2).map(|_| VerificationType::Long).ok_or_else(mismatch)?),
+        Opcode::Fadd | Opcode::Fsub | Opcode::Fmul | Opcode::Fdiv | Opcode::Frem => Some(slots.pop(2).map(|_| VerificationType::Float).ok_or_else(mismatch)?),
+        Opcode::Dadd | Opcode::Dsub | Opcode::Dmul | Opcode::Ddiv | Opcode::Drem => Some(slots.pop(2).map(|_| VerificationType::Double).ok_or_else(mismatch)?),
+        Opcode::Ineg | Opcode::L2i | Opcode::F2i | Opcode::D2i | Opcode::I2b | Opcode::I2c | Opcode::I2s
+        | Opcode::Arraylength | Opcode::Instanceof => Some(slots.pop(1).map(|_| VerificationType::Integer).ok_or_else(mismatch)?),
+        Opcode::Lneg | Opcode::I2l | Opcode::F2l | Opcode::D2l => Some(slots.pop(1).map(|_| VerificationType::Long).ok_or_else(mismatch)?),
+        Opcode::Fneg | Opcode::I2f | Opcode::L2f | Opcode::D2f => Some(slots.pop(1).map(|_| VerificationType::Float).ok_or_else(mismatch)?),
+        Opcode::Dneg | Opcode::I2d | Opcode::L2d | Opcode::F2d => Some(slots.pop(1).map(|_| VerificationType::Double).ok_or_else(mismatch)?),
+        Opcode::Ifeq | Opcode::Ifne | Opcode::Iflt | Opcode::Ifge | Opcode::Ifgt | Opcode::Ifle | Opcode::Ifnull
+        | Opcode::Ifnonnull | Opcode::Monitorenter | Opcode::Monitorexit | Opcode::Putstatic => {
+            slots.pop(1).ok_or_else(mismatch)?;
+            None
+        },
+        Opcode::IfIcmpeq | Opcode::IfIcmpne | Opcode::IfIcmplt | Opcode::IfIcmpge | Opcode::IfIcmpgt | Opcode::IfIcmple
+        | Opcode::IfAcmpeq | Opcode::IfAcmpne | Opcode::Putfield => {
+            slots.pop(2).ok_or_else(mismatch)?;
+            None
+        },
+        Opcode::Goto | Opcode::GotoW | Opcode::Ireturn | Opcode::Lreturn | Opcode::Freturn | Opcode::Dreturn
+        | Opcode::Areturn | Opcode::Return | Opcode::Athrow | Opcode::Tableswitch | Opcode::Lookupswitch => return Ok(None),
+        Opcode::Getstatic | Opcode::Getfield => {
+            if instruction.opcode == Opcode::Getfield {
+                slots.pop(1).ok_or_else(mismatch)?;
+            }
+            let (_, descriptor) = member(constant_pool, index.ok_or_else(mismatch)?)?;
+            Some(verification_type(constant_pool, &descriptor)?)
+        },
+        Opcode::Invokevirtual | Opcode::Invokespecial | Opcode::Invokestatic | Opcode::Invokeinterface
+        | Opcode::Invokedynamic => {
+            let (name, descriptor) = member(constant_pool, index.ok_or_else(mismatch)?)?;
+            let method_type = MethodType::new(&descriptor)
+                .map_err(|_| ClassFileError::InvalidDescriptor{descriptor: descriptor.clone()})?;
+            slots.pop(method_type.parameter_types().len()).ok_or_else(mismatch)?;
+            if instruction.opcode != Opcode::Invokestatic && instruction.opcode != Opcode::Invokedynamic {
+                let receiver = slots.pop(1).ok_or_else(mismatch)?.remove(0);
+                if instruction.opcode == Opcode::Invokespecial && name == "<init>" {
+                    let class_index = match receiver {
+                        VerificationType::UninitializedThis => this_class,
+                        VerificationType::Uninitialized{offset} => match disassembly.instruction_at(offset as i64) {
+                            Some(&Instruction{opcode: Opcode::New, operands: Operands::ConstantPool(index), ..}) => index,
+                            _ => return Err(mismatch()),
+                        },
+                        _ => return Err(mismatch()),
+                    };
+                    slots.initialize(&receiver, class_index);
+                }
+            }
+            match method_type.return_type() {
+                Some(return_type) => Some(verification_type(constant_pool, &return_type.descriptor())?),
+                None => None,
+            }
+        },
+        Opcode::New => Some(VerificationType::Uninitialized{offset: instruction.bci as u16}),
+        Opcode::Newarray => {
+            slots.pop(1).ok_or_else(mismatch)?;
+            let descriptor = match instruction.operands {
+                Operands::ArrayType(4) => "[Z",
+                Operands::ArrayType(5) => "[C",
+                Operands::ArrayType(6) => "[F",
+                Operands::ArrayType(7) => "[D",
+                Operands::ArrayType(8) => "[B",
+                Operands::ArrayType(9) => "[S",
+                Operands::ArrayType(10) => "[I",
+                Operands::ArrayType(11) => "[J",
+                _ => return Err(mismatch()),
+            };
+            Some(verification_type(constant_pool, descriptor)?)
+        },
+        Opcode::Anewarray => {
+            slots.pop(1).ok_or_else(mismatch)?;
+            let component = constant_pool.class_name(index.ok_or_else(mismatch)?).map_err(ClassFileError::ConstantPoolError)?;
+            let descriptor = if component.starts_with('[') { format!("[{}", component) } else { format!("[L{};", component) };
+            Some(verification_type(constant_pool, &descriptor)?)
+        },
+        Opcode::Checkcast => {
+            slots.pop(1).ok_or_else(mismatch)?;
+            Some(VerificationType::Object{class_index: index.ok_or_else(mismatch)?})
+        },
+        Opcode::Multianewarray => {
+            let dimensions = match instruction.operands {
+                Operands::MultiANewArray{dimensions, ..} => dimensions,
+                _ => return Err(mismatch()),
+            };
+            slots.pop(dimensions as usize).ok_or_else(mismatch)?;
+            Some(VerificationType::Object{class_index: index.ok_or_else(mismatch)?})
+        },
+        Opcode::Jsr | Opcode::JsrW | Opcode::Ret | Opcode::Wide | Opcode::Breakpoint | Opcode::Impdep1 | Opcode::Impdep2 => {
+            return Err(ClassFileError::UnsupportedFrameInstruction{opcode: instruction.opcode});
+        },
+    };
+    if let Some(pushed) = pushed {
+        slots.stack.push(pushed);
+    }
+    Ok(Some(slots))
+}
+
+fn is_wide(value: &VerificationType) -> bool {
+    matches!(value, VerificationType::Long | VerificationType::Double)
+}
+
+fn short_form_local(opcode: Opcode) -> Option<u16> {
+    match opcode {
+        Opcode::Iload0 | Opcode::Lload0 | Opcode::Fload0 | Opcode::Dload0 | Opcode::Aload0 | Opcode::Istore0
+        | Opcode::Lstore0 | Opcode::Fstore0 | Opcode::Dstore0 | Opcode::Astore0 => Some(0),
+        Opcode::Iload1 | Opcode::Lload1 | Opcode::Fload1 | Opcode::Dload1 | Opcode::Aload1 | Opcode::Istore1
+        | Opcode::Lstore1 | Opcode::Fstore1 | Opcode::Dstore1 | Opcode::Astore1 => Some(1),
+        Opcode::Iload2 | Opcode::Lload2 | Opcode::Fload2 | Opcode::Dload2 | Opcode::Aload2 | Opcode::Istore2
+        | Opcode::Lstore2 | Opcode::Fstore2 | Opcode::Dstore2 | Opcode::Astore2 => Some(2),
+        Opcode::Iload3 | Opcode::Lload3 | Opcode::Fload3 | Opcode::Dload3 | Opcode::Aload3 | Opcode::Istore3
+        | Opcode::Lstore3 | Opcode::Fstore3 | Opcode::Dstore3 | Opcode::Astore3 => Some(3),
+        _ => None,
+    }
+}
+
+// Type of a value of the field descriptor, classes are added to the constant pool as needed
+fn verification_type(constant_pool: &mut ConstantPool, descriptor: &str) -> Result<VerificationType, ClassFileError> {
+    let class_name = match descriptor.as_bytes().first() {
+        Some(b'B') | Some(b'C') | Some(b'I') | Some(b'S') | Some(b'Z') => return Ok(VerificationType::Integer),
+        Some(b'J') => return Ok(VerificationType::Long),
+        Some(b'F') => return Ok(VerificationType::Float),
+        Some(b'D') => return Ok(VerificationType::Double),
+        Some(b'L') if descriptor.ends_with(';') => &descriptor[1..descriptor.len() - 1],
+        // Array classes are named by their descriptor
+        Some(b'[') => descriptor,
+        _ => return Err(ClassFileError::InvalidDescriptor{descriptor: descriptor.to_string()}),
+    };
+    let class_index = constant_pool.add_class(class_name).map_err(ClassFileError::ConstantPoolError)?;
+    Ok(VerificationType::Object{class_index})
+}
+
+fn class_type(constant_pool: &mut ConstantPool, name: &str) -> Result<VerificationType, ClassFileError> {
+    let class_index = constant_pool.add_class(name).map_err(ClassFileError::ConstantPoolError)?;
+    Ok(VerificationType::Object{class_index})
+}
+
+// Type of the value loaded by ldc
+fn constant_type(constant_pool: &mut ConstantPool, index: u16) -> Result<VerificationType, ClassFileError> {
+    let constant = constant_pool.get(index).cloned()
+        .ok_or(ClassFileError::ConstantPoolError(ConstantPoolError::InvalidIndex{index}))?;
+    match constant {
+        Constant::Integer(_) => Ok(VerificationType::Integer),
+        Constant::Float(_) => Ok(VerificationType::Float),
+        Constant::Long(_) => Ok(VerificationType::Long),
+        Constant::Double(_) => Ok(VerificationType::Double),
+        Constant::String{..} => class_type(constant_pool, "java/lang/String"),
+        Constant::Class{..} => class_type(constant_pool, "java/lang/Class"),
+        Constant::MethodType{..} => class_type(constant_pool, "java/lang/invoke/MethodType"),
+        Constant::MethodHandle{..} => class_type(constant_pool, "java/lang/invoke/MethodHandle"),
+        Constant::Dynamic{name_and_type_index, ..} => {
+            let (_, descriptor) = constant_pool.name_and_type(name_and_type_index).map_err(ClassFileError::ConstantPoolError)?;
+            let descriptor = descriptor.to_string();
+            verification_type(constant_pool, &descriptor)
+        },
+        _ => Err(ClassFileError::ConstantPoolError(ConstantPoolError::UnexpectedConstant{index, expected: "loadable"})),
+    }
+}
+
+// Type of the elements of a reference array, none if the value is not one
+fn component_type(constant_pool: &mut ConstantPool, array: &VerificationType) -> Result<Option<VerificationType>, ClassFileError> {
+    let class_index = match *array {
+        VerificationType::Null => return Ok(Some(VerificationType::Null)),
+        VerificationType::Object{class_index} => class_index,
+        _ => return Ok(None),
+    };
+    let name = constant_pool.class_name(class_index).map_err(ClassFileError::ConstantPoolError)?;
+    match name.strip_prefix('[') {
+        Some(component) if component.starts_with('L') || component.starts_with('[') => {
+            let component = component.to_string();
+            Ok(Some(verification_type(constant_pool, &component)?))
+        },
+        _ => Ok(None),
+    }
+}
+
+// Name and descriptor of the field or method referenced by an instruction
+fn member(constant_pool: &ConstantPool, index: u16) -> Result<(String, String), ClassFileError> {
+    let name_and_type_index = match constant_pool.get(index) {
+        Some(Constant::Fieldref{name_and_type_index, ..}) | Some(Constant::Methodref{name_and_type_index, ..})
+        | Some(Constant::InterfaceMethodref{name_and_type_index, ..})
+        | Some(Constant::InvokeDynamic{name_and_type_index, ..}) => *name_and_type_index,
+        _ => return Err(ClassFileError::ConstantPoolError(ConstantPoolError::UnexpectedConstant{index, expected: "a member reference"})),
+    };
+    let (name, descriptor) = constant_pool.name_and_type(name_and_type_index).map_err(ClassFileError::ConstantPoolError)?;
+    Ok((name.to_string(), descriptor.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_expand_encode() {
+        let mut constant_pool = ConstantPool::new();
+        let this_class = constant_pool.add_class("T").unwrap();
+        let initial = initial_state(&mut constant_pool, this_class, 0x0001, "<init>", "(J[Ljava/lang/String;)V").unwrap();
+        let strings = constant_pool.add_class("[Ljava/lang/String;").unwrap();
+        assert_eq!(initial.locals, vec![VerificationType::UninitializedThis, VerificationType::Long,
+                                        VerificationType::Object{class_index: strings}]);
+        let frames = vec![
+            StackMapFrame::Append{offset_delta: 3, locals: vec![VerificationType::Integer]},
+            StackMapFrame::Chop{offset_delta: 70, count: 2},
+            StackMapFrame::SameLocals1StackItem{offset_delta: 1, stack: VerificationType::Null},
+            StackMapFrame::Full{offset_delta: 0, locals: vec![VerificationType::Float], stack: vec![VerificationType::Top]},
+        ];
+        let states = expand(&initial, &frames).unwrap();
+        assert_eq!(states.iter().map(|(offset, _)| *offset).collect::<Vec<_>>(), vec![3, 74, 76, 77]);
+        assert_eq!(states[1].1.locals, vec![VerificationType::UninitializedThis, VerificationType::Long]);
+        assert_eq!(encode(&initial, &states), frames);
+        assert_eq!(expand(&initial, &[StackMapFrame::Chop{offset_delta: 0, count: 3}, StackMapFrame::Chop{offset_delta: 0, count: 1}]),
+                   Err(ClassFileError::InvalidFrame{offset: 1}));
+    }
+
+}
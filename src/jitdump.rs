@@ -0,0 +1,223 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+// Reader for the jitdump files produced by the agent, used to verify the output
+
+use std::io::{self, Read};
+use std::fs::File;
+use std::path::Path;
+use std::string::FromUtf8Error;
+
+use thiserror::Error;
+use byteorder::{ByteOrder, NativeEndian};
+
+const JITDUMP_MAGIC: u32 = 0x4a695444;
+const HEADER_SIZE: usize = 40;
+const RECORD_HEADER_SIZE: usize = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JitDump {
+    pub header: Header,
+    pub records: Vec<Record>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Header {
+    pub version: u32,
+    pub e_machine: u32,
+    pub pid: u32,
+    pub timestamp: u64,
+    pub flags: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub timestamp: u64,
+    pub body: RecordBody,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordBody {
+    CodeLoad(CodeLoad),
+    DebugInfo(DebugInfo),
+    CodeClose,
+    Unknown{id: u32, data: Vec<u8>},
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeLoad {
+    pub pid: u32,
+    pub tid: u32,
+    pub vma: u64,
+    pub code_address: u64,
+    pub code_index: u64,
+    pub name: String,
+    pub code: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebugInfo {
+    pub code_address: u64,
+    pub entries: Vec<DebugEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebugEntry {
+    pub address: u64,
+    pub line: i32,
+    pub discriminator: i32,
+    pub file_name: String,
+}
+
+impl JitDump {
+
+    pub fn read_file<P: AsRef<Path>>(path: P) -> Result<JitDump, ReadJitDumpError> {
+        let mut file = File::open(path).map_err(ReadJitDumpError::IoError)?;
+        JitDump::read(&mut file)
+    }
+
+    pub fn read<R: Read>(reader: &mut R) -> Result<JitDump, ReadJitDumpError> {
+        let mut bytes = Vec::new();
+        let _ = reader.read_to_end(&mut bytes).map_err(ReadJitDumpError::IoError)?;
+        JitDump::parse(&bytes)
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<JitDump, ReadJitDumpError> {
+        if bytes.len() < HEADER_SIZE {
+            return Err(ReadJitDumpError::TruncatedHeader);
+        }
+        let magic = NativeEndian::read_u32(&bytes[0..4]);
+        if magic != JITDUMP_MAGIC {
+            return Err(ReadJitDumpError::InvalidMagic(magic));
+        }
+        let header_size = NativeEndian::read_u32(&bytes[8..12]) as usize;
+        if header_size < HEADER_SIZE || header_size > bytes.len() {
+            return Err(ReadJitDumpError::InvalidHeaderSize(header_size));
+        }
+        let header = Header {
+            version: NativeEndian::read_u32(&bytes[4..8]),
+            e_machine: NativeEndian::read_u32(&bytes[12..16]),
+            pid: NativeEndian::read_u32(&bytes[20..24]),
+            timestamp: NativeEndian::read_u64(&bytes[24..32]),
+            flags: NativeEndian::read_u64(&bytes[32..40]),
+        };
+        let mut records = Vec::new();
+        let mut offset = header_size;
+        while offset < bytes.len() {
+            if bytes.len() - offset < RECORD_HEADER_SIZE {
+                return Err(ReadJitDumpError::TruncatedRecord(offset));
+            }
+            let id = NativeEndian::read_u32(&bytes[offset..offset + 4]);
+            let size = NativeEndian::read_u32(&bytes[offset + 4..offset + 8]) as usize;
+            if size < RECORD_HEADER_SIZE || size > bytes.len() - offset {
+                return Err(ReadJitDumpError::TruncatedRecord(offset));
+            }
+            let timestamp = NativeEndian::read_u64(&bytes[offset + 8..offset + 16]);
+            let data = &bytes[offset + RECORD_HEADER_SIZE..offset + size];
+            let body = match id {
+                0 => RecordBody::CodeLoad(parse_code_load(data, offset)?),
+                2 => RecordBody::DebugInfo(parse_debug_info(data, offset)?),
+                3 => RecordBody::CodeClose,
+                _ => RecordBody::Unknown{id, data: data.to_vec()},
+            };
+            records.push(Record{timestamp, body});
+            offset += size;
+        }
+        Ok(JitDump{header, records})
+    }
+
+    pub fn code_loads(&self) -> impl Iterator<Item = &CodeLoad> {
+        self.records.iter().filter_map(|r| match r.body {
+            RecordBody::CodeLoad(ref load) => Some(load),
+            _ => None,
+        })
+    }
+
+    pub fn debug_infos(&self) -> impl Iterator<Item = &DebugInfo> {
+        self.records.iter().filter_map(|r| match r.body {
+            RecordBody::DebugInfo(ref info) => Some(info),
+            _ => None,
+        })
+    }
+
+}
+
+fn parse_code_load(data: &[u8], offset: usize) -> Result<CodeLoad, ReadJitDumpError> {
+    if data.len() < 40 {
+        return Err(ReadJitDumpError::TruncatedRecord(offset));
+    }
+    let code_size = NativeEndian::read_u64(&data[24..32]) as usize;
+    let (name, name_length) = parse_string(&data[40..], offset)?;
+    let code = &data[40 + name_length..];
+    if code.len() != code_size {
+        return Err(ReadJitDumpError::TruncatedRecord(offset));
+    }
+    Ok(CodeLoad {
+        pid: NativeEndian::read_u32(&data[0..4]),
+        tid: NativeEndian::read_u32(&data[4..8]),
+        vma: NativeEndian::read_u64(&data[8..16]),
+        code_address: NativeEndian::read_u64(&data[16..24]),
+        code_index: NativeEndian::read_u64(&data[32..40]),
+        name,
+        code: code.to_vec(),
+    })
+}
+
+fn parse_debug_info(data: &[u8], offset: usize) -> Result<DebugInfo, ReadJitDumpError> {
+    if data.len() < 16 {
+        return Err(ReadJitDumpError::TruncatedRecord(offset));
+    }
+    let code_address = NativeEndian::read_u64(&data[0..8]);
+    let entries_count = NativeEndian::read_u64(&data[8..16]);
+    let mut entries = Vec::new();
+    let mut position = 16;
+    for _ in 0..entries_count {
+        if data.len() - position < 16 {
+            return Err(ReadJitDumpError::TruncatedRecord(offset));
+        }
+        let address = NativeEndian::read_u64(&data[position..position + 8]);
+        let line = NativeEndian::read_i32(&data[position + 8..position + 12]);
+        let discriminator = NativeEndian::read_i32(&data[position + 12..position + 16]);
+        let (file_name, file_name_length) = parse_string(&data[position + 16..], offset)?;
+        entries.push(DebugEntry{address, line, discriminator, file_name});
+        position += 16 + file_name_length;
+    }
+    if position != data.len() {
+        return Err(ReadJitDumpError::TrailingData(offset));
+    }
+    Ok(DebugInfo{code_address, entries})
+}
+
+// Returns string and the number of bytes it takes including terminating zero
+fn parse_string(data: &[u8], offset: usize) -> Result<(String, usize), ReadJitDumpError> {
+    match data.iter().position(|b| *b == 0) {
+        Some(end) => {
+            let value = String::from_utf8(data[..end].to_vec()).map_err(ReadJitDumpError::InvalidString)?;
+            Ok((value, end + 1))
+        },
+        None => Err(ReadJitDumpError::TruncatedRecord(offset)),
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ReadJitDumpError {
+    #[error("I/O error: {0}")]
+    IoError(#[source] io::Error),
+    #[error("Jit dump header is truncated")]
+    TruncatedHeader,
+    #[error("Invalid jit dump magic {0:#x}")]
+    InvalidMagic(u32),
+    #[error("Invalid jit dump header size {0}")]
+    InvalidHeaderSize(usize),
+    #[error("Truncated record at offset {0}")]
+    TruncatedRecord(usize),
+    #[error("Unexpected data at the end of record at offset {0}")]
+    TrailingData(usize),
+    #[error("Invalid string: {0}")]
+    InvalidString(#[source] FromUtf8Error),
+}
@@ -5,126 +5,178 @@
 //   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
 // THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-mod rvmti;
+// Explicit returns are the style of this code base
+#![allow(clippy::needless_return)]
+
 mod perf;
-mod demangle;
+pub mod demangle;
+mod threads;
+mod options;
+mod coverage;
+mod natives;
+pub mod classfile;
+mod frames;
+pub mod jitdump;
 
-use std::sync::Mutex;
-use std::sync::PoisonError;
-use std::sync::MutexGuard;
+use std::collections::HashMap;
 use std::thread::{self, JoinHandle};
 use std::sync::mpsc::{channel, Sender, Receiver};
 use std::slice;
 
-use log::{debug, warn, error, info};
-use lazy_static::lazy_static;
+use log::{debug, warn, error, info, log_enabled};
 use thiserror::Error;
 
-pub use crate::rvmti::Agent_OnLoad;
-pub use crate::rvmti::Agent_OnUnload;
-pub use crate::rvmti::jvmti_event_breakpoint_handler;
-pub use crate::rvmti::jvmti_event_class_file_load_hook_handler;
-pub use crate::rvmti::jvmti_event_class_load_handler;
-pub use crate::rvmti::jvmti_event_class_prepare_handler;
-pub use crate::rvmti::jvmti_event_compiled_method_load_handler;
-pub use crate::rvmti::jvmti_event_compiled_method_unload_handler;
-pub use crate::rvmti::jvmti_event_data_dump_request_handler;
-pub use crate::rvmti::jvmti_event_dynamic_code_generated_handler;
-pub use crate::rvmti::jvmti_event_exception_handler;
-pub use crate::rvmti::jvmti_event_exception_catch_handler;
-pub use crate::rvmti::jvmti_event_field_access_handler;
-pub use crate::rvmti::jvmti_event_field_modification_handler;
-pub use crate::rvmti::jvmti_event_frame_pop_handler;
-pub use crate::rvmti::jvmti_event_garbage_collection_finish_handler;
-pub use crate::rvmti::jvmti_event_garbage_collection_start_handler;
-pub use crate::rvmti::jvmti_event_method_entry_handler;
-pub use crate::rvmti::jvmti_event_method_exit_handler;
-pub use crate::rvmti::jvmti_event_monitor_contended_enter_handler;
-pub use crate::rvmti::jvmti_event_monitor_contended_entered_handler;
-pub use crate::rvmti::jvmti_event_monitor_wait_handler;
-pub use crate::rvmti::jvmti_event_monitor_waited_handler;
-pub use crate::rvmti::jvmti_event_native_method_bind_handler;
-pub use crate::rvmti::jvmti_event_object_free_handler;
-pub use crate::rvmti::jvmti_event_resource_exhausted_handler;
-pub use crate::rvmti::jvmti_event_single_step_handler;
-pub use crate::rvmti::jvmti_event_thread_end_handler;
-pub use crate::rvmti::jvmti_event_thread_start_handler;
-pub use crate::rvmti::jvmti_event_vm_death_handler;
-pub use crate::rvmti::jvmti_event_vm_init_handler;
-pub use crate::rvmti::jvmti_event_vm_object_alloc_handler;
-pub use crate::rvmti::jvmti_event_vm_start_handler;
-
-lazy_static! {
-    static ref AGENT_ENV: Mutex<Option<AgentEnv>> = Mutex::new(None);
-}
-
-pub fn agent_on_load(vm: &rvmti::Jvm, options: &Option<String>) -> i32 {
-    info!("Agent starting...");
-    debug!("Agent options: {}", options.as_ref().unwrap_or(&"".to_string()));
-    match do_on_load(vm, options) {
-        Ok(_) => {
-            info!("Agent started");
-            return 0;
+rvmti_api::jvmti_agent! {
+    features: [
+        // Without line numbers or source file names compiled methods are still dumped, but without debug info
+        compiled_methods {
+            required: [can_generate_compiled_method_load_events],
+            optional: [can_get_line_numbers, can_get_source_file_name],
+            events: [CompiledMethodLoad],
+        },
+        // Requested only with debug log, bytecodes only show the instructions of inlined frames there
+        bytecodes {
+            required: [can_get_bytecodes],
+            optional: [],
+            events: [],
+        },
+        dynamic_code {
+            required: [],
+            optional: [],
+            events: [DynamicCodeGenerated],
+        },
+        // Initial thread gets no thread start event, it is registered on VM init. VM metadata is recorded then too
+        threads {
+            required: [],
+            optional: [],
+            events: [VmInit, ThreadStart, ThreadEnd],
+        },
+        // JNI functions get Java names, functions of stripped libraries get code load records as well
+        native_methods {
+            required: [can_generate_native_method_bind_events],
+            optional: [],
+            events: [NativeMethodBind],
+        },
+        // Requested only when `coverage` option is given, breakpoint capability slows the VM down
+        coverage {
+            required: [can_generate_breakpoint_events, can_get_line_numbers],
+            optional: [can_get_source_file_name],
+            events: [ClassPrepare, Breakpoint],
         },
-        Err(err) => {
-            error!("Agent initialization error: {}", err);
-            return -1
+    ],
+    handler: PerfEventHandler,
+}
+
+// Written to the metadata file as key and value, identify the build and command line behind a profile
+const METADATA_PROPERTIES: [&str; 4] = ["java.vm.name", "java.version", "java.vm.version", "sun.java.command"];
+const INPUT_ARGUMENTS_KEY: &str = "jvm.input_arguments";
+const COVERAGE_FEATURE: &str = "coverage";
+const BYTECODES_FEATURE: &str = "bytecodes";
+// Enough for the classes loaded before VM init
+const LOADED_CLASSES_FRAME_CAPACITY: i32 = 4096;
+
+#[allow(clippy::too_many_arguments)]
+pub fn jvmti_event_compiled_method_load(env: &mut rvmti_api::JvmtiEnv, jni_env: &rvmti_api::JniEnv, method_id: &rvmti_api::JMethodId,
+                                        address_locations: &Option<Vec<rvmti_api::AddressLocationEntry>>,
+                                        compile_info: &Option<Vec<rvmti_api::CompiledMethodLoadRecord>>, address: usize, length: usize,
+                                        debug_info: &DebugInfoCapabilities)
+{
+    match on_compiled_method_load(env, jni_env, method_id, address_locations, compile_info, address, length, debug_info) {
+        Ok(_) => (),
+        Err(e) => {
+            warn!("Failed to handle compiled method load event: {}", e);
         }
     }
 }
 
-pub fn agent_on_unload(_vm: &rvmti::Jvm) {
-    info!("Agent unloading...");
-    match AGENT_ENV.lock() {
-        Ok(mut guard) => {
-            match guard.take() {
-                Some(ref mut env) => {
-                    unload_environment(&mut env.env);
-                },
-                None => {
-                    warn!("Agent was not initialized, skipping shutdown");
-                }
-            }
-            debug!("Environments freed");
-        },
-        Err(err) => {
-            warn!("Failed to lock agent environment for unloading: {}", err);
+pub fn jvmti_event_dynamic_code_generated(env: &mut rvmti_api::JvmtiEnv, name: &Option<String>, address: usize, length: usize) {
+    match on_dynamic_code_generated(env, name, address, length) {
+        Ok(_) => (),
+        Err(e) => {
+            warn!("Failed to handle dynamic code generation event: {}", e);
         }
     }
-    info!("Agent unloaded");
 }
 
-pub fn jvmti_event_compiled_method_load(env: &mut rvmti::JvmtiEnv, method_id: &rvmti::JMethodId,
-                                        address_locations: &Option<Vec<rvmti::AddressLocationEntry>>,
-                                        compile_info: &Option<Vec<rvmti::CompiledMethodLoadRecord>>, address: usize, length: usize)
+pub fn jvmti_event_native_method_bind(env: &mut rvmti_api::JvmtiEnv, jni_env: &rvmti_api::JniEnv,
+                                      method_id: &rvmti_api::JMethodId, address: usize)
 {
-    match on_compiled_method_load(env, method_id, address_locations, compile_info, address, length) {
+    match on_native_method_bind(env, jni_env, method_id, address) {
         Ok(_) => (),
         Err(e) => {
-            warn!("Failed to handle compiled method load event: {}", e);
+            warn!("Failed to handle native method bind event: {}", e);
         }
     }
 }
 
-pub fn jvmti_event_dynamic_code_generated(env: &mut rvmti::JvmtiEnv, name: &Option<String>, address: usize, length: usize) {
-    match on_dynamic_code_generated(env, name, address, length) {
+pub fn jvmti_event_thread_start(env: &mut rvmti_api::JvmtiEnv, jni_env: &rvmti_api::JniEnv, thread: &rvmti_api::JThread) {
+    match on_thread_start(env, jni_env, thread) {
         Ok(_) => (),
         Err(e) => {
-            warn!("Failed to handle dynamic code generation event: {}", e);
+            warn!("Failed to handle thread start event: {}", e);
+        }
+    }
+}
+
+pub fn jvmti_event_vm_init(env: &mut rvmti_api::JvmtiEnv, jni_env: &rvmti_api::JniEnv, thread: &rvmti_api::JThread,
+                           debug_info: &DebugInfoCapabilities)
+{
+    jvmti_event_thread_start(env, jni_env, thread);
+    match on_vm_init(env, jni_env) {
+        Ok(_) => (),
+        Err(e) => {
+            warn!("Failed to handle VM init event: {}", e);
+        }
+    }
+    match on_vm_init_coverage(env, jni_env, debug_info) {
+        Ok(_) => (),
+        Err(e) => {
+            warn!("Failed to set coverage breakpoints in loaded classes: {}", e);
+        }
+    }
+}
+
+pub fn jvmti_event_class_prepare(env: &mut rvmti_api::JvmtiEnv, class: &rvmti_api::JClass, debug_info: &DebugInfoCapabilities) {
+    match on_class_prepare(env, class, debug_info) {
+        Ok(_) => (),
+        Err(e) => {
+            warn!("Failed to handle class prepare event: {}", e);
+        }
+    }
+}
+
+pub fn jvmti_event_breakpoint(env: &mut rvmti_api::JvmtiEnv, method_id: &rvmti_api::JMethodId, location: rvmti_api::JLocation) {
+    match on_breakpoint(env, method_id, location) {
+        Ok(_) => (),
+        Err(e) => {
+            warn!("Failed to handle breakpoint event: {}", e);
         }
     }
 }
 
-fn on_compiled_method_load(env: &mut rvmti::JvmtiEnv, method_id: &rvmti::JMethodId,
-                           address_locations: &Option<Vec<rvmti::AddressLocationEntry>>,
-                           compile_info: &Option<Vec<rvmti::CompiledMethodLoadRecord>>,
-                           address: usize, length: usize) -> Result<(), CompiledMethodLoadHandlerError>
+pub fn jvmti_event_thread_end(env: &mut rvmti_api::JvmtiEnv) {
+    match on_thread_end(env) {
+        Ok(_) => (),
+        Err(e) => {
+            warn!("Failed to handle thread end event: {}", e);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn on_compiled_method_load(env: &mut rvmti_api::JvmtiEnv, jni_env: &rvmti_api::JniEnv, method_id: &rvmti_api::JMethodId,
+                           address_locations: &Option<Vec<rvmti_api::AddressLocationEntry>>,
+                           compile_info: &Option<Vec<rvmti_api::CompiledMethodLoadRecord>>,
+                           address: usize, length: usize,
+                           debug_info: &DebugInfoCapabilities) -> Result<(), CompiledMethodLoadHandlerError>
 {
-    let mut guard = AGENT_ENV.lock().map_err(|_e| CompiledMethodLoadHandlerError::FailedToLockAgentEnvironment)?;
-    match *guard {
-        Some(ref mut agent_env) => {
-            let method_info = method_info(env, method_id).map_err(CompiledMethodLoadHandlerError::UnableToGetMethodInfo)?;
-            let stack_info = stack_info(env, compile_info).map_err(CompiledMethodLoadHandlerError::UnableToGetStackInfo)?;
+    let agent_env = env.get_environment_local_storage::<AgentEnv>()
+        .map_err(CompiledMethodLoadHandlerError::UnableToGetAgentEnvironment)?;
+    match agent_env {
+        Some(agent_env) => {
+            let method_info = method_info(env, jni_env, method_id, debug_info)
+                .map_err(CompiledMethodLoadHandlerError::UnableToGetMethodInfo)?;
+            let stack_info = stack_info(env, jni_env, compile_info, debug_info).map_err(CompiledMethodLoadHandlerError::UnableToGetStackInfo)?;
             let timestamp = perf::get_timestamp().map_err(CompiledMethodLoadHandlerError::UnableToGetTimestamp)?;
             agent_env.compiled_method_load(method_info.name, method_info.class.signature,
                                            method_info.class.source_file_name, address, length,
@@ -136,102 +188,437 @@ fn on_compiled_method_load(env: &mut rvmti::JvmtiEnv, method_id: &rvmti::JMethod
     }
 }
 
-fn on_dynamic_code_generated(_env: &mut rvmti::JvmtiEnv, name: &Option<String>, address: usize,
+fn on_dynamic_code_generated(env: &mut rvmti_api::JvmtiEnv, name: &Option<String>, address: usize,
                              length: usize) -> Result<(), DynamicCodeGeneratedHandlerError>
 {
-    let mut guard = AGENT_ENV.lock().map_err(|_e| DynamicCodeGeneratedHandlerError::FailedToLockAgentEnvironment)?;
-    match *guard {
-        Some(ref mut env) => {
+    let agent_env = env.get_environment_local_storage::<AgentEnv>()
+        .map_err(DynamicCodeGeneratedHandlerError::UnableToGetAgentEnvironment)?;
+    match agent_env {
+        Some(agent_env) => {
             let timestamp = perf::get_timestamp().map_err(DynamicCodeGeneratedHandlerError::UnableToGetTimestamp)?;
-            env.dynamic_code_generated(name, address, length, timestamp);
+            agent_env.dynamic_code_generated(name, address, length, timestamp);
             Ok(())
         },
         None => Err(DynamicCodeGeneratedHandlerError::AgentNotInitialized),
     }
 }
 
-fn unload_environment(_env: &mut rvmti::JvmtiEnv) {
+// Native methods are bound on first call or by RegisterNatives, both after the code was loaded
+fn on_native_method_bind(env: &mut rvmti_api::JvmtiEnv, jni_env: &rvmti_api::JniEnv, method_id: &rvmti_api::JMethodId,
+                         address: usize) -> Result<(), NativeMethodBindHandlerError>
+{
+    let agent_env = env.get_environment_local_storage::<AgentEnv>()
+        .map_err(NativeMethodBindHandlerError::UnableToGetAgentEnvironment)?;
+    match agent_env {
+        Some(agent_env) => {
+            let debug_info = DebugInfoCapabilities{line_numbers: false, source_file_names: false, bytecodes: false};
+            let method_info = method_info(env, jni_env, method_id, &debug_info)
+                .map_err(NativeMethodBindHandlerError::UnableToGetMethodInfo)?;
+            let timestamp = perf::get_timestamp().map_err(NativeMethodBindHandlerError::UnableToGetTimestamp)?;
+            let jni_symbol = demangle::ClassType::new(&method_info.class.signature.signature).ok().map(|class_type| {
+                demangle::JniSymbol::for_method(class_type, &method_info.name.name,
+                                                demangle::MethodType::new(&method_info.name.signature).ok())
+            });
+            agent_env.native_method_bind(perf::java_method_name(&method_info.name, &method_info.class.signature),
+                                         jni_symbol, address, timestamp);
+            Ok(())
+        },
+        None => Err(NativeMethodBindHandlerError::AgentNotInitialized),
+    }
 }
 
-fn do_on_load<'a>(vm: &rvmti::Jvm, options: &Option<String>) -> Result<(), AgentInitError> {
-    let mut guard = AGENT_ENV.lock().map_err(AgentInitError::from)?;
-    return match *guard {
-        Some(_) => {
-            warn!("Agent was already initialized, skipping initialization");
+// Thread start and VM init events are sent on the thread itself, so its native id is the current one
+fn on_thread_start(env: &mut rvmti_api::JvmtiEnv, jni_env: &rvmti_api::JniEnv,
+                   thread: &rvmti_api::JThread) -> Result<(), ThreadStartHandlerError>
+{
+    let agent_env = env.get_environment_local_storage::<AgentEnv>()
+        .map_err(ThreadStartHandlerError::UnableToGetAgentEnvironment)?;
+    match agent_env {
+        Some(agent_env) => {
+            let info = env.get_thread_info(jni_env, Some(thread)).map_err(ThreadStartHandlerError::UnableToGetThreadInfo)?;
+            let group = match info.thread_group {
+                Some(ref group) => Some(env.get_thread_group_info(jni_env, group)
+                    .map_err(ThreadStartHandlerError::UnableToGetThreadGroupInfo)?.name),
+                None => None,
+            };
+            agent_env.thread_started(threads::JavaThread{tid: perf::get_tid(), name: info.name, group, daemon: info.is_daemon});
             Ok(())
         },
-        None => {
-            let mut jvmti_env = vm.get_jvmti_env(rvmti::JvmtiVersion::CurrentVersion)
-                .map_err(AgentInitError::UnableToObtainJvmtiEnvironment)?;
-            debug!("Environment obtained");
-            let _ = initialize_agent(&mut jvmti_env, &options)?;
-            debug!("Agent environment successfully initialized");
-            let dump_dir = perf::create_dump_dir()
-                .map_err(AgentInitError::UnableToCreateDumpDir)?;
-            debug!("Jit dump directory created");
-            let dump_file = perf::DumpFile::new(dump_dir)
-                .map_err(AgentInitError::UnableToCreateDumpFile)?;
-            debug!("Jit dump file created");
-            *guard = Some(AgentEnv::new(jvmti_env, dump_file));
+        None => Err(ThreadStartHandlerError::AgentNotInitialized),
+    }
+}
+
+// System properties are read once the class library is initialized, `java.version` is not known to the VM itself
+fn on_vm_init(env: &mut rvmti_api::JvmtiEnv, jni_env: &rvmti_api::JniEnv) -> Result<(), VmInitHandlerError> {
+    let agent_env = env.get_environment_local_storage::<AgentEnv>()
+        .map_err(VmInitHandlerError::UnableToGetAgentEnvironment)?;
+    match agent_env {
+        Some(agent_env) => {
+            let mut entries = Vec::new();
+            for key in METADATA_PROPERTIES.iter() {
+                let value = match env.get_system_property(key).map_err(VmInitHandlerError::UnableToGetSystemProperty)? {
+                    Some(value) => Some(value),
+                    None => java_system_property(jni_env, key).map_err(|e| java_call_error(jni_env, e))?,
+                };
+                if let Some(value) = value {
+                    entries.push(vec![key.to_string(), value]);
+                }
+            }
+            if let Some(arguments) = jvm_input_arguments(jni_env).map_err(|e| java_call_error(jni_env, e))? {
+                entries.push(std::iter::once(INPUT_ARGUMENTS_KEY.to_string()).chain(arguments).collect());
+            }
+            agent_env.metadata(entries);
             Ok(())
-        }
+        },
+        None => Err(VmInitHandlerError::AgentNotInitialized),
     }
 }
 
-fn initialize_agent<'a>(env: &mut rvmti::JvmtiEnv, _options: &Option<String>) -> Result<(), AgentInitError> {
-    let _ = add_capabilities(env)?;
-    let _ = set_event_callbacks(env)?;
-    let _ = enable_events(env)?;
+// Classes prepared before the live phase could not get breakpoints on class prepare
+fn on_vm_init_coverage(env: &mut rvmti_api::JvmtiEnv, jni_env: &rvmti_api::JniEnv,
+                       debug_info: &DebugInfoCapabilities) -> Result<(), VmInitHandlerError>
+{
+    let agent_env = env.get_environment_local_storage::<AgentEnv>()
+        .map_err(VmInitHandlerError::UnableToGetAgentEnvironment)?;
+    let coverage = match agent_env {
+        Some(ref agent_env) => match agent_env.coverage {
+            Some(ref coverage) => coverage,
+            None => return Ok(()),
+        },
+        None => return Err(VmInitHandlerError::AgentNotInitialized),
+    };
+    let mut result = Ok(());
+    jni_env.with_local_frame(LOADED_CLASSES_FRAME_CAPACITY, |frame_env| {
+        result = instrument_loaded_classes(env, frame_env, coverage, debug_info);
+        Ok(None)
+    }).map_err(VmInitHandlerError::UnableToCallJava)?;
+    result.map_err(VmInitHandlerError::UnableToInstrumentClasses)
+}
+
+fn instrument_loaded_classes(env: &mut rvmti_api::JvmtiEnv, jni_env: &rvmti_api::JniEnv, coverage: &coverage::Coverage,
+                             debug_info: &DebugInfoCapabilities) -> Result<(), CoverageError>
+{
+    let classes = env.get_loaded_classes(jni_env).map_err(CoverageError::UnableToGetLoadedClasses)?;
+    for class in classes.iter() {
+        let status = env.get_class_status(class).map_err(CoverageError::UnableToGetClassStatus)?;
+        if status.is_prepared() && !status.is_array() && !status.is_primitive() {
+            instrument_class(env, coverage, class, debug_info)?;
+        }
+    }
     Ok(())
 }
 
-fn add_capabilities<'a>(env: &mut rvmti::JvmtiEnv) -> Result<(), AgentInitError> {
-    let mut capabilities = rvmti::JvmtiCapabilities::new_empty_capabilities();
+// Breakpoints can only be set in the live phase, classes prepared earlier are instrumented on VM init
+fn on_class_prepare(env: &mut rvmti_api::JvmtiEnv, class: &rvmti_api::JClass,
+                    debug_info: &DebugInfoCapabilities) -> Result<(), ClassPrepareHandlerError>
+{
+    let agent_env = env.get_environment_local_storage::<AgentEnv>()
+        .map_err(ClassPrepareHandlerError::UnableToGetAgentEnvironment)?;
+    match agent_env {
+        Some(agent_env) => match agent_env.coverage {
+            Some(ref coverage) => {
+                if env.get_phase().map_err(ClassPrepareHandlerError::UnableToGetPhase)? != rvmti_api::JvmtiPhase::Live {
+                    return Ok(());
+                }
+                instrument_class(env, coverage, class, debug_info).map_err(ClassPrepareHandlerError::UnableToInstrumentClass)
+            },
+            None => Ok(()),
+        },
+        None => Err(ClassPrepareHandlerError::AgentNotInitialized),
+    }
+}
 
-    capabilities.can_generate_all_class_hook_events();
-    capabilities.can_tag_objects();
-    capabilities.can_generate_object_free_events();
-    capabilities.can_get_source_file_name();
-    capabilities.can_get_line_numbers();
-    capabilities.can_generate_vm_object_alloc_events();
-    capabilities.can_generate_compiled_method_load_events();
-    env.add_capabilities(&capabilities).map_err(AgentInitError::UnableToAddCapabilities)?;
-    debug!("Capabilities added to the environment");
-    Ok(())
+// Lines whose breakpoints could not be set are left out of the report, the rest of the class is still covered
+fn instrument_class(env: &mut rvmti_api::JvmtiEnv, coverage: &coverage::Coverage, class: &rvmti_api::JClass,
+                    debug_info: &DebugInfoCapabilities) -> Result<(), CoverageError>
+{
+    let signature = env.get_class_signature(class).map_err(CoverageError::UnableToGetClassSignature)?.signature;
+    if !coverage.is_selected(&signature) {
+        return Ok(());
+    }
+    let class_info = class_info(env, class, debug_info).map_err(CoverageError::UnableToGetClassInfo)?;
+    let source_path = coverage::source_path(&signature, class_info.source_file_name.as_deref());
+    let mut result = Ok(());
+    for method in env.get_class_methods(class).map_err(CoverageError::UnableToGetClassMethods)? {
+        let modifiers = env.get_method_modifiers(&method).map_err(CoverageError::UnableToGetMethodModifiers)?;
+        if modifiers.is_native() || modifiers.is_abstract() {
+            continue;
+        }
+        let line_numbers = match env.get_line_number_table(&method).map_err(CoverageError::UnableToGetMethodLineNumbers)? {
+            Some(line_numbers) => line_numbers,
+            None => continue,
+        };
+        for location in coverage.add_method(method.raw() as usize, &source_path, &line_numbers) {
+            if let Err(e) = env.set_breakpoint(&method, location) {
+                coverage.breakpoint_failed(method.raw() as usize, location);
+                result = Err(CoverageError::UnableToSetBreakpoint(e));
+            }
+        }
+    }
+    result
 }
 
-fn set_event_callbacks<'a>(env: &mut rvmti::JvmtiEnv) -> Result<(), AgentInitError> {
-    let mut settings = rvmti::JvmtiEventCallbacksSettings::new_empty_settings();
-    settings.compiled_method_load_enabled();
-    settings.dynamic_code_generated_enabled();
-    env.set_event_callbacks_settings(&settings).map_err(AgentInitError::UnableToSetEventCallbacks)?;
-    debug!("Event callbacks set for the environment");
-    Ok(())
+fn on_breakpoint(env: &mut rvmti_api::JvmtiEnv, method_id: &rvmti_api::JMethodId,
+                 location: rvmti_api::JLocation) -> Result<(), BreakpointHandlerError>
+{
+    let agent_env = env.get_environment_local_storage::<AgentEnv>()
+        .map_err(BreakpointHandlerError::UnableToGetAgentEnvironment)?;
+    match agent_env {
+        Some(agent_env) => match agent_env.coverage {
+            Some(ref coverage) if coverage.hit(method_id.raw() as usize, location) => {
+                env.clear_breakpoint(method_id, location).map_err(BreakpointHandlerError::UnableToClearBreakpoint)
+            },
+            _ => Ok(()),
+        },
+        None => Err(BreakpointHandlerError::AgentNotInitialized),
+    }
 }
 
-fn enable_events<'a>(env: &mut rvmti::JvmtiEnv) -> Result<(), AgentInitError> {
-    env.set_event_notification_mode(rvmti::JvmtiEventMode::Enable, rvmti::JvmtiEvent::CompiledMethodLoad, None)
-        .map_err(AgentInitError::UnableToEnableEvents)?;
-    env.set_event_notification_mode(rvmti::JvmtiEventMode::Enable, rvmti::JvmtiEvent::DynamicCodeGenerated, None)
-        .map_err(AgentInitError::UnableToEnableEvents)?;
-    debug!("Events enabled for the environment");
-    Ok(())
+// Exception must not leak into the Java code running after the event
+fn java_call_error(jni_env: &rvmti_api::JniEnv, error: rvmti_api::JniCallError) -> VmInitHandlerError {
+    jni_env.exception_clear();
+    VmInitHandlerError::UnableToCallJava(error)
+}
+
+fn java_system_property(jni_env: &rvmti_api::JniEnv, key: &str) -> Result<Option<String>, rvmti_api::JniCallError> {
+    let system = jni_env.find_class("java/lang/System")?;
+    let get_property = jni_env.get_static_method_id(&system, "getProperty", "(Ljava/lang/String;)Ljava/lang/String;")?;
+    let key = jni_env.new_string(key)?;
+    match jni_env.call_static_object_method(&system, &get_property, &[rvmti_api::JArg::Object(Some(&key))])? {
+        Some(value) => jni_env.get_string(&value).map(Some),
+        None => Ok(None),
+    }
+}
+
+// Options given to the VM, without main class and its arguments. Only JDK 9 and later have
+// `jdk.internal.misc.VM`, JNI ignores module encapsulation
+fn jvm_input_arguments(jni_env: &rvmti_api::JniEnv) -> Result<Option<Vec<String>>, rvmti_api::JniCallError> {
+    let vm = match jni_env.find_class("jdk/internal/misc/VM") {
+        Ok(vm) => vm,
+        Err(rvmti_api::JniCallError::PendingException) => {
+            jni_env.exception_clear();
+            return Ok(None);
+        },
+        Err(e) => return Err(e),
+    };
+    let get_runtime_arguments = jni_env.get_static_method_id(&vm, "getRuntimeArguments", "()[Ljava/lang/String;")?;
+    let arguments = match jni_env.call_static_object_method(&vm, &get_runtime_arguments, &[])? {
+        Some(arguments) => arguments,
+        None => return Ok(None),
+    };
+    let length = jni_env.get_array_length(&arguments)?;
+    let mut result = Vec::with_capacity(length as usize);
+    for index in 0..length {
+        if let Some(argument) = jni_env.get_object_array_element(&arguments, index)? {
+            result.push(jni_env.get_string(&argument)?);
+        }
+    }
+    Ok(Some(result))
+}
+
+fn on_thread_end(env: &mut rvmti_api::JvmtiEnv) -> Result<(), ThreadEndHandlerError> {
+    let agent_env = env.get_environment_local_storage::<AgentEnv>()
+        .map_err(ThreadEndHandlerError::UnableToGetAgentEnvironment)?;
+    match agent_env {
+        Some(agent_env) => {
+            agent_env.thread_ended(perf::get_tid());
+            Ok(())
+        },
+        None => Err(ThreadEndHandlerError::AgentNotInitialized),
+    }
+}
+
+impl rvmti_api::Agent for PerfEventHandler {
+
+    type Error = AgentInitError;
+
+    fn init_logging() {
+        match env_logger::try_init() {
+            Ok(_) => {},
+            Err(e) => {
+                println!("Failed to initialize logger: {:?}", e);
+            }
+        }
+    }
+
+    // Invalid options fail on load, every feature is requested then
+    fn is_feature_requested(options: &Option<String>, feature: &str) -> bool {
+        match feature {
+            COVERAGE_FEATURE => options::AgentOptions::parse(options).map(|o| o.coverage.is_some()).unwrap_or(true),
+            BYTECODES_FEATURE => log_enabled!(log::Level::Debug),
+            _ => true,
+        }
+    }
+
+    fn on_load(vm: &rvmti_api::Jvm, env: &mut rvmti_api::JvmtiEnv, options: &Option<String>,
+               capabilities: &rvmti_api::agent::NegotiatedCapabilities) -> Result<PerfEventHandler, AgentInitError>
+    {
+        info!("Agent starting...");
+        debug!("Agent options: {}", options.as_ref().unwrap_or(&"".to_string()));
+        let options = options::AgentOptions::parse(options)
+            .map_err(AgentInitError::InvalidOptions)?;
+        let dump_dir = perf::create_dump_dir()
+            .map_err(AgentInitError::UnableToCreateDumpDir)?;
+        debug!("Jit dump directory created");
+        let dump_file = perf::DumpFile::new(&dump_dir)
+            .map_err(AgentInitError::UnableToCreateDumpFile)?;
+        debug!("Jit dump file created");
+        let threads_file = perf::SidecarFile::new(&dump_dir, "threads")
+            .map_err(AgentInitError::UnableToCreateThreadsFile)?;
+        debug!("Threads file created");
+        let metadata_file = perf::SidecarFile::new(&dump_dir, "meta")
+            .map_err(AgentInitError::UnableToCreateMetadataFile)?;
+        debug!("Metadata file created");
+        let natives_file = perf::SidecarFile::new(&dump_dir, "natives")
+            .map_err(AgentInitError::UnableToCreateNativesFile)?;
+        debug!("Natives file created");
+        let coverage = match options.coverage {
+            Some(ref prefixes) if capabilities.is_feature_enabled(COVERAGE_FEATURE) => {
+                let coverage_file = perf::SidecarFile::new(&dump_dir, "lcov")
+                    .map_err(AgentInitError::UnableToCreateCoverageFile)?;
+                debug!("Coverage file created");
+                Some((coverage::Coverage::new(prefixes), coverage_file))
+            },
+            Some(_) => {
+                warn!("Coverage is not available, breakpoints or line numbers are not supported by the VM");
+                None
+            },
+            None => None,
+        };
+        // Agent environment hangs off JVMTI environment, events reach it through environment local storage without a global lock
+        env.set_environment_local_storage(AgentEnv::new(dump_file, threads_file, metadata_file, natives_file, coverage))
+            .map_err(AgentInitError::UnableToStoreAgentEnvironment)?;
+        let debug_info = DebugInfoCapabilities{
+            line_numbers: capabilities.has_capability(rvmti_api::agent::capability::can_get_line_numbers),
+            source_file_names: capabilities.has_capability(rvmti_api::agent::capability::can_get_source_file_name),
+            bytecodes: capabilities.is_feature_enabled(BYTECODES_FEATURE),
+        };
+        info!("Agent started");
+        Ok(PerfEventHandler{vm: vm.clone(), debug_info})
+    }
+
+    fn on_unload(&self, _vm: &rvmti_api::Jvm, env: &mut rvmti_api::JvmtiEnv) {
+        info!("Agent unloading...");
+        match env.take_environment_local_storage::<AgentEnv>() {
+            // Dropping the agent environment flushes the worker thread and writes the close record
+            Ok(Some(agent_env)) => drop(agent_env),
+            Ok(None) => {
+                warn!("Agent was not initialized, skipping shutdown");
+            },
+            Err(err) => {
+                warn!("Failed to take agent environment for unloading: {}", err);
+            }
+        }
+        info!("Agent unloaded");
+    }
+
+}
+
+impl rvmti_api::AgentEventHandler for PerfEventHandler {
+
+    fn compiled_method_load(&self, env: &mut rvmti_api::JvmtiEnv, method: &rvmti_api::JMethodId,
+                            address_locations: &Option<Vec<rvmti_api::AddressLocationEntry>>,
+                            compile_info: &Option<Vec<rvmti_api::CompiledMethodLoadRecord>>, address: usize, length: usize)
+    {
+        // Compiled method load event carries no JNI environment, compiler threads are attached to the VM though
+        match self.vm.get_jni_env() {
+            Ok(jni_env) => jvmti_event_compiled_method_load(env, &jni_env, method, address_locations, compile_info,
+                                                            address, length, &self.debug_info),
+            Err(e) => {
+                warn!("Failed to obtain JNI environment for compiled method load event: {}", e);
+            }
+        }
+    }
+
+    fn dynamic_code_generated(&self, env: &mut rvmti_api::JvmtiEnv, name: &Option<String>, address: usize, length: usize) {
+        jvmti_event_dynamic_code_generated(env, name, address, length)
+    }
+
+    fn vm_init(&self, env: &mut rvmti_api::JvmtiEnv, jni_env: &mut rvmti_api::JniEnv, thread: &rvmti_api::JThread) {
+        jvmti_event_vm_init(env, jni_env, thread, &self.debug_info)
+    }
+
+    fn class_prepare(&self, env: &mut rvmti_api::JvmtiEnv, _jni_env: &mut rvmti_api::JniEnv, _thread: &rvmti_api::JThread,
+                     class: &rvmti_api::JClass)
+    {
+        jvmti_event_class_prepare(env, class, &self.debug_info)
+    }
+
+    fn breakpoint(&self, env: &mut rvmti_api::JvmtiEnv, _jni_env: &mut rvmti_api::JniEnv, _thread: &rvmti_api::JThread,
+                  method: &rvmti_api::JMethodId, location: rvmti_api::JLocation)
+    {
+        jvmti_event_breakpoint(env, method, location)
+    }
+
+    // Methods bound in the primordial phase can not be named, neither JNI nor most of JVMTI is available then
+    fn native_method_bind(&self, env: &mut rvmti_api::JvmtiEnv, jni_env: Option<&mut rvmti_api::JniEnv>,
+                          _thread: Option<&rvmti_api::JThread>, method: &rvmti_api::JMethodId, address: usize)
+    {
+        match jni_env {
+            Some(jni_env) => jvmti_event_native_method_bind(env, jni_env, method, address),
+            None => debug!("Native method bound in the primordial phase skipped: 0x{:x}", address),
+        }
+    }
+
+    fn thread_start(&self, env: &mut rvmti_api::JvmtiEnv, jni_env: &mut rvmti_api::JniEnv, thread: &rvmti_api::JThread) {
+        jvmti_event_thread_start(env, jni_env, thread)
+    }
+
+    fn thread_end(&self, env: &mut rvmti_api::JvmtiEnv, _jni_env: &mut rvmti_api::JniEnv, _thread: &rvmti_api::JThread) {
+        jvmti_event_thread_end(env)
+    }
+
 }
 
 impl AgentEnv {
 
-    fn new(env: rvmti::JvmtiEnv, dump_file: perf::DumpFile) -> AgentEnv {
+    fn new(dump_file: perf::DumpFile, threads_file: perf::SidecarFile, metadata_file: perf::SidecarFile,
+           natives_file: perf::SidecarFile, coverage: Option<(coverage::Coverage, perf::SidecarFile)>) -> AgentEnv
+    {
         debug!("Spawning agent worker thread...");
         let (sender, receiver) = channel();
+        let (coverage, coverage_file) = match coverage {
+            Some((coverage, coverage_file)) => (Some(coverage), Some(coverage_file)),
+            None => (None, None),
+        };
         let worker = thread::spawn(move|| {
             debug!("Agent worker thread running...");
-            run_worker(receiver, dump_file);
+            run_worker(receiver, dump_file, threads_file, metadata_file, natives_file, coverage_file);
         });
         debug!("Agent worker thread spawned");
-        AgentEnv{env, sender, worker: Some(worker)}
+        AgentEnv{sender, worker: Some(worker), threads: threads::ThreadRegistry::new(), coverage}
+    }
+
+    fn thread_started(&self, thread: threads::JavaThread) {
+        debug!("Java thread started: {:?}", thread);
+        if !self.threads.thread_started(thread.clone()) {
+            return;
+        }
+        match self.sender.send(AgentMessage::ThreadStart {thread}) {
+            Ok(_) => {},
+            Err(e) => {
+                error!("Failed to send thread start event to worker thread: {}", e);
+            },
+        };
+    }
+
+    fn metadata(&self, entries: Vec<Vec<String>>) {
+        match self.sender.send(AgentMessage::Metadata {entries}) {
+            Ok(_) => {},
+            Err(e) => {
+                error!("Failed to send VM metadata to worker thread: {}", e);
+            },
+        };
     }
 
-    fn dynamic_code_generated(&mut self, name: &Option<String>, address: usize, length: usize, timestamp: i64) {
+    fn thread_ended(&self, tid: i32) {
+        match self.threads.thread_ended(tid) {
+            Some(thread) => debug!("Java thread ended: {:?}", thread),
+            None => debug!("Unknown Java thread ended: {}", tid),
+        }
+    }
+
+    fn dynamic_code_generated(&self, name: &Option<String>, address: usize, length: usize, timestamp: i64) {
         let code = unsafe{
             slice::from_raw_parts(address as *const u8, length)
         };
@@ -243,10 +630,33 @@ impl AgentEnv {
         };
     }
 
-    fn compiled_method_load(&mut self, name: rvmti::MethodName, class_signature: rvmti::ClassSignature,
+    // Functions with an ELF symbol are already named by perf, the rest gets a code load record if its length is known
+    fn native_method_bind(&self, name: String, jni_symbol: Option<demangle::JniSymbol>, address: usize, timestamp: i64) {
+        let function = natives::lookup(address);
+        // Exported JNI function links the native frame to the Java declaration
+        let jni_export = match (&function.symbol, jni_symbol) {
+            (Some(symbol), Some(jni_symbol)) if jni_symbol.is_bound_by(symbol) => Some(symbol.clone()),
+            _ => None,
+        };
+        let code = match function {
+            natives::NativeFunction{symbol: None, length: Some(length), ..} => Some(unsafe {
+                slice::from_raw_parts(address as *const u8, length)
+            }.to_vec()),
+            _ => None,
+        };
+        match self.sender.send(AgentMessage::NativeMethodBind {name, function, jni_export, timestamp, code}) {
+            Ok(_) => {},
+            Err(e) => {
+                error!("Failed to send native method bind event to worker thread: {}", e);
+            },
+        };
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn compiled_method_load(&self, name: rvmti_api::MethodName, class_signature: rvmti_api::ClassSignature,
                             class_source_file_name: Option<String>, address: usize, length: usize,
-                            line_numbers: Option<Vec<rvmti::LineNumberEntry>>,
-                            address_locations: Option<Vec<rvmti::AddressLocationEntry>>,
+                            line_numbers: Option<Vec<rvmti_api::LineNumberEntry>>,
+                            address_locations: Option<Vec<rvmti_api::AddressLocationEntry>>,
                             stack_info: Option<Vec<StackInfo>>, timestamp: i64)
     {
         let code = unsafe{
@@ -266,6 +676,11 @@ impl AgentEnv {
 impl Drop for AgentEnv {
 
     fn drop(&mut self) {
+        if let Some(ref coverage) = self.coverage {
+            if let Err(e) = self.sender.send(AgentMessage::Coverage {report: coverage.lcov()}) {
+                error!("Failed to send coverage report to agent worker thread: {}", e);
+            }
+        }
         debug!("Stopping agent worker thread...");
         let send_shutdown_result = self.sender.send(AgentMessage::Shutdown);
         match send_shutdown_result {
@@ -274,24 +689,24 @@ impl Drop for AgentEnv {
                 error!("Failed to send shutdown request to agent worker thread: {}", e)
             },
         };
-        match self.worker.take() {
-            Some(w) => {
-                let worker_thread_result = w.join();
-                match worker_thread_result {
-                    Ok(_) => {},
-                    Err(e) => {
-                        error!("Failed to wait for worker thread shutdown: {:?}", e);
-                    }
+        if let Some(w) = self.worker.take() {
+            let worker_thread_result = w.join();
+            match worker_thread_result {
+                Ok(_) => {},
+                Err(e) => {
+                    error!("Failed to wait for worker thread shutdown: {:?}", e);
                 }
-            },
-            None => {},
+            }
         }
         debug!("Agent worker thread stopped");
     }
 
 }
 
-fn run_worker(receiver: Receiver<AgentMessage>, mut dump_file: perf::DumpFile) {
+fn run_worker(receiver: Receiver<AgentMessage>, mut dump_file: perf::DumpFile, mut threads_file: perf::SidecarFile,
+              mut metadata_file: perf::SidecarFile, mut natives_file: perf::SidecarFile,
+              mut coverage_file: Option<perf::SidecarFile>)
+{
     match dump_file.write_header() {
         Ok(_) => {},
         Err(e) => {
@@ -306,14 +721,17 @@ fn run_worker(receiver: Receiver<AgentMessage>, mut dump_file: perf::DumpFile) {
                     AgentMessage::DynamicCodeGenerated { name, address, length, timestamp, code } => {
                         debug!("'Dynamic code generated' event fired: {}, 0x{:x}, {}",
                               name.as_ref().unwrap_or(&"".to_string()), address, length);
-                        if name.is_some() && address != 0 as usize && length > 0 as usize {
-                            match dump_file.write_jit_code_load(name.unwrap(), address, length, code_index, timestamp, &code) {
-                                Ok(_) => {},
-                                Err(e) => {
-                                    error!("Failed to write jit code load record for dynamically generated code: {}", e);
+                        match name {
+                            Some(name) if address != 0usize && length > 0usize => {
+                                match dump_file.write_jit_code_load(name, address, length, code_index, timestamp, &code) {
+                                    Ok(_) => {},
+                                    Err(e) => {
+                                        error!("Failed to write jit code load record for dynamically generated code: {}", e);
+                                    }
                                 }
-                            }
-                            code_index += 1u64;
+                                code_index += 1u64;
+                            },
+                            _ => {},
                         }
                     },
                     AgentMessage::CompiledMethodLoad { name, class_signature, class_source_file_name,
@@ -321,7 +739,8 @@ fn run_worker(receiver: Receiver<AgentMessage>, mut dump_file: perf::DumpFile) {
                         debug!("'Compiled method load' event fired: {:?}, {:?}, {:?}, 0x{:x}, {}, {:?}, {:?}, {:?}",
                               name, class_signature, class_source_file_name, address, length,
                               line_numbers, address_locations, stack_info);
-                        if address != 0 as usize && length > 0 as usize {
+                        log_inlined_instructions(&stack_info);
+                        if address != 0usize && length > 0usize {
                             match dump_file.write_line_numbers(&name, &class_signature,
                                                                &class_source_file_name,
                                                                address, &line_numbers,
@@ -347,6 +766,64 @@ fn run_worker(receiver: Receiver<AgentMessage>, mut dump_file: perf::DumpFile) {
                             code_index += 1u64;
                         }
                     }
+                    AgentMessage::NativeMethodBind { name, function, jni_export, timestamp, code } => {
+                        debug!("'Native method bind' event fired: {}, {:?}", name, function);
+                        let entry_name = match jni_export {
+                            Some(ref symbol) => format!("{} [{}]", name, symbol),
+                            None => name.clone(),
+                        };
+                        // Entry without size would cover no sample address
+                        match function.length {
+                            Some(length) => match natives_file.write_perf_map_entry(function.address, length, &entry_name) {
+                                Ok(_) => {},
+                                Err(e) => {
+                                    error!("Failed to write native method: {}", e);
+                                }
+                            },
+                            None => debug!("Size of native method {} is unknown, it is not listed", name),
+                        }
+                        if let Some(code) = code {
+                            match dump_file.write_jit_code_load(name, function.address, code.len(), code_index, timestamp, &code) {
+                                Ok(_) => {},
+                                Err(e) => {
+                                    error!("Failed to write jit code load record for native method: {}", e);
+                                }
+                            }
+                            code_index += 1u64;
+                        }
+                    },
+                    AgentMessage::ThreadStart { thread } => {
+                        let tid = thread.tid.to_string();
+                        let daemon = if thread.daemon { "daemon" } else { "user" };
+                        let group = thread.group.as_deref().unwrap_or("");
+                        match threads_file.write_line(&[&tid, &thread.name, group, daemon]) {
+                            Ok(_) => {},
+                            Err(e) => {
+                                error!("Failed to write thread name: {}", e);
+                            }
+                        }
+                    },
+                    AgentMessage::Metadata { entries } => {
+                        for entry in entries {
+                            let fields: Vec<&str> = entry.iter().map(String::as_str).collect();
+                            match metadata_file.write_line(&fields) {
+                                Ok(_) => {},
+                                Err(e) => {
+                                    error!("Failed to write VM metadata: {}", e);
+                                }
+                            }
+                        }
+                    },
+                    AgentMessage::Coverage { report } => {
+                        if let Some(ref mut coverage_file) = coverage_file {
+                            match coverage_file.write_text(&report) {
+                                Ok(_) => {},
+                                Err(e) => {
+                                    error!("Failed to write coverage report: {}", e);
+                                }
+                            }
+                        }
+                    },
                     AgentMessage::Shutdown => {
                         debug!("Received shutdown request for agent worker thread...");
                         break;
@@ -367,25 +844,19 @@ fn run_worker(receiver: Receiver<AgentMessage>, mut dump_file: perf::DumpFile) {
     }
 }
 
-impl<'a> From<PoisonError<MutexGuard<'a, Option<AgentEnv>>>> for AgentInitError {
-
-    fn from(_error: PoisonError<MutexGuard<'a, Option<AgentEnv>>>) -> AgentInitError {
-        AgentInitError::PoisonedMutexError
-    }
-
-}
-
-fn method_info(env: &mut rvmti::JvmtiEnv, method_id: &rvmti::JMethodId) -> Result<MethodInfo, MethodInfoError> {
-    let name = env.get_method_name(&method_id)
+fn method_info(env: &mut rvmti_api::JvmtiEnv, jni_env: &rvmti_api::JniEnv, method_id: &rvmti_api::JMethodId,
+               debug_info: &DebugInfoCapabilities) -> Result<MethodInfo, MethodInfoError>
+{
+    let name = env.get_method_name(method_id)
         .map_err(MethodInfoError::UnableToGetMethodName)?;
-    let declaring_class_id = env.get_method_declaring_class(&method_id)
+    let declaring_class_id = env.get_method_declaring_class(jni_env, method_id)
         .map_err(MethodInfoError::UnableToGetMethodDeclaringClass)?;
-    let class = class_info(env, &declaring_class_id)
+    let class = class_info(env, &declaring_class_id, debug_info)
         .map_err(MethodInfoError::UnableToGetDeclaringClassInfo)?;
     let native_method = env.check_is_method_native(method_id)
         .map_err(MethodInfoError::UnableToCheckIfMethodIsNative)?;
-    let line_numbers = if !native_method {
-        env.get_line_number_table(&method_id).map_err(MethodInfoError::UnableToGetMethodLineNumbers)?
+    let line_numbers = if !native_method && debug_info.line_numbers {
+        env.get_line_number_table(method_id).map_err(MethodInfoError::UnableToGetMethodLineNumbers)?
     } else {
         None
     };
@@ -393,36 +864,39 @@ fn method_info(env: &mut rvmti::JvmtiEnv, method_id: &rvmti::JMethodId) -> Resul
     Ok(method_info)
 }
 
-fn class_info(env: &mut rvmti::JvmtiEnv, class_id: &rvmti::JClass) -> Result<ClassInfo, ClassInfoError> {
+fn class_info(env: &mut rvmti_api::JvmtiEnv, class_id: &rvmti_api::JClass,
+              debug_info: &DebugInfoCapabilities) -> Result<ClassInfo, ClassInfoError>
+{
     let signature = env.get_class_signature(class_id)
         .map_err(ClassInfoError::UnableToGetClassSignature)?;
-    let source_file_name = env.get_source_file_name(class_id)
-        .map_err(ClassInfoError::UnableToGetClassSourceFileName)?;
+    let source_file_name = if debug_info.source_file_names {
+        env.get_source_file_name(class_id).map_err(ClassInfoError::UnableToGetClassSourceFileName)?
+    } else {
+        None
+    };
     let class_info = ClassInfo{signature, source_file_name};
     Ok(class_info)
 }
 
-fn stack_info(env: &mut rvmti::JvmtiEnv, compile_info: &Option<Vec<rvmti::CompiledMethodLoadRecord>>)
-    -> Result<Option<Vec<StackInfo>>, StackInfoError>
+fn stack_info(env: &mut rvmti_api::JvmtiEnv, jni_env: &rvmti_api::JniEnv, compile_info: &Option<Vec<rvmti_api::CompiledMethodLoadRecord>>,
+              debug_info: &DebugInfoCapabilities) -> Result<Option<Vec<StackInfo>>, StackInfoError>
 {
     match compile_info {
-        &Some(ref infos) => {
+        Some(infos) => {
             let mut result = Vec::new();
+            // Same methods show up at many pcs, each one is disassembled once
+            let mut disassemblies = HashMap::new();
             for info in infos.iter() {
-                match info {
-                    &rvmti::CompiledMethodLoadRecord::Inline{ref stack_infos} => {
-                        for stack_info in stack_infos.iter() {
-                            let mut stack_frame_infos: Vec<StackFrameInfo> = Vec::new();
-                            for stack_frame in stack_info.stack_frames.iter() {
-                                let method_info = method_info(env, &stack_frame.method_id)
-                                    .map_err(StackInfoError::UnableToGetMethodInfo)?;
-                                stack_frame_infos.push(StackFrameInfo{method: method_info,
-                                    byte_code_index: stack_frame.byte_code_index});
-                            }
-                            result.push(StackInfo{pc_address: stack_info.pc_address, stack_frames: stack_frame_infos});
+                if let rvmti_api::CompiledMethodLoadRecord::Inline{stack_infos} = info {
+                    for stack_info in stack_infos.iter() {
+                        let mut stack_frame_infos: Vec<StackFrameInfo> = Vec::new();
+                        for stack_frame in stack_info.stack_frames.iter() {
+                            stack_frame_infos.push(stack_frame_info(env, jni_env, &stack_frame.method_id,
+                                                                    stack_frame.byte_code_index, debug_info,
+                                                                    &mut disassemblies)?);
                         }
-                    },
-                    _ => {},
+                        result.push(StackInfo{pc_address: stack_info.pc_address, stack_frames: stack_frame_infos});
+                    }
                 }
             }
             return Ok(Some(result));
@@ -431,34 +905,98 @@ fn stack_info(env: &mut rvmti::JvmtiEnv, compile_info: &Option<Vec<rvmti::Compil
     }
 }
 
+// Bytecode instruction each inlined frame was compiled from, frames carry instructions only with debug log enabled
+fn log_inlined_instructions(stack_info: &Option<Vec<StackInfo>>) {
+    for info in stack_info.iter().flatten() {
+        for frame in info.stack_frames.iter() {
+            if let Some(ref instruction) = frame.instruction {
+                debug!("Inlined frame at 0x{:x}: {} bci {}: {}", info.pc_address,
+                       perf::java_method_name(&frame.method.name, &frame.method.class.signature),
+                       frame.byte_code_index, instruction);
+            }
+        }
+    }
+}
+
+// Resolves frames of a Java stack trace the same way as inlined frames of compiled methods
+pub fn stack_trace_info(env: &mut rvmti_api::JvmtiEnv, jni_env: &rvmti_api::JniEnv, frames: &[rvmti_api::FrameInfo],
+                        debug_info: &DebugInfoCapabilities) -> Result<Vec<StackFrameInfo>, StackInfoError>
+{
+    let mut disassemblies = HashMap::new();
+    frames.iter()
+        .map(|frame| stack_frame_info(env, jni_env, &frame.method, frame.location as i32, debug_info, &mut disassemblies))
+        .collect()
+}
+
+fn stack_frame_info(env: &mut rvmti_api::JvmtiEnv, jni_env: &rvmti_api::JniEnv, method_id: &rvmti_api::JMethodId,
+                    byte_code_index: i32, debug_info: &DebugInfoCapabilities,
+                    disassemblies: &mut HashMap<usize, Option<rvmti_api::Disassembly>>) -> Result<StackFrameInfo, StackInfoError>
+{
+    let method_info = method_info(env, jni_env, method_id, debug_info)
+        .map_err(StackInfoError::UnableToGetMethodInfo)?;
+    let instruction = if debug_info.bytecodes && !method_info.native_method && byte_code_index >= 0 {
+        // Only shown in debug log, a method that can not be disassembled leaves the frame without instruction
+        let disassembly = disassemblies.entry(method_id.raw() as usize).or_insert_with(|| {
+            env.get_bytecodes(method_id).ok().and_then(|bytecodes| rvmti_api::Disassembly::decode(&bytecodes).ok())
+        });
+        disassembly.as_ref().and_then(|d| d.instruction_at(byte_code_index as i64)).map(|i| i.to_string())
+    } else {
+        None
+    };
+    Ok(StackFrameInfo{method: method_info, byte_code_index, instruction})
+}
+
 #[derive(Debug)]
 enum AgentMessage {
     Shutdown,
+    ThreadStart { thread: threads::JavaThread },
+    // Key followed by one or more values
+    Metadata { entries: Vec<Vec<String>> },
+    // LCOV tracefile, sent once on shutdown
+    Coverage { report: String },
     DynamicCodeGenerated { name: Option<String>, address: usize, length: usize, timestamp: i64, code: Vec<u8> },
-    CompiledMethodLoad { name: rvmti::MethodName, class_signature: rvmti::ClassSignature, class_source_file_name: Option<String>,
-        address: usize, length: usize, line_numbers: Option<Vec<rvmti::LineNumberEntry>>,
-        address_locations: Option<Vec<rvmti::AddressLocationEntry>>, stack_info: Option<Vec<StackInfo>>,
+    // Code is only copied for functions perf can not name from ELF symbols
+    NativeMethodBind { name: String, function: natives::NativeFunction, jni_export: Option<String>, timestamp: i64,
+                       code: Option<Vec<u8>> },
+    CompiledMethodLoad { name: rvmti_api::MethodName, class_signature: rvmti_api::ClassSignature, class_source_file_name: Option<String>,
+        address: usize, length: usize, line_numbers: Option<Vec<rvmti_api::LineNumberEntry>>,
+        address_locations: Option<Vec<rvmti_api::AddressLocationEntry>>, stack_info: Option<Vec<StackInfo>>,
         timestamp: i64, code: Vec<u8> },
 }
 
+#[derive(Debug)]
+struct PerfEventHandler {
+    vm: rvmti_api::Jvm,
+    debug_info: DebugInfoCapabilities,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DebugInfoCapabilities {
+    line_numbers: bool,
+    source_file_names: bool,
+    // Granted and debug log enabled
+    bytecodes: bool,
+}
+
 #[derive(Debug)]
 struct AgentEnv {
-    env: rvmti::JvmtiEnv,
     sender: Sender<AgentMessage>,
     worker: Option<JoinHandle<()>>,
+    threads: threads::ThreadRegistry,
+    coverage: Option<coverage::Coverage>,
 }
 
 #[derive(Debug)]
 pub struct MethodInfo {
-    name: rvmti::MethodName,
+    name: rvmti_api::MethodName,
     class: ClassInfo,
     native_method: bool,
-    line_numbers: Option<Vec<rvmti::LineNumberEntry>>,
+    line_numbers: Option<Vec<rvmti_api::LineNumberEntry>>,
 }
 
 #[derive(Debug)]
 pub struct ClassInfo {
-    signature: rvmti::ClassSignature,
+    signature: rvmti_api::ClassSignature,
     source_file_name: Option<String>,
 }
 
@@ -466,6 +1004,8 @@ pub struct ClassInfo {
 pub struct StackFrameInfo {
     method: MethodInfo,
     byte_code_index: i32,
+    // Instruction at the bci, e.g. `invokevirtual #12`
+    instruction: Option<String>,
 }
 
 #[derive(Debug)]
@@ -476,46 +1016,48 @@ pub struct StackInfo {
 
 #[derive(Error, Debug)]
 enum AgentInitError {
-    #[error("Failed to add capabilities: {0}")]
-    UnableToAddCapabilities(#[source] rvmti::JvmtiError),
-    #[error("Failed to set event callbacks: {0}")]
-    UnableToSetEventCallbacks(#[source] rvmti::JvmtiError),
-    #[error("Failed to enable events: {0}")]
-    UnableToEnableEvents(#[source] rvmti::JvmtiError),
-    #[error("Failed to obtain jvmti environment: {0}")]
-    UnableToObtainJvmtiEnvironment(#[source] rvmti::JniError),
-    #[error("The mutex was poisoned")]
-    PoisonedMutexError,
     #[error("Failed to create jit dump directory: {0}")]
     UnableToCreateDumpDir(#[source] perf::CreteDumpDirError),
     #[error("Failed to create jit dump file: {0}")]
     UnableToCreateDumpFile(#[source] perf::NewDumpFileError),
+    #[error("Failed to create threads file: {0}")]
+    UnableToCreateThreadsFile(#[source] perf::NewDumpFileError),
+    #[error("Failed to create metadata file: {0}")]
+    UnableToCreateMetadataFile(#[source] perf::NewDumpFileError),
+    #[error("Failed to create natives file: {0}")]
+    UnableToCreateNativesFile(#[source] perf::NewDumpFileError),
+    #[error("Failed to create coverage file: {0}")]
+    UnableToCreateCoverageFile(#[source] perf::NewDumpFileError),
+    #[error("Invalid agent options: {0}")]
+    InvalidOptions(#[source] options::OptionsError),
+    #[error("Failed to store agent environment: {0}")]
+    UnableToStoreAgentEnvironment(#[source] rvmti_api::LocalStorageError),
 }
 
 #[derive(Error, Debug)]
-enum MethodInfoError {
+pub enum MethodInfoError {
     #[error("Failed to obtain method name: {0}")]
-    UnableToGetMethodName(#[source] rvmti::GetMethodNameError),
+    UnableToGetMethodName(#[source] rvmti_api::GetMethodNameError),
     #[error("Failed to obtain method declaring class id: {0}")]
-    UnableToGetMethodDeclaringClass(#[source] rvmti::JvmtiError),
+    UnableToGetMethodDeclaringClass(#[source] rvmti_api::JvmtiError),
     #[error("Failed to obtain method declaring class info: {0}")]
     UnableToGetDeclaringClassInfo(#[source] ClassInfoError),
     #[error("Failed to check if method is native: {0}")]
-    UnableToCheckIfMethodIsNative(#[source] rvmti::JvmtiError),
+    UnableToCheckIfMethodIsNative(#[source] rvmti_api::JvmtiError),
     #[error("Failed to obtain method line numbers: {0}")]
-    UnableToGetMethodLineNumbers(#[source] rvmti::JvmtiError),
+    UnableToGetMethodLineNumbers(#[source] rvmti_api::JvmtiError),
 }
 
 #[derive(Error, Debug)]
-enum ClassInfoError {
+pub enum ClassInfoError {
     #[error("Failed to obtain class signature: {0}")]
-    UnableToGetClassSignature(#[source] rvmti::GetClassSignatureError),
+    UnableToGetClassSignature(#[source] rvmti_api::GetClassSignatureError),
     #[error("Failed to obtain class source file name: {0}")]
-    UnableToGetClassSourceFileName(#[source] rvmti::GetSourceFileNameError),
+    UnableToGetClassSourceFileName(#[source] rvmti_api::GetSourceFileNameError),
 }
 
 #[derive(Error, Debug)]
-enum StackInfoError {
+pub enum StackInfoError {
     #[error("Failed to obtain method metadata: {0}")]
     UnableToGetMethodInfo(#[source] MethodInfoError),
 }
@@ -526,8 +1068,8 @@ enum DynamicCodeGeneratedHandlerError {
     UnableToGetTimestamp(#[source] nix::errno::Errno),
     #[error("Agent is not initialized")]
     AgentNotInitialized,
-    #[error("Failed to lock agent environment")]
-    FailedToLockAgentEnvironment,
+    #[error("Failed to obtain agent environment: {0}")]
+    UnableToGetAgentEnvironment(#[source] rvmti_api::LocalStorageError),
 }
 
 #[derive(Error, Debug)]
@@ -536,10 +1078,483 @@ enum CompiledMethodLoadHandlerError {
     UnableToGetTimestamp(#[source] nix::errno::Errno),
     #[error("Agent is not initialized")]
     AgentNotInitialized,
-    #[error("Failed to lock agent environment")]
-    FailedToLockAgentEnvironment,
+    #[error("Failed to obtain agent environment: {0}")]
+    UnableToGetAgentEnvironment(#[source] rvmti_api::LocalStorageError),
     #[error("Unable to get method info: {0}")]
     UnableToGetMethodInfo(#[source] MethodInfoError),
     #[error("Unable to get stack info: {0}")]
     UnableToGetStackInfo(#[source] StackInfoError),
 }
+
+#[derive(Error, Debug)]
+enum NativeMethodBindHandlerError {
+    #[error("Unable to get timestamp: {0}")]
+    UnableToGetTimestamp(#[source] nix::errno::Errno),
+    #[error("Agent is not initialized")]
+    AgentNotInitialized,
+    #[error("Failed to obtain agent environment: {0}")]
+    UnableToGetAgentEnvironment(#[source] rvmti_api::LocalStorageError),
+    #[error("Unable to get method info: {0}")]
+    UnableToGetMethodInfo(#[source] MethodInfoError),
+}
+
+#[derive(Error, Debug)]
+enum ThreadStartHandlerError {
+    #[error("Agent is not initialized")]
+    AgentNotInitialized,
+    #[error("Failed to obtain agent environment: {0}")]
+    UnableToGetAgentEnvironment(#[source] rvmti_api::LocalStorageError),
+    #[error("Unable to get thread info: {0}")]
+    UnableToGetThreadInfo(#[source] rvmti_api::GetThreadInfoError),
+    #[error("Unable to get thread group info: {0}")]
+    UnableToGetThreadGroupInfo(#[source] rvmti_api::GetThreadInfoError),
+}
+
+#[derive(Error, Debug)]
+enum VmInitHandlerError {
+    #[error("Agent is not initialized")]
+    AgentNotInitialized,
+    #[error("Failed to obtain agent environment: {0}")]
+    UnableToGetAgentEnvironment(#[source] rvmti_api::LocalStorageError),
+    #[error("Unable to get system property: {0}")]
+    UnableToGetSystemProperty(#[source] rvmti_api::GetSystemPropertyError),
+    #[error("Unable to call Java: {0}")]
+    UnableToCallJava(#[source] rvmti_api::JniCallError),
+    #[error("Unable to instrument loaded classes: {0}")]
+    UnableToInstrumentClasses(#[source] CoverageError),
+}
+
+#[derive(Error, Debug)]
+enum ClassPrepareHandlerError {
+    #[error("Agent is not initialized")]
+    AgentNotInitialized,
+    #[error("Failed to obtain agent environment: {0}")]
+    UnableToGetAgentEnvironment(#[source] rvmti_api::LocalStorageError),
+    #[error("Unable to get VM phase: {0}")]
+    UnableToGetPhase(#[source] rvmti_api::JvmtiError),
+    #[error("Unable to instrument class: {0}")]
+    UnableToInstrumentClass(#[source] CoverageError),
+}
+
+#[derive(Error, Debug)]
+enum BreakpointHandlerError {
+    #[error("Agent is not initialized")]
+    AgentNotInitialized,
+    #[error("Failed to obtain agent environment: {0}")]
+    UnableToGetAgentEnvironment(#[source] rvmti_api::LocalStorageError),
+    #[error("Unable to clear breakpoint: {0}")]
+    UnableToClearBreakpoint(#[source] rvmti_api::JvmtiError),
+}
+
+#[derive(Error, Debug)]
+#[allow(clippy::enum_variant_names)]
+enum CoverageError {
+    #[error("Failed to obtain loaded classes: {0}")]
+    UnableToGetLoadedClasses(#[source] rvmti_api::JvmtiError),
+    #[error("Failed to obtain class status: {0}")]
+    UnableToGetClassStatus(#[source] rvmti_api::JvmtiError),
+    #[error("Failed to obtain class signature: {0}")]
+    UnableToGetClassSignature(#[source] rvmti_api::GetClassSignatureError),
+    #[error("Failed to obtain class info: {0}")]
+    UnableToGetClassInfo(#[source] ClassInfoError),
+    #[error("Failed to obtain class methods: {0}")]
+    UnableToGetClassMethods(#[source] rvmti_api::JvmtiError),
+    #[error("Failed to obtain method modifiers: {0}")]
+    UnableToGetMethodModifiers(#[source] rvmti_api::JvmtiError),
+    #[error("Failed to obtain method line numbers: {0}")]
+    UnableToGetMethodLineNumbers(#[source] rvmti_api::JvmtiError),
+    #[error("Failed to set breakpoint: {0}")]
+    UnableToSetBreakpoint(#[source] rvmti_api::JvmtiError),
+}
+
+#[derive(Error, Debug)]
+enum ThreadEndHandlerError {
+    #[error("Agent is not initialized")]
+    AgentNotInitialized,
+    #[error("Failed to obtain agent environment: {0}")]
+    UnableToGetAgentEnvironment(#[source] rvmti_api::LocalStorageError),
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use rvmti_api::mock::{MockJvmti, MockCompileInfo, address_location_map};
+    use crate::jitdump::{JitDump, RecordBody, CodeLoad, DebugEntry};
+
+    fn run_agent<F: FnOnce()>(test_name: &str, mock: &MockJvmti, f: F) -> JitDump {
+        let debug_info = super::DebugInfoCapabilities{line_numbers: true, source_file_names: true, bytecodes: false};
+        run_agent_with_handler(test_name, mock, perf_handler(mock, debug_info), f)
+    }
+
+    fn perf_handler(mock: &MockJvmti, debug_info: super::DebugInfoCapabilities) -> super::PerfEventHandler {
+        super::PerfEventHandler{vm: rvmti_api::Jvm::cons(mock.vm_ptr()), debug_info}
+    }
+
+    fn run_agent_with_handler<F: FnOnce()>(test_name: &str, mock: &MockJvmti, handler: super::PerfEventHandler,
+                                           f: F) -> JitDump
+    {
+        run_agent_with_sidecars(test_name, mock, handler, None, f).0
+    }
+
+    // Returns jit dump, threads, metadata, natives and coverage sidecar contents, coverage one only exists with coverage
+    // enabled
+    fn run_agent_with_sidecars<F: FnOnce()>(test_name: &str, mock: &MockJvmti, handler: super::PerfEventHandler,
+                                            coverage: Option<super::coverage::Coverage>,
+                                            f: F) -> (JitDump, String, String, String, Option<String>)
+    {
+        let dir = std::env::temp_dir().join(format!("rvmti-test-{}-{}", std::process::id(), test_name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let dump_file = super::perf::DumpFile::new(&dir).unwrap();
+        let mut env = rvmti_api::JvmtiEnv::cons(mock.env_ptr());
+        env.set_event_handler(Arc::new(handler)).unwrap();
+        let threads_file = super::perf::SidecarFile::new(&dir, "threads").unwrap();
+        let metadata_file = super::perf::SidecarFile::new(&dir, "meta").unwrap();
+        let natives_file = super::perf::SidecarFile::new(&dir, "natives").unwrap();
+        let coverage = coverage.map(|c| (c, super::perf::SidecarFile::new(&dir, "lcov").unwrap()));
+        env.set_environment_local_storage(super::AgentEnv::new(dump_file, threads_file, metadata_file, natives_file,
+                                                               coverage)).unwrap();
+        f();
+        // Dropping the agent environment flushes the worker thread and writes the close record
+        let agent_env = env.take_environment_local_storage::<super::AgentEnv>().unwrap();
+        drop(agent_env);
+        rvmti_api::JvmtiEnv::cons(mock.env_ptr()).clear_event_handler();
+        let path: PathBuf = dir.join(format!("jit-{}.dump", std::process::id()));
+        let dump = JitDump::read_file(&path).unwrap();
+        let threads = fs::read_to_string(dir.join(format!("jit-{}.threads", std::process::id()))).unwrap();
+        let metadata = fs::read_to_string(dir.join(format!("jit-{}.meta", std::process::id()))).unwrap();
+        let natives = fs::read_to_string(dir.join(format!("jit-{}.natives", std::process::id()))).unwrap();
+        let coverage = fs::read_to_string(dir.join(format!("jit-{}.lcov", std::process::id()))).ok();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(dump.header.version, 1);
+        assert_eq!(dump.header.pid, std::process::id());
+        (dump, threads, metadata, natives, coverage)
+    }
+
+    fn record_ids(dump: &JitDump) -> Vec<u32> {
+        dump.records.iter().map(|r| match r.body {
+            RecordBody::CodeLoad(_) => 0,
+            RecordBody::DebugInfo(_) => 2,
+            RecordBody::CodeClose => 3,
+            RecordBody::Unknown{id, ..} => id,
+        }).collect()
+    }
+
+    fn debug_entry(address: usize, line: i32, file_name: &str) -> DebugEntry {
+        DebugEntry{address: address as u64, line, discriminator: 0, file_name: file_name.to_string()}
+    }
+
+    #[test]
+    fn test_compiled_method_load_with_line_numbers() {
+        let mock = MockJvmti::new();
+        let class = mock.add_class("Lcom/example/Worker;", Some("Worker.java"));
+        let method = mock.add_method(class, "process", "(I[Ljava/lang/String;)J", Some(vec![(0, 10), (4, 12), (9, 15)]));
+        let code = vec![0x90u8; 32];
+        let address = code.as_ptr() as usize;
+        let map = address_location_map(&[(address, 0), (address + 8, 5), (address + 16, 9)]);
+        let dump = run_agent("line_numbers", &mock, || {
+            rvmti_api::events::jvmti_event_compiled_method_load_handler(mock.env_ptr(), method, code.len() as i32,
+                                                                   code.as_ptr() as *const std::os::raw::c_void,
+                                                                   map.len() as i32, map.as_ptr(), std::ptr::null());
+        });
+        assert_eq!(record_ids(&dump), vec![2, 0, 3]);
+        let debug_info = dump.debug_infos().next().unwrap();
+        assert_eq!(debug_info.code_address, address as u64);
+        assert_eq!(debug_info.entries, vec![
+            debug_entry(address, 10, "com/example/Worker.java"),
+            debug_entry(address + 8, 12, "com/example/Worker.java"),
+            debug_entry(address + 16, 15, "com/example/Worker.java"),
+        ]);
+        let load = dump.code_loads().next().unwrap();
+        assert_eq!(load.code_address, address as u64);
+        assert_eq!(load.vma, address as u64);
+        assert_eq!(load.code_index, 0);
+        assert_eq!(load.name, "long com.example.Worker.process(int p0, java.lang.String[] p1)");
+        assert_eq!(load.code, code);
+        assert_eq!(mock.outstanding_allocations(), 0);
+        assert_eq!(mock.outstanding_local_refs(), 0);
+    }
+
+    #[test]
+    fn test_compiled_method_load_without_debug_info_capabilities() {
+        let mock = MockJvmti::new();
+        let class = mock.add_class("Lcom/example/Worker;", Some("Worker.java"));
+        let method = mock.add_method(class, "process", "()V", Some(vec![(0, 10), (4, 12)]));
+        let code = [0x90u8; 16];
+        let address = code.as_ptr() as usize;
+        let map = address_location_map(&[(address, 0), (address + 8, 4)]);
+        let debug_info = super::DebugInfoCapabilities{line_numbers: false, source_file_names: false, bytecodes: false};
+        let dump = run_agent_with_handler("no_debug_info", &mock, perf_handler(&mock, debug_info), || {
+            rvmti_api::events::jvmti_event_compiled_method_load_handler(mock.env_ptr(), method, code.len() as i32,
+                                                                   code.as_ptr() as *const std::os::raw::c_void,
+                                                                   map.len() as i32, map.as_ptr(), std::ptr::null());
+        });
+        assert_eq!(record_ids(&dump), vec![0, 3]);
+        assert_eq!(dump.code_loads().next().unwrap().name, "void com.example.Worker.process()");
+        assert_eq!(mock.outstanding_allocations(), 0);
+        assert_eq!(mock.outstanding_local_refs(), 0);
+    }
+
+    #[test]
+    fn test_compiled_method_load_with_inline_records() {
+        let mock = MockJvmti::new();
+        let outer_class = mock.add_class("Lcom/example/Outer;", Some("Outer.java"));
+        let inner_class = mock.add_class("Lcom/example/util/Inner;", Some("Inner.java"));
+        let no_source_class = mock.add_class("Lcom/example/Generated;", None);
+        let outer = mock.add_method(outer_class, "run", "()V", Some(vec![(0, 20), (7, 21)]));
+        let inner = mock.add_method(inner_class, "compute", "(J)I", Some(vec![(0, 100), (3, 101)]));
+        let generated = mock.add_method(no_source_class, "stub", "()V", None);
+        let native = mock.add_native_method(inner_class, "hash", "(J)I");
+        let code = vec![0xccu8; 48];
+        let address = code.as_ptr() as usize;
+        let mut compile_info = MockCompileInfo::new()
+            .dummy_record()
+            .inline_record(vec![
+                (address, vec![(outer, 0)]),
+                (address + 8, vec![(inner, 4), (outer, 7)]),
+                (address + 16, vec![(native, 0), (generated, 2), (outer, 7)]),
+                (address + 24, vec![(generated, 0)]),
+            ]);
+        let dump = run_agent("inline_records", &mock, || {
+            rvmti_api::events::jvmti_event_compiled_method_load_handler(mock.env_ptr(), outer, code.len() as i32,
+                                                                   code.as_ptr() as *const std::os::raw::c_void,
+                                                                   0, std::ptr::null(), compile_info.as_ptr());
+        });
+        assert_eq!(record_ids(&dump), vec![2, 0, 3]);
+        // Frames without source information are skipped in favour of the first outer frame that has it
+        assert_eq!(dump.debug_infos().next().unwrap().entries, vec![
+            debug_entry(address, 20, "com/example/Outer.java"),
+            debug_entry(address + 8, 101, "com/example/util/Inner.java"),
+            debug_entry(address + 16, 21, "com/example/Outer.java"),
+        ]);
+        let load = dump.code_loads().next().unwrap();
+        assert_eq!(load.name, "void com.example.Outer.run()");
+        assert_eq!(load.code, code);
+        assert_eq!(mock.outstanding_allocations(), 0);
+        assert_eq!(mock.outstanding_local_refs(), 0);
+    }
+
+    #[test]
+    fn test_dynamic_code_generated() {
+        let mock = MockJvmti::new();
+        let first = vec![0x01u8; 16];
+        let second = vec![0x02u8; 8];
+        let first_name = std::ffi::CString::new("Interpreter").unwrap();
+        let second_name = std::ffi::CString::new("StubRoutines (1)").unwrap();
+        let dump = run_agent("dynamic_code", &mock, || {
+            rvmti_api::events::jvmti_event_dynamic_code_generated_handler(mock.env_ptr(), first_name.as_ptr(),
+                                                                     first.as_ptr() as *const std::os::raw::c_void,
+                                                                     first.len() as i32);
+            rvmti_api::events::jvmti_event_dynamic_code_generated_handler(mock.env_ptr(), second_name.as_ptr(),
+                                                                     second.as_ptr() as *const std::os::raw::c_void,
+                                                                     second.len() as i32);
+        });
+        assert_eq!(record_ids(&dump), vec![0, 0, 3]);
+        let loads: Vec<&CodeLoad> = dump.code_loads().collect();
+        assert_eq!(loads[0].name, "Interpreter");
+        assert_eq!(loads[0].code_address, first.as_ptr() as u64);
+        assert_eq!(loads[0].code_index, 0);
+        assert_eq!(loads[0].code, first);
+        assert_eq!(loads[1].name, "StubRoutines (1)");
+        assert_eq!(loads[1].code_index, 1);
+        assert_eq!(loads[1].code, second);
+    }
+
+    #[test]
+    fn test_compiled_method_load_with_unknown_method() {
+        let mock = MockJvmti::new();
+        let code = [0x90u8; 8];
+        let dump = run_agent("unknown_method", &mock, || {
+            rvmti_api::events::jvmti_event_compiled_method_load_handler(mock.env_ptr(), 0xdead0 as rvmti_sys::jmethodID,
+                                                                   code.len() as i32,
+                                                                   code.as_ptr() as *const std::os::raw::c_void,
+                                                                   0, std::ptr::null(), std::ptr::null());
+        });
+        assert_eq!(record_ids(&dump), vec![3]);
+        assert_eq!(mock.outstanding_allocations(), 0);
+        assert_eq!(mock.outstanding_local_refs(), 0);
+    }
+
+    #[test]
+    fn test_enabled_event_dispatches_to_handler() {
+        let mock = MockJvmti::new();
+        let code = [0x90u8; 16];
+        let dump = run_agent("enabled_event", &mock, || {
+            let mut env = rvmti_api::JvmtiEnv::cons(mock.env_ptr());
+            env.enable_event(rvmti_api::JvmtiEvent::DynamicCodeGenerated, None).unwrap();
+            assert!(mock.is_event_enabled(rvmti_sys::jvmtiEvent_JVMTI_EVENT_DYNAMIC_CODE_GENERATED));
+            assert!(!mock.is_event_enabled(rvmti_sys::jvmtiEvent_JVMTI_EVENT_COMPILED_METHOD_LOAD));
+            let callbacks = mock.callbacks().unwrap();
+            assert!(callbacks.CompiledMethodLoad.is_none());
+            let name = std::ffi::CString::new("Interpreter").unwrap();
+            unsafe {
+                callbacks.DynamicCodeGenerated.unwrap()(mock.env_ptr(), name.as_ptr(),
+                                                        code.as_ptr() as *const std::os::raw::c_void, code.len() as i32);
+            }
+            env.disable_event(rvmti_api::JvmtiEvent::DynamicCodeGenerated, None).unwrap();
+            assert!(!mock.is_event_enabled(rvmti_sys::jvmtiEvent_JVMTI_EVENT_DYNAMIC_CODE_GENERATED));
+        });
+        assert_eq!(record_ids(&dump), vec![0, 3]);
+        assert_eq!(dump.code_loads().next().unwrap().name, "Interpreter");
+    }
+
+    #[test]
+    fn test_thread_start_writes_thread_names() {
+        let mock = MockJvmti::new();
+        let system = mock.add_thread_group("system", None);
+        let main = mock.add_thread_group("main", Some(system));
+        let main_thread = mock.add_thread("main", Some(main), false);
+        let worker_thread = mock.add_thread("pool-1-thread-1\tlong name exceeding comm", Some(main), true);
+        let tid = super::perf::get_tid();
+        let handler = perf_handler(&mock, super::DebugInfoCapabilities{line_numbers: true, source_file_names: true, bytecodes: false});
+        let (dump, threads, _, _, _) = run_agent_with_sidecars("thread_names", &mock, handler, None, || {
+            rvmti_api::events::jvmti_event_vm_init_handler(mock.env_ptr(), mock.jni_env_ptr(), main_thread);
+            // Same thread reported twice is written once
+            rvmti_api::events::jvmti_event_thread_start_handler(mock.env_ptr(), mock.jni_env_ptr(), main_thread);
+            rvmti_api::events::jvmti_event_thread_end_handler(mock.env_ptr(), mock.jni_env_ptr(), main_thread);
+            rvmti_api::events::jvmti_event_thread_start_handler(mock.env_ptr(), mock.jni_env_ptr(), worker_thread);
+            rvmti_api::events::jvmti_event_thread_start_handler(mock.env_ptr(), mock.jni_env_ptr(),
+                                                                0xdead0 as rvmti_sys::jthread);
+        });
+        assert_eq!(record_ids(&dump), vec![3]);
+        assert_eq!(threads, format!("{}\tmain\tmain\tuser\n{}\tpool-1-thread-1\\tlong name exceeding comm\tmain\tdaemon\n",
+                                    tid, tid));
+        assert_eq!(mock.outstanding_allocations(), 0);
+        assert_eq!(mock.outstanding_local_refs(), 0);
+    }
+
+    #[test]
+    fn test_vm_init_writes_metadata() {
+        let mock = MockJvmti::new();
+        let main_thread = mock.add_thread("main", None, false);
+        mock.set_system_property("java.vm.name", "OpenJDK 64-Bit Server VM");
+        mock.set_system_property("java.vm.version", "17.0.15+6");
+        mock.set_system_property("java.version", "17.0.15");
+        mock.set_system_property("sun.java.command", "com.example.Main\targ");
+        mock.set_system_property("java.home", "/usr/lib/jvm/java-17");
+        let handler = perf_handler(&mock, super::DebugInfoCapabilities{line_numbers: true, source_file_names: true, bytecodes: false});
+        let (_, _, metadata, _, _) = run_agent_with_sidecars("metadata", &mock, handler, None, || {
+            rvmti_api::events::jvmti_event_vm_init_handler(mock.env_ptr(), mock.jni_env_ptr(), main_thread);
+        });
+        // Mock has no Java classes, input arguments are skipped and the failed lookup leaves no exception behind
+        assert_eq!(metadata, "java.vm.name\tOpenJDK 64-Bit Server VM\njava.version\t17.0.15\njava.vm.version\t17.0.15+6\n\
+                              sun.java.command\tcom.example.Main\\targ\n");
+        assert!(!mock.has_pending_exception());
+        assert_eq!(mock.outstanding_allocations(), 0);
+        assert_eq!(mock.outstanding_local_refs(), 0);
+    }
+
+    #[test]
+    fn test_coverage_breakpoints() {
+        let mock = MockJvmti::new();
+        let main_thread = mock.add_thread("main", None, false);
+        let early = mock.add_class("Lcom/example/Early;", Some("Early.java"));
+        let early_init = mock.add_method(early, "<clinit>", "()V", Some(vec![(0, 3)]));
+        let handler = perf_handler(&mock, super::DebugInfoCapabilities{line_numbers: true, source_file_names: true, bytecodes: false});
+        let coverage = super::coverage::Coverage::new(&["com.example.".to_string()]);
+        let (_, _, _, _, coverage) = run_agent_with_sidecars("coverage", &mock, handler, Some(coverage), || {
+            // Breakpoints are not available before the live phase, VM init catches up
+            mock.set_phase(rvmti_sys::jvmtiPhase_JVMTI_PHASE_START);
+            rvmti_api::events::jvmti_event_class_prepare_handler(mock.env_ptr(), mock.jni_env_ptr(), main_thread, early);
+            assert!(mock.breakpoints().is_empty());
+            mock.set_phase(rvmti_sys::jvmtiPhase_JVMTI_PHASE_LIVE);
+            rvmti_api::events::jvmti_event_vm_init_handler(mock.env_ptr(), mock.jni_env_ptr(), main_thread);
+            assert_eq!(mock.breakpoints(), vec![(early_init, 0)]);
+
+            let worker = mock.add_class("Lcom/example/Worker$Task;", Some("Worker.java"));
+            let worker_run = mock.add_method(worker, "run", "()V", Some(vec![(0, 10), (4, 11), (9, 12), (15, 11)]));
+            mock.add_native_method(worker, "hash", "(J)I");
+            let other = mock.add_class("Lorg/example/Other;", Some("Other.java"));
+            mock.add_method(other, "run", "()V", Some(vec![(0, 1)]));
+            rvmti_api::events::jvmti_event_class_prepare_handler(mock.env_ptr(), mock.jni_env_ptr(), main_thread, worker);
+            rvmti_api::events::jvmti_event_class_prepare_handler(mock.env_ptr(), mock.jni_env_ptr(), main_thread, other);
+            assert_eq!(mock.breakpoints(), vec![(early_init, 0), (worker_run, 0), (worker_run, 4), (worker_run, 9)]);
+
+            // First hit clears the breakpoint, breakpoints unknown to coverage are left alone
+            rvmti_api::events::jvmti_event_breakpoint_handler(mock.env_ptr(), mock.jni_env_ptr(), main_thread, worker_run, 0);
+            rvmti_api::events::jvmti_event_breakpoint_handler(mock.env_ptr(), mock.jni_env_ptr(), main_thread, worker_run, 9);
+            rvmti_api::events::jvmti_event_breakpoint_handler(mock.env_ptr(), mock.jni_env_ptr(), main_thread, worker_run, 15);
+            rvmti_api::events::jvmti_event_breakpoint_handler(mock.env_ptr(), mock.jni_env_ptr(), main_thread, early_init, 0);
+            assert_eq!(mock.breakpoints(), vec![(worker_run, 4)]);
+        });
+        assert_eq!(coverage.unwrap(), "TN:\nSF:com/example/Early.java\nDA:3,1\nLF:1\nLH:1\nend_of_record\n\
+                                       TN:\nSF:com/example/Worker.java\nDA:10,1\nDA:11,0\nDA:12,1\nLF:3\nLH:2\nend_of_record\n");
+        assert_eq!(mock.outstanding_allocations(), 0);
+        assert_eq!(mock.outstanding_local_refs(), 0);
+    }
+
+    #[test]
+    fn test_native_method_bind() {
+        let mock = MockJvmti::new();
+        let main_thread = mock.add_thread("main", None, false);
+        let class = mock.add_class("Lcom/example/Native;", Some("Native.java"));
+        let exported = mock.add_native_method(class, "pid", "()I");
+        let private = mock.add_native_method(class, "mix", "(J)J");
+        let unknown = mock.add_native_method(class, "unknown", "()V");
+        let exported_address = libc::getpid as *const () as usize;
+        let private_address = super::natives::private_native as *const () as usize;
+        assert_eq!(super::natives::private_native(1), 0x5ada);
+        let handler = perf_handler(&mock, super::DebugInfoCapabilities{line_numbers: true, source_file_names: true, bytecodes: false});
+        let (dump, _, _, natives, _) = run_agent_with_sidecars("natives", &mock, handler, None, || {
+            // Nothing tells the size of a function at 0x10, it is not listed
+            for &(method, address) in [(exported, exported_address), (private, private_address), (unknown, 0x10)].iter() {
+                rvmti_api::events::jvmti_event_native_method_bind_handler(mock.env_ptr(), mock.jni_env_ptr(), main_thread,
+                                                                          method, address as *mut std::os::raw::c_void,
+                                                                          std::ptr::null_mut());
+            }
+            // Primordial phase binds come without JNI environment and are skipped
+            rvmti_api::events::jvmti_event_native_method_bind_handler(mock.env_ptr(), std::ptr::null_mut(),
+                                                                      std::ptr::null_mut(), private,
+                                                                      private_address as *mut std::os::raw::c_void,
+                                                                      std::ptr::null_mut());
+        });
+        let exported_length = super::natives::lookup(exported_address).length.unwrap();
+        let private_length = super::natives::lookup(private_address).length.unwrap();
+        assert_eq!(natives, format!("{:x} {:x} int com.example.Native.pid()\n{:x} {:x} long com.example.Native.mix(long p0)\n",
+                                    exported_address, exported_length, private_address, private_length));
+        // Only the function without ELF symbol gets a code load record
+        assert_eq!(record_ids(&dump), vec![0, 3]);
+        let load = dump.code_loads().next().unwrap();
+        assert_eq!(load.name, "long com.example.Native.mix(long p0)");
+        assert_eq!(load.code_address, private_address as u64);
+        assert_eq!(load.code.len(), private_length);
+        assert_eq!(mock.outstanding_allocations(), 0);
+        assert_eq!(mock.outstanding_local_refs(), 0);
+    }
+
+    #[test]
+    fn test_stack_trace_info() {
+        let mock = MockJvmti::new();
+        let class = mock.add_class("Lcom/example/Worker;", Some("Worker.java"));
+        let run = mock.add_method(class, "run", "()V", Some(vec![(0, 10), (6, 11)]));
+        let process = mock.add_method(class, "process", "(I)J", Some(vec![(0, 20), (3, 21)]));
+        let native = mock.add_native_method(class, "hash", "(J)I");
+        // iload_1, i2l, lconst_1, ladd, lreturn
+        mock.set_bytecodes(process, &[0x1b, 0x85, 0x0a, 0x61, 0xad]);
+        let main = mock.add_thread("main", None, false);
+        mock.set_stack(main, &[(native, -1), (process, 3), (run, 6)]);
+        mock.set_current_thread(main);
+        let mut env = rvmti_api::JvmtiEnv::cons(mock.env_ptr());
+        let jni_env = rvmti_api::JniEnv::cons(mock.jni_env_ptr());
+        let debug_info = super::DebugInfoCapabilities{line_numbers: true, source_file_names: true, bytecodes: true};
+        let frames = env.get_stack_trace(None, 0, 10).unwrap();
+        let resolved = super::stack_trace_info(&mut env, &jni_env, &frames, &debug_info).unwrap();
+        let names: Vec<(&str, bool, i32)> = resolved.iter()
+            .map(|f| (f.method.name.name.as_str(), f.method.native_method, f.byte_code_index))
+            .collect();
+        assert_eq!(names, vec![("hash", true, -1), ("process", false, 3), ("run", false, 6)]);
+        assert_eq!(resolved[1].method.class.source_file_name.as_deref(), Some("Worker.java"));
+        assert_eq!(resolved[1].method.line_numbers.as_ref().unwrap().len(), 2);
+        // Frames of methods without bytecodes get no instruction
+        let instructions: Vec<Option<&str>> = resolved.iter().map(|f| f.instruction.as_deref()).collect();
+        assert_eq!(instructions, vec![None, Some("ladd"), None]);
+        drop(resolved);
+        assert_eq!(mock.outstanding_allocations(), 0);
+        assert_eq!(mock.outstanding_local_refs(), 0);
+    }
+
+}
@@ -0,0 +1,294 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+// Native functions bound to Java native methods. Exported JNI functions are named by ELF symbols already, functions of
+// stripped libraries and functions registered with RegisterNatives may only be found in unwind tables
+
+use std::ffi::CStr;
+use std::mem;
+use std::os::raw::c_void;
+#[cfg(target_os = "linux")]
+use std::ptr;
+use std::slice;
+
+#[cfg(all(target_os = "linux", target_env = "gnu", target_pointer_width = "64"))]
+type ElfSymbol = libc::Elf64_Sym;
+#[cfg(all(target_os = "linux", target_env = "gnu", target_pointer_width = "32"))]
+type ElfSymbol = libc::Elf32_Sym;
+
+// From dlfcn.h, makes dladdr1 return the symbol table entry
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+const RTLD_DL_SYMENT: libc::c_int = 1;
+const DW_EH_PE_OMIT: u8 = 0xff;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NativeFunction {
+    pub address: usize,
+    // ELF symbol covering the address, perf resolves such functions by itself
+    pub symbol: Option<String>,
+    // Bytes from the address to the end of the function, if known
+    pub length: Option<usize>,
+}
+
+#[cfg(target_os = "linux")]
+#[repr(C)]
+struct DwarfEhBases {
+    tbase: *mut c_void,
+    dbase: *mut c_void,
+    func: *mut c_void,
+}
+
+#[cfg(target_os = "linux")]
+extern "C" {
+    // Provided by the unwinder, searches `.eh_frame_hdr` of every loaded object
+    fn _Unwind_Find_FDE(pc: *mut c_void, bases: *mut DwarfEhBases) -> *const u8;
+}
+
+pub fn lookup(address: usize) -> NativeFunction {
+    match symbol_at(address) {
+        Some((name, length)) => NativeFunction{address, symbol: Some(name), length},
+        None => NativeFunction{address, symbol: None, length: unwind_length(address)},
+    }
+}
+
+// Name of the symbol covering the address and the bytes from the address to its end
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+fn symbol_at(address: usize) -> Option<(String, Option<usize>)> {
+    let mut info: libc::Dl_info = unsafe { mem::zeroed() };
+    let mut symbol: *mut ElfSymbol = ptr::null_mut();
+    let found = unsafe {
+        libc::dladdr1(address as *const c_void, &mut info, &mut symbol as *mut *mut ElfSymbol as *mut *mut c_void,
+                      RTLD_DL_SYMENT)
+    };
+    // dladdr finds the closest symbol below the address, which only covers it if it is large enough
+    if found != 0 && !symbol.is_null() && !info.dli_sname.is_null() {
+        let start = info.dli_saddr as usize;
+        let size = unsafe { (*symbol).st_size } as usize;
+        if address == start || (address > start && address < start + size) {
+            let name = unsafe { CStr::from_ptr(info.dli_sname) }.to_string_lossy().into_owned();
+            let length = if size > 0 { Some(start + size - address) } else { None };
+            return Some((name, length));
+        }
+    }
+    None
+}
+
+// Plain dladdr does not tell the symbol size, so only a symbol starting at the address is known to cover it
+#[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+fn symbol_at(address: usize) -> Option<(String, Option<usize>)> {
+    let mut info: libc::Dl_info = unsafe { mem::zeroed() };
+    let found = unsafe { libc::dladdr(address as *const c_void, &mut info) };
+    if found == 0 || info.dli_sname.is_null() || info.dli_saddr as usize != address {
+        return None;
+    }
+    let name = unsafe { CStr::from_ptr(info.dli_sname) }.to_string_lossy().into_owned();
+    Some((name, None))
+}
+
+fn unwind_length(address: usize) -> Option<usize> {
+    let (fde, start) = find_fde(address)?;
+    let range = unsafe { fde_pc_range(fde) }?;
+    (start + range).checked_sub(address).filter(|l| *l > 0)
+}
+
+// FDE covering the address and the start of the function it describes
+#[cfg(target_os = "linux")]
+fn find_fde(address: usize) -> Option<(*const u8, usize)> {
+    let mut bases = DwarfEhBases{tbase: ptr::null_mut(), dbase: ptr::null_mut(), func: ptr::null_mut()};
+    let fde = unsafe { _Unwind_Find_FDE(address as *mut c_void, &mut bases) };
+    if fde.is_null() || bases.func as usize > address {
+        return None;
+    }
+    Some((fde, bases.func as usize))
+}
+
+// Other unwinders have no lookup of FDEs by address
+#[cfg(not(target_os = "linux"))]
+fn find_fde(_address: usize) -> Option<(*const u8, usize)> {
+    None
+}
+
+// Length of the code an FDE describes. Only the value format of the CIE pointer encoding matters, pc_begin is already
+// resolved by the unwinder
+unsafe fn fde_pc_range(fde: *const u8) -> Option<usize> {
+    let fde = record(fde)?;
+    let cie_offset = u32_at(fde, 4)? as usize;
+    let cie = record(fde.as_ptr().add(4).sub(cie_offset))?;
+    let encoding = pointer_encoding(cie)?;
+    let mut position = 8;
+    read_encoded(fde, &mut position, encoding)?;
+    read_encoded(fde, &mut position, encoding).map(|range| range as usize)
+}
+
+// Record with its length field, 64-bit DWARF records are not used in `.eh_frame`
+unsafe fn record<'a>(start: *const u8) -> Option<&'a [u8]> {
+    let length = u32_at(slice::from_raw_parts(start, 4), 0)?;
+    if length == 0 || length == u32::MAX {
+        return None;
+    }
+    Some(slice::from_raw_parts(start, 4 + length as usize))
+}
+
+fn pointer_encoding(cie: &[u8]) -> Option<u8> {
+    let version = *cie.get(8)?;
+    let augmentation_start = 9;
+    let augmentation_end = augmentation_start + cie.get(augmentation_start..)?.iter().position(|b| *b == 0)?;
+    let augmentation = &cie[augmentation_start..augmentation_end];
+    if augmentation.is_empty() {
+        return Some(0);
+    }
+    // Augmentation data can only be skipped if its length is known
+    if augmentation[0] != b'z' {
+        return None;
+    }
+    let mut position = augmentation_end + 1;
+    uleb128(cie, &mut position)?;
+    uleb128(cie, &mut position)?;
+    if version == 1 {
+        position += 1;
+    } else {
+        uleb128(cie, &mut position)?;
+    }
+    uleb128(cie, &mut position)?;
+    for c in augmentation[1..].iter() {
+        match *c {
+            b'R' => return cie.get(position).cloned(),
+            b'P' => {
+                let encoding = *cie.get(position)?;
+                position += 1;
+                read_encoded(cie, &mut position, encoding)?;
+            },
+            b'L' => position += 1,
+            b'S' | b'B' => {},
+            _ => return None,
+        }
+    }
+    Some(0)
+}
+
+fn read_encoded(bytes: &[u8], position: &mut usize, encoding: u8) -> Option<u64> {
+    if encoding == DW_EH_PE_OMIT {
+        return None;
+    }
+    let size = match encoding & 0x0f {
+        0x00 => mem::size_of::<usize>(),
+        0x01 => return uleb128(bytes, position),
+        0x09 => return sleb128(bytes, position).map(|v| v as u64),
+        0x02 | 0x0a => 2,
+        0x03 | 0x0b => 4,
+        0x04 | 0x0c => 8,
+        _ => return None,
+    };
+    let field = bytes.get(*position..*position + size)?;
+    *position += size;
+    // Signed formats only differ once the value is negative, which a range never is
+    let value = match *field {
+        [b0, b1] => u16::from_ne_bytes([b0, b1]) as u64,
+        [b0, b1, b2, b3] => u32::from_ne_bytes([b0, b1, b2, b3]) as u64,
+        [b0, b1, b2, b3, b4, b5, b6, b7] => u64::from_ne_bytes([b0, b1, b2, b3, b4, b5, b6, b7]),
+        _ => return None,
+    };
+    Some(value)
+}
+
+fn u32_at(bytes: &[u8], position: usize) -> Option<u32> {
+    let field = bytes.get(position..position + 4)?;
+    Some(u32::from_ne_bytes([field[0], field[1], field[2], field[3]]))
+}
+
+fn uleb128(bytes: &[u8], position: &mut usize) -> Option<u64> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes.get(*position)?;
+        *position += 1;
+        if shift < 64 {
+            value |= ((byte & 0x7f) as u64) << shift;
+        }
+        shift += 7;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+}
+
+fn sleb128(bytes: &[u8], position: &mut usize) -> Option<i64> {
+    let mut value = 0i64;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes.get(*position)?;
+        *position += 1;
+        if shift < 64 {
+            value |= ((byte & 0x7f) as i64) << shift;
+        }
+        shift += 7;
+        if byte & 0x80 == 0 {
+            if shift < 64 && byte & 0x40 != 0 {
+                value |= -1 << shift;
+            }
+            return Some(value);
+        }
+    }
+}
+
+// Not exported, so only unwind tables know where it ends
+#[cfg(test)]
+#[inline(never)]
+pub(crate) extern "C" fn private_native(value: u64) -> u64 {
+    value.rotate_left(7) ^ 0x5a5a
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::private_native;
+
+    #[test]
+    fn test_lookup_exported_symbol() {
+        let getpid = super::lookup(libc::getpid as *const () as usize);
+        // Aliases share the address, glibc may report `__getpid`
+        assert!(getpid.symbol.unwrap().ends_with("getpid"));
+        // Only dladdr1 of glibc tells the symbol size
+        assert_eq!(getpid.length.filter(|l| *l > 0).is_some(), cfg!(all(target_os = "linux", target_env = "gnu")));
+    }
+
+    #[test]
+    fn test_lookup_unwind_tables() {
+        assert_eq!(private_native(1), 0x5ada);
+        let function = super::lookup(private_native as *const () as usize);
+        assert_eq!(function.symbol, None);
+        let length = function.length.unwrap();
+        assert!(length > 4 && length < 4096, "{}", length);
+        assert_eq!(super::lookup(0x10).length, None);
+    }
+
+    #[test]
+    fn test_read_encoded() {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&0x1234u16.to_ne_bytes());
+        bytes.extend_from_slice(&0x1234_5678u32.to_ne_bytes());
+        bytes.extend_from_slice(&0x1234_5678_9abc_def0u64.to_ne_bytes());
+        let mut position = 0;
+        assert_eq!(super::read_encoded(&bytes, &mut position, 0x02), Some(0x1234));
+        assert_eq!(super::read_encoded(&bytes, &mut position, 0x0b), Some(0x1234_5678));
+        assert_eq!(super::read_encoded(&bytes, &mut position, 0x04), Some(0x1234_5678_9abc_def0));
+        assert_eq!(super::read_encoded(&bytes, &mut position, 0x03), None);
+        assert_eq!(super::read_encoded(&bytes, &mut position, super::DW_EH_PE_OMIT), None);
+    }
+
+    #[test]
+    fn test_leb128() {
+        let bytes = [0xe5, 0x8e, 0x26, 0x7f, 0x80, 0x7f];
+        let mut position = 0;
+        assert_eq!(super::uleb128(&bytes, &mut position), Some(624485));
+        assert_eq!(super::sleb128(&bytes, &mut position), Some(-1));
+        assert_eq!(super::sleb128(&bytes, &mut position), Some(-128));
+        assert_eq!(super::uleb128(&bytes, &mut position), None);
+    }
+
+}
@@ -0,0 +1,70 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+// Agent options, given as comma separated `key=value` pairs after the agent path:
+// `-agentpath:librvmti.so=coverage=com.example.:org.example.Main`
+
+use thiserror::Error;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AgentOptions {
+    // Class name prefixes selecting classes for line coverage, `com.example.` or `com.example.Main`
+    pub coverage: Option<Vec<String>>,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum OptionsError {
+    #[error("Unknown option '{0}'")]
+    UnknownOption(String),
+    #[error("Option '{0}' requires a value")]
+    MissingValue(String),
+}
+
+impl AgentOptions {
+
+    pub fn parse(options: &Option<String>) -> Result<AgentOptions, OptionsError> {
+        let mut result = AgentOptions::default();
+        let options = match options {
+            Some(options) => options,
+            None => return Ok(result),
+        };
+        for option in options.split(',').filter(|o| !o.is_empty()) {
+            let (key, value) = option.split_once('=').unwrap_or((option, ""));
+            match key {
+                "coverage" => {
+                    let prefixes: Vec<String> = value.split(':').filter(|p| !p.is_empty()).map(str::to_string).collect();
+                    if prefixes.is_empty() {
+                        return Err(OptionsError::MissingValue(key.to_string()));
+                    }
+                    result.coverage = Some(prefixes);
+                },
+                _ => return Err(OptionsError::UnknownOption(key.to_string())),
+            }
+        }
+        Ok(result)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(AgentOptions::parse(&None), Ok(AgentOptions{coverage: None}));
+        assert_eq!(AgentOptions::parse(&Some("".to_string())), Ok(AgentOptions{coverage: None}));
+        assert_eq!(AgentOptions::parse(&Some("coverage=com.example.:org.example.Main".to_string())),
+                   Ok(AgentOptions{coverage: Some(vec!["com.example.".to_string(), "org.example.Main".to_string()])}));
+        assert_eq!(AgentOptions::parse(&Some("coverage=".to_string())),
+                   Err(OptionsError::MissingValue("coverage".to_string())));
+        assert_eq!(AgentOptions::parse(&Some("coverage=com.example.,verbose".to_string())),
+                   Err(OptionsError::UnknownOption("verbose".to_string())));
+    }
+
+}
@@ -21,13 +21,12 @@ use rand::distributions::Alphanumeric;
 use nix::sys::mman::{mmap, munmap, ProtFlags, MapFlags};
 use chrono::prelude::Local;
 
-use super::rvmti;
 use super::demangle;
 
 pub fn create_dump_dir() -> Result<PathBuf, CreteDumpDirError> {
     let cur_dir = env::current_dir().map_err(CreteDumpDirError::IoError)?;
     let jit_dir = cur_dir.join(".debug").join("jit");
-    let _ = DirBuilder::new().recursive(true).mode(0o755).create(&jit_dir).map_err(CreteDumpDirError::IoError)?;
+    DirBuilder::new().recursive(true).mode(0o755).create(&jit_dir).map_err(CreteDumpDirError::IoError)?;
     let date = Local::now().format("%Y%m%d").to_string();
     let prefix = format!("java-jit-{}", date);
     let mut rng = thread_rng();
@@ -57,6 +56,12 @@ pub struct DumpFile {
 // Required for global thread-safe store of initialized environments
 unsafe impl Send for DumpFile {}
 
+// Text file next to the jit dump for data perf has no records for, one tab separated line per entry
+#[derive(Debug)]
+pub struct SidecarFile {
+    file: File,
+}
+
 impl DumpFile {
 
     pub fn new<P: AsRef<Path>>(path: P) -> Result<DumpFile, NewDumpFileError> {
@@ -80,7 +85,7 @@ impl DumpFile {
                 prot_flags.insert(ProtFlags::PROT_READ);
                 prot_flags.insert(ProtFlags::PROT_EXEC);
                 let mapped_file = unsafe {
-                    mmap(ptr::null_mut() as *mut libc::c_void, map_size, prot_flags,
+                    mmap(ptr::null_mut(), map_size, prot_flags,
                          MapFlags::MAP_PRIVATE, file.as_raw_fd(), 0)
                         .map_err(NewDumpFileError::MmapError)?
                 };
@@ -131,7 +136,7 @@ impl DumpFile {
     }
 
     pub fn write_jit_code_load(&mut self, name: String, address: usize, length: usize, code_index: u64,
-                               timestamp: i64, code: &Vec<u8>) -> Result<(), WriteRecordError> {
+                               timestamp: i64, code: &[u8]) -> Result<(), WriteRecordError> {
         let pid = get_pid();
         let tid = get_tid();
         let name_bytes = name.as_bytes();
@@ -158,25 +163,23 @@ impl DumpFile {
         NativeEndian::write_u32_into(&third_record_part, &mut record[16..24]);
         NativeEndian::write_u64_into(&fourth_record_part, &mut record[24..56]);
         let _ = self.file.write(&record).map_err(WriteRecordError::IoError)?;
-        let _ = self.file.write(&name_bytes).map_err(WriteRecordError::IoError)?;
+        let _ = self.file.write(name_bytes).map_err(WriteRecordError::IoError)?;
         let _ = self.file.write(&[0u8; 1]).map_err(WriteRecordError::IoError)?;
-        let _ = self.file.write(&code).map_err(WriteRecordError::IoError)?;
+        let _ = self.file.write(code).map_err(WriteRecordError::IoError)?;
         Ok(())
     }
 
-    pub fn write_compiled_method_load(&mut self, name: rvmti::MethodName, class_signature: rvmti::ClassSignature,
+    #[allow(clippy::too_many_arguments)]
+    pub fn write_compiled_method_load(&mut self, name: rvmti_api::MethodName, class_signature: rvmti_api::ClassSignature,
                                       _class_source_file_name: Option<String>, address: usize, length: usize,
-                                      _line_numbers: Option<Vec<rvmti::LineNumberEntry>>,
-                                      _address_locations: Option<Vec<rvmti::AddressLocationEntry>>,
+                                      _line_numbers: Option<Vec<rvmti_api::LineNumberEntry>>,
+                                      _address_locations: Option<Vec<rvmti_api::AddressLocationEntry>>,
                                       _stack_info: Option<Vec<super::StackInfo>>,
-                                      code_index: u64, timestamp: i64, code: &Vec<u8>) -> Result<(), WriteRecordError>
+                                      code_index: u64, timestamp: i64, code: &[u8]) -> Result<(), WriteRecordError>
     {
         let pid = get_pid();
         let tid = get_tid();
-        let combined_name = demangle::MethodType::new(&name.signature)
-            .and_then(|mt| demangle::ClassType::new(&class_signature.signature)
-                .map(|cs| mt.display_as_method_definition(&name.name, &cs)))
-            .unwrap_or_else(|_| format!("{}.{}{}", class_signature.signature, name.name, name.signature));
+        let combined_name = java_method_name(&name, &class_signature);
         let name_bytes = combined_name.as_bytes();
         let mut record = [0u8; 56];
         let first_record_part = [
@@ -201,16 +204,17 @@ impl DumpFile {
         NativeEndian::write_u32_into(&third_record_part, &mut record[16..24]);
         NativeEndian::write_u64_into(&fourth_record_part, &mut record[24..56]);
         let _ = self.file.write(&record).map_err(WriteRecordError::IoError)?;
-        let _ = self.file.write(&name_bytes).map_err(WriteRecordError::IoError)?;
+        let _ = self.file.write(name_bytes).map_err(WriteRecordError::IoError)?;
         let _ = self.file.write(&[0u8; 1]).map_err(WriteRecordError::IoError)?;
-        let _ = self.file.write(&code).map_err(WriteRecordError::IoError)?;
+        let _ = self.file.write(code).map_err(WriteRecordError::IoError)?;
         Ok(())
     }
 
-    pub fn write_line_numbers(&mut self, name: &rvmti::MethodName, class_signature: &rvmti::ClassSignature,
+    #[allow(clippy::too_many_arguments)]
+    pub fn write_line_numbers(&mut self, name: &rvmti_api::MethodName, class_signature: &rvmti_api::ClassSignature,
                           class_source_file_name: &Option<String>, address: usize,
-                          line_numbers: &Option<Vec<rvmti::LineNumberEntry>>,
-                          address_locations: &Option<Vec<rvmti::AddressLocationEntry>>,
+                          line_numbers: &Option<Vec<rvmti_api::LineNumberEntry>>,
+                          address_locations: &Option<Vec<rvmti_api::AddressLocationEntry>>,
                           stack_info: &Option<Vec<super::StackInfo>>,
                           timestamp: i64) -> Result<(), WriteRecordError>
     {
@@ -234,11 +238,12 @@ impl DumpFile {
         Ok(())
     }
 
-    fn write_line_numbers_without_stack_info(&mut self, _name: &rvmti::MethodName,
-                                             class_signature: &rvmti::ClassSignature,
+    #[allow(clippy::too_many_arguments)]
+    fn write_line_numbers_without_stack_info(&mut self, _name: &rvmti_api::MethodName,
+                                             class_signature: &rvmti_api::ClassSignature,
                                              class_source_file_name: &str,
-                                             line_numbers: &Vec<rvmti::LineNumberEntry>,
-                                             address_locations: &Vec<rvmti::AddressLocationEntry>,
+                                             line_numbers: &Vec<rvmti_api::LineNumberEntry>,
+                                             address_locations: &Vec<rvmti_api::AddressLocationEntry>,
                                              address: usize, timestamp: i64) -> Result<(), WriteRecordError>
     {
         let mut record_size = 32u32;
@@ -269,7 +274,7 @@ impl DumpFile {
         let _ = self.file.write(&record).map_err(WriteRecordError::IoError)?;
         for location in address_locations {
             let line = self.find_line_number_entry(location.location as i32, line_numbers);
-            if !line.is_some() {
+            if line.is_none() {
                 continue;
             }
             let mut entry = [0u8; 16];
@@ -285,7 +290,7 @@ impl DumpFile {
             NativeEndian::write_u64_into(&first_entry_part, &mut entry[0..8]);
             NativeEndian::write_i32_into(&second_entry_part, &mut entry[8..16]);
             let _ = self.file.write(&entry).map_err(WriteRecordError::IoError)?;
-            let _ = self.file.write(&name_bytes).map_err(WriteRecordError::IoError)?;
+            let _ = self.file.write(name_bytes).map_err(WriteRecordError::IoError)?;
             let _ = self.file.write(&[0u8; 1]).map_err(WriteRecordError::IoError)?;
         }
         Ok(())
@@ -316,9 +321,7 @@ impl DumpFile {
             if info.stack_frames.is_empty() {
                 continue;
             }
-            let maybe_frame = self.find_frame(&info.stack_frames);
-            if maybe_frame.is_some() {
-                let frame = maybe_frame.unwrap();
+            if let Some(frame) = self.find_frame(&info.stack_frames) {
                 let method = &frame.method;
                 let maybe_line_numbers = &method.line_numbers;
                 if !maybe_line_numbers.is_some() {
@@ -327,7 +330,7 @@ impl DumpFile {
 
                 let line_numbers = maybe_line_numbers.as_ref().unwrap();
                 let line = self.find_line_number_entry(frame.byte_code_index, line_numbers);
-                if !line.is_some() {
+                if line.is_none() {
                     continue;
                 }
                 let mut entry = [0u8; 16];
@@ -341,13 +344,13 @@ impl DumpFile {
                 ];
                 let class_location = method.class.source_file_name.as_ref()
                     .map(|n| demangle::ClassType::new(&method.class.signature.signature)
-                    .map(|v| v.package_as_file_path(&n)).unwrap_or_else(|_| n.to_owned()))
+                    .map(|v| v.package_as_file_path(n)).unwrap_or_else(|_| n.to_owned()))
                     .unwrap_or_else(|| "".to_owned());
                 let name_bytes = class_location.as_bytes();
                 NativeEndian::write_u64_into(&first_entry_part, &mut entry[0..8]);
                 NativeEndian::write_i32_into(&second_entry_part, &mut entry[8..16]);
                 let _ = self.file.write(&entry).map_err(WriteRecordError::IoError)?;
-                let _ = self.file.write(&name_bytes).map_err(WriteRecordError::IoError)?;
+                let _ = self.file.write(name_bytes).map_err(WriteRecordError::IoError)?;
                 let _ = self.file.write(&[0u8; 1]).map_err(WriteRecordError::IoError)?;
             }
         }
@@ -362,13 +365,12 @@ impl DumpFile {
                 // No line numbers info, skip
                 continue;
             }
-            let frame = self.find_frame(&info.stack_frames);
-            if frame.is_some() {
+            if let Some(frame) = self.find_frame(&info.stack_frames) {
                 entries_count += 1u64;
-                let method = &frame.unwrap().method;
+                let method = &frame.method;
                 let class_location = method.class.source_file_name.as_ref()
                     .map(|n| demangle::ClassType::new(&method.class.signature.signature)
-                    .map(|v| v.package_as_file_path(&n)).unwrap_or_else(|_| n.to_owned()))
+                    .map(|v| v.package_as_file_path(n)).unwrap_or_else(|_| n.to_owned()))
                     .unwrap_or_else(|| "".to_owned());
                 let name_bytes = class_location.as_bytes();
                 record_size += 17u32 + name_bytes.len() as u32;
@@ -377,7 +379,7 @@ impl DumpFile {
         (record_size, entries_count)
     }
 
-    fn find_frame<'a>(&self, stack_frames: &'a Vec<super::StackFrameInfo>) -> Option<&'a super::StackFrameInfo> {
+    fn find_frame<'a>(&self, stack_frames: &'a [super::StackFrameInfo]) -> Option<&'a super::StackFrameInfo> {
         // Take first suitable frame
         stack_frames.iter().find(|&f| {
             let method = &f.method;
@@ -390,19 +392,19 @@ impl DumpFile {
                 return false;
             } else {
                 // suitable line number is found
-                let line_num = line_numbers.as_ref().and_then(|nums| self.find_line_number_entry(f.byte_code_index, &nums));
+                let line_num = line_numbers.as_ref().and_then(|nums| self.find_line_number_entry(f.byte_code_index, nums));
                 return line_num.is_some();
             }
         })
     }
 
     fn find_line_number_entry<'a>(&self, byte_code_index: i32,
-                              line_numbers: &'a Vec<rvmti::LineNumberEntry>) -> Option<&'a rvmti::LineNumberEntry>
+                              line_numbers: &'a Vec<rvmti_api::LineNumberEntry>) -> Option<&'a rvmti_api::LineNumberEntry>
     {
-        let mut result: Option<&rvmti::LineNumberEntry> = None;
+        let mut result: Option<&rvmti_api::LineNumberEntry> = None;
         for entry in line_numbers {
-            if entry.start_location as i64 <= byte_code_index as i64 {
-                result = Some(&entry)
+            if entry.start_location <= byte_code_index as i64 {
+                result = Some(entry)
             } else {
                 break;
             }
@@ -427,6 +429,65 @@ impl Drop for DumpFile {
 
 }
 
+impl SidecarFile {
+
+    pub fn new<P: AsRef<Path>>(path: P, extension: &str) -> Result<SidecarFile, NewDumpFileError> {
+        let file_name = format!("jit-{}.{}", get_pid(), extension);
+        let file_path = path.as_ref().join(file_name);
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o0644)
+            .open(&file_path)
+            .map_err(NewDumpFileError::IoError)?;
+        debug!("Sidecar file: {:?}", &file_path);
+        Ok(SidecarFile{file})
+    }
+
+    pub fn write_line(&mut self, fields: &[&str]) -> Result<(), WriteRecordError> {
+        let escaped: Vec<String> = fields.iter().map(|f| escape_sidecar_field(f)).collect();
+        let line = escaped.join("\t") + "\n";
+        self.file.write_all(line.as_bytes()).map_err(WriteRecordError::IoError)
+    }
+
+    // Line of a perf map, `/tmp/perf-<pid>.map`, hexadecimal start and size followed by the symbol name
+    pub fn write_perf_map_entry(&mut self, address: usize, length: usize, name: &str) -> Result<(), WriteRecordError> {
+        let line = format!("{:x} {:x} {}\n", address, length, escape_sidecar_field(name));
+        self.file.write_all(line.as_bytes()).map_err(WriteRecordError::IoError)
+    }
+
+    // For sidecars in a format of their own
+    pub fn write_text(&mut self, text: &str) -> Result<(), WriteRecordError> {
+        self.file.write_all(text.as_bytes()).map_err(WriteRecordError::IoError)
+    }
+
+}
+
+// Java names may contain anything, including separators
+fn escape_sidecar_field(field: &str) -> String {
+    let mut result = String::with_capacity(field.len());
+    for c in field.chars() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '\t' => result.push_str("\\t"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+// Java declaration, e.g. `long com.example.Worker.process(int p0)`, raw signatures if they do not demangle
+pub fn java_method_name(name: &rvmti_api::MethodName, class_signature: &rvmti_api::ClassSignature) -> String {
+    demangle::MethodType::new(&name.signature)
+        .and_then(|mt| demangle::ClassType::new(&class_signature.signature)
+            .map(|cs| mt.display_as_method_definition(&name.name, &cs)))
+        .unwrap_or_else(|_| format!("{}.{}{}", class_signature.signature, name.name, name.signature))
+}
+
+// Fields of timespec are narrower on 32-bit targets
+#[allow(clippy::unnecessary_cast)]
 pub fn get_timestamp() -> Result<i64, nix::errno::Errno> {
     let mut ts: libc::timespec = libc::timespec {tv_sec: 0, tv_nsec: 0};
     let result = unsafe {
@@ -439,23 +500,23 @@ pub fn get_timestamp() -> Result<i64, nix::errno::Errno> {
     }
 }
 
-fn get_tid() -> i32 {
+pub fn get_tid() -> i32 {
     libc::pid_t::from(nix::unistd::gettid()) as i32
 }
 
 fn get_pid() -> i32 {
-    libc::pid_t::from(nix::unistd::Pid::this()) as i32
+    libc::pid_t::from(nix::unistd::Pid::this())
 }
 
 fn get_e_machine() -> Result<u16, GetEMachineError> {
     let mut f = File::open("/proc/self/exe").map_err(GetEMachineError::IoError)?;
     let mut id = [0; 16];
-    let _ = f.read_exact(&mut id).map_err(GetEMachineError::IoError)?;
+    f.read_exact(&mut id).map_err(GetEMachineError::IoError)?;
     if id[0] != 0x7f || id[1] != 0x45 || id[2] != 0x4c || id[3] != 0x46 {
         return Err(GetEMachineError::NotAnElfFile);
     }
     let mut info = [0u16; 2];
-    let _ = f.read_u16_into::<NativeEndian>(&mut info).map_err(GetEMachineError::IoError)?;
+    f.read_u16_into::<NativeEndian>(&mut info).map_err(GetEMachineError::IoError)?;
     Ok(info[1])
 }
 
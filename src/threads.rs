@@ -0,0 +1,47 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+// Java threads seen by the agent keyed by native thread id, perf only knows threads by tid and truncated comm
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JavaThread {
+    pub tid: i32,
+    pub name: String,
+    pub group: Option<String>,
+    pub daemon: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct ThreadRegistry {
+    threads: Mutex<HashMap<i32, JavaThread>>,
+}
+
+impl ThreadRegistry {
+
+    pub fn new() -> ThreadRegistry {
+        Default::default()
+    }
+
+    // Returns false if the same thread is already known, tids are reused by the OS though
+    pub fn thread_started(&self, thread: JavaThread) -> bool {
+        let mut threads = self.threads.lock().unwrap_or_else(|e| e.into_inner());
+        if threads.get(&thread.tid) == Some(&thread) {
+            return false;
+        }
+        threads.insert(thread.tid, thread);
+        true
+    }
+
+    pub fn thread_ended(&self, tid: i32) -> Option<JavaThread> {
+        self.threads.lock().unwrap_or_else(|e| e.into_inner()).remove(&tid)
+    }
+
+}
@@ -0,0 +1,276 @@
+// Copyright 2018 rel-eng
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the following conditions are met:
+//   1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following disclaimer.
+//   2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the following disclaimer in the documentation and/or other materials provided with the distribution.
+//   3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote products derived from this software without specific prior written permission.
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+
+// Runs a real JVM with the agent attached and checks the resulting jit dump
+
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use rvmti::jitdump::{JitDump, RecordBody};
+use rvmti::classfile::{ClassFile, Member};
+use rvmti_api::{Instruction, Opcode, Operands};
+
+const WORKLOAD_SOURCE: &str = r#"package rvmti.workload;
+
+public class Workload {
+
+    static long mix(long value, int round) {
+        long result = value * 31 + round;
+        return result ^ (result >>> 7);
+    }
+
+    static long compute(int iterations) {
+        long accumulator = 17;
+        for (int i = 0; i < iterations; i++) {
+            accumulator = mix(accumulator, i);
+        }
+        return accumulator;
+    }
+
+    public static void main(String[] args) throws InterruptedException {
+        Thread worker = new Thread(() -> System.out.println(compute(10)), "rvmti-workload-thread");
+        worker.start();
+        worker.join();
+        long total = 0;
+        for (int i = 0; i < 20000; i++) {
+            total += compute(1000);
+        }
+        System.out.println(total);
+    }
+
+}
+"#;
+
+const SOURCE_PATH: &str = "rvmti/workload/Workload.java";
+
+fn jdk_tool(name: &str) -> PathBuf {
+    match env::var_os("JAVA_HOME") {
+        Some(home) => Path::new(&home).join("bin").join(name),
+        None => PathBuf::from(name),
+    }
+}
+
+fn jdk_available() -> bool {
+    ["java", "javac"].iter().all(|tool| Command::new(jdk_tool(tool)).arg("-version").output()
+        .map(|o| o.status.success()).unwrap_or(false))
+}
+
+fn agent_path() -> PathBuf {
+    // Test binary lives in target/<profile>/deps, the agent library is built into target/<profile>
+    let exe = env::current_exe().unwrap();
+    exe.parent().and_then(|p| p.parent()).unwrap().join("librvmti.so")
+}
+
+// Inclusive range of source lines from the line containing start up to the first closing brace on its own line
+fn method_lines(start: &str) -> (i32, i32) {
+    let lines: Vec<&str> = WORKLOAD_SOURCE.lines().collect();
+    let first = lines.iter().position(|l| l.contains(start)).unwrap();
+    let last = first + lines[first..].iter().position(|l| l.trim_end() == "    }").unwrap();
+    (first as i32 + 1, last as i32 + 1)
+}
+
+fn find_dump_file(dir: &Path, extension: &str) -> PathBuf {
+    let jit_dir = dir.join(".debug").join("jit");
+    let dump_dir = fs::read_dir(&jit_dir).unwrap().next().unwrap().unwrap().path();
+    fs::read_dir(&dump_dir).unwrap()
+        .map(|e| e.unwrap().path())
+        .find(|p| p.extension() == Some(OsStr::new(extension)))
+        .unwrap()
+}
+
+// Returns the class directory
+fn compile_workload(dir: &Path) -> PathBuf {
+    let _ = fs::remove_dir_all(dir);
+    let source = dir.join("src").join(SOURCE_PATH);
+    fs::create_dir_all(source.parent().unwrap()).unwrap();
+    fs::write(&source, WORKLOAD_SOURCE).unwrap();
+    let classes = dir.join("classes");
+    let javac = Command::new(jdk_tool("javac")).arg("-g").arg("-d").arg(&classes).arg(&source).output().unwrap();
+    assert!(javac.status.success(), "javac failed: {}", String::from_utf8_lossy(&javac.stderr));
+    classes
+}
+
+#[test]
+fn test_jit_dump_from_real_jvm() {
+    if !jdk_available() {
+        eprintln!("Skipping JVM test, no JDK found");
+        return;
+    }
+    let agent = agent_path();
+    assert!(agent.exists(), "Agent library not found at {:?}", agent);
+    let dir = env::temp_dir().join(format!("rvmti-jvm-test-{}", std::process::id()));
+    let classes = compile_workload(&dir);
+
+    let java = Command::new(jdk_tool("java"))
+        .current_dir(&dir)
+        .arg(format!("-agentpath:{}", agent.display()))
+        .arg("-Xbatch")
+        .arg("-cp").arg(&classes)
+        .arg("rvmti.workload.Workload")
+        .output().unwrap();
+    assert!(java.status.success(), "java failed: {}", String::from_utf8_lossy(&java.stderr));
+
+    let dump = JitDump::read_file(find_dump_file(&dir, "dump")).unwrap();
+    assert_eq!(dump.header.version, 1);
+    assert_ne!(dump.header.pid, 0);
+    assert_eq!(dump.records.last().map(|r| &r.body), Some(&RecordBody::CodeClose));
+    assert_eq!(dump.records.iter().filter(|r| r.body == RecordBody::CodeClose).count(), 1);
+
+    let compute_loads: Vec<_> = dump.code_loads()
+        .filter(|l| l.name == "long rvmti.workload.Workload.compute(int p0)")
+        .collect();
+    assert!(!compute_loads.is_empty(), "No code load for Workload.compute");
+    assert!(dump.code_loads().any(|l| l.name == "long rvmti.workload.Workload.mix(long p0, int p1)"),
+            "No code load for Workload.mix");
+    assert!(compute_loads.iter().all(|l| !l.code.is_empty() && l.pid == dump.header.pid));
+
+    let (compute_first, compute_last) = method_lines("static long compute(");
+    let (mix_first, mix_last) = method_lines("static long mix(");
+    let mut compute_lines_found = false;
+    for load in &compute_loads {
+        for info in dump.debug_infos().filter(|i| i.code_address == load.code_address) {
+            for entry in info.entries.iter().filter(|e| e.file_name == SOURCE_PATH) {
+                // Only compute itself and the inlined mix may appear in its debug info
+                assert!((entry.line >= compute_first && entry.line <= compute_last)
+                            || (entry.line >= mix_first && entry.line <= mix_last),
+                        "Unexpected line {} for Workload.compute", entry.line);
+                compute_lines_found |= entry.line >= compute_first && entry.line <= compute_last;
+            }
+        }
+    }
+    assert!(compute_lines_found, "No debug info lines for Workload.compute");
+
+    let threads = fs::read_to_string(find_dump_file(&dir, "threads")).unwrap();
+    let thread_fields: Vec<Vec<&str>> = threads.lines().map(|l| l.split('\t').collect()).collect();
+    assert!(thread_fields.iter().all(|f| f.len() == 4 && f[0].parse::<i32>().is_ok()), "Malformed threads file: {}", threads);
+    assert!(thread_fields.iter().any(|f| f[1..] == ["main", "main", "user"]), "No main thread: {}", threads);
+    assert!(thread_fields.iter().any(|f| f[1..] == ["rvmti-workload-thread", "main", "user"]),
+            "No workload thread: {}", threads);
+
+    let metadata = fs::read_to_string(find_dump_file(&dir, "meta")).unwrap();
+    let metadata_fields: Vec<Vec<&str>> = metadata.lines().map(|l| l.split('\t').collect()).collect();
+    let metadata_value = |key: &str| metadata_fields.iter().find(|f| f[0] == key).map(|f| f[1..].to_vec());
+    assert!(metadata_value("java.vm.name").map(|v| v.len() == 1 && !v[0].is_empty()).unwrap_or(false),
+            "No VM name: {}", metadata);
+    assert!(metadata_value("java.version").is_some(), "No Java version: {}", metadata);
+    assert!(metadata_value("java.vm.version").is_some(), "No VM version: {}", metadata);
+    assert_eq!(metadata_value("sun.java.command"), Some(vec!["rvmti.workload.Workload"]), "{}", metadata);
+    assert!(metadata_value("jvm.input_arguments").map(|v| v.contains(&"-Xbatch")).unwrap_or(false),
+            "No input arguments: {}", metadata);
+
+    // JDK libraries export their JNI functions, so natives are only listed in the perf map style sidecar, linked to the
+    // exported symbol
+    let natives = fs::read_to_string(find_dump_file(&dir, "natives")).unwrap();
+    let native_entries: Vec<(u64, u64, &str)> = natives.lines().map(|l| {
+        let mut fields = l.splitn(3, ' ');
+        let address = u64::from_str_radix(fields.next().unwrap(), 16).unwrap();
+        let length = u64::from_str_radix(fields.next().unwrap(), 16).unwrap();
+        (address, length, fields.next().unwrap())
+    }).collect();
+    assert!(native_entries.iter().all(|&(address, length, _)| address != 0 && length > 0), "{}", natives);
+    assert!(native_entries.iter().any(|e| e.2 == "java.lang.Class java.lang.ClassLoader.findLoadedClass0(java.lang.String p0) \
+                                      [Java_java_lang_ClassLoader_findLoadedClass0]"),
+            "No class loader natives: {}", natives);
+    assert!(dump.code_loads().all(|l| native_entries.iter().all(|e| e.0 != l.code_address)), "{}", natives);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_coverage_from_real_jvm() {
+    if !jdk_available() {
+        eprintln!("Skipping JVM test, no JDK found");
+        return;
+    }
+    let agent = agent_path();
+    assert!(agent.exists(), "Agent library not found at {:?}", agent);
+    let dir = env::temp_dir().join(format!("rvmti-jvm-coverage-test-{}", std::process::id()));
+    let classes = compile_workload(&dir);
+
+    let java = Command::new(jdk_tool("java"))
+        .current_dir(&dir)
+        .arg(format!("-agentpath:{}=coverage=rvmti.workload.", agent.display()))
+        .arg("-cp").arg(&classes)
+        .arg("rvmti.workload.Workload")
+        .output().unwrap();
+    assert!(java.status.success(), "java failed: {}", String::from_utf8_lossy(&java.stderr));
+
+    let coverage = fs::read_to_string(find_dump_file(&dir, "lcov")).unwrap();
+    let lines: Vec<&str> = coverage.lines().collect();
+    assert_eq!(lines[..2], ["TN:", &format!("SF:{}", SOURCE_PATH)], "{}", coverage);
+    assert_eq!(lines.last(), Some(&"end_of_record"), "{}", coverage);
+    let hits: Vec<(i32, u32)> = lines.iter().filter_map(|l| l.strip_prefix("DA:")).map(|l| {
+        let (line, hit) = l.split_once(',').unwrap();
+        (line.parse().unwrap(), hit.parse().unwrap())
+    }).collect();
+    assert!(lines.contains(&format!("LF:{}", hits.len()).as_str()), "{}", coverage);
+    assert!(lines.contains(&format!("LH:{}", hits.iter().filter(|(_, h)| *h == 1).count()).as_str()), "{}", coverage);
+    // Every method runs except the implicit constructor, which belongs to the class declaration line
+    let (class_line, _) = method_lines("public class Workload");
+    let (main_first, main_last) = method_lines("public static void main(");
+    let (mix_first, _) = method_lines("static long mix(");
+    assert!(hits.contains(&(class_line, 0)), "{}", coverage);
+    assert!(hits.iter().filter(|(l, _)| *l != class_line).all(|(_, h)| *h == 1), "{}", coverage);
+    assert!(hits.iter().any(|(l, _)| *l > mix_first && *l < main_first), "{}", coverage);
+    assert!(hits.iter().any(|(l, _)| *l > main_first && *l < main_last), "{}", coverage);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_instrumented_class_from_real_jvm() {
+    if !jdk_available() {
+        eprintln!("Skipping JVM test, no JDK found");
+        return;
+    }
+    let dir = env::temp_dir().join(format!("rvmti-jvm-classfile-test-{}", std::process::id()));
+    let classes = compile_workload(&dir);
+    let class_path = classes.join("rvmti").join("workload").join("Workload.class");
+    let bytes = fs::read(&class_path).unwrap();
+    let mut class = ClassFile::parse(&bytes).unwrap();
+    assert_eq!(class.write(), bytes);
+    assert_eq!(class.class_name(), Ok("rvmti/workload/Workload"));
+
+    // Counts compute calls in a new static field, main prints on entry and prints the count on exit
+    let pool = class.constant_pool_mut();
+    let counter = pool.add_fieldref("rvmti/workload/Workload", "computeCalls", "J").unwrap();
+    let out = pool.add_fieldref("java/lang/System", "out", "Ljava/io/PrintStream;").unwrap();
+    let message = pool.add_string("main entry").unwrap();
+    let print_string = pool.add_methodref("java/io/PrintStream", "println", "(Ljava/lang/String;)V").unwrap();
+    let print_long = pool.add_methodref("java/io/PrintStream", "println", "(J)V").unwrap();
+    let field = Member{access_flags: 0x0008, name_index: pool.add_utf8("computeCalls").unwrap(),
+                       descriptor_index: pool.add_utf8("J").unwrap(), attributes: Vec::new()};
+    class.fields.push(field);
+    let main_entry = [Instruction::new(Opcode::Getstatic, Operands::ConstantPool(out)),
+                      Instruction::new(Opcode::LdcW, Operands::ConstantPool(message)),
+                      Instruction::new(Opcode::Invokevirtual, Operands::ConstantPool(print_string))];
+    let main_exit = [Instruction::new(Opcode::Getstatic, Operands::ConstantPool(out)),
+                     Instruction::new(Opcode::Getstatic, Operands::ConstantPool(counter)),
+                     Instruction::new(Opcode::Invokevirtual, Operands::ConstantPool(print_long))];
+    let compute_exit = [Instruction::new(Opcode::Getstatic, Operands::ConstantPool(counter)),
+                        Instruction::new(Opcode::Lconst1, Operands::None), Instruction::new(Opcode::Ladd, Operands::None),
+                        Instruction::new(Opcode::Putstatic, Operands::ConstantPool(counter))];
+    class.instrument_method("main", "([Ljava/lang/String;)V", &main_entry, &main_exit).unwrap();
+    class.instrument_method("compute", "(I)J", &[], &compute_exit).unwrap();
+    let instrumented = class.write();
+    assert_eq!(ClassFile::parse(&instrumented).unwrap().write(), instrumented);
+    fs::write(&class_path, &instrumented).unwrap();
+
+    // Application classes are verified against the relocated stack map frames
+    let java = Command::new(jdk_tool("java"))
+        .arg("-cp").arg(&classes)
+        .arg("rvmti.workload.Workload")
+        .output().unwrap();
+    assert!(java.status.success(), "java failed: {}", String::from_utf8_lossy(&java.stderr));
+    let stdout = String::from_utf8_lossy(&java.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 4, "{}", stdout);
+    assert_eq!((lines[0], lines[3]), ("main entry", "20001"), "{}", stdout);
+    fs::remove_dir_all(&dir).unwrap();
+}